@@ -0,0 +1,77 @@
+//! Estimates of how well a multiplexed connection batches its writes
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Estimates how many commands a multiplexed connection sends per flush.
+///
+/// redis-rs doesn't expose its flushes, so they are approximated from the number of
+/// commands in flight: a command sent while the connection is idle starts a new
+/// batch, and commands sent while others are still awaiting replies are assumed to
+/// join it. Over each window of `window` commands the estimate is the number of
+/// commands divided by the number of batches, from 1 (no batching) upwards.
+///
+/// This overestimates batching when commands trickle in just as earlier ones are
+/// flushed, so it is best read as a trend rather than an exact figure.
+#[derive(Debug)]
+pub struct BatchEstimator {
+    window: usize,
+    inflight: AtomicUsize,
+    counts: Mutex<WindowCounts>,
+}
+
+#[derive(Debug, Default)]
+struct WindowCounts {
+    commands: usize,
+    batches: usize,
+}
+
+impl BatchEstimator {
+    /// Creates an estimator reporting once every `window` commands
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            inflight: AtomicUsize::new(0),
+            counts: Mutex::new(WindowCounts::default()),
+        }
+    }
+
+    /// Counts one command sent while `inflight` others were awaiting replies.
+    ///
+    /// Returns the estimate for the window when this command completes it.
+    pub fn observe(&self, inflight: usize) -> Option<f64> {
+        let mut counts = self.counts.lock().unwrap();
+        counts.commands += 1;
+        if inflight == 0 {
+            counts.batches += 1;
+        }
+        if counts.commands < self.window {
+            return None;
+        }
+        let estimate = counts.commands as f64 / counts.batches.max(1) as f64;
+        *counts = WindowCounts::default();
+        Some(estimate)
+    }
+
+    /// Marks a command as in flight until the returned guard is dropped, and counts it
+    /// with the number of commands already in flight.
+    pub fn submit(&self) -> (InflightGuard<'_>, Option<f64>) {
+        let inflight = self.inflight.fetch_add(1, Ordering::Relaxed);
+        (InflightGuard(&self.inflight), self.observe(inflight))
+    }
+
+    /// Returns the number of commands currently awaiting replies
+    pub fn inflight(&self) -> usize {
+        self.inflight.load(Ordering::Relaxed)
+    }
+}
+
+/// Keeps a command counted as in flight, see [`BatchEstimator::submit`]
+#[derive(Debug)]
+pub struct InflightGuard<'a>(&'a AtomicUsize);
+
+impl Drop for InflightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,54 @@
+//! Criticality tags recorded on async command spans
+
+use std::future::Future;
+
+tokio::task_local! {
+    static CRITICALITY: Criticality;
+}
+
+/// How important a Redis call is to the request it serves.
+///
+/// Recorded as `redis.criticality` so SLO analysis can separate user-facing calls
+/// from best-effort work such as prefetching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criticality {
+    /// The caller is waiting on the result, e.g. a user-facing read
+    Critical,
+    /// The result is nice to have, e.g. a cache warm-up or prefetch
+    BestEffort,
+}
+
+impl Criticality {
+    /// Returns the value recorded in `redis.criticality`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Criticality::Critical => "critical",
+            Criticality::BestEffort => "best_effort",
+        }
+    }
+}
+
+/// Runs `fut` with `criticality` attached to every command it sends through an
+/// instrumented async connection, including the convenience methods.
+///
+/// # Example
+/// ```rust,ignore
+/// use otel_instrumentation_redis::aio::{with_criticality, Criticality};
+///
+/// let name: Option<String> = with_criticality(Criticality::Critical, conn.get("user:1:name")).await?;
+/// ```
+pub async fn with_criticality<F: Future>(criticality: Criticality, fut: F) -> F::Output {
+    CRITICALITY.scope(criticality, fut).await
+}
+
+/// Returns the criticality set by an enclosing [`with_criticality`], if any
+pub fn current_criticality() -> Option<Criticality> {
+    CRITICALITY.try_with(|criticality| *criticality).ok()
+}
+
+/// Records the criticality in scope, if any, on a command span
+pub(crate) fn record_criticality(span: &tracing::Span) {
+    if let Some(criticality) = current_criticality() {
+        span.record("redis.criticality", criticality.as_str());
+    }
+}
@@ -0,0 +1,28 @@
+//! A best-effort dispatch point for commands sent on async connections
+
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::time::Instant;
+
+/// Drives `future` to completion, also returning when it was dispatched if `enabled`.
+///
+/// redis-rs doesn't say when a command leaves for the server, so the end of the
+/// first poll of its future stands in: by then the command has been packed and,
+/// on a multiplexed connection, queued for the connection's writer task. See
+/// [`crate::InstrumentationConfig::with_dispatch_timing`].
+pub(crate) async fn track_dispatch<F: Future>(
+    future: F,
+    enabled: bool,
+) -> (F::Output, Option<Instant>) {
+    let mut future = pin!(future);
+    let mut dispatched = None;
+    let output = poll_fn(|cx| {
+        let poll = future.as_mut().poll(cx);
+        if enabled && dispatched.is_none() {
+            dispatched = Some(Instant::now());
+        }
+        poll
+    })
+    .await;
+    (output, dispatched)
+}
@@ -1,19 +1,97 @@
 //! Asynchronous Redis connection instrumentation
 
-use crate::common::{apply_span_attributes, create_command_span, record_command_result};
+#[cfg(feature = "metrics")]
+use crate::common::record_command_metrics;
+use crate::common::{
+    apply_span_attributes, collapse_operation, command_start_time, convert_reply, count_operation,
+    create_connection_command_span, create_connection_raw_command_span, dedupe_keys,
+    eviction_sample_due, generate_span_name, get_packed_command_name, method_operation,
+    new_connection_state, parse_cluster_state, parse_evicted_keys, pipeline_command_count,
+    record_command_count, record_command_result, record_command_weight, record_connection_state,
+    record_database_index, record_dispatch_timing, record_error_on_span, record_error_statement,
+    record_eviction_pressure, record_execution_context, record_exists_result,
+    record_extracted_attributes, record_getex_expiry, record_hash_set_fields,
+    record_keyspace_lookup, record_latency_bucket, record_multi_key_counts,
+    record_pipeline_result_count, record_pipeline_result_events, record_publish_channel,
+    record_reconnect_state, record_reply_attributes, record_response_size, record_score_bounds,
+    record_server_exec_time, record_set_sample_count, record_smove_keys_with_config,
+    record_string_length, record_string_range, record_transaction_result, record_unix_timestamps,
+    record_watch_conflict, record_window_aggregate, record_zadd_flags, run_error_hook,
+    slowlog_lookup_due, transaction_span, ConnectionState, RedisSpan, ServerAddress,
+    SLOWLOG_LOOKUP_ENTRIES,
+};
+use crate::config::InstrumentationConfig;
+use opentelemetry::KeyValue;
+#[cfg(feature = "connection-manager")]
+use redis::aio::ConnectionManager;
 use redis::aio::{ConnectionLike, MultiplexedConnection};
-use redis::{Cmd, RedisResult, Value};
-use tracing::instrument;
+use redis::{Cmd, RedisError, RedisResult, Value};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::{instrument, Instrument, Span};
+
+#[cfg(feature = "metrics")]
+mod batching;
+mod criticality;
+mod dispatch;
+mod pubsub;
+mod scan;
+
+pub use crate::common::{ZAddFlag, MAX_PUBSUB_NAMES};
+#[cfg(feature = "metrics")]
+pub use batching::{BatchEstimator, InflightGuard};
+pub use criticality::{current_criticality, with_criticality, Criticality};
+pub use pubsub::{
+    split_pubsub, split_pubsub_with_config, ActiveChannels, InstrumentedPubSubSink,
+    InstrumentedPubSubStream, Subscriptions,
+};
+pub use scan::ScanErrorPolicy;
+
+use criticality::record_criticality;
+use dispatch::track_dispatch;
+use scan::{migrate_span, parse_scan_reply, scan_command, scan_span, ScanTally};
 
 /// An instrumented wrapper around an async Redis connection
 pub struct InstrumentedAsyncConnection<C> {
     inner: C,
+    config: Arc<InstrumentationConfig>,
+    state: Arc<ConnectionState>,
 }
 
 impl<C: ConnectionLike> InstrumentedAsyncConnection<C> {
     /// Create a new instrumented async connection
     pub fn new(connection: C) -> Self {
-        Self { inner: connection }
+        Self::with_config(connection, Arc::new(InstrumentationConfig::default()))
+    }
+
+    /// Create a new instrumented async connection with a shared configuration
+    pub fn with_config(connection: C, config: Arc<InstrumentationConfig>) -> Self {
+        Self {
+            state: Arc::new(new_connection_state(connection.get_db(), &config, false)),
+            inner: connection,
+            config,
+        }
+    }
+
+    /// Records `server` as `server.address` and `server.port` on every command span.
+    ///
+    /// [`crate::InstrumentedClient`] sets this from its connection info on the
+    /// connections it hands out.
+    pub fn with_server_address(self, server: ServerAddress) -> Self {
+        self.state.set_server_address(server);
+        self
+    }
+
+    /// Get the instrumentation configuration
+    pub fn config(&self) -> &InstrumentationConfig {
+        &self.config
+    }
+
+    /// Get the connection-scoped state observed from commands sent through this wrapper
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
     }
 
     /// Get the underlying connection
@@ -28,21 +106,192 @@ impl<C: ConnectionLike> InstrumentedAsyncConnection<C> {
 
     /// Execute a Redis command with tracing
     pub async fn req_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
-        let (span, attributes) = create_command_span(cmd);
-        let _enter = span.enter();
+        self.req_command_with_span_fn(cmd, |_| {}).await
+    }
 
-        // Apply additional attributes
-        apply_span_attributes(&span, &attributes);
+    /// Execute a Redis command with tracing, letting `enrich` add to its span first.
+    ///
+    /// `enrich` is called with the command span once it is created, before the
+    /// command is sent, e.g. to record a business-entity id derived from the
+    /// caller's context. The span only keeps values of the fields it declares; other
+    /// keys can be attached as events under it. Command attributes are recorded
+    /// after `enrich` runs and take precedence.
+    pub async fn req_command_with_span_fn(
+        &mut self,
+        cmd: &Cmd,
+        enrich: impl FnOnce(&Span),
+    ) -> RedisResult<Value> {
+        let command = self.open_command(cmd, enrich);
+        self.run_command(cmd, &command, None).await
+    }
+
+    /// Runs `cmd` within the span of `command`.
+    ///
+    /// `raw_operation` is the operation given to [`Self::req_command_raw`], which
+    /// names the command in metrics instead of the parsed command name.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    async fn run_command(
+        &mut self,
+        cmd: &Cmd,
+        command: &RedisSpan,
+        raw_operation: Option<&'static str>,
+    ) -> RedisResult<Value> {
+        let span = command.span();
+        self.record_command_start(cmd, command.attributes(), span);
 
         // Execute the command using the query trait
-        let result = cmd.query_async(&mut self.inner).await;
+        let started_at = command_start_time(&self.config);
+        let started = Instant::now();
+        let (result, dispatched) = track_dispatch(
+            cmd.query_async(&mut self.inner),
+            self.config.dispatch_timing(),
+        )
+        .instrument(span.clone())
+        .await;
+        let elapsed = started.elapsed();
+        record_dispatch_timing(span, started, dispatched);
+        record_unix_timestamps(span, started_at);
+        record_latency_bucket(span, elapsed, &self.config);
+        #[cfg(feature = "metrics")]
+        match raw_operation {
+            Some(operation) => crate::metrics::record_operation(operation, elapsed, &result),
+            None => record_command_metrics(cmd, elapsed, &result, &self.config),
+        }
+        record_window_aggregate(cmd, elapsed, &self.state);
+        self.state.observe_outcome(&result);
+        if result.is_ok() {
+            self.state.observe_command(cmd);
+        }
+        self.state.observe_transaction(cmd, &result);
+        record_reply_attributes(span, cmd, &result);
+        record_keyspace_lookup(span, cmd, &result, &self.config);
+        record_response_size(span, &result, &self.config);
+        record_extracted_attributes(span, cmd, &result, &self.config);
+        if eviction_sample_due(cmd, &self.config, &self.state) {
+            let info: RedisResult<String> = redis::cmd("INFO")
+                .arg("stats")
+                .query_async(&mut self.inner)
+                .instrument(span.clone())
+                .await;
+            if let Some(evicted_keys) = info.ok().as_deref().and_then(parse_evicted_keys) {
+                self.state.eviction().record(evicted_keys);
+            }
+        }
+        record_eviction_pressure(span, cmd, &self.state);
+        if slowlog_lookup_due(cmd, elapsed, &self.config, &self.state) {
+            let slowlog: RedisResult<Value> = redis::cmd("SLOWLOG")
+                .arg("GET")
+                .arg(SLOWLOG_LOOKUP_ENTRIES)
+                .query_async(&mut self.inner)
+                .instrument(span.clone())
+                .await;
+            if let Ok(slowlog) = &slowlog {
+                record_server_exec_time(span, cmd, slowlog, elapsed);
+            }
+        }
 
         // Record the result
-        record_command_result(&span, &result);
+        record_command_result(span, &result);
+        record_error_statement(span, cmd, &result, &self.config);
+        span.in_scope(|| run_error_hook(cmd, &result, &self.config));
 
         result
     }
 
+    /// Creates the span of a command the caller executes, with the attributes
+    /// `req_command` records before sending it.
+    ///
+    /// Execute the command through [`Self::inner_mut`] or any other means, instrumenting
+    /// it with [`RedisSpan::span`] so nested spans land under it, then call
+    /// [`RedisSpan::record_result`] and [`RedisSpan::finish`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use otel_instrumentation_redis::aio::InstrumentedAsyncConnection;
+    /// use redis::aio::ConnectionLike;
+    /// use tracing::Instrument;
+    ///
+    /// # async fn example() -> redis::RedisResult<()> {
+    /// let client = redis::Client::open("redis://127.0.0.1/")?;
+    /// let mut conn = InstrumentedAsyncConnection::new(client.get_multiplexed_async_connection().await?);
+    ///
+    /// let cmd = redis::cmd("GET").arg("key").clone();
+    /// let command = conn.begin_command(&cmd);
+    /// let result = conn
+    ///     .inner_mut()
+    ///     .req_packed_command(&cmd)
+    ///     .instrument(command.span().clone())
+    ///     .await;
+    /// command.record_result(&result);
+    /// command.finish();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn begin_command(&self, cmd: &Cmd) -> RedisSpan {
+        let command = self.open_command(cmd, |_| {});
+        self.record_command_start(cmd, command.attributes(), command.span());
+        command
+    }
+
+    /// Creates and counts the span of `cmd`, letting `enrich` add to it
+    fn open_command(&self, cmd: &Cmd, enrich: impl FnOnce(&Span)) -> RedisSpan {
+        let (span, attributes) = create_connection_command_span(cmd, &self.config, &self.state);
+        count_operation(cmd, &self.config);
+        enrich(&span);
+        RedisSpan::new(span, attributes, self.config.clone())
+    }
+
+    /// Records what is known about a command before it is sent
+    fn record_command_start(&self, cmd: &Cmd, attributes: &[KeyValue], span: &Span) {
+        // Command attributes such as the target of SELECT override the connection's database
+        record_database_index(span, &self.config, &self.state);
+        apply_span_attributes(span, attributes);
+        record_connection_state(span, cmd, &self.state);
+        record_command_weight(span, cmd, &self.config, &self.state);
+        record_reconnect_state(span, &self.config, &self.state);
+        record_execution_context(span, &self.config);
+        record_criticality(span);
+    }
+
+    /// Execute a Redis command with tracing, tagged with an explicit criticality.
+    ///
+    /// Overrides any criticality set by an enclosing [`with_criticality`].
+    pub async fn req_command_critical(
+        &mut self,
+        cmd: &Cmd,
+        criticality: Criticality,
+    ) -> RedisResult<Value> {
+        with_criticality(criticality, self.req_command(cmd)).await
+    }
+
+    /// Execute a Redis command with tracing, using a precomputed operation name and attributes.
+    ///
+    /// The command is not parsed to build its span: the span is built by
+    /// [`crate::common::create_raw_command_span`] from `operation` and `attributes` as
+    /// given, which suits callers that already know the command's shape. `operation` is
+    /// collapsed when [`InstrumentationConfig::with_collapse_subcommands`] is set, and
+    /// names the command in metrics. Everything else `req_command` records is recorded
+    /// as well.
+    pub async fn req_command_raw(
+        &mut self,
+        cmd: &Cmd,
+        operation: &'static str,
+        attributes: &[KeyValue],
+    ) -> RedisResult<Value> {
+        let operation = if self.config.collapse_subcommands() {
+            collapse_operation(operation)
+        } else {
+            operation
+        };
+        let span =
+            create_connection_raw_command_span(operation, attributes, &self.config, &self.state);
+        if let Some(counters) = self.config.local_counters() {
+            counters.increment(operation);
+        }
+        let command = RedisSpan::new(span, attributes.to_vec(), self.config.clone());
+        self.run_command(cmd, &command, Some(operation)).await
+    }
+
     /// Execute a pipeline of commands with tracing
     pub async fn execute_pipeline(
         &mut self,
@@ -50,22 +299,289 @@ impl<C: ConnectionLike> InstrumentedAsyncConnection<C> {
     ) -> RedisResult<Vec<Value>> {
         let span = tracing::info_span!(
             "redis_pipeline",
-            db.system = "redis",
-            db.operation = "pipeline"
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = "pipeline",
+            redis.latency_bucket = Empty,
+            redis.command_count = pipeline_command_count(pipeline) as i64,
+            redis.pipeline.events_truncated = Empty,
+            redis.transaction.conflict = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
         );
-        let _enter = span.enter();
 
         // Execute the pipeline
-        let result: RedisResult<Vec<Value>> = pipeline.query_async(&mut self.inner).await;
+        let started = Instant::now();
+        let reply: RedisResult<Value> = pipeline
+            .query_async(&mut self.inner)
+            .instrument(span.clone())
+            .await;
+        record_latency_bucket(&span, started.elapsed(), &self.config);
+        // A transaction replies nil when EXEC was aborted by a WATCH conflict
+        let conflict = pipeline.is_transaction() && matches!(reply, Ok(Value::Nil));
+        let result: RedisResult<Vec<Value>> = reply.and_then(redis::from_owned_redis_value);
+        if let Ok(values) = &result {
+            record_pipeline_result_events(&span, values, &self.config);
+        }
 
         // Record the result
         record_command_result(&span, &result);
+        if conflict {
+            record_watch_conflict(&span, &self.config);
+        }
+
+        result
+    }
+
+    /// Runs the commands `queue` adds to a pipeline as a `MULTI`/`EXEC` transaction,
+    /// with tracing.
+    ///
+    /// The transaction gets a `redis MULTI` span, under which `MULTI`, each queued
+    /// command and `EXEC` get their own command span, as for the sync wrapper's
+    /// `transaction`. It records the number of commands the server queued as
+    /// `db.redis.transaction.command_count`, and the outcome of `EXEC`.
+    ///
+    /// Multiplexed connections have no such helper: commands sent on their clones
+    /// could be queued into the transaction. Use an atomic pipeline with
+    /// [`InstrumentedMultiplexedConnection::execute_pipeline`] there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error variant of [`RedisResult`] if:
+    /// - The connection to Redis fails.
+    /// - `EXEC` is aborted because a command was rejected while queuing.
+    /// - The reply can't be converted to `RV`.
+    pub async fn transaction<RV: redis::FromRedisValue>(
+        &mut self,
+        queue: impl FnOnce(&mut redis::Pipeline),
+    ) -> RedisResult<RV> {
+        let mut pipeline = redis::pipe();
+        queue(&mut pipeline);
+
+        let span = transaction_span(&self.config);
+        let mut queued = 0;
+        let result = async {
+            self.req_command(&redis::cmd("MULTI")).await?;
+            for cmd in pipeline.cmd_iter() {
+                match self.req_command(cmd).await {
+                    Ok(_) => queued += 1,
+                    // The server rejected the command; EXEC aborts the transaction
+                    Err(err) if err.code().is_some() => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            self.req_command(&redis::cmd("EXEC")).await
+        }
+        .instrument(span.clone())
+        .await;
+        record_transaction_result(&span, queued, &result, &self.config);
+
+        span.in_scope(|| convert_reply(&result?))
+    }
+
+    /// Execute the commands of `pipeline` from `offset`, returning `count` replies, with tracing.
+    ///
+    /// This is the pipeline entry point of [`ConnectionLike`], used by redis-rs when a
+    /// pipeline is queried through the wrapper. The span is the one the sync wrapper's
+    /// `req_packed_commands` creates, with `db.operation = "pipeline"`.
+    #[instrument(
+        skip(self, pipeline),
+        fields(
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = "pipeline",
+            redis.pipeline.count = %count,
+            redis.pipeline.result_count = Empty,
+            redis.command_count = Empty,
+            redis.latency_bucket = Empty,
+            redis.pipeline.events_truncated = Empty,
+            redis.transaction.conflict = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
+        )
+    )]
+    pub async fn req_packed_commands(
+        &mut self,
+        pipeline: &redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisResult<Vec<Value>> {
+        let span = Span::current();
+        record_command_count(&span, offset + count);
+
+        let started = Instant::now();
+        let result = self
+            .inner
+            .req_packed_commands(pipeline, offset, count)
+            .await;
+        record_latency_bucket(&span, started.elapsed(), &self.config);
+        if let Ok(values) = &result {
+            record_pipeline_result_count(&span, values.len());
+            record_pipeline_result_events(&span, values, &self.config);
+        }
+
+        record_command_result(&span, &result);
+        // Only transactions skip replies; a nil EXEC reply means a WATCH conflict
+        if offset > 0 && matches!(result.as_deref(), Ok([Value::Nil])) {
+            record_watch_conflict(&span, &self.config);
+        }
+
+        result
+    }
+
+    /// Send `cmd` down the low-level packed command path, with tracing.
+    ///
+    /// For callers pushing commands at the connection directly rather than through
+    /// [`Self::req_command`]: no command attributes are extracted, and the operation is
+    /// parsed back from the packed bytes like the sync wrapper's `req_packed_command`
+    /// does. The span records `redis.reply.received = true` once the reply arrived. A
+    /// fire-and-forget send, whose future is dropped before its reply, still closes the
+    /// span, with `redis.reply.received = false`.
+    #[instrument(
+        skip(self, cmd),
+        fields(
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = Empty,
+            otel.name = Empty,
+            redis.latency_bucket = Empty,
+            redis.reply.received = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
+        )
+    )]
+    pub async fn send_packed_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        let span = Span::current();
+
+        // Recover the operation name from the packed bytes
+        let operation = get_packed_command_name(&cmd.get_packed_command())
+            .unwrap_or_else(|| "packed_command".to_string());
+        span.record("db.operation", operation.as_str());
+        span.record("otel.name", generate_span_name(&operation).as_str());
+
+        let pending = PendingReply::new(&span);
+        let started = Instant::now();
+        let result = self.inner.req_packed_command(cmd).await;
+        pending.received();
+        record_latency_bucket(&span, started.elapsed(), &self.config);
+
+        record_command_result(&span, &result);
+
+        result
+    }
 
+    /// Scan keys matching `pattern` and apply `f` to each of them under one span.
+    ///
+    /// The SCAN calls and any instrumented commands issued by `f` nest under a
+    /// `redis scan` span recording `redis.scan.processed` and `redis.scan.failed`, plus
+    /// the number of SCAN calls as `redis.command_count`; commands issued by `f` have
+    /// their own spans and are not included.
+    /// A failing key doesn't stop the scan; see [`Self::scan_and_with_policy`].
+    /// `f` runs inside the span, so it can report progress with
+    /// [`crate::common::record_progress`].
+    /// Returns the number of keys `f` was applied to.
+    pub async fn scan_and<F, Fut, T>(&mut self, pattern: &str, f: F) -> RedisResult<usize>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        self.scan_and_with_policy(pattern, ScanErrorPolicy::Continue, f)
+            .await
+    }
+
+    /// Like [`Self::scan_and`], with `policy` deciding whether a failing key aborts the scan
+    pub async fn scan_and_with_policy<F, Fut, T>(
+        &mut self,
+        pattern: &str,
+        policy: ScanErrorPolicy,
+        mut f: F,
+    ) -> RedisResult<usize>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        let span = scan_span(&self.config);
+        let mut tally = ScanTally::default();
+        let result = async {
+            let mut cursor = 0;
+            loop {
+                tally.scans += 1;
+                let reply = self.req_command(&scan_command(cursor, pattern)).await?;
+                let (next, keys) = parse_scan_reply(&reply)?;
+                for key in keys {
+                    tally.apply(key, &mut f, policy).await?;
+                }
+                if next == 0 {
+                    return Ok(tally.processed);
+                }
+                cursor = next;
+            }
+        }
+        .instrument(span.clone())
+        .await;
+        tally.finish(&span, &result);
+        result
+    }
+
+    /// Scan keys matching `pattern` and copy each of them to `dest` with `copy`, under
+    /// one span.
+    ///
+    /// The SCAN calls and the commands `copy` issues against `dest` nest under a
+    /// `redis migrate` span recording `redis.migrate.keys_processed`,
+    /// `redis.migrate.keys_failed` and the number of SCAN calls as
+    /// `redis.command_count`. A key `copy` fails for is counted and skipped.
+    ///
+    /// After each SCAN batch, a `redis.progress` event records the keys processed so
+    /// far and `redis.migrate.cursor`, the cursor of the next batch. If the migration
+    /// is interrupted, passing the last recorded cursor as `start_cursor` resumes it
+    /// without revisiting finished batches; `None` starts a new scan.
+    /// Returns the number of keys `copy` was applied to.
+    pub async fn migrate_keys<D, F, T>(
+        &mut self,
+        pattern: &str,
+        dest: &mut D,
+        start_cursor: Option<u64>,
+        mut copy: F,
+    ) -> RedisResult<usize>
+    where
+        F: AsyncFnMut(String, &mut D) -> RedisResult<T>,
+    {
+        let span = migrate_span(&self.config);
+        if let Some(cursor) = start_cursor {
+            span.record("redis.migrate.start_cursor", cursor);
+        }
+        let mut tally = ScanTally::default();
+        let result = async {
+            let mut cursor = start_cursor.unwrap_or(0);
+            loop {
+                tally.scans += 1;
+                let reply = self.req_command(&scan_command(cursor, pattern)).await?;
+                let (next, keys) = parse_scan_reply(&reply)?;
+                for key in keys {
+                    let copied = copy(key, dest).await;
+                    tally.count(&copied);
+                }
+                tally.record_migration_progress(next);
+                if next == 0 {
+                    return Ok(tally.processed);
+                }
+                cursor = next;
+            }
+        }
+        .instrument(span.clone())
+        .await;
+        tally.finish_migration(&span, &result);
         result
     }
 
     /// Convenience method: GET a key with instrumentation
-    #[instrument(skip(self, key), fields(db.operation = "GET"))]
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("GET", &self.config)))]
     pub async fn get<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
         &mut self,
         key: K,
@@ -73,11 +589,11 @@ impl<C: ConnectionLike> InstrumentedAsyncConnection<C> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("GET").arg(key);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: SET a key with instrumentation
-    #[instrument(skip(self, key, value), fields(db.operation = "SET"))]
+    #[instrument(skip(self, key, value), fields(db.operation = %method_operation("SET", &self.config)))]
     pub async fn set<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -86,194 +602,523 @@ impl<C: ConnectionLike> InstrumentedAsyncConnection<C> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("SET").arg(key).arg(value);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: DEL keys with instrumentation
-    #[instrument(skip(self, keys), fields(db.operation = "DEL"))]
+    #[instrument(skip(self, keys), fields(db.operation = %method_operation("DEL", &self.config)))]
     pub async fn del<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("DEL").arg(keys);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
-    /// Convenience method: EXISTS check with instrumentation
-    #[instrument(skip(self, keys), fields(db.operation = "EXISTS"))]
-    pub async fn exists<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<bool> {
+    /// Convenience method: TOUCH keys to refresh their LRU/LFU access time, with instrumentation
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("TOUCH", &self.config),
+            redis.keys.count = Empty,
+            redis.keys.affected = Empty
+        )
+    )]
+    pub async fn touch<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
         let mut cmd = redis::Cmd::new();
-        cmd.arg("EXISTS").arg(keys);
-        let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        cmd.arg("TOUCH").arg(keys);
+        let result = self.req_command(&cmd).await;
+        record_multi_key_counts(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
     }
 
-    /// Convenience method: EXPIRE key with instrumentation
-    #[instrument(skip(self, key), fields(db.operation = "EXPIRE"))]
-    pub async fn expire<K: redis::ToRedisArgs>(
-        &mut self,
-        key: K,
-        seconds: usize,
-    ) -> RedisResult<bool> {
+    /// Convenience method: UNLINK keys, reclaiming their memory in the background, with instrumentation
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("UNLINK", &self.config),
+            redis.keys.count = Empty,
+            redis.keys.affected = Empty
+        )
+    )]
+    pub async fn unlink<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
         let mut cmd = redis::Cmd::new();
-        cmd.arg("EXPIRE").arg(key).arg(seconds);
-        let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        cmd.arg("UNLINK").arg(keys);
+        let result = self.req_command(&cmd).await;
+        record_multi_key_counts(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
     }
 
-    /// Convenience method: HGET hash field with instrumentation
-    #[instrument(skip(self, key, field), fields(db.operation = "HGET"))]
-    pub async fn hget<K: redis::ToRedisArgs, F: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+    /// Convenience method: GETRANGE a substring with instrumentation
+    ///
+    /// `start` and `end` are inclusive byte offsets; negative offsets count from the end.
+    #[instrument(
+        skip(self, key),
+        fields(
+            db.operation = %method_operation("GETRANGE", &self.config),
+            redis.range.start = Empty,
+            redis.range.end = Empty
+        )
+    )]
+    pub async fn getrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
         &mut self,
         key: K,
-        field: F,
+        start: isize,
+        end: isize,
     ) -> RedisResult<RV> {
         let mut cmd = redis::Cmd::new();
-        cmd.arg("HGET").arg(key).arg(field);
+        cmd.arg("GETRANGE").arg(key).arg(start).arg(end);
+        record_string_range(&Span::current(), &cmd);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
-    /// Convenience method: HSET hash field with instrumentation
-    #[instrument(skip(self, key, field, value), fields(db.operation = "HSET"))]
-    pub async fn hset<K: redis::ToRedisArgs, F: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+    /// Convenience method: SETRANGE to overwrite part of a string with instrumentation
+    ///
+    /// Returns the length of the string after the write.
+    #[instrument(
+        skip(self, key, value),
+        fields(
+            db.operation = %method_operation("SETRANGE", &self.config),
+            redis.range.start = Empty,
+            redis.range.end = Empty,
+            redis.string.length = Empty
+        )
+    )]
+    pub async fn setrange<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
         &mut self,
         key: K,
-        field: F,
+        offset: usize,
         value: V,
-    ) -> RedisResult<bool> {
+    ) -> RedisResult<i64> {
         let mut cmd = redis::Cmd::new();
-        cmd.arg("HSET").arg(key).arg(field).arg(value);
-        let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        cmd.arg("SETRANGE").arg(key).arg(offset).arg(value);
+        record_string_range(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await;
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
     }
 
-    /// Convenience method: SADD to set with instrumentation
-    #[instrument(skip(self, key, members), fields(db.operation = "SADD"))]
-    pub async fn sadd<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+    /// Convenience method: STRLEN with instrumentation
+    #[instrument(
+        skip(self, key),
+        fields(db.operation = %method_operation("STRLEN", &self.config), redis.string.length = Empty)
+    )]
+    pub async fn strlen<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("STRLEN").arg(key);
+        let result = self.req_command(&cmd).await;
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: APPEND to a string with instrumentation
+    ///
+    /// Returns the length of the string after the append.
+    #[instrument(
+        skip(self, key, value),
+        fields(db.operation = %method_operation("APPEND", &self.config), redis.string.length = Empty)
+    )]
+    pub async fn append<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
         &mut self,
         key: K,
-        members: M,
+        value: V,
     ) -> RedisResult<i64> {
         let mut cmd = redis::Cmd::new();
-        cmd.arg("SADD").arg(key).arg(members);
+        cmd.arg("APPEND").arg(key).arg(value);
+        let result = self.req_command(&cmd).await;
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: INCR with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCR", &self.config)))]
+    pub async fn incr<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCR").arg(key);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
-    /// Convenience method: SISMEMBER check with instrumentation
-    #[instrument(skip(self, key, member), fields(db.operation = "SISMEMBER"))]
-    pub async fn sismember<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+    /// Convenience method: DECR with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("DECR", &self.config)))]
+    pub async fn decr<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("DECR").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: INCRBY with instrumentation
+    ///
+    /// A negative `delta` decrements.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCRBY", &self.config)))]
+    pub async fn incr_by<K: redis::ToRedisArgs>(&mut self, key: K, delta: i64) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCRBY").arg(key).arg(delta);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: INCRBYFLOAT with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCRBYFLOAT", &self.config)))]
+    pub async fn incr_by_float<K: redis::ToRedisArgs>(
         &mut self,
         key: K,
-        member: M,
-    ) -> RedisResult<bool> {
+        delta: f64,
+    ) -> RedisResult<f64> {
         let mut cmd = redis::Cmd::new();
-        cmd.arg("SISMEMBER").arg(key).arg(member);
+        cmd.arg("INCRBYFLOAT").arg(key).arg(delta);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
-}
 
-/// An instrumented wrapper around `redis::aio::MultiplexedConnection`
-#[derive(Clone)]
-pub struct InstrumentedMultiplexedConnection {
-    inner: MultiplexedConnection,
-}
+    /// Convenience method: CLUSTER INFO with instrumentation
+    ///
+    /// Records the reported `cluster_state` as `redis.cluster.state`.
+    #[instrument(
+        skip(self),
+        fields(
+            db.operation = %method_operation("CLUSTER INFO", &self.config),
+            redis.admin_command = true,
+            redis.cluster.state = Empty
+        )
+    )]
+    pub async fn cluster_info(&mut self) -> RedisResult<String> {
+        let result = self
+            .req_command_raw(redis::cmd("CLUSTER").arg("INFO"), "CLUSTER INFO", &[])
+            .await?;
+        let info: String = convert_reply(&result)?;
+        if let Some(state) = parse_cluster_state(&info) {
+            Span::current().record("redis.cluster.state", state);
+        }
+        Ok(info)
+    }
 
-impl InstrumentedMultiplexedConnection {
-    /// Create a new instrumented multiplexed connection
-    pub fn new(connection: MultiplexedConnection) -> Self {
-        Self { inner: connection }
+    /// Convenience method: CLUSTER NODES with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("CLUSTER NODES", &self.config), redis.admin_command = true)
+    )]
+    pub async fn cluster_nodes(&mut self) -> RedisResult<String> {
+        let result = self
+            .req_command_raw(redis::cmd("CLUSTER").arg("NODES"), "CLUSTER NODES", &[])
+            .await?;
+        convert_reply(&result)
     }
 
-    /// Get the underlying connection
-    pub fn inner(&self) -> &MultiplexedConnection {
-        &self.inner
+    /// Convenience method: CLUSTER SHARDS with instrumentation
+    ///
+    /// The reply is returned as is, one entry of slots and nodes per shard.
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("CLUSTER SHARDS", &self.config), redis.admin_command = true)
+    )]
+    pub async fn cluster_shards(&mut self) -> RedisResult<Value> {
+        self.req_command_raw(redis::cmd("CLUSTER").arg("SHARDS"), "CLUSTER SHARDS", &[])
+            .await
     }
 
-    /// Execute a Redis command with tracing
-    pub async fn req_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
-        let (span, attributes) = create_command_span(cmd);
-        let _enter = span.enter();
+    /// Convenience method: SCRIPT LOAD with instrumentation
+    ///
+    /// Records the returned SHA1 digest as `db.redis.script.sha`.
+    #[instrument(
+        skip(self, script),
+        fields(db.operation = %method_operation("SCRIPT LOAD", &self.config), db.redis.script.sha = Empty)
+    )]
+    pub async fn script_load(&mut self, script: &str) -> RedisResult<String> {
+        let result = self
+            .req_command_raw(
+                redis::cmd("SCRIPT").arg("LOAD").arg(script),
+                "SCRIPT LOAD",
+                &[],
+            )
+            .await?;
+        let sha: String = convert_reply(&result)?;
+        Span::current().record("db.redis.script.sha", sha.as_str());
+        Ok(sha)
+    }
 
-        // Apply additional attributes
-        apply_span_attributes(&span, &attributes);
+    /// Convenience method: SCRIPT EXISTS with instrumentation
+    ///
+    /// Records how many of the queried scripts are cached as `redis.script.exists_count`.
+    #[instrument(
+        skip(self, shas),
+        fields(
+            db.operation = %method_operation("SCRIPT EXISTS", &self.config),
+            redis.script.count = shas.len(),
+            redis.script.exists_count = Empty
+        )
+    )]
+    pub async fn script_exists(&mut self, shas: &[&str]) -> RedisResult<Vec<bool>> {
+        let result = self
+            .req_command_raw(
+                redis::cmd("SCRIPT").arg("EXISTS").arg(shas),
+                "SCRIPT EXISTS",
+                &[],
+            )
+            .await?;
+        let exists: Vec<bool> = convert_reply(&result)?;
+        let cached = exists.iter().filter(|exists| **exists).count();
+        Span::current().record("redis.script.exists_count", cached as i64);
+        Ok(exists)
+    }
 
-        // Execute the command using the query trait
-        let result = cmd.query_async(&mut self.inner).await;
+    /// Convenience method: SCRIPT FLUSH with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("SCRIPT FLUSH", &self.config), redis.admin_command = true)
+    )]
+    pub async fn script_flush(&mut self) -> RedisResult<()> {
+        let result = self
+            .req_command_raw(redis::cmd("SCRIPT").arg("FLUSH"), "SCRIPT FLUSH", &[])
+            .await?;
+        convert_reply(&result)
+    }
 
-        // Record the result
-        record_command_result(&span, &result);
+    /// Convenience method: LATENCY LATEST with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("LATENCY LATEST", &self.config), redis.admin_command = true)
+    )]
+    pub async fn latency_latest<RV: redis::FromRedisValue>(&mut self) -> RedisResult<RV> {
+        let result = self
+            .req_command_raw(redis::cmd("LATENCY").arg("LATEST"), "LATENCY LATEST", &[])
+            .await?;
+        convert_reply(&result)
+    }
 
-        result
+    /// Convenience method: LATENCY HISTORY of one event with instrumentation
+    #[instrument(
+        skip(self, event),
+        fields(db.operation = %method_operation("LATENCY HISTORY", &self.config), redis.admin_command = true)
+    )]
+    pub async fn latency_history<RV: redis::FromRedisValue>(
+        &mut self,
+        event: &str,
+    ) -> RedisResult<RV> {
+        let result = self
+            .req_command_raw(
+                redis::cmd("LATENCY").arg("HISTORY").arg(event),
+                "LATENCY HISTORY",
+                &[],
+            )
+            .await?;
+        convert_reply(&result)
     }
 
-    /// Execute a pipeline of commands with tracing
-    pub async fn execute_pipeline(
+    /// Convenience method: SLOWLOG GET with instrumentation
+    ///
+    /// Fetches up to `count` entries, or the server's default of 10 when `None`, and
+    /// records the number of entries returned as `redis.slowlog.entries`.
+    #[instrument(
+        skip(self),
+        fields(
+            db.operation = %method_operation("SLOWLOG GET", &self.config),
+            redis.admin_command = true,
+            redis.slowlog.entries = Empty
+        )
+    )]
+    pub async fn slowlog_get<RV: redis::FromRedisValue>(
         &mut self,
-        pipeline: &redis::Pipeline,
-    ) -> RedisResult<Vec<Value>> {
-        let span = tracing::info_span!(
-            "redis_pipeline",
-            db.system = "redis",
-            db.operation = "pipeline"
-        );
-        let _enter = span.enter();
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::cmd("SLOWLOG");
+        cmd.arg("GET").arg(count);
+        let result = self.req_command_raw(&cmd, "SLOWLOG GET", &[]).await?;
+        if let Value::Array(entries) = &result {
+            Span::current().record("redis.slowlog.entries", entries.len() as i64);
+        }
+        convert_reply(&result)
+    }
 
-        // Execute the pipeline
-        let result: RedisResult<Vec<Value>> = pipeline.query_async(&mut self.inner).await;
+    /// Convenience method: SLOWLOG RESET with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("SLOWLOG RESET", &self.config), redis.admin_command = true)
+    )]
+    pub async fn slowlog_reset(&mut self) -> RedisResult<()> {
+        let result = self
+            .req_command_raw(redis::cmd("SLOWLOG").arg("RESET"), "SLOWLOG RESET", &[])
+            .await?;
+        convert_reply(&result)
+    }
 
-        // Record the result
-        record_command_result(&span, &result);
+    /// Convenience method: SETNX a key, setting it only if it doesn't exist, with instrumentation
+    ///
+    /// Returns `true` and records `redis.conditional.applied = true` if the key was set.
+    #[instrument(
+        skip(self, key, value),
+        fields(db.operation = %method_operation("SETNX", &self.config), redis.conditional.applied = Empty)
+    )]
+    pub async fn setnx<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SETNX").arg(key).arg(value);
+        let result = self.req_command(&cmd).await;
+        record_reply_attributes(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
 
-        result
+    /// Convenience method: MSETNX several keys, setting them only if none exists, with instrumentation
+    ///
+    /// MSETNX is all-or-nothing: `false`, recorded as `redis.conditional.applied = false`,
+    /// means none of the keys were set. `redis.keys.count` is the number of pairs.
+    #[instrument(
+        skip(self, items),
+        fields(
+            db.operation = %method_operation("MSETNX", &self.config),
+            redis.keys.count = items.len() as i64,
+            redis.conditional.applied = Empty
+        )
+    )]
+    pub async fn msetnx<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        items: &[(K, V)],
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MSETNX").arg(items);
+        let result = self.req_command(&cmd).await;
+        record_reply_attributes(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
     }
 
-    /// Convenience method: GET a key with instrumentation
-    #[instrument(skip(self, key), fields(db.operation = "GET"))]
-    pub async fn get<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+    /// Convenience method: GET several keys with one MGET, with instrumentation
+    ///
+    /// Keys repeated in `keys` are fetched once: the MGET carries each distinct key
+    /// and its value is fanned back out, so the result has one entry per input key,
+    /// in input order. `redis.keys.count` is the number of input keys and
+    /// `redis.batch.deduped` the number of repeats left out of the MGET.
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("MGET", &self.config),
+            redis.keys.count = keys.len() as i64,
+            redis.batch.deduped = Empty
+        )
+    )]
+    pub async fn get_many<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
         &mut self,
-        key: K,
-    ) -> RedisResult<RV> {
+        keys: &[K],
+    ) -> RedisResult<Vec<RV>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (unique, positions) = dedupe_keys(keys);
+        Span::current().record("redis.batch.deduped", (keys.len() - unique.len()) as i64);
+
         let mut cmd = redis::Cmd::new();
-        cmd.arg("GET").arg(key);
+        cmd.arg("MGET").arg(&unique);
+        let values: Vec<Value> = convert_reply(&self.req_command(&cmd).await?)?;
+        positions
+            .into_iter()
+            .map(|position| match values.get(position) {
+                Some(value) => convert_reply(value),
+                None => Err(RedisError::from((
+                    redis::ErrorKind::TypeError,
+                    "MGET returned fewer values than keys",
+                ))),
+            })
+            .collect()
+    }
+
+    /// Convenience method: MGET several keys with instrumentation
+    ///
+    /// Returns one value per key, in order, with nil for missing keys. Unlike
+    /// [`Self::get_many`], repeated keys are sent as given.
+    #[instrument(
+        skip(self, keys),
+        fields(db.operation = %method_operation("MGET", &self.config), redis.keys.count = keys.len() as i64)
+    )]
+    pub async fn mget<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        keys: &[K],
+    ) -> RedisResult<Vec<RV>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MGET").arg(keys);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
-    /// Convenience method: SET a key with instrumentation
-    #[instrument(skip(self, key, value), fields(db.operation = "SET"))]
-    pub async fn set<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+    /// Convenience method: MSET several key-value pairs with instrumentation
+    ///
+    /// `redis.keys.count` is the number of pairs.
+    #[instrument(
+        skip(self, items),
+        fields(db.operation = %method_operation("MSET", &self.config), redis.keys.count = items.len() as i64)
+    )]
+    pub async fn mset<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
         &mut self,
-        key: K,
-        value: V,
+        items: &[(K, V)],
     ) -> RedisResult<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
         let mut cmd = redis::Cmd::new();
-        cmd.arg("SET").arg(key).arg(value);
+        cmd.arg("MSET").arg(items);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
-    /// Convenience method: DEL keys with instrumentation
-    #[instrument(skip(self, keys), fields(db.operation = "DEL"))]
-    pub async fn del<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
+    /// Convenience method: GETDEL a key, returning its value, with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("GETDEL", &self.config)))]
+    pub async fn getdel<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+    ) -> RedisResult<RV> {
         let mut cmd = redis::Cmd::new();
-        cmd.arg("DEL").arg(keys);
+        cmd.arg("GETDEL").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: GETEX a key, returning its value and updating its TTL, with instrumentation
+    #[instrument(
+        skip(self, key, expiry),
+        fields(
+            db.operation = %method_operation("GETEX", &self.config),
+            redis.ttl.mode = Empty,
+            redis.ttl.value = Empty
+        )
+    )]
+    pub async fn getex<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        expiry: redis::Expiry,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GETEX").arg(key).arg(expiry);
+        record_getex_expiry(&Span::current(), &cmd);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: EXISTS check with instrumentation
-    #[instrument(skip(self, keys), fields(db.operation = "EXISTS"))]
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("EXISTS", &self.config),
+            redis.exists.found = Empty,
+            redis.exists.found_count = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty
+        )
+    )]
     pub async fn exists<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<bool> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("EXISTS").arg(keys);
-        let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        let result = self.req_command(&cmd).await;
+        record_exists_result(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
     }
 
     /// Convenience method: EXPIRE key with instrumentation
-    #[instrument(skip(self, key), fields(db.operation = "EXPIRE"))]
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("EXPIRE", &self.config)))]
     pub async fn expire<K: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -282,11 +1127,11 @@ impl InstrumentedMultiplexedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("EXPIRE").arg(key).arg(seconds);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: HGET hash field with instrumentation
-    #[instrument(skip(self, key, field), fields(db.operation = "HGET"))]
+    #[instrument(skip(self, key, field), fields(db.operation = %method_operation("HGET", &self.config)))]
     pub async fn hget<K: redis::ToRedisArgs, F: redis::ToRedisArgs, RV: redis::FromRedisValue>(
         &mut self,
         key: K,
@@ -295,11 +1140,18 @@ impl InstrumentedMultiplexedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("HGET").arg(key).arg(field);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: HSET hash field with instrumentation
-    #[instrument(skip(self, key, field, value), fields(db.operation = "HSET"))]
+    #[instrument(
+        skip(self, key, field, value),
+        fields(
+            db.operation = %method_operation("HSET", &self.config),
+            redis.hash.fields_set = Empty,
+            redis.hash.new_fields = Empty
+        )
+    )]
     pub async fn hset<K: redis::ToRedisArgs, F: redis::ToRedisArgs, V: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -308,12 +1160,167 @@ impl InstrumentedMultiplexedConnection {
     ) -> RedisResult<bool> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("HSET").arg(key).arg(field).arg(value);
+        let result = self.req_command(&cmd).await;
+        record_hash_set_fields(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: HSET multiple hash fields with instrumentation
+    #[instrument(
+        skip(self, key, items),
+        fields(
+            db.operation = %method_operation("HSET", &self.config),
+            redis.hash.fields_set = Empty,
+            redis.hash.new_fields = Empty
+        )
+    )]
+    pub async fn hset_multiple<
+        K: redis::ToRedisArgs,
+        F: redis::ToRedisArgs,
+        V: redis::ToRedisArgs,
+    >(
+        &mut self,
+        key: K,
+        items: &[(F, V)],
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("HSET").arg(key).arg(items);
+        let result = self.req_command(&cmd).await;
+        record_hash_set_fields(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: ZADD a member with instrumentation
+    ///
+    /// `flags` are sent before the score and recorded as `redis.zset.flags`.
+    #[instrument(
+        skip(self, key, member, score, flags),
+        fields(db.operation = %method_operation("ZADD", &self.config), redis.zset.flags = Empty)
+    )]
+    pub async fn zadd<K: redis::ToRedisArgs, M: redis::ToRedisArgs, S: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        member: M,
+        score: S,
+        flags: &[ZAddFlag],
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZADD").arg(key).arg(flags).arg(score).arg(member);
+        record_zadd_flags(&Span::current(), flags);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZRANGEBYSCORE with instrumentation
+    ///
+    /// Records the parsed score range, including `(` exclusive and `-inf`/`+inf` bounds.
+    #[instrument(
+        skip(self, key, min, max),
+        fields(
+            db.operation = %method_operation("ZRANGEBYSCORE", &self.config),
+            redis.zset.min = Empty,
+            redis.zset.max = Empty,
+            redis.zset.min_exclusive = Empty,
+            redis.zset.max_exclusive = Empty
+        )
+    )]
+    pub async fn zrangebyscore<
+        K: redis::ToRedisArgs,
+        M: redis::ToRedisArgs,
+        MM: redis::ToRedisArgs,
+        RV: redis::FromRedisValue,
+    >(
+        &mut self,
+        key: K,
+        min: M,
+        max: MM,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZRANGEBYSCORE").arg(key).arg(min).arg(max);
+        record_score_bounds(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZREVRANGEBYSCORE with instrumentation
+    ///
+    /// Records the parsed score range with `redis.zset.min` as the lower bound, even
+    /// though the command takes `max` first.
+    #[instrument(
+        skip(self, key, max, min),
+        fields(
+            db.operation = %method_operation("ZREVRANGEBYSCORE", &self.config),
+            redis.zset.min = Empty,
+            redis.zset.max = Empty,
+            redis.zset.min_exclusive = Empty,
+            redis.zset.max_exclusive = Empty
+        )
+    )]
+    pub async fn zrevrangebyscore<
+        K: redis::ToRedisArgs,
+        MM: redis::ToRedisArgs,
+        M: redis::ToRedisArgs,
+        RV: redis::FromRedisValue,
+    >(
+        &mut self,
+        key: K,
+        max: MM,
+        min: M,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZREVRANGEBYSCORE").arg(key).arg(max).arg(min);
+        record_score_bounds(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZREM members from a sorted set with instrumentation
+    #[instrument(skip(self, key, members), fields(db.operation = %method_operation("ZREM", &self.config)))]
+    pub async fn zrem<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        members: M,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZREM").arg(key).arg(members);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZSCORE with instrumentation
+    ///
+    /// A missing member or key replies nil, so `RV` is typically `Option<f64>`.
+    #[instrument(skip(self, key, member), fields(db.operation = %method_operation("ZSCORE", &self.config)))]
+    pub async fn zscore<K: redis::ToRedisArgs, M: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        member: M,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZSCORE").arg(key).arg(member);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZRANGE by rank with instrumentation
+    ///
+    /// `start` and `stop` are inclusive and may be negative to count from the highest
+    /// score.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("ZRANGE", &self.config)))]
+    pub async fn zrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        stop: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZRANGE").arg(key).arg(start).arg(stop);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
     }
 
     /// Convenience method: SADD to set with instrumentation
-    #[instrument(skip(self, key, members), fields(db.operation = "SADD"))]
+    #[instrument(skip(self, key, members), fields(db.operation = %method_operation("SADD", &self.config)))]
     pub async fn sadd<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -322,11 +1329,11 @@ impl InstrumentedMultiplexedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("SADD").arg(key).arg(members);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: SISMEMBER check with instrumentation
-    #[instrument(skip(self, key, member), fields(db.operation = "SISMEMBER"))]
+    #[instrument(skip(self, key, member), fields(db.operation = %method_operation("SISMEMBER", &self.config)))]
     pub async fn sismember<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -335,6 +1342,1881 @@ impl InstrumentedMultiplexedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("SISMEMBER").arg(key).arg(member);
         let result = self.req_command(&cmd).await?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SMOVE a member between sets with instrumentation
+    #[instrument(
+        skip(self, source, destination, member),
+        fields(
+            db.operation = %method_operation("SMOVE", &self.config),
+            redis.smove.source = Empty,
+            redis.smove.destination = Empty
+        )
+    )]
+    pub async fn smove<S: redis::ToRedisArgs, D: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        source: S,
+        destination: D,
+        member: M,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SMOVE").arg(source).arg(destination).arg(member);
+        record_smove_keys_with_config(&Span::current(), &cmd, &self.config);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SPOP random members with instrumentation
+    ///
+    /// Without `count` a single member (or nil) is returned, with it a list.
+    #[instrument(
+        skip(self, key),
+        fields(db.operation = %method_operation("SPOP", &self.config), redis.spop.count = Empty)
+    )]
+    pub async fn spop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SPOP").arg(key).arg(count);
+        record_set_sample_count(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SRANDMEMBER with instrumentation
+    ///
+    /// A negative `count` may return duplicates and records `redis.set.allow_duplicates`.
+    #[instrument(
+        skip(self, key),
+        fields(
+            db.operation = %method_operation("SRANDMEMBER", &self.config),
+            redis.srandmember.count = Empty,
+            redis.set.allow_duplicates = Empty
+        )
+    )]
+    pub async fn srandmember<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<isize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SRANDMEMBER").arg(key).arg(count);
+        record_set_sample_count(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
     }
+
+    /// Convenience method: LPUSH values onto a list with instrumentation
+    #[instrument(skip(self, key, values), fields(db.operation = %method_operation("LPUSH", &self.config)))]
+    pub async fn lpush<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        values: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LPUSH").arg(key).arg(values);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: RPUSH values onto a list with instrumentation
+    #[instrument(skip(self, key, values), fields(db.operation = %method_operation("RPUSH", &self.config)))]
+    pub async fn rpush<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        values: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("RPUSH").arg(key).arg(values);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LPOP with instrumentation
+    ///
+    /// Without `count` a single element (or nil) is returned, with it a list.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LPOP", &self.config)))]
+    pub async fn lpop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LPOP").arg(key).arg(count);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: RPOP with instrumentation
+    ///
+    /// Without `count` a single element (or nil) is returned, with it a list.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("RPOP", &self.config)))]
+    pub async fn rpop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("RPOP").arg(key).arg(count);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LLEN with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LLEN", &self.config)))]
+    pub async fn llen<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LLEN").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LRANGE with instrumentation
+    ///
+    /// `start` and `stop` are inclusive and may be negative to count from the tail.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LRANGE", &self.config)))]
+    pub async fn lrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        stop: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LRANGE").arg(key).arg(start).arg(stop);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: PUBLISH with instrumentation, returning the number of clients
+    /// that received the message
+    ///
+    /// The channel is recorded as `redis.pubsub.channel`, after the configured key
+    /// redactions, and the number of receivers as `redis.pubsub.receivers`.
+    #[instrument(
+        skip(self, channel, message),
+        fields(
+            db.operation = %method_operation("PUBLISH", &self.config),
+            redis.pubsub.channel = Empty,
+            redis.pubsub.receivers = Empty
+        )
+    )]
+    pub async fn publish<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        channel: K,
+        message: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("PUBLISH").arg(channel).arg(message);
+        let span = Span::current();
+        record_publish_channel(&span, &cmd, &self.config);
+        let result = self.req_command(&cmd).await?;
+        let receivers: i64 = convert_reply(&result)?;
+        span.record("redis.pubsub.receivers", receivers);
+        Ok(receivers)
+    }
+}
+
+/// Lets the wrapper stand in for its connection in redis-rs's generic APIs, such as
+/// [`redis::AsyncCommands`] and `query_async`.
+///
+/// Commands issued this way are instrumented exactly like calls to the inherent methods:
+///
+/// ```rust,ignore
+/// use redis::AsyncCommands;
+///
+/// let mut conn = InstrumentedAsyncConnection::new(connection);
+/// // Produces a `redis zcard` span with `db.operation = ZCARD`
+/// let players: i64 = conn.zcard("leaderboard").await?;
+/// ```
+impl<C: ConnectionLike + Send> ConnectionLike for InstrumentedAsyncConnection<C> {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> redis::RedisFuture<'a, Value> {
+        Box::pin(self.req_command(cmd))
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> redis::RedisFuture<'a, Vec<Value>> {
+        Box::pin(Self::req_packed_commands(self, cmd, offset, count))
+    }
+
+    fn get_db(&self) -> i64 {
+        self.inner.get_db()
+    }
+}
+
+/// An instrumented wrapper around `redis::aio::ConnectionManager`.
+///
+/// Commands are instrumented like on [`InstrumentedMultiplexedConnection`], with the
+/// same convenience methods. A command failing because the server connection dropped
+/// emits a `redis.reconnect` event under its span, as the manager reconnects in the
+/// background; [`InstrumentationConfig::with_reconnect_state`] records the inferred
+/// reconnect state on the spans of the commands that follow.
+#[cfg(feature = "connection-manager")]
+pub type InstrumentedConnectionManager = InstrumentedMultiplexedConnection<ConnectionManager>;
+
+/// An instrumented wrapper around an asynchronous Redis Cluster connection, see
+/// [`crate::InstrumentedClusterClient::get_async_connection`]
+#[cfg(feature = "cluster-async")]
+pub type InstrumentedClusterAsyncConnection =
+    InstrumentedMultiplexedConnection<redis::cluster_async::ClusterConnection>;
+
+/// A connection shared between clones, which [`InstrumentedMultiplexedConnection`] can
+/// wrap
+pub trait SharedConnection: ConnectionLike + Clone + Send {
+    /// `true` if the connection replaces itself once the server connection dropped
+    const RECONNECTS: bool;
+}
+
+impl SharedConnection for MultiplexedConnection {
+    const RECONNECTS: bool = false;
+}
+
+#[cfg(feature = "connection-manager")]
+impl SharedConnection for ConnectionManager {
+    const RECONNECTS: bool = true;
+}
+
+#[cfg(feature = "cluster-async")]
+impl SharedConnection for redis::cluster_async::ClusterConnection {
+    // Reconnects to nodes are routine in a cluster and don't replace the connection
+    const RECONNECTS: bool = false;
+}
+
+/// An instrumented wrapper around `redis::aio::MultiplexedConnection`, or another
+/// [`SharedConnection`] such as a `ConnectionManager`
+#[derive(Clone)]
+pub struct InstrumentedMultiplexedConnection<C = MultiplexedConnection> {
+    inner: C,
+    config: Arc<InstrumentationConfig>,
+    state: Arc<ConnectionState>,
+    #[cfg(feature = "metrics")]
+    batching: Option<Arc<BatchEstimator>>,
+}
+
+impl<C: SharedConnection> InstrumentedMultiplexedConnection<C> {
+    /// Create a new instrumented multiplexed connection
+    pub fn new(connection: C) -> Self {
+        Self::with_config(connection, Arc::new(InstrumentationConfig::default()))
+    }
+
+    /// Create a new instrumented multiplexed connection with a shared configuration
+    pub fn with_config(connection: C, config: Arc<InstrumentationConfig>) -> Self {
+        Self {
+            state: Arc::new(new_connection_state(connection.get_db(), &config, true)),
+            inner: connection,
+            #[cfg(feature = "metrics")]
+            batching: config
+                .batch_efficiency()
+                .map(|window| Arc::new(BatchEstimator::new(window))),
+            config,
+        }
+    }
+
+    /// Records `server` as `server.address` and `server.port` on every command span.
+    ///
+    /// [`crate::InstrumentedClient`] sets this from its connection info on the
+    /// connections it hands out.
+    pub fn with_server_address(self, server: ServerAddress) -> Self {
+        self.state.set_server_address(server);
+        self
+    }
+
+    /// Get the instrumentation configuration
+    pub fn config(&self) -> &InstrumentationConfig {
+        &self.config
+    }
+
+    /// Get the connection-scoped state observed from commands sent through this wrapper
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
+    /// Get the underlying connection
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Execute a Redis command with tracing
+    pub async fn req_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        self.req_command_with_span_fn(cmd, |_| {}).await
+    }
+
+    /// Execute a Redis command with tracing, letting `enrich` add to its span first.
+    ///
+    /// `enrich` is called with the command span once it is created, before the
+    /// command is sent, e.g. to record a business-entity id derived from the
+    /// caller's context. The span only keeps values of the fields it declares; other
+    /// keys can be attached as events under it. Command attributes are recorded
+    /// after `enrich` runs and take precedence.
+    pub async fn req_command_with_span_fn(
+        &mut self,
+        cmd: &Cmd,
+        enrich: impl FnOnce(&Span),
+    ) -> RedisResult<Value> {
+        let command = self.open_command(cmd, enrich);
+        self.run_command(cmd, &command, None).await
+    }
+
+    /// Runs `cmd` within the span of `command`.
+    ///
+    /// `raw_operation` is the operation given to [`Self::req_command_raw`], which
+    /// names the command in metrics instead of the parsed command name.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    async fn run_command(
+        &mut self,
+        cmd: &Cmd,
+        command: &RedisSpan,
+        raw_operation: Option<&'static str>,
+    ) -> RedisResult<Value> {
+        let span = command.span();
+        self.record_command_start(cmd, command.attributes(), span);
+
+        #[cfg(feature = "metrics")]
+        let _inflight = self.batching.as_deref().map(|batching| {
+            let (guard, estimate) = batching.submit();
+            if let Some(estimate) = estimate {
+                crate::metrics::batch_efficiency()
+                    .record(estimate, &[KeyValue::new("db.system", "redis")]);
+            }
+            guard
+        });
+
+        // Execute the command using the query trait
+        let started_at = command_start_time(&self.config);
+        let started = Instant::now();
+        let (result, dispatched) = track_dispatch(
+            cmd.query_async(&mut self.inner),
+            self.config.dispatch_timing(),
+        )
+        .instrument(span.clone())
+        .await;
+        let elapsed = started.elapsed();
+        record_dispatch_timing(span, started, dispatched);
+        record_unix_timestamps(span, started_at);
+        record_latency_bucket(span, elapsed, &self.config);
+        #[cfg(feature = "metrics")]
+        match raw_operation {
+            Some(operation) => crate::metrics::record_operation(operation, elapsed, &result),
+            None => record_command_metrics(cmd, elapsed, &result, &self.config),
+        }
+        record_window_aggregate(cmd, elapsed, &self.state);
+        self.state.observe_outcome(&result);
+        record_reconnect::<C, _>(span, &result);
+        if result.is_ok() {
+            self.state.observe_command(cmd);
+        }
+        self.state.observe_transaction(cmd, &result);
+        record_reply_attributes(span, cmd, &result);
+        record_keyspace_lookup(span, cmd, &result, &self.config);
+        record_response_size(span, &result, &self.config);
+        record_extracted_attributes(span, cmd, &result, &self.config);
+        if eviction_sample_due(cmd, &self.config, &self.state) {
+            let info: RedisResult<String> = redis::cmd("INFO")
+                .arg("stats")
+                .query_async(&mut self.inner)
+                .instrument(span.clone())
+                .await;
+            if let Some(evicted_keys) = info.ok().as_deref().and_then(parse_evicted_keys) {
+                self.state.eviction().record(evicted_keys);
+            }
+        }
+        record_eviction_pressure(span, cmd, &self.state);
+        if slowlog_lookup_due(cmd, elapsed, &self.config, &self.state) {
+            let slowlog: RedisResult<Value> = redis::cmd("SLOWLOG")
+                .arg("GET")
+                .arg(SLOWLOG_LOOKUP_ENTRIES)
+                .query_async(&mut self.inner)
+                .instrument(span.clone())
+                .await;
+            if let Ok(slowlog) = &slowlog {
+                record_server_exec_time(span, cmd, slowlog, elapsed);
+            }
+        }
+
+        // Record the result
+        record_command_result(span, &result);
+        record_error_statement(span, cmd, &result, &self.config);
+        span.in_scope(|| run_error_hook(cmd, &result, &self.config));
+
+        result
+    }
+
+    /// Creates the span of a command the caller executes, with the attributes
+    /// `req_command` records before sending it.
+    ///
+    /// Execute the command through a clone of [`Self::inner`] or any other means, instrumenting
+    /// it with [`RedisSpan::span`] so nested spans land under it, then call
+    /// [`RedisSpan::record_result`] and [`RedisSpan::finish`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use otel_instrumentation_redis::aio::InstrumentedMultiplexedConnection;
+    /// use redis::aio::ConnectionLike;
+    /// use tracing::Instrument;
+    ///
+    /// # async fn example() -> redis::RedisResult<()> {
+    /// let client = redis::Client::open("redis://127.0.0.1/")?;
+    /// let conn = InstrumentedMultiplexedConnection::new(client.get_multiplexed_async_connection().await?);
+    ///
+    /// let cmd = redis::cmd("GET").arg("key").clone();
+    /// let command = conn.begin_command(&cmd);
+    /// let result = conn
+    ///     .inner()
+    ///     .clone()
+    ///     .req_packed_command(&cmd)
+    ///     .instrument(command.span().clone())
+    ///     .await;
+    /// command.record_result(&result);
+    /// command.finish();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn begin_command(&self, cmd: &Cmd) -> RedisSpan {
+        let command = self.open_command(cmd, |_| {});
+        self.record_command_start(cmd, command.attributes(), command.span());
+        command
+    }
+
+    /// Creates and counts the span of `cmd`, letting `enrich` add to it
+    fn open_command(&self, cmd: &Cmd, enrich: impl FnOnce(&Span)) -> RedisSpan {
+        let (span, attributes) = create_connection_command_span(cmd, &self.config, &self.state);
+        count_operation(cmd, &self.config);
+        enrich(&span);
+        RedisSpan::new(span, attributes, self.config.clone())
+    }
+
+    /// Records what is known about a command before it is sent
+    fn record_command_start(&self, cmd: &Cmd, attributes: &[KeyValue], span: &Span) {
+        // Command attributes such as the target of SELECT override the connection's database
+        record_database_index(span, &self.config, &self.state);
+        apply_span_attributes(span, attributes);
+        record_connection_state(span, cmd, &self.state);
+        record_command_weight(span, cmd, &self.config, &self.state);
+        record_reconnect_state(span, &self.config, &self.state);
+        record_execution_context(span, &self.config);
+        record_criticality(span);
+    }
+
+    /// Execute a Redis command with tracing, tagged with an explicit criticality.
+    ///
+    /// Overrides any criticality set by an enclosing [`with_criticality`].
+    pub async fn req_command_critical(
+        &mut self,
+        cmd: &Cmd,
+        criticality: Criticality,
+    ) -> RedisResult<Value> {
+        with_criticality(criticality, self.req_command(cmd)).await
+    }
+
+    /// Execute a Redis command with tracing, using a precomputed operation name and attributes.
+    ///
+    /// The command is not parsed to build its span: the span is built by
+    /// [`crate::common::create_raw_command_span`] from `operation` and `attributes` as
+    /// given, which suits callers that already know the command's shape. `operation` is
+    /// collapsed when [`InstrumentationConfig::with_collapse_subcommands`] is set, and
+    /// names the command in metrics. Everything else `req_command` records is recorded
+    /// as well.
+    pub async fn req_command_raw(
+        &mut self,
+        cmd: &Cmd,
+        operation: &'static str,
+        attributes: &[KeyValue],
+    ) -> RedisResult<Value> {
+        let operation = if self.config.collapse_subcommands() {
+            collapse_operation(operation)
+        } else {
+            operation
+        };
+        let span =
+            create_connection_raw_command_span(operation, attributes, &self.config, &self.state);
+        if let Some(counters) = self.config.local_counters() {
+            counters.increment(operation);
+        }
+        let command = RedisSpan::new(span, attributes.to_vec(), self.config.clone());
+        self.run_command(cmd, &command, Some(operation)).await
+    }
+
+    /// Execute a pipeline of commands with tracing
+    pub async fn execute_pipeline(
+        &mut self,
+        pipeline: &redis::Pipeline,
+    ) -> RedisResult<Vec<Value>> {
+        let span = tracing::info_span!(
+            "redis_pipeline",
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = "pipeline",
+            redis.latency_bucket = Empty,
+            redis.command_count = pipeline_command_count(pipeline) as i64,
+            redis.pipeline.events_truncated = Empty,
+            redis.transaction.conflict = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
+        );
+
+        // Execute the pipeline
+        let started = Instant::now();
+        let reply: RedisResult<Value> = pipeline
+            .query_async(&mut self.inner)
+            .instrument(span.clone())
+            .await;
+        record_latency_bucket(&span, started.elapsed(), &self.config);
+        // A transaction replies nil when EXEC was aborted by a WATCH conflict
+        let conflict = pipeline.is_transaction() && matches!(reply, Ok(Value::Nil));
+        let result: RedisResult<Vec<Value>> = reply.and_then(redis::from_owned_redis_value);
+        if let Ok(values) = &result {
+            record_pipeline_result_events(&span, values, &self.config);
+        }
+
+        // Record the result
+        record_command_result(&span, &result);
+        if conflict {
+            record_watch_conflict(&span, &self.config);
+        }
+
+        result
+    }
+
+    /// Execute the commands of `pipeline` from `offset`, returning `count` replies, with tracing.
+    ///
+    /// This is the pipeline entry point of [`ConnectionLike`], used by redis-rs when a
+    /// pipeline is queried through the wrapper. The span is the one the sync wrapper's
+    /// `req_packed_commands` creates, with `db.operation = "pipeline"`.
+    #[instrument(
+        skip(self, pipeline),
+        fields(
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = "pipeline",
+            redis.pipeline.count = %count,
+            redis.pipeline.result_count = Empty,
+            redis.command_count = Empty,
+            redis.latency_bucket = Empty,
+            redis.pipeline.events_truncated = Empty,
+            redis.transaction.conflict = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
+        )
+    )]
+    pub async fn req_packed_commands(
+        &mut self,
+        pipeline: &redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisResult<Vec<Value>> {
+        let span = Span::current();
+        record_command_count(&span, offset + count);
+
+        let started = Instant::now();
+        let result = self
+            .inner
+            .req_packed_commands(pipeline, offset, count)
+            .await;
+        record_latency_bucket(&span, started.elapsed(), &self.config);
+        self.state.observe_outcome(&result);
+        record_reconnect::<C, _>(&span, &result);
+        if let Ok(values) = &result {
+            record_pipeline_result_count(&span, values.len());
+            record_pipeline_result_events(&span, values, &self.config);
+        }
+
+        record_command_result(&span, &result);
+        // Only transactions skip replies; a nil EXEC reply means a WATCH conflict
+        if offset > 0 && matches!(result.as_deref(), Ok([Value::Nil])) {
+            record_watch_conflict(&span, &self.config);
+        }
+
+        result
+    }
+
+    /// Send `cmd` down the low-level packed command path, with tracing.
+    ///
+    /// For callers pushing commands at the connection directly rather than through
+    /// [`Self::req_command`]: no command attributes are extracted, and the operation is
+    /// parsed back from the packed bytes like the sync wrapper's `req_packed_command`
+    /// does. The span records `redis.reply.received = true` once the reply arrived. A
+    /// fire-and-forget send, whose future is dropped before its reply, still closes the
+    /// span, with `redis.reply.received = false`.
+    #[instrument(
+        skip(self, cmd),
+        fields(
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = Empty,
+            otel.name = Empty,
+            redis.latency_bucket = Empty,
+            redis.reply.received = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
+        )
+    )]
+    pub async fn send_packed_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        let span = Span::current();
+
+        // Recover the operation name from the packed bytes
+        let operation = get_packed_command_name(&cmd.get_packed_command())
+            .unwrap_or_else(|| "packed_command".to_string());
+        span.record("db.operation", operation.as_str());
+        span.record("otel.name", generate_span_name(&operation).as_str());
+
+        let pending = PendingReply::new(&span);
+        let started = Instant::now();
+        let result = self.inner.req_packed_command(cmd).await;
+        pending.received();
+        record_latency_bucket(&span, started.elapsed(), &self.config);
+        self.state.observe_outcome(&result);
+        record_reconnect::<C, _>(&span, &result);
+
+        record_command_result(&span, &result);
+
+        result
+    }
+
+    /// Scan keys matching `pattern` and apply `f` to each of them under one span.
+    ///
+    /// The SCAN calls and any instrumented commands issued by `f` nest under a
+    /// `redis scan` span recording `redis.scan.processed` and `redis.scan.failed`, plus
+    /// the number of SCAN calls as `redis.command_count`; commands issued by `f` have
+    /// their own spans and are not included.
+    /// A failing key doesn't stop the scan; see [`Self::scan_and_with_policy`].
+    /// `f` runs inside the span, so it can report progress with
+    /// [`crate::common::record_progress`].
+    /// Returns the number of keys `f` was applied to.
+    pub async fn scan_and<F, Fut, T>(&mut self, pattern: &str, f: F) -> RedisResult<usize>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        self.scan_and_with_policy(pattern, ScanErrorPolicy::Continue, f)
+            .await
+    }
+
+    /// Like [`Self::scan_and`], with `policy` deciding whether a failing key aborts the scan
+    pub async fn scan_and_with_policy<F, Fut, T>(
+        &mut self,
+        pattern: &str,
+        policy: ScanErrorPolicy,
+        mut f: F,
+    ) -> RedisResult<usize>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        let span = scan_span(&self.config);
+        let mut tally = ScanTally::default();
+        let result = async {
+            let mut cursor = 0;
+            loop {
+                tally.scans += 1;
+                let reply = self.req_command(&scan_command(cursor, pattern)).await?;
+                let (next, keys) = parse_scan_reply(&reply)?;
+                for key in keys {
+                    tally.apply(key, &mut f, policy).await?;
+                }
+                if next == 0 {
+                    return Ok(tally.processed);
+                }
+                cursor = next;
+            }
+        }
+        .instrument(span.clone())
+        .await;
+        tally.finish(&span, &result);
+        result
+    }
+
+    /// Scan keys matching `pattern` and copy each of them to `dest` with `copy`, under
+    /// one span.
+    ///
+    /// The SCAN calls and the commands `copy` issues against `dest` nest under a
+    /// `redis migrate` span recording `redis.migrate.keys_processed`,
+    /// `redis.migrate.keys_failed` and the number of SCAN calls as
+    /// `redis.command_count`. A key `copy` fails for is counted and skipped.
+    ///
+    /// After each SCAN batch, a `redis.progress` event records the keys processed so
+    /// far and `redis.migrate.cursor`, the cursor of the next batch. If the migration
+    /// is interrupted, passing the last recorded cursor as `start_cursor` resumes it
+    /// without revisiting finished batches; `None` starts a new scan.
+    /// Returns the number of keys `copy` was applied to.
+    pub async fn migrate_keys<D, F, T>(
+        &mut self,
+        pattern: &str,
+        dest: &mut D,
+        start_cursor: Option<u64>,
+        mut copy: F,
+    ) -> RedisResult<usize>
+    where
+        F: AsyncFnMut(String, &mut D) -> RedisResult<T>,
+    {
+        let span = migrate_span(&self.config);
+        if let Some(cursor) = start_cursor {
+            span.record("redis.migrate.start_cursor", cursor);
+        }
+        let mut tally = ScanTally::default();
+        let result = async {
+            let mut cursor = start_cursor.unwrap_or(0);
+            loop {
+                tally.scans += 1;
+                let reply = self.req_command(&scan_command(cursor, pattern)).await?;
+                let (next, keys) = parse_scan_reply(&reply)?;
+                for key in keys {
+                    let copied = copy(key, dest).await;
+                    tally.count(&copied);
+                }
+                tally.record_migration_progress(next);
+                if next == 0 {
+                    return Ok(tally.processed);
+                }
+                cursor = next;
+            }
+        }
+        .instrument(span.clone())
+        .await;
+        tally.finish_migration(&span, &result);
+        result
+    }
+
+    /// Convenience method: GET a key with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("GET", &self.config)))]
+    pub async fn get<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GET").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SET a key with instrumentation
+    #[instrument(skip(self, key, value), fields(db.operation = %method_operation("SET", &self.config)))]
+    pub async fn set<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> RedisResult<()> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SET").arg(key).arg(value);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: DEL keys with instrumentation
+    #[instrument(skip(self, keys), fields(db.operation = %method_operation("DEL", &self.config)))]
+    pub async fn del<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("DEL").arg(keys);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: TOUCH keys to refresh their LRU/LFU access time, with instrumentation
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("TOUCH", &self.config),
+            redis.keys.count = Empty,
+            redis.keys.affected = Empty
+        )
+    )]
+    pub async fn touch<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("TOUCH").arg(keys);
+        let result = self.req_command(&cmd).await;
+        record_multi_key_counts(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: UNLINK keys, reclaiming their memory in the background, with instrumentation
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("UNLINK", &self.config),
+            redis.keys.count = Empty,
+            redis.keys.affected = Empty
+        )
+    )]
+    pub async fn unlink<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("UNLINK").arg(keys);
+        let result = self.req_command(&cmd).await;
+        record_multi_key_counts(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: GETRANGE a substring with instrumentation
+    ///
+    /// `start` and `end` are inclusive byte offsets; negative offsets count from the end.
+    #[instrument(
+        skip(self, key),
+        fields(
+            db.operation = %method_operation("GETRANGE", &self.config),
+            redis.range.start = Empty,
+            redis.range.end = Empty
+        )
+    )]
+    pub async fn getrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        end: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GETRANGE").arg(key).arg(start).arg(end);
+        record_string_range(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SETRANGE to overwrite part of a string with instrumentation
+    ///
+    /// Returns the length of the string after the write.
+    #[instrument(
+        skip(self, key, value),
+        fields(
+            db.operation = %method_operation("SETRANGE", &self.config),
+            redis.range.start = Empty,
+            redis.range.end = Empty,
+            redis.string.length = Empty
+        )
+    )]
+    pub async fn setrange<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        offset: usize,
+        value: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SETRANGE").arg(key).arg(offset).arg(value);
+        record_string_range(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await;
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: STRLEN with instrumentation
+    #[instrument(
+        skip(self, key),
+        fields(db.operation = %method_operation("STRLEN", &self.config), redis.string.length = Empty)
+    )]
+    pub async fn strlen<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("STRLEN").arg(key);
+        let result = self.req_command(&cmd).await;
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: APPEND to a string with instrumentation
+    ///
+    /// Returns the length of the string after the append.
+    #[instrument(
+        skip(self, key, value),
+        fields(db.operation = %method_operation("APPEND", &self.config), redis.string.length = Empty)
+    )]
+    pub async fn append<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("APPEND").arg(key).arg(value);
+        let result = self.req_command(&cmd).await;
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: INCR with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCR", &self.config)))]
+    pub async fn incr<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCR").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: DECR with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("DECR", &self.config)))]
+    pub async fn decr<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("DECR").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: INCRBY with instrumentation
+    ///
+    /// A negative `delta` decrements.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCRBY", &self.config)))]
+    pub async fn incr_by<K: redis::ToRedisArgs>(&mut self, key: K, delta: i64) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCRBY").arg(key).arg(delta);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: INCRBYFLOAT with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCRBYFLOAT", &self.config)))]
+    pub async fn incr_by_float<K: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        delta: f64,
+    ) -> RedisResult<f64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCRBYFLOAT").arg(key).arg(delta);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: CLUSTER INFO with instrumentation
+    ///
+    /// Records the reported `cluster_state` as `redis.cluster.state`.
+    #[instrument(
+        skip(self),
+        fields(
+            db.operation = %method_operation("CLUSTER INFO", &self.config),
+            redis.admin_command = true,
+            redis.cluster.state = Empty
+        )
+    )]
+    pub async fn cluster_info(&mut self) -> RedisResult<String> {
+        let result = self
+            .req_command_raw(redis::cmd("CLUSTER").arg("INFO"), "CLUSTER INFO", &[])
+            .await?;
+        let info: String = convert_reply(&result)?;
+        if let Some(state) = parse_cluster_state(&info) {
+            Span::current().record("redis.cluster.state", state);
+        }
+        Ok(info)
+    }
+
+    /// Convenience method: CLUSTER NODES with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("CLUSTER NODES", &self.config), redis.admin_command = true)
+    )]
+    pub async fn cluster_nodes(&mut self) -> RedisResult<String> {
+        let result = self
+            .req_command_raw(redis::cmd("CLUSTER").arg("NODES"), "CLUSTER NODES", &[])
+            .await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: CLUSTER SHARDS with instrumentation
+    ///
+    /// The reply is returned as is, one entry of slots and nodes per shard.
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("CLUSTER SHARDS", &self.config), redis.admin_command = true)
+    )]
+    pub async fn cluster_shards(&mut self) -> RedisResult<Value> {
+        self.req_command_raw(redis::cmd("CLUSTER").arg("SHARDS"), "CLUSTER SHARDS", &[])
+            .await
+    }
+
+    /// Convenience method: SCRIPT LOAD with instrumentation
+    ///
+    /// Records the returned SHA1 digest as `db.redis.script.sha`.
+    #[instrument(
+        skip(self, script),
+        fields(db.operation = %method_operation("SCRIPT LOAD", &self.config), db.redis.script.sha = Empty)
+    )]
+    pub async fn script_load(&mut self, script: &str) -> RedisResult<String> {
+        let result = self
+            .req_command_raw(
+                redis::cmd("SCRIPT").arg("LOAD").arg(script),
+                "SCRIPT LOAD",
+                &[],
+            )
+            .await?;
+        let sha: String = convert_reply(&result)?;
+        Span::current().record("db.redis.script.sha", sha.as_str());
+        Ok(sha)
+    }
+
+    /// Convenience method: SCRIPT EXISTS with instrumentation
+    ///
+    /// Records how many of the queried scripts are cached as `redis.script.exists_count`.
+    #[instrument(
+        skip(self, shas),
+        fields(
+            db.operation = %method_operation("SCRIPT EXISTS", &self.config),
+            redis.script.count = shas.len(),
+            redis.script.exists_count = Empty
+        )
+    )]
+    pub async fn script_exists(&mut self, shas: &[&str]) -> RedisResult<Vec<bool>> {
+        let result = self
+            .req_command_raw(
+                redis::cmd("SCRIPT").arg("EXISTS").arg(shas),
+                "SCRIPT EXISTS",
+                &[],
+            )
+            .await?;
+        let exists: Vec<bool> = convert_reply(&result)?;
+        let cached = exists.iter().filter(|exists| **exists).count();
+        Span::current().record("redis.script.exists_count", cached as i64);
+        Ok(exists)
+    }
+
+    /// Convenience method: SCRIPT FLUSH with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("SCRIPT FLUSH", &self.config), redis.admin_command = true)
+    )]
+    pub async fn script_flush(&mut self) -> RedisResult<()> {
+        let result = self
+            .req_command_raw(redis::cmd("SCRIPT").arg("FLUSH"), "SCRIPT FLUSH", &[])
+            .await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LATENCY LATEST with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("LATENCY LATEST", &self.config), redis.admin_command = true)
+    )]
+    pub async fn latency_latest<RV: redis::FromRedisValue>(&mut self) -> RedisResult<RV> {
+        let result = self
+            .req_command_raw(redis::cmd("LATENCY").arg("LATEST"), "LATENCY LATEST", &[])
+            .await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LATENCY HISTORY of one event with instrumentation
+    #[instrument(
+        skip(self, event),
+        fields(db.operation = %method_operation("LATENCY HISTORY", &self.config), redis.admin_command = true)
+    )]
+    pub async fn latency_history<RV: redis::FromRedisValue>(
+        &mut self,
+        event: &str,
+    ) -> RedisResult<RV> {
+        let result = self
+            .req_command_raw(
+                redis::cmd("LATENCY").arg("HISTORY").arg(event),
+                "LATENCY HISTORY",
+                &[],
+            )
+            .await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SLOWLOG GET with instrumentation
+    ///
+    /// Fetches up to `count` entries, or the server's default of 10 when `None`, and
+    /// records the number of entries returned as `redis.slowlog.entries`.
+    #[instrument(
+        skip(self),
+        fields(
+            db.operation = %method_operation("SLOWLOG GET", &self.config),
+            redis.admin_command = true,
+            redis.slowlog.entries = Empty
+        )
+    )]
+    pub async fn slowlog_get<RV: redis::FromRedisValue>(
+        &mut self,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::cmd("SLOWLOG");
+        cmd.arg("GET").arg(count);
+        let result = self.req_command_raw(&cmd, "SLOWLOG GET", &[]).await?;
+        if let Value::Array(entries) = &result {
+            Span::current().record("redis.slowlog.entries", entries.len() as i64);
+        }
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SLOWLOG RESET with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("SLOWLOG RESET", &self.config), redis.admin_command = true)
+    )]
+    pub async fn slowlog_reset(&mut self) -> RedisResult<()> {
+        let result = self
+            .req_command_raw(redis::cmd("SLOWLOG").arg("RESET"), "SLOWLOG RESET", &[])
+            .await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SETNX a key, setting it only if it doesn't exist, with instrumentation
+    ///
+    /// Returns `true` and records `redis.conditional.applied = true` if the key was set.
+    #[instrument(
+        skip(self, key, value),
+        fields(db.operation = %method_operation("SETNX", &self.config), redis.conditional.applied = Empty)
+    )]
+    pub async fn setnx<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SETNX").arg(key).arg(value);
+        let result = self.req_command(&cmd).await;
+        record_reply_attributes(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: MSETNX several keys, setting them only if none exists, with instrumentation
+    ///
+    /// MSETNX is all-or-nothing: `false`, recorded as `redis.conditional.applied = false`,
+    /// means none of the keys were set. `redis.keys.count` is the number of pairs.
+    #[instrument(
+        skip(self, items),
+        fields(
+            db.operation = %method_operation("MSETNX", &self.config),
+            redis.keys.count = items.len() as i64,
+            redis.conditional.applied = Empty
+        )
+    )]
+    pub async fn msetnx<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        items: &[(K, V)],
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MSETNX").arg(items);
+        let result = self.req_command(&cmd).await;
+        record_reply_attributes(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: GET several keys with one MGET, with instrumentation
+    ///
+    /// Keys repeated in `keys` are fetched once: the MGET carries each distinct key
+    /// and its value is fanned back out, so the result has one entry per input key,
+    /// in input order. `redis.keys.count` is the number of input keys and
+    /// `redis.batch.deduped` the number of repeats left out of the MGET.
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("MGET", &self.config),
+            redis.keys.count = keys.len() as i64,
+            redis.batch.deduped = Empty
+        )
+    )]
+    pub async fn get_many<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        keys: &[K],
+    ) -> RedisResult<Vec<RV>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (unique, positions) = dedupe_keys(keys);
+        Span::current().record("redis.batch.deduped", (keys.len() - unique.len()) as i64);
+
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MGET").arg(&unique);
+        let values: Vec<Value> = convert_reply(&self.req_command(&cmd).await?)?;
+        positions
+            .into_iter()
+            .map(|position| match values.get(position) {
+                Some(value) => convert_reply(value),
+                None => Err(RedisError::from((
+                    redis::ErrorKind::TypeError,
+                    "MGET returned fewer values than keys",
+                ))),
+            })
+            .collect()
+    }
+
+    /// Convenience method: MGET several keys with instrumentation
+    ///
+    /// Returns one value per key, in order, with nil for missing keys. Unlike
+    /// [`Self::get_many`], repeated keys are sent as given.
+    #[instrument(
+        skip(self, keys),
+        fields(db.operation = %method_operation("MGET", &self.config), redis.keys.count = keys.len() as i64)
+    )]
+    pub async fn mget<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        keys: &[K],
+    ) -> RedisResult<Vec<RV>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MGET").arg(keys);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: MSET several key-value pairs with instrumentation
+    ///
+    /// `redis.keys.count` is the number of pairs.
+    #[instrument(
+        skip(self, items),
+        fields(db.operation = %method_operation("MSET", &self.config), redis.keys.count = items.len() as i64)
+    )]
+    pub async fn mset<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        items: &[(K, V)],
+    ) -> RedisResult<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MSET").arg(items);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: GETDEL a key, returning its value, with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("GETDEL", &self.config)))]
+    pub async fn getdel<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GETDEL").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: GETEX a key, returning its value and updating its TTL, with instrumentation
+    #[instrument(
+        skip(self, key, expiry),
+        fields(
+            db.operation = %method_operation("GETEX", &self.config),
+            redis.ttl.mode = Empty,
+            redis.ttl.value = Empty
+        )
+    )]
+    pub async fn getex<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        expiry: redis::Expiry,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GETEX").arg(key).arg(expiry);
+        record_getex_expiry(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: EXISTS check with instrumentation
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("EXISTS", &self.config),
+            redis.exists.found = Empty,
+            redis.exists.found_count = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty
+        )
+    )]
+    pub async fn exists<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("EXISTS").arg(keys);
+        let result = self.req_command(&cmd).await;
+        record_exists_result(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: EXPIRE key with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("EXPIRE", &self.config)))]
+    pub async fn expire<K: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        seconds: usize,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("EXPIRE").arg(key).arg(seconds);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: HGET hash field with instrumentation
+    #[instrument(skip(self, key, field), fields(db.operation = %method_operation("HGET", &self.config)))]
+    pub async fn hget<K: redis::ToRedisArgs, F: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        field: F,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("HGET").arg(key).arg(field);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: HSET hash field with instrumentation
+    #[instrument(
+        skip(self, key, field, value),
+        fields(
+            db.operation = %method_operation("HSET", &self.config),
+            redis.hash.fields_set = Empty,
+            redis.hash.new_fields = Empty
+        )
+    )]
+    pub async fn hset<K: redis::ToRedisArgs, F: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        field: F,
+        value: V,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("HSET").arg(key).arg(field).arg(value);
+        let result = self.req_command(&cmd).await;
+        record_hash_set_fields(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: HSET multiple hash fields with instrumentation
+    #[instrument(
+        skip(self, key, items),
+        fields(
+            db.operation = %method_operation("HSET", &self.config),
+            redis.hash.fields_set = Empty,
+            redis.hash.new_fields = Empty
+        )
+    )]
+    pub async fn hset_multiple<
+        K: redis::ToRedisArgs,
+        F: redis::ToRedisArgs,
+        V: redis::ToRedisArgs,
+    >(
+        &mut self,
+        key: K,
+        items: &[(F, V)],
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("HSET").arg(key).arg(items);
+        let result = self.req_command(&cmd).await;
+        record_hash_set_fields(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: ZADD a member with instrumentation
+    ///
+    /// `flags` are sent before the score and recorded as `redis.zset.flags`.
+    #[instrument(
+        skip(self, key, member, score, flags),
+        fields(db.operation = %method_operation("ZADD", &self.config), redis.zset.flags = Empty)
+    )]
+    pub async fn zadd<K: redis::ToRedisArgs, M: redis::ToRedisArgs, S: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        member: M,
+        score: S,
+        flags: &[ZAddFlag],
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZADD").arg(key).arg(flags).arg(score).arg(member);
+        record_zadd_flags(&Span::current(), flags);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZRANGEBYSCORE with instrumentation
+    ///
+    /// Records the parsed score range, including `(` exclusive and `-inf`/`+inf` bounds.
+    #[instrument(
+        skip(self, key, min, max),
+        fields(
+            db.operation = %method_operation("ZRANGEBYSCORE", &self.config),
+            redis.zset.min = Empty,
+            redis.zset.max = Empty,
+            redis.zset.min_exclusive = Empty,
+            redis.zset.max_exclusive = Empty
+        )
+    )]
+    pub async fn zrangebyscore<
+        K: redis::ToRedisArgs,
+        M: redis::ToRedisArgs,
+        MM: redis::ToRedisArgs,
+        RV: redis::FromRedisValue,
+    >(
+        &mut self,
+        key: K,
+        min: M,
+        max: MM,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZRANGEBYSCORE").arg(key).arg(min).arg(max);
+        record_score_bounds(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZREVRANGEBYSCORE with instrumentation
+    ///
+    /// Records the parsed score range with `redis.zset.min` as the lower bound, even
+    /// though the command takes `max` first.
+    #[instrument(
+        skip(self, key, max, min),
+        fields(
+            db.operation = %method_operation("ZREVRANGEBYSCORE", &self.config),
+            redis.zset.min = Empty,
+            redis.zset.max = Empty,
+            redis.zset.min_exclusive = Empty,
+            redis.zset.max_exclusive = Empty
+        )
+    )]
+    pub async fn zrevrangebyscore<
+        K: redis::ToRedisArgs,
+        MM: redis::ToRedisArgs,
+        M: redis::ToRedisArgs,
+        RV: redis::FromRedisValue,
+    >(
+        &mut self,
+        key: K,
+        max: MM,
+        min: M,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZREVRANGEBYSCORE").arg(key).arg(max).arg(min);
+        record_score_bounds(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZREM members from a sorted set with instrumentation
+    #[instrument(skip(self, key, members), fields(db.operation = %method_operation("ZREM", &self.config)))]
+    pub async fn zrem<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        members: M,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZREM").arg(key).arg(members);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZSCORE with instrumentation
+    ///
+    /// A missing member or key replies nil, so `RV` is typically `Option<f64>`.
+    #[instrument(skip(self, key, member), fields(db.operation = %method_operation("ZSCORE", &self.config)))]
+    pub async fn zscore<K: redis::ToRedisArgs, M: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        member: M,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZSCORE").arg(key).arg(member);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZRANGE by rank with instrumentation
+    ///
+    /// `start` and `stop` are inclusive and may be negative to count from the highest
+    /// score.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("ZRANGE", &self.config)))]
+    pub async fn zrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        stop: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZRANGE").arg(key).arg(start).arg(stop);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SADD to set with instrumentation
+    #[instrument(skip(self, key, members), fields(db.operation = %method_operation("SADD", &self.config)))]
+    pub async fn sadd<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        members: M,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SADD").arg(key).arg(members);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SISMEMBER check with instrumentation
+    #[instrument(skip(self, key, member), fields(db.operation = %method_operation("SISMEMBER", &self.config)))]
+    pub async fn sismember<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        member: M,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SISMEMBER").arg(key).arg(member);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SMOVE a member between sets with instrumentation
+    #[instrument(
+        skip(self, source, destination, member),
+        fields(
+            db.operation = %method_operation("SMOVE", &self.config),
+            redis.smove.source = Empty,
+            redis.smove.destination = Empty
+        )
+    )]
+    pub async fn smove<S: redis::ToRedisArgs, D: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        source: S,
+        destination: D,
+        member: M,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SMOVE").arg(source).arg(destination).arg(member);
+        record_smove_keys_with_config(&Span::current(), &cmd, &self.config);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SPOP random members with instrumentation
+    ///
+    /// Without `count` a single member (or nil) is returned, with it a list.
+    #[instrument(
+        skip(self, key),
+        fields(db.operation = %method_operation("SPOP", &self.config), redis.spop.count = Empty)
+    )]
+    pub async fn spop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SPOP").arg(key).arg(count);
+        record_set_sample_count(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SRANDMEMBER with instrumentation
+    ///
+    /// A negative `count` may return duplicates and records `redis.set.allow_duplicates`.
+    #[instrument(
+        skip(self, key),
+        fields(
+            db.operation = %method_operation("SRANDMEMBER", &self.config),
+            redis.srandmember.count = Empty,
+            redis.set.allow_duplicates = Empty
+        )
+    )]
+    pub async fn srandmember<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<isize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SRANDMEMBER").arg(key).arg(count);
+        record_set_sample_count(&Span::current(), &cmd);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LPUSH values onto a list with instrumentation
+    #[instrument(skip(self, key, values), fields(db.operation = %method_operation("LPUSH", &self.config)))]
+    pub async fn lpush<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        values: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LPUSH").arg(key).arg(values);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: RPUSH values onto a list with instrumentation
+    #[instrument(skip(self, key, values), fields(db.operation = %method_operation("RPUSH", &self.config)))]
+    pub async fn rpush<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        values: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("RPUSH").arg(key).arg(values);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LPOP with instrumentation
+    ///
+    /// Without `count` a single element (or nil) is returned, with it a list.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LPOP", &self.config)))]
+    pub async fn lpop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LPOP").arg(key).arg(count);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: RPOP with instrumentation
+    ///
+    /// Without `count` a single element (or nil) is returned, with it a list.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("RPOP", &self.config)))]
+    pub async fn rpop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("RPOP").arg(key).arg(count);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LLEN with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LLEN", &self.config)))]
+    pub async fn llen<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LLEN").arg(key);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LRANGE with instrumentation
+    ///
+    /// `start` and `stop` are inclusive and may be negative to count from the tail.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LRANGE", &self.config)))]
+    pub async fn lrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        stop: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LRANGE").arg(key).arg(start).arg(stop);
+        let result = self.req_command(&cmd).await?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: PUBLISH with instrumentation, returning the number of clients
+    /// that received the message
+    ///
+    /// The channel is recorded as `redis.pubsub.channel`, after the configured key
+    /// redactions, and the number of receivers as `redis.pubsub.receivers`.
+    #[instrument(
+        skip(self, channel, message),
+        fields(
+            db.operation = %method_operation("PUBLISH", &self.config),
+            redis.pubsub.channel = Empty,
+            redis.pubsub.receivers = Empty
+        )
+    )]
+    pub async fn publish<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        channel: K,
+        message: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("PUBLISH").arg(channel).arg(message);
+        let span = Span::current();
+        record_publish_channel(&span, &cmd, &self.config);
+        let result = self.req_command(&cmd).await?;
+        let receivers: i64 = convert_reply(&result)?;
+        span.record("redis.pubsub.receivers", receivers);
+        Ok(receivers)
+    }
+}
+
+/// Emits a `redis.reconnect` event under `span` when `result` shows the server
+/// connection of a reconnecting connection dropped.
+///
+/// `ConnectionManager` doesn't report its reconnect attempts; it starts one in the
+/// background when a command fails with a connection-level error, which is what the
+/// event marks.
+fn record_reconnect<C: SharedConnection, T>(span: &Span, result: &RedisResult<T>) {
+    let Err(err) = result else {
+        return;
+    };
+    if C::RECONNECTS
+        && (err.is_connection_dropped() || err.is_connection_refusal() || err.is_io_error())
+    {
+        tracing::info!(
+            parent: span,
+            error.message = %err,
+            "redis.reconnect"
+        );
+    }
+}
+
+/// Records whether a packed command's reply arrived on its span, see
+/// [`InstrumentedMultiplexedConnection::send_packed_command`]
+struct PendingReply {
+    span: Span,
+    received: bool,
+}
+
+impl PendingReply {
+    fn new(span: &Span) -> Self {
+        Self {
+            span: span.clone(),
+            received: false,
+        }
+    }
+
+    /// Marks the reply as received
+    fn received(mut self) {
+        self.received = true;
+    }
+}
+
+impl Drop for PendingReply {
+    fn drop(&mut self) {
+        self.span.record("redis.reply.received", self.received);
+    }
+}
+
+/// Lets the wrapper stand in for a multiplexed connection in redis-rs's generic APIs,
+/// such as [`redis::AsyncCommands`] and `query_async`.
+///
+/// Commands issued this way are instrumented exactly like calls to the inherent methods:
+///
+/// ```rust,no_run
+/// use otel_instrumentation_redis::InstrumentedClient;
+/// use redis::AsyncCommands;
+///
+/// # async fn example() -> redis::RedisResult<()> {
+/// let client = InstrumentedClient::new(redis::Client::open("redis://127.0.0.1/")?);
+/// let mut conn = client.get_multiplexed_async_connection().await?;
+/// // Produces a `redis zcard` span with `db.operation = ZCARD`
+/// let players: i64 = conn.zcard("leaderboard").await?;
+/// # Ok(())
+/// # }
+/// ```
+impl<C: SharedConnection> ConnectionLike for InstrumentedMultiplexedConnection<C> {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> redis::RedisFuture<'a, Value> {
+        Box::pin(self.req_command(cmd))
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> redis::RedisFuture<'a, Vec<Value>> {
+        Box::pin(Self::req_packed_commands(self, cmd, offset, count))
+    }
+
+    fn get_db(&self) -> i64 {
+        self.inner.get_db()
+    }
+}
+
+/// An instrumented connection usable as a trait object.
+///
+/// The wrappers are generic over the connection they wrap, which rules them out where
+/// the connection is chosen at runtime. Holding them as `Box<dyn InstrumentedRedis +
+/// Send>` instead lets such code send commands through any of them:
+///
+/// ```rust,no_run
+/// use otel_instrumentation_redis::aio::InstrumentedRedis;
+/// use otel_instrumentation_redis::InstrumentedClient;
+///
+/// # async fn example() -> redis::RedisResult<()> {
+/// let client = InstrumentedClient::new(redis::Client::open("redis://127.0.0.1/")?);
+/// let mut conn: Box<dyn InstrumentedRedis + Send> =
+///     Box::new(client.get_multiplexed_async_connection().await?);
+/// conn.req_command(&redis::cmd("PING")).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait InstrumentedRedis {
+    /// Executes `cmd` with tracing, like the wrapper's inherent `req_command`
+    fn req_command<'a>(&'a mut self, cmd: &'a Cmd) -> redis::RedisFuture<'a, Value>;
+}
+
+impl<C: ConnectionLike + Send> InstrumentedRedis for InstrumentedAsyncConnection<C> {
+    fn req_command<'a>(&'a mut self, cmd: &'a Cmd) -> redis::RedisFuture<'a, Value> {
+        Box::pin(Self::req_command(self, cmd))
+    }
+}
+
+impl<C: SharedConnection> InstrumentedRedis for InstrumentedMultiplexedConnection<C> {
+    fn req_command<'a>(&'a mut self, cmd: &'a Cmd) -> redis::RedisFuture<'a, Value> {
+        Box::pin(Self::req_command(self, cmd))
+    }
+}
+
+/// Wraps any future in a span covering a composite Redis operation.
+///
+/// Use this for work that issues several commands through code you can't instrument
+/// per command, such as a distributed-lock crate built on redis-rs. The span is named
+/// `redis <name>`, carries `db.system = "redis"` and `db.operation = name`, and is
+/// marked as failed when `redis_error` finds a `RedisError` in the future's output.
+/// For a future that resolves to a [`redis::RedisResult`], use
+/// [`instrument_redis_result`].
+///
+/// # Example
+/// ```rust,ignore
+/// use otel_instrumentation_redis::aio::instrument_redis_operation;
+///
+/// let guard = instrument_redis_operation(
+///     "lock_acquire",
+///     lock_manager.lock("resource"),
+///     |result| match result {
+///         Err(LockError::Redis(err)) => Some(err),
+///         _ => None,
+///     },
+/// )
+/// .await?;
+/// ```
+pub fn instrument_redis_operation<F, E>(
+    name: &str,
+    fut: F,
+    redis_error: E,
+) -> impl Future<Output = F::Output>
+where
+    F: Future,
+    E: FnOnce(&F::Output) -> Option<&RedisError>,
+{
+    let span = tracing::info_span!(
+        "redis_operation",
+        otel.name = %generate_span_name(name),
+        db.system = "redis",
+        db.operation = %name,
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        error = Empty,
+        error.message = Empty,
+        error.type = Empty
+    );
+
+    async move {
+        let output = fut.instrument(span.clone()).await;
+        match redis_error(&output) {
+            Some(err) => record_error_on_span(&span, err),
+            None => {
+                span.record("otel.status_code", "OK");
+            }
+        }
+        output
+    }
+}
+
+/// Like [`instrument_redis_operation`], for a future that resolves to a
+/// [`redis::RedisResult`]: the span is marked as failed when it resolves to an error.
+///
+/// # Example
+/// ```rust,ignore
+/// use otel_instrumentation_redis::aio::instrument_redis_result;
+///
+/// let released: i64 = instrument_redis_result("lock_release", release(&mut conn, "resource")).await?;
+/// ```
+pub fn instrument_redis_result<F, T>(
+    name: &str,
+    fut: F,
+) -> impl Future<Output = redis::RedisResult<T>>
+where
+    F: Future<Output = redis::RedisResult<T>>,
+{
+    instrument_redis_operation(name, fut, |result| result.as_ref().err())
 }
@@ -0,0 +1,483 @@
+//! Instrumented wrappers for the split halves of an async pub/sub connection
+
+use crate::common::{generate_span_name, record_command_result, record_subscription_names};
+use crate::config::InstrumentationConfig;
+use futures_util::Stream;
+use redis::aio::{PubSub, PubSubSink, PubSubStream};
+use redis::{FromRedisValue, Msg, RedisResult, ToRedisArgs};
+use std::collections::BTreeSet;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tracing::field::Empty;
+use tracing::Instrument;
+
+/// Number of channels and patterns currently subscribed through a split pub/sub connection.
+///
+/// The sink and stream halves each hold a clone of the same counter, so the
+/// stream can report how many subscriptions were active when a message arrived
+/// while the sink manages them. The sink sets it from its [`Subscriptions`], so a
+/// name subscribed twice counts once and unsubscribing an unknown name changes
+/// nothing.
+#[derive(Debug, Clone, Default)]
+pub struct ActiveChannels {
+    count: Arc<AtomicUsize>,
+}
+
+impl ActiveChannels {
+    /// Creates a counter with no active subscriptions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current number of active subscriptions
+    pub fn get(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Records `n` new subscriptions
+    pub fn add(&self, n: usize) {
+        self.count.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Records `n` removed subscriptions, never going below zero
+    pub fn remove(&self, n: usize) {
+        let _ = self
+            .count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(n))
+            });
+    }
+
+    /// Forgets all subscriptions, as after an argument-less UNSUBSCRIBE
+    pub fn clear(&self) {
+        self.count.store(0, Ordering::Relaxed);
+    }
+
+    /// Sets the number of active subscriptions to `n`
+    pub(crate) fn set(&self, n: usize) {
+        self.count.store(n, Ordering::Relaxed);
+    }
+}
+
+/// Channels and patterns subscribed through a split pub/sub connection's sink.
+///
+/// Clones share the same names, so subscriptions made through any clone of the sink
+/// are re-established by [`InstrumentedPubSubSink::reconnect`].
+#[derive(Debug, Clone, Default)]
+pub struct Subscriptions {
+    names: Arc<Mutex<SubscribedNames>>,
+}
+
+#[derive(Debug, Default)]
+struct SubscribedNames {
+    channels: BTreeSet<Vec<u8>>,
+    patterns: BTreeSet<Vec<u8>>,
+}
+
+impl SubscribedNames {
+    fn of_kind(&mut self, pattern: bool) -> &mut BTreeSet<Vec<u8>> {
+        if pattern {
+            &mut self.patterns
+        } else {
+            &mut self.channels
+        }
+    }
+}
+
+impl Subscriptions {
+    /// Creates an empty set of subscriptions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `names` as subscribed channels, or patterns if `pattern` is set
+    pub fn add(&self, names: Vec<Vec<u8>>, pattern: bool) {
+        self.names.lock().unwrap().of_kind(pattern).extend(names);
+    }
+
+    /// Records `names` as unsubscribed channels, or patterns if `pattern` is set.
+    ///
+    /// No names drops every channel, or every pattern, as an argument-less
+    /// UNSUBSCRIBE or PUNSUBSCRIBE does.
+    pub fn remove(&self, names: Vec<Vec<u8>>, pattern: bool) {
+        let mut subscribed = self.names.lock().unwrap();
+        let subscribed = subscribed.of_kind(pattern);
+        if names.is_empty() {
+            subscribed.clear();
+        }
+        for name in names {
+            subscribed.remove(&name);
+        }
+    }
+
+    /// Returns the subscribed channels, in byte order
+    pub fn channels(&self) -> Vec<Vec<u8>> {
+        self.names
+            .lock()
+            .unwrap()
+            .channels
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the subscribed patterns, in byte order
+    pub fn patterns(&self) -> Vec<Vec<u8>> {
+        self.names
+            .lock()
+            .unwrap()
+            .patterns
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the number of subscribed channels and patterns
+    pub fn len(&self) -> usize {
+        let names = self.names.lock().unwrap();
+        names.channels.len() + names.patterns.len()
+    }
+
+    /// Returns `true` if nothing is subscribed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Splits an async pub/sub connection into instrumented sink and stream halves
+/// sharing one [`ActiveChannels`] counter.
+pub fn split_pubsub(pubsub: PubSub) -> (InstrumentedPubSubSink, InstrumentedPubSubStream) {
+    split_pubsub_with_config(pubsub, Arc::new(InstrumentationConfig::default()))
+}
+
+/// Splits an async pub/sub connection like [`split_pubsub`], with the spans of both
+/// halves recorded according to a shared configuration.
+pub fn split_pubsub_with_config(
+    pubsub: PubSub,
+    config: Arc<InstrumentationConfig>,
+) -> (InstrumentedPubSubSink, InstrumentedPubSubStream) {
+    let (sink, stream) = pubsub.split();
+    let active_channels = ActiveChannels::new();
+    (
+        InstrumentedPubSubSink::with_config(sink, active_channels.clone(), config.clone()),
+        InstrumentedPubSubStream::with_config(stream, active_channels, config),
+    )
+}
+
+/// An instrumented wrapper around `redis::aio::PubSubSink`
+///
+/// Every subscribe and unsubscribe call produces a span carrying the number of
+/// channels in the request and the number of subscriptions active afterwards. The
+/// names themselves are recorded as `redis.pubsub.channels`, or `redis.pubsub.patterns`
+/// for pattern subscriptions, up to [`crate::common::MAX_PUBSUB_NAMES`] and passed through
+/// [`InstrumentationConfig::redact_key`]. The subscribed names are tracked so
+/// [`Self::reconnect`] can restore them.
+#[derive(Clone)]
+pub struct InstrumentedPubSubSink {
+    inner: PubSubSink,
+    active_channels: ActiveChannels,
+    subscriptions: Subscriptions,
+    config: Arc<InstrumentationConfig>,
+}
+
+impl InstrumentedPubSubSink {
+    /// Create a new instrumented sink sharing `active_channels` with its stream half
+    pub fn new(sink: PubSubSink, active_channels: ActiveChannels) -> Self {
+        Self::with_config(
+            sink,
+            active_channels,
+            Arc::new(InstrumentationConfig::default()),
+        )
+    }
+
+    /// Create a new instrumented sink with a shared configuration
+    pub fn with_config(
+        sink: PubSubSink,
+        active_channels: ActiveChannels,
+        config: Arc<InstrumentationConfig>,
+    ) -> Self {
+        Self {
+            inner: sink,
+            active_channels,
+            subscriptions: Subscriptions::new(),
+            config,
+        }
+    }
+
+    /// Get the underlying sink
+    pub fn inner(&self) -> &PubSubSink {
+        &self.inner
+    }
+
+    /// Get the shared subscription counter
+    pub fn active_channels(&self) -> &ActiveChannels {
+        &self.active_channels
+    }
+
+    /// Get the channels and patterns subscribed through this sink and its clones
+    pub fn subscriptions(&self) -> &Subscriptions {
+        &self.subscriptions
+    }
+
+    /// Subscribe to channel(s) with tracing
+    pub async fn subscribe(&mut self, channel_name: impl ToRedisArgs) -> RedisResult<()> {
+        let names = channel_name.to_redis_args();
+        let channels = names.len();
+        let span = subscription_span("SUBSCRIBE", channels, &self.config);
+        record_subscription_names(&span, &names, false, &self.config);
+        let result = self
+            .inner
+            .subscribe(channel_name)
+            .instrument(span.clone())
+            .await;
+        if result.is_ok() {
+            self.subscriptions.add(names, false);
+            self.sync_active_channels();
+        }
+        self.finish(&span, &result);
+        result
+    }
+
+    /// Unsubscribe from channel(s) with tracing
+    pub async fn unsubscribe(&mut self, channel_name: impl ToRedisArgs) -> RedisResult<()> {
+        let names = channel_name.to_redis_args();
+        let channels = names.len();
+        let span = subscription_span("UNSUBSCRIBE", channels, &self.config);
+        record_subscription_names(&span, &names, false, &self.config);
+        let result = self
+            .inner
+            .unsubscribe(channel_name)
+            .instrument(span.clone())
+            .await;
+        if result.is_ok() {
+            self.subscriptions.remove(names, false);
+            self.sync_active_channels();
+        }
+        self.finish(&span, &result);
+        result
+    }
+
+    /// Subscribe to channel pattern(s) with tracing
+    pub async fn psubscribe(&mut self, channel_pattern: impl ToRedisArgs) -> RedisResult<()> {
+        let names = channel_pattern.to_redis_args();
+        let patterns = names.len();
+        let span = subscription_span("PSUBSCRIBE", patterns, &self.config);
+        record_subscription_names(&span, &names, true, &self.config);
+        let result = self
+            .inner
+            .psubscribe(channel_pattern)
+            .instrument(span.clone())
+            .await;
+        if result.is_ok() {
+            self.subscriptions.add(names, true);
+            self.sync_active_channels();
+        }
+        self.finish(&span, &result);
+        result
+    }
+
+    /// Unsubscribe from channel pattern(s) with tracing
+    pub async fn punsubscribe(&mut self, channel_pattern: impl ToRedisArgs) -> RedisResult<()> {
+        let names = channel_pattern.to_redis_args();
+        let patterns = names.len();
+        let span = subscription_span("PUNSUBSCRIBE", patterns, &self.config);
+        record_subscription_names(&span, &names, true, &self.config);
+        let result = self
+            .inner
+            .punsubscribe(channel_pattern)
+            .instrument(span.clone())
+            .await;
+        if result.is_ok() {
+            self.subscriptions.remove(names, true);
+            self.sync_active_channels();
+        }
+        self.finish(&span, &result);
+        result
+    }
+
+    /// Ping the server with tracing, e.g. to keep an idle subscriber connection alive
+    pub async fn ping<T: FromRedisValue>(&mut self) -> RedisResult<T> {
+        let span = ping_span(&self.config);
+        let result = self.inner.ping().instrument(span.clone()).await;
+        self.finish(&span, &result);
+        result
+    }
+
+    /// Re-establish the tracked subscriptions on `pubsub`, a new connection replacing
+    /// one that dropped, with tracing.
+    ///
+    /// redis-rs doesn't reconnect pub/sub connections by itself, so once the stream
+    /// half ends the caller opens a new connection, e.g. with
+    /// `client.inner().get_async_pubsub()`, and hands it over. The sink switches to it
+    /// and subscribes again to every channel and pattern subscribed through it or its
+    /// clones, under a `redis reconnect` span recording how many were restored as
+    /// `redis.pubsub.resubscribed_channels`. Returns the new connection's stream half.
+    /// Clones of the sink made earlier keep using the old connection.
+    pub async fn reconnect(&mut self, pubsub: PubSub) -> RedisResult<InstrumentedPubSubStream> {
+        let (sink, stream) = pubsub.split();
+        self.inner = sink;
+        self.active_channels.clear();
+
+        let (channels, patterns) = (self.subscriptions.channels(), self.subscriptions.patterns());
+        let span = reconnect_span(&self.config);
+        let result = async {
+            let mut resubscribed = 0;
+            if !channels.is_empty() {
+                self.inner.subscribe(&channels).await?;
+                resubscribed += channels.len();
+            }
+            if !patterns.is_empty() {
+                self.inner.psubscribe(&patterns).await?;
+                resubscribed += patterns.len();
+            }
+            Ok(resubscribed)
+        }
+        .instrument(span.clone())
+        .await;
+        if let Ok(resubscribed) = result {
+            self.active_channels.add(resubscribed);
+            span.record("redis.pubsub.resubscribed_channels", resubscribed as i64);
+        }
+        self.finish(&span, &result);
+
+        result.map(|_| {
+            InstrumentedPubSubStream::with_config(
+                stream,
+                self.active_channels.clone(),
+                self.config.clone(),
+            )
+        })
+    }
+
+    /// Counts the subscriptions held after a subscribe or unsubscribe
+    fn sync_active_channels(&self) {
+        self.active_channels.set(self.subscriptions.len());
+    }
+
+    fn finish<T>(&self, span: &tracing::Span, result: &RedisResult<T>) {
+        span.record(
+            "redis.pubsub.active_channels",
+            self.active_channels.get() as i64,
+        );
+        record_command_result(span, result);
+    }
+}
+
+/// An instrumented wrapper around `redis::aio::PubSubStream`
+///
+/// Each delivered message produces a span recording its channel, payload size
+/// and the number of subscriptions active on the connection.
+pub struct InstrumentedPubSubStream {
+    inner: PubSubStream,
+    active_channels: ActiveChannels,
+    config: Arc<InstrumentationConfig>,
+}
+
+impl InstrumentedPubSubStream {
+    /// Create a new instrumented stream sharing `active_channels` with its sink half
+    pub fn new(stream: PubSubStream, active_channels: ActiveChannels) -> Self {
+        Self::with_config(
+            stream,
+            active_channels,
+            Arc::new(InstrumentationConfig::default()),
+        )
+    }
+
+    /// Create a new instrumented stream with a shared configuration
+    pub fn with_config(
+        stream: PubSubStream,
+        active_channels: ActiveChannels,
+        config: Arc<InstrumentationConfig>,
+    ) -> Self {
+        Self {
+            inner: stream,
+            active_channels,
+            config,
+        }
+    }
+
+    /// Get the underlying stream
+    pub fn inner(&self) -> &PubSubStream {
+        &self.inner
+    }
+
+    /// Get the shared subscription counter
+    pub fn active_channels(&self) -> &ActiveChannels {
+        &self.active_channels
+    }
+}
+
+impl Stream for InstrumentedPubSubStream {
+    type Item = Msg;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Msg>> {
+        let poll = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(Some(msg)) = &poll {
+            let _span = tracing::info_span!(
+                "redis_pubsub_message",
+                otel.name = "redis message",
+                db.system = self.config.db_system_attribute().then_some("redis"),
+                db.operation = "MESSAGE",
+                redis.pubsub.channel = msg.get_channel_name(),
+                redis.pubsub.pattern = msg.from_pattern(),
+                redis.pubsub.payload_size = msg.get_payload_bytes().len() as i64,
+                redis.pubsub.active_channels = self.active_channels.get() as i64
+            );
+        }
+        poll
+    }
+}
+
+fn subscription_span(
+    operation: &'static str,
+    channels: usize,
+    config: &InstrumentationConfig,
+) -> tracing::Span {
+    tracing::info_span!(
+        "redis_pubsub",
+        otel.name = %generate_span_name(operation),
+        db.system = config.db_system_attribute().then_some("redis"),
+        db.operation = operation,
+        redis.pubsub.channel_count = channels as i64,
+        redis.pubsub.channels = Empty,
+        redis.pubsub.patterns = Empty,
+        redis.pubsub.active_channels = Empty,
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        error = Empty,
+        error.message = Empty,
+        error.type = Empty
+    )
+}
+
+fn ping_span(config: &InstrumentationConfig) -> tracing::Span {
+    tracing::info_span!(
+        "redis_pubsub",
+        otel.name = "redis ping",
+        db.system = config.db_system_attribute().then_some("redis"),
+        db.operation = "PING",
+        redis.pubsub.active_channels = Empty,
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        error = Empty,
+        error.message = Empty,
+        error.type = Empty
+    )
+}
+
+fn reconnect_span(config: &InstrumentationConfig) -> tracing::Span {
+    tracing::info_span!(
+        "redis_pubsub_reconnect",
+        otel.name = "redis reconnect",
+        db.system = config.db_system_attribute().then_some("redis"),
+        redis.pubsub.resubscribed_channels = Empty,
+        redis.pubsub.active_channels = Empty,
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        error = Empty,
+        error.message = Empty,
+        error.type = Empty
+    )
+}
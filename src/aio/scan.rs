@@ -0,0 +1,130 @@
+//! Shared pieces of the `scan_and` and `migrate_keys` helpers on the async wrappers
+
+use crate::common::{generate_span_name, record_command_count, record_command_result};
+use crate::config::InstrumentationConfig;
+use redis::{Cmd, FromRedisValue, RedisResult, Value};
+use std::future::Future;
+use tracing::field::Empty;
+use tracing::Span;
+
+/// What `scan_and` does when the closure fails for one key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanErrorPolicy {
+    /// Count the failure in `redis.scan.failed` and move on to the next key
+    #[default]
+    Continue,
+    /// Stop scanning and return the key's error
+    Abort,
+}
+
+/// SCAN calls issued so far, keys handed to the closure, and how many of them failed
+#[derive(Debug, Default)]
+pub(crate) struct ScanTally {
+    pub(crate) scans: usize,
+    pub(crate) processed: usize,
+    pub(crate) failed: usize,
+}
+
+impl ScanTally {
+    /// Applies `f` to `key`, returning the error only if `policy` says to abort
+    pub(crate) async fn apply<F, Fut, T>(
+        &mut self,
+        key: String,
+        f: &mut F,
+        policy: ScanErrorPolicy,
+    ) -> RedisResult<()>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        let result = f(key).await;
+        self.count(&result);
+        match (result, policy) {
+            (Err(err), ScanErrorPolicy::Abort) => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a key the closure was applied to, and whether it failed
+    pub(crate) fn count<T>(&mut self, result: &RedisResult<T>) {
+        self.processed += 1;
+        if result.is_err() {
+            self.failed += 1;
+        }
+    }
+
+    /// Records the counts and the overall outcome on the scan span
+    pub(crate) fn finish(&self, span: &Span, result: &RedisResult<usize>) {
+        span.record("redis.scan.processed", self.processed as i64);
+        span.record("redis.scan.failed", self.failed as i64);
+        record_command_count(span, self.scans);
+        record_command_result(span, result);
+    }
+
+    /// Records the counts and the overall outcome on the migration span
+    pub(crate) fn finish_migration(&self, span: &Span, result: &RedisResult<usize>) {
+        span.record("redis.migrate.keys_processed", self.processed as i64);
+        span.record("redis.migrate.keys_failed", self.failed as i64);
+        record_command_count(span, self.scans);
+        record_command_result(span, result);
+    }
+
+    /// Emits a `redis.progress` event for the keys migrated so far, with the cursor to
+    /// resume from
+    pub(crate) fn record_migration_progress(&self, next_cursor: u64) {
+        tracing::info!(
+            redis.progress.processed = self.processed as u64,
+            redis.migrate.keys_failed = self.failed as u64,
+            redis.migrate.cursor = next_cursor,
+            "redis.progress"
+        );
+    }
+}
+
+/// The parent span covering every SCAN call and per-key operation of one `scan_and`
+pub(crate) fn scan_span(config: &InstrumentationConfig) -> Span {
+    tracing::info_span!(
+        "redis_scan",
+        otel.name = %generate_span_name("scan"),
+        db.system = config.db_system_attribute().then_some("redis"),
+        db.operation = "SCAN",
+        redis.scan.processed = Empty,
+        redis.scan.failed = Empty,
+        redis.command_count = Empty,
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        error = Empty,
+        error.message = Empty,
+        error.type = Empty
+    )
+}
+
+/// The parent span covering every SCAN call and per-key copy of one `migrate_keys`
+pub(crate) fn migrate_span(config: &InstrumentationConfig) -> Span {
+    tracing::info_span!(
+        "redis_migrate",
+        otel.name = %generate_span_name("migrate"),
+        db.system = config.db_system_attribute().then_some("redis"),
+        redis.migrate.keys_processed = Empty,
+        redis.migrate.keys_failed = Empty,
+        redis.migrate.start_cursor = Empty,
+        redis.command_count = Empty,
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        error = Empty,
+        error.message = Empty,
+        error.type = Empty
+    )
+}
+
+/// Builds `SCAN <cursor> MATCH <pattern>`
+pub(crate) fn scan_command(cursor: u64, pattern: &str) -> Cmd {
+    let mut cmd = Cmd::new();
+    cmd.arg("SCAN").arg(cursor).arg("MATCH").arg(pattern);
+    cmd
+}
+
+/// Splits a SCAN reply into the next cursor and the keys of this batch
+pub(crate) fn parse_scan_reply(reply: &Value) -> RedisResult<(u64, Vec<String>)> {
+    FromRedisValue::from_redis_value(reply)
+}
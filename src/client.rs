@@ -1,6 +1,15 @@
 //! A module providing an instrumented wrapper around a Redis client for enhanced observability.
 
-use redis::{Client, RedisError};
+use crate::common::ServerAddress;
+use crate::config::InstrumentationConfig;
+use redis::Client;
+#[cfg(any(feature = "sync", feature = "aio"))]
+use redis::RedisError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(any(feature = "sync", feature = "aio"))]
+use std::time::{Duration, Instant};
 use tracing::instrument;
 
 /// A struct that wraps around a `Client` to provide additional instrumentation capabilities.
@@ -17,6 +26,10 @@ use tracing::instrument;
 ///
 /// # Fields
 /// - `inner`: The inner `Client` instance that is being wrapped by this struct.
+/// - `config`: The [`InstrumentationConfig`] shared with every connection the client creates.
+/// - `connections_created`: The number of connections handed out, shared between clones.
+/// - `server`: The server address from the client's connection info, recorded on the
+///   command spans of every connection the client creates.
 ///
 /// # Example
 /// ```rust,ignore
@@ -31,6 +44,9 @@ use tracing::instrument;
 #[derive(Debug, Clone)]
 pub struct InstrumentedClient {
     inner: Client,
+    config: Arc<InstrumentationConfig>,
+    connections_created: Arc<AtomicU64>,
+    server: ServerAddress,
 }
 
 impl InstrumentedClient {
@@ -53,7 +69,27 @@ impl InstrumentedClient {
     /// ```
     #[instrument(skip(client))]
     pub fn new(client: Client) -> Self {
-        Self { inner: client }
+        Self::with_config(client, InstrumentationConfig::default())
+    }
+
+    /// Creates a new instance that instruments its connections according to `config`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use otel_instrumentation_redis::{InstrumentationConfig, InstrumentedClient};
+    ///
+    /// let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    /// let config = InstrumentationConfig::new().with_default_latency_buckets();
+    /// let instrumented = InstrumentedClient::with_config(client, config);
+    /// ```
+    #[instrument(skip(client, config))]
+    pub fn with_config(client: Client, config: InstrumentationConfig) -> Self {
+        Self {
+            server: ServerAddress::from_connection_info(client.get_connection_info()),
+            inner: client,
+            config: Arc::new(config),
+            connections_created: Arc::new(AtomicU64::new(0)),
+        }
     }
 
     /// Returns a reference to the inner `Client` instance.
@@ -73,6 +109,53 @@ impl InstrumentedClient {
         &self.inner
     }
 
+    /// Returns the instrumentation configuration shared with this client's connections.
+    pub fn config(&self) -> &InstrumentationConfig {
+        &self.config
+    }
+
+    /// Returns the server address the client's connections record as `server.address`
+    /// and `server.port`.
+    pub fn server_address(&self) -> &ServerAddress {
+        &self.server
+    }
+
+    /// Returns the database index the client's connections open on, from its connection info.
+    ///
+    /// The connection wrappers start from this index when recording
+    /// `db.redis.database_index`, so spans carry it without an explicit `SELECT`.
+    pub fn database_index(&self) -> i64 {
+        self.inner.get_connection_info().redis.db
+    }
+
+    /// Returns how many connections this client and its clones have handed out.
+    ///
+    /// Only successful acquisitions are counted, which makes a steadily growing
+    /// value a hint that connections are being leaked or not reused.
+    pub fn connections_created(&self) -> u64 {
+        self.connections_created.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many commands each operation has run through this client's connections.
+    ///
+    /// Empty unless [`InstrumentationConfig::with_local_counters`] is enabled.
+    pub fn operation_counts(&self) -> HashMap<String, u64> {
+        self.config
+            .local_counters()
+            .map(|counters| counters.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// Returns the share of `GET`, `GETEX`, `GETDEL` and `HGET` lookups that found a value.
+    ///
+    /// `None` unless [`InstrumentationConfig::with_hit_ratio`] is enabled and at least
+    /// one lookup completed.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        self.config
+            .hit_counters()
+            .and_then(|counters| counters.ratio())
+    }
+
     /// Retrieves a synchronous instrumented Redis connection.
     ///
     /// This function is available only when the `sync` feature is enabled.
@@ -88,6 +171,11 @@ impl InstrumentedClient {
     ///
     /// This function returns a `RedisError` in case the underlying call to `get_connection` fails.
     ///
+    /// # Instrumentation
+    ///
+    /// The acquire span records `redis.connection.reused = false`, as every call
+    /// establishes a fresh connection.
+    ///
     /// # Example
     ///
     /// ```rust,ignore
@@ -114,19 +202,141 @@ impl InstrumentedClient {
     /// otel_instrumentation_redis = { version = "1.0", features = ["sync"] }
     /// ```
     #[cfg(feature = "sync")]
-    #[instrument(skip(self))]
+    #[instrument(
+        skip(self),
+        fields(
+            redis.connection.reused = tracing::field::Empty,
+            redis.client.connections_created = tracing::field::Empty,
+            redis.tls = tracing::field::Empty,
+            redis.tls.handshake_ms = tracing::field::Empty
+        )
+    )]
     pub fn get_connection(&self) -> Result<crate::sync::InstrumentedConnection, RedisError> {
-        let conn = self.inner.get_connection()?;
-        Ok(crate::sync::InstrumentedConnection::new(conn))
+        let started = Instant::now();
+        let conn = self.inner.get_connection();
+        self.record_tls_handshake(started.elapsed());
+        let conn = conn?;
+        self.record_fresh_connection();
+        Ok(
+            crate::sync::InstrumentedConnection::with_config(conn, Arc::clone(&self.config))
+                .with_server_address(self.server.clone()),
+        )
     }
 
     /// Get a multiplexed asynchronous connection to the Redis server
+    ///
+    /// The acquire span records `redis.connection.reused = false`, as every call
+    /// establishes a fresh connection.
     #[cfg(feature = "aio")]
-    #[instrument(skip(self))]
+    #[instrument(
+        skip(self),
+        fields(
+            redis.connection.reused = tracing::field::Empty,
+            redis.client.connections_created = tracing::field::Empty,
+            redis.tls = tracing::field::Empty,
+            redis.tls.handshake_ms = tracing::field::Empty
+        )
+    )]
     pub async fn get_multiplexed_async_connection(
         &self,
     ) -> Result<crate::aio::InstrumentedMultiplexedConnection, RedisError> {
-        let conn = self.inner.get_multiplexed_async_connection().await?;
-        Ok(crate::aio::InstrumentedMultiplexedConnection::new(conn))
+        let started = Instant::now();
+        let conn = self.inner.get_multiplexed_async_connection().await;
+        self.record_tls_handshake(started.elapsed());
+        let conn = conn?;
+        self.record_fresh_connection();
+        Ok(crate::aio::InstrumentedMultiplexedConnection::with_config(
+            conn,
+            Arc::clone(&self.config),
+        )
+        .with_server_address(self.server.clone()))
+    }
+
+    /// Get an auto-reconnecting connection manager
+    ///
+    /// The manager connects before it is returned, so the acquire span records
+    /// `redis.connection.reused = false`; its later reconnects reuse the wrapper.
+    #[cfg(feature = "connection-manager")]
+    #[instrument(
+        skip(self),
+        fields(
+            redis.connection.reused = tracing::field::Empty,
+            redis.client.connections_created = tracing::field::Empty,
+            redis.tls = tracing::field::Empty,
+            redis.tls.handshake_ms = tracing::field::Empty
+        )
+    )]
+    pub async fn get_connection_manager(
+        &self,
+    ) -> Result<crate::aio::InstrumentedConnectionManager, RedisError> {
+        let started = Instant::now();
+        let manager = self.inner.get_connection_manager().await;
+        self.record_tls_handshake(started.elapsed());
+        let manager = manager?;
+        self.record_fresh_connection();
+        Ok(crate::aio::InstrumentedConnectionManager::with_config(
+            manager,
+            Arc::clone(&self.config),
+        )
+        .with_server_address(self.server.clone()))
+    }
+
+    /// Get an async pub/sub connection split into instrumented sink and stream halves
+    #[cfg(feature = "aio")]
+    #[instrument(
+        skip(self),
+        fields(
+            redis.connection.reused = tracing::field::Empty,
+            redis.client.connections_created = tracing::field::Empty,
+            redis.tls = tracing::field::Empty,
+            redis.tls.handshake_ms = tracing::field::Empty
+        )
+    )]
+    pub async fn get_async_pubsub_split(
+        &self,
+    ) -> Result<
+        (
+            crate::aio::InstrumentedPubSubSink,
+            crate::aio::InstrumentedPubSubStream,
+        ),
+        RedisError,
+    > {
+        let started = Instant::now();
+        let pubsub = self.inner.get_async_pubsub().await;
+        self.record_tls_handshake(started.elapsed());
+        let pubsub = pubsub?;
+        self.record_fresh_connection();
+        Ok(crate::aio::split_pubsub_with_config(
+            pubsub,
+            Arc::clone(&self.config),
+        ))
+    }
+
+    /// Records the TLS handshake time of a connection attempt on the current acquire span.
+    ///
+    /// redis-rs doesn't expose the handshake phase, so for `rediss://` clients the whole
+    /// connect time, `elapsed`, is recorded as `redis.tls.handshake_ms` next to
+    /// `redis.tls = true`; it includes the TCP connect and any `AUTH`/`SELECT` sent on
+    /// connect. Failed attempts are recorded too, as a slow failing handshake is what
+    /// this helps diagnose. Nothing is recorded for plain TCP or Unix socket clients.
+    #[cfg(any(feature = "sync", feature = "aio"))]
+    fn record_tls_handshake(&self, elapsed: Duration) {
+        if let redis::ConnectionAddr::TcpTls { .. } = self.inner.get_connection_info().addr {
+            let span = tracing::Span::current();
+            span.record("redis.tls", true);
+            span.record("redis.tls.handshake_ms", elapsed.as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// Counts a newly established connection and records it on the current acquire span.
+    ///
+    /// `redis.connection.reused` is `false`, as the connection was just opened; wrappers
+    /// handing out pooled or managed connections record `true` instead.
+    #[cfg(any(feature = "sync", feature = "aio"))]
+    fn record_fresh_connection(&self) {
+        let created = self.connections_created.fetch_add(1, Ordering::Relaxed) + 1;
+        let span = tracing::Span::current();
+        span.record("redis.connection.reused", false);
+        span.record("redis.client.connections_created", created);
     }
 }
@@ -0,0 +1,120 @@
+//! An instrumented wrapper around a Redis Cluster client
+
+use crate::config::InstrumentationConfig;
+use redis::cluster::ClusterClient;
+#[cfg(any(feature = "sync", feature = "cluster-async"))]
+use redis::RedisError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(any(feature = "sync", feature = "cluster-async"))]
+use tracing::instrument;
+
+/// Wraps a `redis::cluster::ClusterClient`, handing out instrumented cluster connections.
+///
+/// This is the cluster counterpart of [`crate::InstrumentedClient`]. Its connections
+/// record the same command spans as standalone ones. redis-rs follows `MOVED` and
+/// `ASK` redirections itself; when one still fails the command, its span records
+/// the node it pointed to as `redis.redirect.node`. Command spans don't carry
+/// `server.address`, as each command may be served by a different node.
+///
+/// # Example
+/// ```rust
+/// use otel_instrumentation_redis::{InstrumentationConfig, InstrumentedClusterClient};
+/// use redis::cluster::ClusterClient;
+///
+/// let client = ClusterClient::new(vec!["redis://127.0.0.1:7000/"]).unwrap();
+/// let instrumented = InstrumentedClusterClient::with_config(client, InstrumentationConfig::new());
+/// ```
+#[derive(Clone)]
+pub struct InstrumentedClusterClient {
+    inner: ClusterClient,
+    config: Arc<InstrumentationConfig>,
+    connections_created: Arc<AtomicU64>,
+}
+
+impl InstrumentedClusterClient {
+    /// Wraps `client` with the default configuration
+    pub fn new(client: ClusterClient) -> Self {
+        Self::with_config(client, InstrumentationConfig::default())
+    }
+
+    /// Wraps `client`, instrumenting its connections according to `config`
+    pub fn with_config(client: ClusterClient, config: InstrumentationConfig) -> Self {
+        Self {
+            inner: client,
+            config: Arc::new(config),
+            connections_created: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns the wrapped cluster client
+    pub fn inner(&self) -> &ClusterClient {
+        &self.inner
+    }
+
+    /// Returns the instrumentation configuration shared with this client's connections
+    pub fn config(&self) -> &InstrumentationConfig {
+        &self.config
+    }
+
+    /// Returns how many connections this client and its clones have handed out
+    pub fn connections_created(&self) -> u64 {
+        self.connections_created.load(Ordering::Relaxed)
+    }
+
+    /// Connects to the cluster and returns an instrumented synchronous connection
+    #[cfg(feature = "sync")]
+    #[instrument(
+        skip(self),
+        fields(
+            redis.connection.reused = tracing::field::Empty,
+            redis.client.connections_created = tracing::field::Empty
+        )
+    )]
+    pub fn get_connection(&self) -> Result<crate::sync::InstrumentedClusterConnection, RedisError> {
+        let conn = self.inner.get_connection()?;
+        self.record_fresh_connection();
+        Ok(crate::sync::InstrumentedSyncConnection::with_config(
+            conn,
+            Arc::clone(&self.config),
+        ))
+    }
+
+    /// Connects to the cluster and returns an instrumented asynchronous connection
+    #[cfg(feature = "cluster-async")]
+    #[instrument(
+        skip(self),
+        fields(
+            redis.connection.reused = tracing::field::Empty,
+            redis.client.connections_created = tracing::field::Empty
+        )
+    )]
+    pub async fn get_async_connection(
+        &self,
+    ) -> Result<crate::aio::InstrumentedClusterAsyncConnection, RedisError> {
+        let conn = self.inner.get_async_connection().await?;
+        self.record_fresh_connection();
+        Ok(crate::aio::InstrumentedMultiplexedConnection::with_config(
+            conn,
+            Arc::clone(&self.config),
+        ))
+    }
+
+    /// Counts a newly established connection and records it on the current acquire span
+    #[cfg(any(feature = "sync", feature = "cluster-async"))]
+    fn record_fresh_connection(&self) {
+        let created = self.connections_created.fetch_add(1, Ordering::Relaxed) + 1;
+        let span = tracing::Span::current();
+        span.record("redis.connection.reused", false);
+        span.record("redis.client.connections_created", created);
+    }
+}
+
+impl std::fmt::Debug for InstrumentedClusterClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InstrumentedClusterClient")
+            .field("config", &self.config)
+            .field("connections_created", &self.connections_created)
+            .finish_non_exhaustive()
+    }
+}
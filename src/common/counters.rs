@@ -0,0 +1,87 @@
+//! In-process per-operation command counters, independent of OpenTelemetry
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Counts of the commands sent through a client's connections, keyed by operation name.
+///
+/// Enabled with [`crate::InstrumentationConfig::with_local_counters`] and read through
+/// [`crate::InstrumentedClient::operation_counts`], e.g. from a debug endpoint.
+/// Counting an operation seen before only takes a read lock and an atomic increment.
+#[derive(Debug, Default)]
+pub struct OperationCounters {
+    counts: RwLock<HashMap<String, AtomicU64>>,
+}
+
+impl OperationCounters {
+    /// Creates an empty set of counters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one command for `operation`
+    pub fn increment(&self, operation: &str) {
+        if let Some(count) = self.counts.read().unwrap().get(operation) {
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.counts
+            .write()
+            .unwrap()
+            .entry(operation.to_string())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns a snapshot of the counts by operation
+    pub fn snapshot(&self) -> HashMap<String, u64> {
+        self.counts
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(operation, count)| (operation.clone(), count.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+/// Keyspace hits and misses of the lookups sent through a client's connections.
+///
+/// Enabled with [`crate::InstrumentationConfig::with_hit_ratio`]. Only `GET`, `GETEX`,
+/// `GETDEL` and `HGET` are counted, since a nil reply from them means the key or field
+/// was missing; commands such as `EXISTS` answer a different question.
+#[derive(Debug, Default)]
+pub struct HitCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl HitCounters {
+    /// Creates counters with no lookups recorded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one lookup, a hit unless the reply was nil
+    pub fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of lookups that found a value
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of lookups that returned nil
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the share of lookups that were hits, or `None` before the first lookup
+    pub fn ratio(&self) -> Option<f64> {
+        let hits = self.hits();
+        let total = hits + self.misses();
+        (total > 0).then(|| hits as f64 / total as f64)
+    }
+}
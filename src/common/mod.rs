@@ -1,7 +1,29 @@
 //! Common utilities and types shared across sync and async implementations
 
+use crate::config::{
+    ConnectionSpanMode, InstrumentationConfig, OperationCase, SpanDecision, SpanNameStrategy,
+    StatementCapture,
+};
 use opentelemetry::KeyValue;
 use opentelemetry_semantic_conventions as semconv;
+use std::time::{Duration, Instant, SystemTime};
+
+mod counters;
+mod deferred;
+mod resp;
+mod response;
+mod span;
+mod state;
+mod summary;
+mod target;
+
+pub use counters::{HitCounters, OperationCounters};
+pub use deferred::{DeferredRecorder, RecordFields};
+pub use resp::{resp_reply_len, ReplyByteCounter, DEFAULT_MAX_REPLY_LEN};
+pub use response::MAX_RESPONSE_ATTRIBUTES;
+pub use span::RedisSpan;
+pub use state::{ConnectionState, EvictionSampler, ReconnectState, ServerAddress, Throttle};
+pub use summary::{WindowAggregator, MAX_SUMMARY_OPERATIONS};
 
 /// Extracts command attributes from a Redis command.
 ///
@@ -21,6 +43,7 @@ use opentelemetry_semantic_conventions as semconv;
 /// * `DB_OPERATION_NAME` - The name of the command (e.g., "GET", "SET"), if it can
 ///   be extracted from the provided `cmd`. If the command name
 ///   cannot be determined, this attribute is omitted.
+/// * `db.redis.database_index` - The database a `SELECT` switches to.
 ///
 /// # Example
 ///
@@ -43,19 +66,369 @@ use opentelemetry_semantic_conventions as semconv;
 /// If `get_command_name` returns `None`, the `DB_OPERATION_NAME` attribute will not
 /// be added to the result vector.
 pub fn extract_command_attributes(cmd: &redis::Cmd) -> Vec<KeyValue> {
-    let mut attributes = vec![KeyValue::new(semconv::attribute::DB_SYSTEM_NAME, "redis")];
+    extract_command_attributes_with_config(cmd, &InstrumentationConfig::default())
+}
+
+/// Extracts command attributes from a Redis command, honouring an [`InstrumentationConfig`].
+///
+/// Returns the same attributes as [`extract_command_attributes`], with the operation
+/// name cased according to [`InstrumentationConfig::with_operation_case`], plus any
+/// optional attributes enabled in `config`:
+/// * `redis.command.raw_verb` - The command name exactly as sent, before normalization,
+///   when [`InstrumentationConfig::with_raw_verb_capture`] is enabled.
+/// * `db.statement` - The command, when [`InstrumentationConfig::with_statement_capture`]
+///   enables it.
+/// * `db.redis.key_count` - The keys or members a multi-key command involves; see
+///   [`key_count`].
+/// * `db.redis.numkeys` - The `numkeys` argument of commands such as `EVAL` and
+///   `ZUNION`; see [`numkeys`].
+/// * `redis.expire.unit`, `redis.expire.absolute` and `redis.expire.ttl_ms` - The
+///   expiry set by `EXPIRE`, `PEXPIRE`, `EXPIREAT`, `PEXPIREAT`, `GETEX` and `SET`
+///   with an expiry option; see [`command_expiry`].
+/// * `redis.shard` - The shard [`InstrumentationConfig::with_shard_resolver`] maps the
+///   command's first key to, when a resolver is set.
+///
+/// `redis.idempotent` is always added for commands with a name; see [`is_idempotent`].
+/// So is `redis.command.fingerprint`; see [`command_fingerprint`].
+pub fn extract_command_attributes_with_config(
+    cmd: &redis::Cmd,
+    config: &InstrumentationConfig,
+) -> Vec<KeyValue> {
+    let mut attributes = Vec::new();
+    if config.db_system_attribute() {
+        attributes.push(KeyValue::new(semconv::attribute::DB_SYSTEM_NAME, "redis"));
+    }
 
     // Try to extract the command name
-    if let Some(cmd_name) = get_command_name(cmd) {
+    if let Some(cmd_name) = get_operation_name(cmd, config) {
         attributes.push(KeyValue::new(
             semconv::attribute::DB_OPERATION_NAME,
             cmd_name,
         ));
     }
 
+    if let Some(db) = selected_database(cmd) {
+        attributes.push(KeyValue::new("db.redis.database_index", db));
+    }
+
+    if is_connection_setup_command(cmd) {
+        attributes.push(KeyValue::new("redis.connection_setup", true));
+    }
+
+    if get_command_name(cmd).is_some() {
+        attributes.push(KeyValue::new("redis.idempotent", is_idempotent(cmd)));
+    }
+
+    if let Some(fingerprint) = command_fingerprint(cmd) {
+        attributes.push(KeyValue::new("redis.command.fingerprint", fingerprint));
+    }
+
+    if let Some(count) = key_count(cmd) {
+        attributes.push(KeyValue::new("db.redis.key_count", count));
+    }
+
+    if let Some(numkeys) = numkeys(cmd) {
+        attributes.push(KeyValue::new("db.redis.numkeys", numkeys));
+    }
+
+    if let Some(expiry) = command_expiry(cmd) {
+        attributes.push(KeyValue::new("redis.expire.unit", expiry.unit));
+        attributes.push(KeyValue::new("redis.expire.absolute", expiry.absolute));
+        attributes.push(KeyValue::new("redis.expire.ttl_ms", expiry.ttl_ms));
+    }
+
+    if let (Some(resolver), Some(key)) = (config.shard_resolver(), first_key(cmd)) {
+        if let Some(shard) = call_user_callback("Redis shard resolver", || resolver(&key)) {
+            attributes.push(KeyValue::new("redis.shard", i64::from(shard)));
+        }
+    }
+
+    if config.raw_verb_capture() {
+        if let Some(raw_verb) = get_raw_command_verb(cmd) {
+            attributes.push(KeyValue::new("redis.command.raw_verb", raw_verb));
+        }
+    }
+
+    let statement = match config.statement_capture() {
+        StatementCapture::Disabled => None,
+        StatementCapture::OperationOnly => get_operation_name(cmd, config),
+        StatementCapture::Full => Some(full_statement(cmd, config)),
+    };
+    if let Some(statement) = statement {
+        let statement = truncate_statement(statement, config.statement_max_length());
+        attributes.push(KeyValue::new("db.statement", statement));
+    }
+
     attributes
 }
 
+/// Returns a fingerprint of a command's structure, for grouping commands that differ
+/// only in their keys and values.
+///
+/// The fingerprint hashes the command name with the number and kind of its other
+/// arguments (integers, other values and cursors), but not their contents, so
+/// `GET a` and `GET b` share a fingerprint while `GET a` and `MGET a b` don't. It is
+/// rendered as 16 hex digits, and stable across processes and releases.
+///
+/// Returns `None` for commands without a name.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::command_fingerprint;
+///
+/// let get_a = command_fingerprint(redis::cmd("GET").arg("a"));
+/// assert_eq!(get_a, command_fingerprint(redis::cmd("get").arg("b")));
+/// assert_ne!(get_a, command_fingerprint(redis::cmd("MGET").arg("a").arg("b")));
+/// ```
+pub fn command_fingerprint(cmd: &redis::Cmd) -> Option<String> {
+    let name = get_command_name(cmd)?;
+    let mut shape = name.into_bytes();
+    let mut args = cmd.args_iter();
+    // Skip everything up to and including the command name
+    args.by_ref()
+        .find(|arg| matches!(arg, redis::Arg::Simple(_)));
+    for arg in args {
+        shape.push(b' ');
+        shape.push(match arg {
+            redis::Arg::Simple(bytes) if is_integer(bytes) => b'i',
+            redis::Arg::Simple(_) => b's',
+            redis::Arg::Cursor => b'c',
+        });
+    }
+    Some(format!("{:016x}", fnv1a(&shape)))
+}
+
+/// Returns whether `bytes` are a decimal integer, as sent for integer arguments
+fn is_integer(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok_and(|text| text.parse::<i64>().is_ok())
+}
+
+/// Hashes `bytes` with 64-bit FNV-1a, whose output doesn't depend on the platform or
+/// the Rust release, unlike the standard library's hashers.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Returns the database index a `SELECT` command switches to
+fn selected_database(cmd: &redis::Cmd) -> Option<i64> {
+    let mut args = cmd.args_iter();
+    match (args.next()?, args.next()?) {
+        (redis::Arg::Simple(name), redis::Arg::Simple(db))
+            if name.eq_ignore_ascii_case(b"SELECT") =>
+        {
+            std::str::from_utf8(db).ok()?.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Returns the first key of a command whose first argument is a key.
+///
+/// Commands that don't take keys, and those starting with something else such as a
+/// key count (`ZUNION`), a script (`EVAL_RO`) or a subcommand (`BITOP`), have none.
+fn first_key(cmd: &redis::Cmd) -> Option<String> {
+    let operation = get_command_name(cmd)?;
+    match operation.as_str() {
+        "KEYS" | "SCAN" | "RANDOMKEY" | "DBSIZE" | "FLUSHDB" | "FLUSHALL" | "SWAPDB" | "XREAD"
+        | "XREADGROUP" | "EVAL_RO" | "EVALSHA_RO" | "FCALL_RO" | "LMPOP" | "BLMPOP" | "ZMPOP"
+        | "BZMPOP" | "SINTERCARD" | "ZINTER" | "ZUNION" | "ZDIFF" | "BITOP" => None,
+        operation if classify_command(operation) == CommandKind::Other => None,
+        _ => command_arg(cmd, 1),
+    }
+}
+
+/// Returns the number of keys or members a multi-key command involves.
+///
+/// Commands taking only keys (`DEL`, `MGET`, `EXISTS`, ...) count their keys.
+/// Commands adding to or reading from one collection (`SADD`, `HDEL`, `LPUSH`, ...)
+/// count the members after the key. Commands interleaving names and values count
+/// logical units: pairs for `MSET`, fields for `HSET`, members for `ZADD` after its
+/// flags. Commands with a `numkeys` argument count the keys it names, plus the
+/// destination of `ZUNIONSTORE`, `ZINTERSTORE` and `ZDIFFSTORE`; see [`numkeys`].
+/// Other commands give `None`.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::key_count;
+///
+/// assert_eq!(key_count(redis::cmd("DEL").arg("a").arg("b").arg("c")), Some(3));
+/// assert_eq!(key_count(redis::cmd("MSET").arg("k1").arg("v1").arg("k2").arg("v2")), Some(2));
+/// assert_eq!(key_count(redis::cmd("SADD").arg("set").arg("a").arg("b")), Some(2));
+/// assert_eq!(key_count(redis::cmd("GET").arg("key")), None);
+/// ```
+pub fn key_count(cmd: &redis::Cmd) -> Option<i64> {
+    let operation = get_command_name(cmd)?;
+    if let Some(numkeys) = numkeys(cmd) {
+        let destination = matches!(
+            operation.as_str(),
+            "ZUNIONSTORE" | "ZINTERSTORE" | "ZDIFFSTORE"
+        );
+        return Some(numkeys + i64::from(destination));
+    }
+    let args = cmd.args_iter().count() - 1;
+    let count = match operation.as_str() {
+        "DEL" | "UNLINK" | "EXISTS" | "TOUCH" | "MGET" | "WATCH" | "SINTER" | "SUNION"
+        | "SDIFF" | "PFCOUNT" => args,
+        "SADD" | "SREM" | "SMISMEMBER" | "LPUSH" | "RPUSH" | "LPUSHX" | "RPUSHX" | "PFADD"
+        | "HDEL" | "HMGET" | "ZREM" | "ZMSCORE" => args.checked_sub(1)?,
+        "MSET" | "MSETNX" => args / 2,
+        "HSET" | "HMSET" => args.checked_sub(1)? / 2,
+        "ZADD" => {
+            let flags = (2..=args)
+                .map_while(|index| command_arg(cmd, index))
+                .take_while(|arg| {
+                    ["NX", "XX", "GT", "LT", "CH", "INCR"]
+                        .iter()
+                        .any(|flag| arg.eq_ignore_ascii_case(flag))
+                })
+                .count();
+            args.checked_sub(1 + flags)? / 2
+        }
+        _ => return None,
+    };
+    i64::try_from(count).ok()
+}
+
+/// Returns the `numkeys` argument of a command following the `numkeys key... arg...`
+/// convention.
+///
+/// Covers the scripting commands (`EVAL`, `EVALSHA`, `FCALL` and their read-only
+/// variants), `ZUNION`, `ZINTER`, `ZDIFF` and their `STORE` variants, `ZINTERCARD`,
+/// `SINTERCARD`, `LMPOP`, `ZMPOP`, `BLMPOP` and `BZMPOP`. A `numkeys` that isn't a
+/// non-negative integer, or names more keys than the command has arguments, gives
+/// `None` like any other command.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::numkeys;
+///
+/// let eval = redis::cmd("EVAL").arg("return KEYS[1]").arg(2).arg("a").arg("b").arg("x").clone();
+/// assert_eq!(numkeys(&eval), Some(2));
+/// assert_eq!(numkeys(redis::cmd("ZUNION").arg(1).arg("z")), Some(1));
+/// assert_eq!(numkeys(redis::cmd("EVAL").arg("return 1").arg("two")), None);
+/// ```
+pub fn numkeys(cmd: &redis::Cmd) -> Option<i64> {
+    let operation = get_command_name(cmd)?;
+    let index = match operation.as_str() {
+        "EVAL" | "EVALSHA" | "EVAL_RO" | "EVALSHA_RO" | "FCALL" | "FCALL_RO" | "ZUNIONSTORE"
+        | "ZINTERSTORE" | "ZDIFFSTORE" | "BLMPOP" | "BZMPOP" => 2,
+        "ZUNION" | "ZINTER" | "ZDIFF" | "ZINTERCARD" | "SINTERCARD" | "LMPOP" | "ZMPOP" => 1,
+        _ => return None,
+    };
+    let numkeys: usize = command_arg(cmd, index)?.parse().ok()?;
+    if index + numkeys >= cmd.args_iter().count() {
+        return None;
+    }
+    i64::try_from(numkeys).ok()
+}
+
+/// The expiry a command sets on its key, see [`command_expiry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandExpiry {
+    /// `seconds` or `milliseconds`, the unit the command gave the expiry in
+    pub unit: &'static str,
+    /// `true` when the expiry is a Unix time (`EXPIREAT`, `PXAT`, ...) rather than a TTL
+    pub absolute: bool,
+    /// The TTL in milliseconds. Absolute expiries are converted using the local
+    /// clock, so clock skew with the server shifts them; past times count as 0.
+    pub ttl_ms: i64,
+}
+
+/// Returns the expiry set by an expire-family command, in a unit-independent form.
+///
+/// Covers `EXPIRE`, `PEXPIRE`, `EXPIREAT` and `PEXPIREAT`, plus `GETEX` and `SET` with
+/// an `EX`, `PX`, `EXAT` or `PXAT` option. Other commands, `GETEX ... PERSIST`, `SET`
+/// without an expiry and unparsable values give `None`.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::command_expiry;
+///
+/// let expiry = command_expiry(redis::cmd("EXPIRE").arg("session").arg(30)).unwrap();
+/// assert_eq!(expiry.unit, "seconds");
+/// assert_eq!(expiry.ttl_ms, 30_000);
+/// assert!(!expiry.absolute);
+///
+/// let expiry = command_expiry(redis::cmd("SET").arg("k").arg("v").arg("PX").arg(250)).unwrap();
+/// assert_eq!((expiry.unit, expiry.ttl_ms), ("milliseconds", 250));
+/// ```
+pub fn command_expiry(cmd: &redis::Cmd) -> Option<CommandExpiry> {
+    let operation = get_command_name(cmd)?;
+    let (option, value) = match operation.as_str() {
+        "EXPIRE" => ("EX".to_string(), command_arg(cmd, 2)?),
+        "PEXPIRE" => ("PX".to_string(), command_arg(cmd, 2)?),
+        "EXPIREAT" => ("EXAT".to_string(), command_arg(cmd, 2)?),
+        "PEXPIREAT" => ("PXAT".to_string(), command_arg(cmd, 2)?),
+        // GETEX key option [value], SET key value [NX|XX] [GET] option [value]
+        "GETEX" | "SET" => {
+            let first_option = if operation == "GETEX" { 2 } else { 3 };
+            let index = (first_option..cmd.args_iter().count()).find(|&index| {
+                command_arg(cmd, index).is_some_and(|option| {
+                    ["EX", "PX", "EXAT", "PXAT"]
+                        .iter()
+                        .any(|expiry| option.eq_ignore_ascii_case(expiry))
+                })
+            })?;
+            (
+                command_arg(cmd, index)?.to_ascii_uppercase(),
+                command_arg(cmd, index + 1)?,
+            )
+        }
+        _ => return None,
+    };
+    let value: i64 = value.trim().parse().ok()?;
+
+    let (unit, absolute) = match option.as_str() {
+        "EX" => ("seconds", false),
+        "PX" => ("milliseconds", false),
+        "EXAT" => ("seconds", true),
+        _ => ("milliseconds", true),
+    };
+    let millis = if unit == "seconds" {
+        value.saturating_mul(1000)
+    } else {
+        value
+    };
+    let ttl_ms = if absolute {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |now| i64::try_from(now.as_millis()).unwrap_or(i64::MAX));
+        millis.saturating_sub(now_ms).max(0)
+    } else {
+        millis
+    };
+    Some(CommandExpiry {
+        unit,
+        absolute,
+        ttl_ms,
+    })
+}
+
+/// Returns the command name exactly as sent, lossily rendered as UTF-8.
+///
+/// Unlike [`get_command_name`], the casing is preserved, which helps debug proxies
+/// that reject unusually cased commands.
+fn get_raw_command_verb(cmd: &redis::Cmd) -> Option<String> {
+    command_name_arg(cmd).map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Returns the bytes of the command name, the first argument that isn't a cursor.
+///
+/// Cursors of `SCAN`, `HSCAN`, `SSCAN` and `ZSCAN` follow the command name (and key),
+/// so they never name the command themselves.
+fn command_name_arg(cmd: &redis::Cmd) -> Option<&[u8]> {
+    cmd.args_iter().find_map(|arg| match arg {
+        redis::Arg::Simple(bytes) => Some(bytes),
+        redis::Arg::Cursor => None,
+    })
+}
+
 /// Extracts the name of a Redis command from a `redis::Cmd` object.
 ///
 /// This function attempts to determine the name of the Redis command
@@ -65,9 +438,9 @@ pub fn extract_command_attributes(cmd: &redis::Cmd) -> Vec<KeyValue> {
 /// - Transforms the command name into uppercase to provide a consistent format.
 ///
 /// ## Behavior
-/// - If the first argument of the command is `redis::Arg::Cursor`, the function assumes it belongs
-///   to the SCAN family of commands and directly returns `"SCAN"`.
-/// - If the first argument is a simple byte slice, the function attempts to parse it as UTF-8:
+/// - Cursor arguments (`redis::Arg::Cursor`) are skipped: the name is the first simple
+///   argument, so `HSCAN myhash 0` is reported as `HSCAN`.
+/// - The name is parsed as UTF-8:
 ///   - If parsing is successful, the uppercase version of the command name is returned.
 ///   - If parsing fails, a warning is logged (using the `tracing` crate), and the function returns `None`.
 /// - If the command's argument list is empty, the function returns `None`.
@@ -90,8 +463,8 @@ pub fn extract_command_attributes(cmd: &redis::Cmd) -> Vec<KeyValue> {
 ///
 /// // Cursor-based command
 /// let mut command = Cmd::new();
-/// command.arg(Arg::Cursor);
-/// assert_eq!(get_command_name(&command), Some("SCAN".to_string()));
+/// command.arg("HSCAN").arg("myhash").cursor_arg(0);
+/// assert_eq!(get_command_name(&command), Some("HSCAN".to_string()));
 ///
 /// // Invalid UTF-8
 /// let mut command = Cmd::new();
@@ -104,35 +477,120 @@ pub fn extract_command_attributes(cmd: &redis::Cmd) -> Vec<KeyValue> {
 /// ```
 ///
 /// ## Notes
-/// - This function assumes that the first argument in the `redis::Cmd` object is always
-///   the command name, which is common in Redis command usage.
+/// - This function assumes that the first simple argument in the `redis::Cmd` object is
+///   always the command name, which is common in Redis command usage.
 ///
 /// ## Logs
 /// - If a command name fails UTF-8 parsing, a warning is logged using the `tracing` crate.
 fn get_command_name(cmd: &redis::Cmd) -> Option<String> {
-    // Get the first argument which should be the command name
-    let mut args_iter = cmd.args_iter();
-    if let Some(first_arg) = args_iter.next() {
-        // Convert arg to bytes slice
-        let arg_bytes = match first_arg {
-            redis::Arg::Simple(bytes) => bytes,
-            redis::Arg::Cursor => return Some("SCAN".to_string()), // Cursor commands are SCAN family
-        };
+    command_name_from_bytes(command_name_arg(cmd)?)
+}
 
-        // Convert bytes to string, handling UTF-8 conversion
-        match std::str::from_utf8(arg_bytes) {
-            Ok(cmd_name) => Some(cmd_name.to_uppercase()),
-            Err(_) => {
-                // If we can't parse as UTF-8, return None
-                tracing::warn!("Failed to parse Redis command name as UTF-8");
-                None
-            }
-        }
+/// Extracts the operation name of a command, cased according to
+/// [`InstrumentationConfig::operation_case`].
+///
+/// [`OperationCase::Upper`] yields the same name as [`get_command_name`]. The other
+/// cases are derived from the bytes as sent, so `Preserve` keeps e.g. `"Get"` intact.
+/// The name is reduced to its family when [`InstrumentationConfig::collapse_subcommands`]
+/// is set.
+fn get_operation_name(cmd: &redis::Cmd, config: &InstrumentationConfig) -> Option<String> {
+    operation_name(command_name_arg(cmd)?, config)
+}
+
+/// Returns the `db.operation` of a convenience method's span, for the command
+/// `operation` it sends, cased and collapsed like `get_operation_name` does.
+pub fn method_operation(operation: &str, config: &InstrumentationConfig) -> String {
+    operation_name(operation.as_bytes(), config).unwrap_or_else(|| operation.to_string())
+}
+
+/// Cases a command name as sent, `name`, as [`get_operation_name`] describes
+fn operation_name(name: &[u8], config: &InstrumentationConfig) -> Option<String> {
+    let name = match config.operation_case() {
+        OperationCase::Upper => command_name_from_bytes(name),
+        OperationCase::Lower => command_name_from_bytes(name).map(|name| name.to_lowercase()),
+        OperationCase::Preserve => std::str::from_utf8(name).ok().map(str::to_string),
+    }?;
+    if config.collapse_subcommands() {
+        Some(collapse_operation(&name).to_string())
     } else {
-        None
+        Some(name)
+    }
+}
+
+/// Returns the command family of an operation name, without its subcommand or the
+/// module-specific part of a module command.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::collapse_operation;
+///
+/// assert_eq!(collapse_operation("CONFIG GET"), "CONFIG");
+/// assert_eq!(collapse_operation("FT.SEARCH"), "FT");
+/// assert_eq!(collapse_operation("GET"), "GET");
+/// ```
+pub fn collapse_operation(operation: &str) -> &str {
+    let command = operation.split(' ').next().unwrap_or(operation);
+    match command.split_once('.') {
+        Some((module, _)) if !module.is_empty() => module,
+        _ => command,
+    }
+}
+
+/// Converts the raw bytes of a command name into its normalized, uppercase form.
+///
+/// Logs a warning and returns `None` if the bytes are not valid UTF-8.
+fn command_name_from_bytes(bytes: &[u8]) -> Option<String> {
+    // Convert bytes to string, handling UTF-8 conversion
+    match std::str::from_utf8(bytes) {
+        Ok(cmd_name) => Some(cmd_name.to_uppercase()),
+        Err(_) => {
+            // If we can't parse as UTF-8, return None
+            tracing::warn!("Failed to parse Redis command name as UTF-8");
+            None
+        }
     }
 }
 
+/// Extracts the name of a Redis command from its RESP-encoded form.
+///
+/// Commands sent through `ConnectionLike::req_packed_command` arrive already packed
+/// as a RESP array of bulk strings (`*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n`), so the
+/// original `redis::Cmd` is no longer available. This function reads the first
+/// bulk string of that array, which is the command name, and normalizes it the
+/// same way as [`extract_command_attributes`] does.
+///
+/// # Returns
+///
+/// - `Some(String)`: The uppercase command name.
+/// - `None`: If the bytes are not a RESP array starting with a bulk string, or the
+///   name is not valid UTF-8.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::get_packed_command_name;
+///
+/// let packed = redis::cmd("get").arg("key").get_packed_command();
+/// assert_eq!(get_packed_command_name(&packed), Some("GET".to_string()));
+///
+/// assert_eq!(get_packed_command_name(b"PING\r\n"), None);
+/// ```
+pub fn get_packed_command_name(packed: &[u8]) -> Option<String> {
+    let rest = packed.strip_prefix(b"*")?;
+    let (_, rest) = split_resp_line(rest)?;
+    let rest = rest.strip_prefix(b"$")?;
+    let (len, rest) = split_resp_line(rest)?;
+    let len: usize = std::str::from_utf8(len).ok()?.parse().ok()?;
+    command_name_from_bytes(rest.get(..len)?)
+}
+
+/// Splits a RESP buffer at the first CRLF, returning the line and the remainder.
+fn split_resp_line(buf: &[u8]) -> Option<(&[u8], &[u8])> {
+    let end = buf.windows(2).position(|window| window == b"\r\n")?;
+    Some((&buf[..end], &buf[end + 2..]))
+}
+
 /// Generates a span name for a Redis operation.
 ///
 /// This function takes an operation name as input, converts it to lowercase,
@@ -208,29 +666,268 @@ pub fn generate_span_name(operation: &str) -> String {
 /// * `extract_command_attributes` - Helper to retrieve additional attributes from the command context.
 ///
 pub fn create_command_span(cmd: &redis::Cmd) -> (tracing::Span, Vec<KeyValue>) {
-    let attributes = extract_command_attributes(cmd);
+    create_command_span_with_config(cmd, &InstrumentationConfig::default())
+}
+
+/// Creates a tracing span for a Redis command according to an [`InstrumentationConfig`].
+///
+/// This is the configurable counterpart of [`create_command_span`], which the connection
+/// wrappers use so that options set on [`crate::InstrumentedClient`] apply to every
+/// command. The returned attributes come from [`extract_command_attributes_with_config`].
+///
+/// The span is disabled for commands the configuration doesn't instrument, see
+/// [`InstrumentationConfig::with_writes_only`], and for commands a span preprocessor
+/// skips, see [`InstrumentationConfig::with_span_preprocessor`].
+/// Attributes added by a span enricher, see [`InstrumentationConfig::with_span_enricher`],
+/// are returned with the others.
+pub fn create_command_span_with_config(
+    cmd: &redis::Cmd,
+    config: &InstrumentationConfig,
+) -> (tracing::Span, Vec<KeyValue>) {
+    let attributes = extract_command_attributes_with_config(cmd, config);
+
+    if config.writes_only() {
+        let kind = get_command_name(cmd).map_or(CommandKind::Other, |op| classify_command(&op));
+        if !config.instruments(kind) {
+            return (tracing::Span::none(), attributes);
+        }
+    }
 
-    // Extract command name for span name
-    let operation = get_command_name(cmd).unwrap_or_else(|| "command".to_string());
-    let span_name = generate_span_name(&operation);
+    // Extract command name for span name. Span names are lowercase unless the
+    // configuration asks for the command to be shown exactly as sent.
+    let case = config.operation_case();
+    let operation = get_operation_name(cmd, config).unwrap_or_else(|| "command".to_string());
+    let span_name = match config.span_name_strategy() {
+        SpanNameStrategy::RedisPrefixed => match case {
+            OperationCase::Preserve => format!("redis {operation}"),
+            OperationCase::Upper | OperationCase::Lower => generate_span_name(&operation),
+        },
+        SpanNameStrategy::OperationOnly => operation.clone(),
+        SpanNameStrategy::OperationAndCollection => match first_key(cmd) {
+            Some(key) => format!("{operation} {key}"),
+            None => operation.clone(),
+        },
+    };
 
-    // Create span with initial attributes
+    let mut attributes = attributes;
+    if let Some(enricher) = config.span_enricher() {
+        call_user_callback("Redis span enricher", || {
+            enricher.enrich(cmd, &mut attributes)
+        });
+    }
+    if let Some(preprocessor) = config.span_preprocessor() {
+        let decision = call_user_callback("Redis span preprocessor", || {
+            preprocessor(&mut attributes, &operation)
+        });
+        if decision == Some(SpanDecision::Skip) {
+            return (tracing::Span::none(), attributes);
+        }
+    }
+
+    let db_system = config.db_system_attribute().then_some("redis");
+    let span = match config.tracing_target() {
+        Some(target) => target::command_span(target, &span_name, &operation, db_system),
+        None => command_span(&span_name, &operation, db_system),
+    };
+    if config.span_enricher().is_some() || config.span_preprocessor().is_some() {
+        response::emit_undeclared_attributes(&span, &attributes, "redis.attributes");
+    }
+
+    (span, attributes)
+}
+
+/// Creates the state of a freshly opened connection to database `db`.
+///
+/// When [`InstrumentationConfig::with_connection_span`] is set, this also opens the
+/// connection's `redis connection` span. Connections `shared` between concurrent
+/// requests, such as multiplexed ones, always use [`ConnectionSpanMode::Link`].
+pub fn new_connection_state(
+    db: i64,
+    config: &InstrumentationConfig,
+    shared: bool,
+) -> ConnectionState {
+    let mut state = ConnectionState::with_database_index(db);
+    if let Some(window) = config.window_aggregation() {
+        state = state.with_window_aggregation(window);
+    }
+    let Some(mode) = config.connection_span() else {
+        return state;
+    };
+    let mode = if shared {
+        ConnectionSpanMode::Link
+    } else {
+        mode
+    };
     let span = tracing::info_span!(
-        "redis_command",
-        otel.name = %span_name,
-        db.system = "redis",
-        db.operation = %operation
+        parent: None,
+        "redis_connection",
+        otel.name = "redis connection",
+        db.system = config.db_system_attribute().then_some("redis"),
+        db.redis.database_index = db
     );
+    state.with_connection_span(span, mode)
+}
 
-    (span, attributes)
+/// Creates the span for a command sent over a connection with the given state.
+///
+/// This is [`create_command_span_with_config`], with the span parented or linked to
+/// the connection's lifetime span when the state has one. With window aggregation,
+/// see [`InstrumentationConfig::with_window_aggregation`], no span is created and
+/// the command is summarized by [`record_window_aggregate`] instead.
+pub fn create_connection_command_span(
+    cmd: &redis::Cmd,
+    config: &InstrumentationConfig,
+    state: &ConnectionState,
+) -> (tracing::Span, Vec<KeyValue>) {
+    if state.window_aggregator().is_some() {
+        return (tracing::Span::none(), Vec::new());
+    }
+    match state.connection_span() {
+        Some((connection, ConnectionSpanMode::Parent)) => {
+            connection.in_scope(|| create_command_span_with_config(cmd, config))
+        }
+        Some((connection, ConnectionSpanMode::Link)) => {
+            let (span, attributes) = create_command_span_with_config(cmd, config);
+            span.follows_from(connection);
+            (span, attributes)
+        }
+        None => create_command_span_with_config(cmd, config),
+    }
+}
+
+/// Creates a tracing span for a Redis command from a precomputed operation name.
+///
+/// Nothing is parsed from the command: `db.operation` is set to `operation` verbatim
+/// and `attributes` are applied as given. Use this when the caller already knows the
+/// command's shape and wants to skip the work done by [`create_command_span`].
+///
+/// The span is named after [`InstrumentationConfig::with_span_name_strategy`], with
+/// no key for [`SpanNameStrategy::OperationAndCollection`], and uses the configured
+/// tracing target. Like [`create_command_span_with_config`], it is disabled for
+/// operations [`InstrumentationConfig::with_writes_only`] doesn't instrument.
+/// `db.system` is recorded unless [`InstrumentationConfig::with_db_system_attribute`]
+/// turned it off. Span enrichers and preprocessors are not run, as they take the command.
+pub fn create_raw_command_span(
+    operation: &'static str,
+    attributes: &[KeyValue],
+    config: &InstrumentationConfig,
+) -> tracing::Span {
+    if config.writes_only() {
+        let command = operation.split(' ').next().unwrap_or(operation);
+        if !config.instruments(classify_command(command)) {
+            return tracing::Span::none();
+        }
+    }
+
+    let span_name = match config.span_name_strategy() {
+        SpanNameStrategy::RedisPrefixed => match config.operation_case() {
+            OperationCase::Preserve => format!("redis {operation}"),
+            OperationCase::Upper | OperationCase::Lower => generate_span_name(operation),
+        },
+        SpanNameStrategy::OperationOnly | SpanNameStrategy::OperationAndCollection => {
+            operation.to_string()
+        }
+    };
+    let db_system = config.db_system_attribute().then_some("redis");
+    let span = match config.tracing_target() {
+        Some(target) => target::command_span(target, &span_name, operation, db_system),
+        None => command_span(&span_name, operation, db_system),
+    };
+    apply_span_attributes(&span, attributes);
+    span
+}
+
+/// Creates the span for a raw command sent over a connection with the given state.
+///
+/// This is [`create_raw_command_span`], parented or linked to the connection's
+/// lifetime span like [`create_connection_command_span`], and disabled the same way
+/// under window aggregation.
+pub fn create_connection_raw_command_span(
+    operation: &'static str,
+    attributes: &[KeyValue],
+    config: &InstrumentationConfig,
+    state: &ConnectionState,
+) -> tracing::Span {
+    if state.window_aggregator().is_some() {
+        return tracing::Span::none();
+    }
+    match state.connection_span() {
+        Some((connection, ConnectionSpanMode::Parent)) => {
+            connection.in_scope(|| create_raw_command_span(operation, attributes, config))
+        }
+        Some((connection, ConnectionSpanMode::Link)) => {
+            let span = create_raw_command_span(operation, attributes, config);
+            span.follows_from(connection);
+            span
+        }
+        None => create_raw_command_span(operation, attributes, config),
+    }
+}
+
+/// Creates the `redis_command` span, declaring every field the wrappers may record later.
+///
+/// Fields added here must also be added to [`target::COMMAND_SPAN_FIELDS`].
+///
+/// `db.system` is left unset when `db_system` is `None`.
+fn command_span(name: &str, operation: &str, db_system: Option<&'static str>) -> tracing::Span {
+    tracing::info_span!(
+        "redis_command",
+        otel.name = %name,
+        otel.kind = "client",
+        db.system = db_system,
+        db.operation = %operation,
+        redis.latency_bucket = tracing::field::Empty,
+        redis.tracking.enabled = tracing::field::Empty,
+        redis.tracking.key_tracked = tracing::field::Empty,
+        redis.routing = tracing::field::Empty,
+        redis.connection.state = tracing::field::Empty,
+        server.address = tracing::field::Empty,
+        server.port = tracing::field::Empty,
+        redis.command.raw_verb = tracing::field::Empty,
+        db.redis.key_count = tracing::field::Empty,
+        db.redis.numkeys = tracing::field::Empty,
+        redis.shard = tracing::field::Empty,
+        redis.command.weight = tracing::field::Empty,
+        redis.expire.unit = tracing::field::Empty,
+        redis.expire.absolute = tracing::field::Empty,
+        redis.expire.ttl_ms = tracing::field::Empty,
+        db.statement = tracing::field::Empty,
+        redis.connection_setup = tracing::field::Empty,
+        redis.idempotent = tracing::field::Empty,
+        redis.command.fingerprint = tracing::field::Empty,
+        db.redis.protocol_version = tracing::field::Empty,
+        redis.criticality = tracing::field::Empty,
+        redis.eviction_pressure = tracing::field::Empty,
+        db.redis.database_index = tracing::field::Empty,
+        db.redis.database_name = tracing::field::Empty,
+        redis.conditional.applied = tracing::field::Empty,
+        redis.cache.hit_ratio = tracing::field::Empty,
+        thread.id = tracing::field::Empty,
+        thread.name = tracing::field::Empty,
+        tokio.task.id = tracing::field::Empty,
+        redis.error.statement = tracing::field::Empty,
+        redis.error.reply = tracing::field::Empty,
+        redis.redirect.node = tracing::field::Empty,
+        redis.redirect.slot = tracing::field::Empty,
+        db.redis.response.size = tracing::field::Empty,
+        redis.resp.reply_bytes = tracing::field::Empty,
+        redis.server.exec_us = tracing::field::Empty,
+        redis.start_unix_nanos = tracing::field::Empty,
+        redis.end_unix_nanos = tracing::field::Empty,
+        redis.queue_wait_ms = tracing::field::Empty,
+        redis.server_wait_ms = tracing::field::Empty,
+        otel.status_code = tracing::field::Empty,
+        otel.status_description = tracing::field::Empty,
+        error.type = tracing::field::Empty
+    )
 }
 
 /// Applies a set of attributes as fields to a given `tracing::Span`.
 ///
 /// This function iterates through a list of attributes (key-value pairs) and maps
 /// them to the provided span by recording their values. Only attributes with
-/// supported value types (`String`, `i64`, `f64`, and `bool`) are recorded.
-/// Unsupported value types are ignored.
+/// supported value types (`String`, `i64`, `f64`, `bool`, and arrays of these) are
+/// recorded. Unsupported value types are ignored.
 ///
 /// # Parameters
 ///
@@ -247,6 +944,8 @@ pub fn create_command_span(cmd: &redis::Cmd) -> (tracing::Span, Vec<KeyValue>) {
 /// - `i64`: 64-bit integers are recorded as is.
 /// - `f64`: 64-bit floating point numbers are recorded as is.
 /// - `bool`: Boolean values are recorded as is.
+/// - `Array`: Arrays are recorded as their elements joined with commas, e.g.
+///   `user:1,user:2` for an array of strings; see [`join_array`].
 ///
 /// # Behavior
 ///
@@ -288,20 +987,23 @@ pub fn create_command_span(cmd: &redis::Cmd) -> (tracing::Span, Vec<KeyValue>) {
 /// This function does not return errors. However, if the span itself encounters
 /// an issue (e.g., if it is invalid), the corresponding attribute recording
 /// operation might fail silently depending on the configuration of `tracing`.
-pub fn apply_span_attributes(span: &tracing::Span, attributes: &[KeyValue]) {
+pub fn apply_span_attributes(span: &impl RecordFields, attributes: &[KeyValue]) {
     for attr in attributes {
         match &attr.value {
             opentelemetry::Value::String(s) => {
-                span.record(attr.key.as_str(), s.as_str());
+                span.record_field(attr.key.as_str(), s.as_str());
             }
             opentelemetry::Value::I64(i) => {
-                span.record(attr.key.as_str(), *i);
+                span.record_field(attr.key.as_str(), *i);
             }
             opentelemetry::Value::F64(f) => {
-                span.record(attr.key.as_str(), *f);
+                span.record_field(attr.key.as_str(), *f);
             }
             opentelemetry::Value::Bool(b) => {
-                span.record(attr.key.as_str(), *b);
+                span.record_field(attr.key.as_str(), *b);
+            }
+            opentelemetry::Value::Array(array) => {
+                span.record_field(attr.key.as_str(), join_array(array).as_str());
             }
             _ => {
                 // Skip other value types that don't map well to tracing fields
@@ -310,6 +1012,38 @@ pub fn apply_span_attributes(span: &tracing::Span, attributes: &[KeyValue]) {
     }
 }
 
+/// Renders an attribute array as its elements joined with commas.
+///
+/// Strings are joined as they are, without quotes, so `["a", "b"]` renders as `a,b`.
+///
+/// # Examples
+///
+/// ```rust
+/// use opentelemetry::{Array, StringValue};
+/// use otel_instrumentation_redis::common::join_array;
+///
+/// let keys = Array::String(vec![StringValue::from("user:1"), StringValue::from("user:2")]);
+/// assert_eq!(join_array(&keys), "user:1,user:2");
+/// assert_eq!(join_array(&Array::I64(vec![1, 2, 3])), "1,2,3");
+/// ```
+pub fn join_array(array: &opentelemetry::Array) -> String {
+    fn join<T: std::fmt::Display>(values: &[T]) -> String {
+        values
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    match array {
+        opentelemetry::Array::Bool(values) => join(values),
+        opentelemetry::Array::I64(values) => join(values),
+        opentelemetry::Array::F64(values) => join(values),
+        opentelemetry::Array::String(values) => join(values),
+        other => other.to_string(),
+    }
+}
+
 /// Records the result of a command execution to a tracing span.
 ///
 /// This function takes a tracing span and a result object (of type `Result`)
@@ -328,7 +1062,9 @@ pub fn apply_span_attributes(span: &tracing::Span, attributes: &[KeyValue]) {
 /// # Behavior
 /// - If `result` is `Ok`, it records the status code "OK" on the given `span`.
 /// - If `result` is `Err`, it calls the `record_error_on_span` function to handle
-///   the error.
+///   the error. A cluster redirection, a `MOVED` or `ASK` error, also records the
+///   node it points to as `redis.redirect.node` and the key's slot as
+///   `redis.redirect.slot`.
 ///
 /// # Examples
 ///
@@ -354,15 +1090,248 @@ pub fn apply_span_attributes(span: &tracing::Span, attributes: &[KeyValue]) {
 /// # Notes
 /// Ensure that the `record_error_on_span` function is properly implemented to handle and log
 /// error details to the span. This function assumes `record_error_on_span` is already defined elsewhere in the code.
-pub fn record_command_result<T>(span: &tracing::Span, result: &Result<T, redis::RedisError>) {
+pub fn record_command_result<T>(span: &impl RecordFields, result: &Result<T, redis::RedisError>) {
     match result {
         Ok(_) => {
-            span.record("otel.status_code", "OK");
+            span.record_field("otel.status_code", "OK");
         }
         Err(err) => {
             record_error_on_span(span, err);
+            if let Some((node, slot)) = err.redirect_node() {
+                span.record_field("redis.redirect.node", node);
+                span.record_field("redis.redirect.slot", i64::from(slot));
+            }
+        }
+    }
+}
+
+/// Converts a reply to `RV`, recording why on the current span when it can't.
+///
+/// The convenience methods convert replies once their command span has closed, so a
+/// failed conversion is recorded on the method's span instead:
+///
+/// - `redis.conversion.target_type`: The requested type, from [`std::any::type_name`]
+/// - `redis.conversion.actual_type`: The variant of the reply, e.g. `BulkString`
+///
+/// Spans that don't declare these fields get them on a `redis.conversion` event.
+pub fn convert_reply<RV: redis::FromRedisValue>(value: &redis::Value) -> redis::RedisResult<RV> {
+    RV::from_redis_value(value).inspect_err(|err| {
+        if err.kind() == redis::ErrorKind::TypeError {
+            let attributes = [
+                KeyValue::new("redis.conversion.target_type", std::any::type_name::<RV>()),
+                KeyValue::new("redis.conversion.actual_type", value_type_name(value)),
+            ];
+            let span = tracing::Span::current();
+            apply_span_attributes(&span, &attributes);
+            response::emit_undeclared_attributes(&span, &attributes, "redis.conversion");
+        }
+    })
+}
+
+/// Returns the name of `value`'s variant
+fn value_type_name(value: &redis::Value) -> &'static str {
+    use redis::Value;
+
+    match value {
+        Value::Nil => "Nil",
+        Value::Int(_) => "Int",
+        Value::BulkString(_) => "BulkString",
+        Value::Array(_) => "Array",
+        Value::SimpleString(_) => "SimpleString",
+        Value::Okay => "Okay",
+        Value::Map(_) => "Map",
+        Value::Attribute { .. } => "Attribute",
+        Value::Set(_) => "Set",
+        Value::Double(_) => "Double",
+        Value::Boolean(_) => "Boolean",
+        Value::VerbatimString { .. } => "VerbatimString",
+        Value::BigNumber(_) => "BigNumber",
+        Value::Push { .. } => "Push",
+        Value::ServerError(_) => "ServerError",
+    }
+}
+
+/// Calls the configured error hook if `result` is an error.
+///
+/// Panics raised by the hook are caught and logged, so a faulty hook can't take
+/// down the command path. See [`InstrumentationConfig::with_error_hook`].
+pub fn run_error_hook<T>(
+    cmd: &redis::Cmd,
+    result: &Result<T, redis::RedisError>,
+    config: &InstrumentationConfig,
+) {
+    let (Err(err), Some(hook)) = (result, config.error_hook()) else {
+        return;
+    };
+    call_user_callback("Redis error hook", || hook(cmd, err));
+}
+
+/// Records the attributes the configured response extractor derives from a
+/// successful reply, see [`InstrumentationConfig::with_response_attribute_extractor`].
+pub fn record_extracted_attributes(
+    span: &tracing::Span,
+    cmd: &redis::Cmd,
+    result: &Result<redis::Value, redis::RedisError>,
+    config: &InstrumentationConfig,
+) {
+    let (Ok(reply), Some(extractor)) = (result, config.response_attribute_extractor()) else {
+        return;
+    };
+    if span.is_disabled() || matches!(reply, redis::Value::ServerError(_)) {
+        return;
+    }
+    let Some(operation) = get_command_name(cmd) else {
+        return;
+    };
+    if let Some(attributes) = call_user_callback("Redis response attribute extractor", || {
+        extractor(&operation, reply)
+    }) {
+        response::record_response_attributes(span, &attributes);
+    }
+}
+
+/// Calls a user-supplied callback, logging instead of propagating a panic so a
+/// faulty callback can't take down the command path.
+fn call_user_callback<R>(description: &str, callback: impl FnOnce() -> R) -> Option<R> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(callback)) {
+        Ok(value) => Some(value),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("non-string panic payload");
+            tracing::warn!(panic = message, "{description} panicked");
+            None
+        }
+    }
+}
+
+/// Records the redacted statement and error reply of a failed command.
+///
+/// - `redis.error.statement`: The command as rendered by [`redacted_statement`].
+/// - `redis.error.reply`: The error code and message replied by the server, omitted
+///   for client-side errors such as a dropped connection.
+///
+/// A command counts as failed when it returns an error or the server replies with
+/// one. Does nothing for other commands or unless
+/// [`InstrumentationConfig::with_error_statement_capture`] is enabled.
+pub fn record_error_statement(
+    span: &impl RecordFields,
+    cmd: &redis::Cmd,
+    result: &Result<redis::Value, redis::RedisError>,
+    config: &InstrumentationConfig,
+) {
+    if !config.error_statement_capture() {
+        return;
+    }
+    let reply = match result {
+        Err(err) => err.code().map(|code| (code, err.detail())),
+        Ok(redis::Value::ServerError(err)) => Some((err.code(), err.details())),
+        Ok(_) => return,
+    };
+    span.record_field(
+        "redis.error.statement",
+        redacted_statement(cmd, config).as_str(),
+    );
+    match reply {
+        Some((code, Some(detail))) => {
+            span.record_field("redis.error.reply", format!("{code} {detail}").as_str());
+        }
+        Some((code, None)) => span.record_field("redis.error.reply", code),
+        None => {}
+    }
+}
+
+/// Renders `cmd` with its values masked, for recording on a span.
+///
+/// The command name is kept and the key, the first argument, passes through
+/// [`InstrumentationConfig::redact_key`]; every other argument becomes `?`, so
+/// `SET user:1 secret EX 60` renders as `SET user:1 ? ? ?`. Commands that may carry
+/// credentials (`AUTH`, `HELLO`, `MIGRATE`, `ACL` and `CONFIG`) keep only their name.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::redacted_statement;
+/// use otel_instrumentation_redis::InstrumentationConfig;
+///
+/// let config = InstrumentationConfig::new();
+/// let cmd = redis::cmd("AUTH").arg("admin").arg("hunter2").clone();
+/// assert_eq!(redacted_statement(&cmd, &config), "AUTH ? ?");
+/// ```
+pub fn redacted_statement(cmd: &redis::Cmd, config: &InstrumentationConfig) -> String {
+    let mut args = cmd.args_iter();
+    let Some(redis::Arg::Simple(name)) = args.next() else {
+        return String::new();
+    };
+    let name = String::from_utf8_lossy(name);
+    let carries_credentials = carries_credentials(&name);
+
+    let mut statement = name.into_owned();
+    for (index, arg) in args.enumerate() {
+        statement.push(' ');
+        match arg {
+            redis::Arg::Simple(key) if index == 0 && !carries_credentials => {
+                statement.push_str(&config.redact_key(&String::from_utf8_lossy(key)));
+            }
+            _ => statement.push('?'),
+        }
+    }
+    statement
+}
+
+/// Returns `true` for commands whose arguments may include credentials
+fn carries_credentials(name: &str) -> bool {
+    ["AUTH", "HELLO", "MIGRATE", "ACL", "CONFIG"]
+        .iter()
+        .any(|command| name.eq_ignore_ascii_case(command))
+}
+
+/// Renders `cmd` with its arguments as sent, for [`StatementCapture::Full`].
+///
+/// The key, the first argument, passes through [`InstrumentationConfig::redact_key`].
+/// The arguments of commands that may carry credentials become `?`, except for
+/// `HELLO`, whose protocol version and options before `AUTH` are kept.
+fn full_statement(cmd: &redis::Cmd, config: &InstrumentationConfig) -> String {
+    let mut args = cmd.args_iter();
+    let Some(redis::Arg::Simple(name)) = args.next() else {
+        return String::new();
+    };
+    let name = String::from_utf8_lossy(name);
+    let is_hello = name.eq_ignore_ascii_case("HELLO");
+    let mut masked = carries_credentials(&name) && !is_hello;
+
+    let mut statement = name.into_owned();
+    for (index, arg) in args.enumerate() {
+        statement.push(' ');
+        let redis::Arg::Simple(arg) = arg else {
+            statement.push('?');
+            continue;
+        };
+        let arg = String::from_utf8_lossy(arg);
+        if masked {
+            statement.push('?');
+        } else if is_hello {
+            statement.push_str(&arg);
+            masked = arg.eq_ignore_ascii_case("AUTH");
+        } else if index == 0 {
+            statement.push_str(&config.redact_key(&arg));
+        } else {
+            statement.push_str(&arg);
         }
     }
+    statement
+}
+
+/// Shortens `statement` to `max_length` characters, the last of them an ellipsis
+fn truncate_statement(statement: String, max_length: usize) -> String {
+    if max_length == 0 || statement.chars().count() <= max_length {
+        return statement;
+    }
+    let mut truncated: String = statement.chars().take(max_length - 1).collect();
+    truncated.push('…');
+    truncated
 }
 
 /// Records an error into a given tracing span with detailed metadata for observability.
@@ -421,64 +1390,64 @@ pub fn record_command_result<T>(span: &tracing::Span, result: &Result<T, redis::
 /// ```
 ///
 /// In this example, the span will be enriched with error metadata, categorizing the error type as `"type_error"`.
-pub fn record_error_on_span(span: &tracing::Span, err: &redis::RedisError) {
-    span.record("error", true);
-    span.record("error.message", tracing::field::display(err));
-    span.record("otel.status_code", "ERROR");
-    span.record("otel.status_description", tracing::field::display(err));
+pub fn record_error_on_span(span: &impl RecordFields, err: &redis::RedisError) {
+    span.record_field("error", true);
+    span.record_field("error.message", tracing::field::display(err));
+    span.record_field("otel.status_code", "ERROR");
+    span.record_field("otel.status_description", tracing::field::display(err));
 
     // Add error type categorization for better observability
     match err.kind() {
         redis::ErrorKind::ResponseError => {
-            span.record("error.type", "response_error");
+            span.record_field("error.type", "response_error");
         }
         redis::ErrorKind::AuthenticationFailed => {
-            span.record("error.type", "authentication_failed");
+            span.record_field("error.type", "authentication_failed");
         }
         redis::ErrorKind::TypeError => {
-            span.record("error.type", "type_error");
+            span.record_field("error.type", "type_error");
         }
         redis::ErrorKind::ExecAbortError => {
-            span.record("error.type", "exec_abort_error");
+            span.record_field("error.type", "exec_abort_error");
         }
         redis::ErrorKind::BusyLoadingError => {
-            span.record("error.type", "busy_loading_error");
+            span.record_field("error.type", "busy_loading_error");
         }
         redis::ErrorKind::NoScriptError => {
-            span.record("error.type", "no_script_error");
+            span.record_field("error.type", "no_script_error");
         }
         redis::ErrorKind::InvalidClientConfig => {
-            span.record("error.type", "invalid_client_config");
+            span.record_field("error.type", "invalid_client_config");
         }
         redis::ErrorKind::Moved => {
-            span.record("error.type", "moved");
+            span.record_field("error.type", "moved");
         }
         redis::ErrorKind::Ask => {
-            span.record("error.type", "ask");
+            span.record_field("error.type", "ask");
         }
         redis::ErrorKind::TryAgain => {
-            span.record("error.type", "try_again");
+            span.record_field("error.type", "try_again");
         }
         redis::ErrorKind::ClusterDown => {
-            span.record("error.type", "cluster_down");
+            span.record_field("error.type", "cluster_down");
         }
         redis::ErrorKind::CrossSlot => {
-            span.record("error.type", "cross_slot");
+            span.record_field("error.type", "cross_slot");
         }
         redis::ErrorKind::MasterDown => {
-            span.record("error.type", "master_down");
+            span.record_field("error.type", "master_down");
         }
         redis::ErrorKind::IoError => {
-            span.record("error.type", "io_error");
+            span.record_field("error.type", "io_error");
         }
         redis::ErrorKind::ClientError => {
-            span.record("error.type", "client_error");
+            span.record_field("error.type", "client_error");
         }
         redis::ErrorKind::ExtensionError => {
-            span.record("error.type", "extension_error");
+            span.record_field("error.type", "extension_error");
         }
         _ => {
-            span.record("error.type", "unknown");
+            span.record_field("error.type", "unknown");
         }
     }
 }
@@ -544,3 +1513,1449 @@ pub fn record_command_result_with_context<T>(
         }
     }
 }
+
+/// Counts the field-value pairs written by an `HSET` or `HMSET` command.
+///
+/// Both commands take the form `HSET key field value [field value ...]`, so the
+/// number of pairs is derived from the arguments following the key.
+///
+/// # Returns
+///
+/// - `Some(count)`: The number of field-value pairs in the command.
+/// - `None`: If the command does not contain at least one complete pair.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::hash_fields_set;
+///
+/// let mut cmd = redis::Cmd::new();
+/// cmd.arg("HSET").arg("user:1").arg("name").arg("Alice").arg("age").arg(30);
+/// assert_eq!(hash_fields_set(&cmd), Some(2));
+/// ```
+pub fn hash_fields_set(cmd: &redis::Cmd) -> Option<i64> {
+    // Skip the command name and the key
+    let pair_args = cmd.args_iter().count().checked_sub(2)?;
+    match pair_args / 2 {
+        0 => None,
+        pairs => Some(pairs as i64),
+    }
+}
+
+/// Records the hash field counts of an `HSET`/`HMSET` command on a span.
+///
+/// - `redis.hash.fields_set`: The number of field-value pairs in the command,
+///   as computed by [`hash_fields_set`].
+/// - `redis.hash.new_fields`: The integer reply of `HSET`, i.e. how many of those
+///   fields did not exist before. `HMSET` replies with `OK`, so this is omitted.
+///
+/// The span must declare both fields for the values to be recorded.
+pub fn record_hash_set_fields(
+    span: &tracing::Span,
+    cmd: &redis::Cmd,
+    result: &Result<redis::Value, redis::RedisError>,
+) {
+    if let Some(fields_set) = hash_fields_set(cmd) {
+        span.record("redis.hash.fields_set", fields_set);
+    }
+    if let Ok(redis::Value::Int(new_fields)) = result {
+        span.record("redis.hash.new_fields", *new_fields);
+    }
+}
+
+/// Returns argument `index` of a command as a string, replacing invalid UTF-8.
+fn command_arg(cmd: &redis::Cmd, index: usize) -> Option<String> {
+    match cmd.args_iter().nth(index)? {
+        redis::Arg::Simple(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+        redis::Arg::Cursor => None,
+    }
+}
+
+/// Records the keys of an `SMOVE source destination member` command on a span.
+///
+/// - `redis.smove.source`: The set the member is moved from.
+/// - `redis.smove.destination`: The set the member is moved to.
+///
+/// The span must declare both fields for the values to be recorded.
+pub fn record_smove_keys(span: &tracing::Span, cmd: &redis::Cmd) {
+    record_smove_keys_with_config(span, cmd, &InstrumentationConfig::default());
+}
+
+/// Records the keys of an `SMOVE` command like [`record_smove_keys`], after applying
+/// the key redactions of an [`InstrumentationConfig`].
+pub fn record_smove_keys_with_config(
+    span: &tracing::Span,
+    cmd: &redis::Cmd,
+    config: &InstrumentationConfig,
+) {
+    if let Some(source) = command_arg(cmd, 1) {
+        span.record("redis.smove.source", config.redact_key(&source).as_ref());
+    }
+    if let Some(destination) = command_arg(cmd, 2) {
+        span.record(
+            "redis.smove.destination",
+            config.redact_key(&destination).as_ref(),
+        );
+    }
+}
+
+/// Most channel or pattern names recorded on a subscription span; further ones are
+/// only counted
+pub const MAX_PUBSUB_NAMES: usize = 32;
+
+/// Records the channel of a `PUBLISH channel message` command on a span as
+/// `redis.pubsub.channel`, after applying the key redactions of `config`.
+///
+/// The span must declare the field for the value to be recorded.
+pub fn record_publish_channel(
+    span: &tracing::Span,
+    cmd: &redis::Cmd,
+    config: &InstrumentationConfig,
+) {
+    if let Some(channel) = command_arg(cmd, 1) {
+        span.record("redis.pubsub.channel", config.redact_key(&channel).as_ref());
+    }
+}
+
+/// Records the names of a subscribe or unsubscribe call on a span.
+///
+/// - `redis.pubsub.channels`: The channel names, for `SUBSCRIBE` and `UNSUBSCRIBE`.
+/// - `redis.pubsub.patterns`: The patterns, for `PSUBSCRIBE` and `PUNSUBSCRIBE`,
+///   recorded when `pattern` is set.
+///
+/// Names pass through [`InstrumentationConfig::redact_key`] and are recorded as an
+/// array attribute, so they render comma-joined, see [`apply_span_attributes`]. Only
+/// the first [`MAX_PUBSUB_NAMES`] are recorded.
+pub fn record_subscription_names(
+    span: &impl RecordFields,
+    names: &[Vec<u8>],
+    pattern: bool,
+    config: &InstrumentationConfig,
+) {
+    if names.is_empty() {
+        return;
+    }
+    let key = if pattern {
+        "redis.pubsub.patterns"
+    } else {
+        "redis.pubsub.channels"
+    };
+    record_key_names(span, key, names, config);
+}
+
+/// Records the first [`MAX_PUBSUB_NAMES`] of `names` as a redacted array attribute
+fn record_key_names(
+    span: &impl RecordFields,
+    key: &'static str,
+    names: &[Vec<u8>],
+    config: &InstrumentationConfig,
+) {
+    let names = names
+        .iter()
+        .take(MAX_PUBSUB_NAMES)
+        .map(|name| {
+            let name = String::from_utf8_lossy(name);
+            opentelemetry::StringValue::from(config.redact_key(&name).into_owned())
+        })
+        .collect();
+    let names = opentelemetry::Value::Array(opentelemetry::Array::String(names));
+    apply_span_attributes(span, &[KeyValue::new(key, names)]);
+}
+
+/// Records the optional count of an `SPOP` or `SRANDMEMBER` command on a span.
+///
+/// - `redis.spop.count` / `redis.srandmember.count`: The count argument, if given.
+/// - `redis.set.allow_duplicates`: Set to `true` for `SRANDMEMBER` with a negative
+///   count, which may return the same member several times. `SPOP` rejects
+///   negative counts, so it never records this.
+///
+/// The span must declare the fields for the values to be recorded.
+pub fn record_set_sample_count(span: &tracing::Span, cmd: &redis::Cmd) {
+    let Some(count) = command_arg(cmd, 2).and_then(|count| count.parse::<i64>().ok()) else {
+        return;
+    };
+    match get_command_name(cmd).as_deref() {
+        Some("SPOP") => {
+            span.record("redis.spop.count", count);
+        }
+        Some("SRANDMEMBER") => {
+            span.record("redis.srandmember.count", count);
+            if count < 0 {
+                span.record("redis.set.allow_duplicates", true);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Removes repeated keys, returning the distinct keys in first-seen order and, for
+/// each input key, the index of its distinct key.
+///
+/// Keys are compared by their serialized form, so `"a"` and `b"a"` are the same key.
+pub fn dedupe_keys<K: redis::ToRedisArgs>(keys: &[K]) -> (Vec<Vec<u8>>, Vec<usize>) {
+    let mut unique: Vec<Vec<u8>> = Vec::with_capacity(keys.len());
+    let mut indexes: std::collections::HashMap<Vec<u8>, usize> = Default::default();
+    let positions = keys
+        .iter()
+        .flat_map(|key| key.to_redis_args())
+        .map(|key| {
+            *indexes.entry(key).or_insert_with_key(|key| {
+                unique.push(key.clone());
+                unique.len() - 1
+            })
+        })
+        .collect();
+    (unique, positions)
+}
+
+/// Records the key counts of a multi-key command such as `TOUCH` or `UNLINK` on a span.
+///
+/// - `redis.keys.count`: The number of keys in the command.
+/// - `redis.keys.affected`: The integer reply, i.e. how many of those keys existed.
+///
+/// The span must declare both fields for the values to be recorded.
+pub fn record_multi_key_counts(
+    span: &tracing::Span,
+    cmd: &redis::Cmd,
+    result: &Result<redis::Value, redis::RedisError>,
+) {
+    if let Some(keys) = cmd.args_iter().count().checked_sub(1) {
+        span.record("redis.keys.count", keys as i64);
+    }
+    if let Ok(redis::Value::Int(affected)) = result {
+        span.record("redis.keys.affected", *affected);
+    }
+}
+
+/// Records the outcome of an `EXISTS` command on a span.
+///
+/// - `redis.exists.found`: `false` when none of the keys exist, so cache-miss
+///   analysis can tell a missing key apart from a failed lookup.
+/// - `redis.exists.found_count`: How many of the keys exist, for multi-key calls.
+/// - `otel.status_code`: `OK` for any integer reply, including 0.
+///
+/// The span must declare these fields for the values to be recorded.
+pub fn record_exists_result(
+    span: &tracing::Span,
+    cmd: &redis::Cmd,
+    result: &Result<redis::Value, redis::RedisError>,
+) {
+    if let Ok(redis::Value::Int(found)) = result {
+        span.record("redis.exists.found", *found > 0);
+        if cmd.args_iter().count() > 2 {
+            span.record("redis.exists.found_count", *found);
+        }
+    }
+    record_command_result(span, result);
+}
+
+/// Records the expiry option of a `GETEX` command on a span.
+///
+/// - `redis.ttl.mode`: `EX`, `PX`, `EXAT`, `PXAT` or `PERSIST`, omitted when the
+///   command leaves the TTL untouched.
+/// - `redis.ttl.value`: The seconds, milliseconds or Unix time given with the mode.
+///
+/// The span must declare both fields for the values to be recorded.
+pub fn record_getex_expiry(span: &tracing::Span, cmd: &redis::Cmd) {
+    let Some(mode) = command_arg(cmd, 2).map(|mode| mode.to_ascii_uppercase()) else {
+        return;
+    };
+    if !matches!(mode.as_str(), "EX" | "PX" | "EXAT" | "PXAT" | "PERSIST") {
+        return;
+    }
+    span.record("redis.ttl.mode", mode.as_str());
+    if let Some(value) = command_arg(cmd, 3).and_then(|value| value.parse::<i64>().ok()) {
+        span.record("redis.ttl.value", value);
+    }
+}
+
+/// Records the byte range of a `GETRANGE` or `SETRANGE` command on a span.
+///
+/// - `redis.range.start`: The start offset.
+/// - `redis.range.end`: The inclusive end offset. For `SETRANGE` this is the last
+///   byte written, the offset plus the value length minus one, and is omitted for
+///   an empty value.
+///
+/// Offsets are recorded as sent, so `GETRANGE` may record negative offsets counted
+/// from the end of the string. The span must declare both fields for the values
+/// to be recorded.
+pub fn record_string_range(span: &tracing::Span, cmd: &redis::Cmd) {
+    let Some(start) = command_arg(cmd, 2).and_then(|start| start.parse::<i64>().ok()) else {
+        return;
+    };
+    span.record("redis.range.start", start);
+    let end = match get_command_name(cmd).as_deref() {
+        Some("GETRANGE") => command_arg(cmd, 3).and_then(|end| end.parse::<i64>().ok()),
+        Some("SETRANGE") => match cmd.args_iter().nth(3) {
+            Some(redis::Arg::Simple(value)) if !value.is_empty() => {
+                Some(start + value.len() as i64 - 1)
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some(end) = end {
+        span.record("redis.range.end", end);
+    }
+}
+
+/// Records the string length returned by `APPEND`, `SETRANGE` or `STRLEN` as
+/// `redis.string.length`.
+///
+/// The span must declare the field for the value to be recorded.
+pub fn record_string_length(
+    span: &tracing::Span,
+    result: &Result<redis::Value, redis::RedisError>,
+) {
+    if let Ok(redis::Value::Int(length)) = result {
+        span.record("redis.string.length", *length);
+    }
+}
+
+/// One end of a sorted-set score range, as accepted by `ZRANGEBYSCORE` and friends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreBound {
+    /// The score, possibly infinite for `-inf`/`+inf`
+    pub value: f64,
+    /// `true` for the `(score` form, which excludes the score itself
+    pub exclusive: bool,
+}
+
+/// Parses a score bound such as `1.5`, `(1`, `-inf` or `(+inf`.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::{parse_score_bound, ScoreBound};
+///
+/// assert_eq!(parse_score_bound(b"(1"), Some(ScoreBound { value: 1.0, exclusive: true }));
+/// assert_eq!(
+///     parse_score_bound(b"+inf"),
+///     Some(ScoreBound { value: f64::INFINITY, exclusive: false })
+/// );
+/// assert_eq!(parse_score_bound(b"nan"), None);
+/// ```
+pub fn parse_score_bound(arg: &[u8]) -> Option<ScoreBound> {
+    let arg = std::str::from_utf8(arg).ok()?;
+    let (arg, exclusive) = match arg.strip_prefix('(') {
+        Some(rest) => (rest, true),
+        None => (arg, false),
+    };
+    let value = if arg.eq_ignore_ascii_case("-inf") {
+        f64::NEG_INFINITY
+    } else if arg.eq_ignore_ascii_case("+inf") || arg.eq_ignore_ascii_case("inf") {
+        f64::INFINITY
+    } else {
+        arg.parse::<f64>().ok().filter(|value| value.is_finite())?
+    };
+    Some(ScoreBound { value, exclusive })
+}
+
+/// Returns the `(min, max)` score bounds of a `ZRANGEBYSCORE`-style command.
+///
+/// `ZREVRANGEBYSCORE` takes its bounds as `max min`; they are swapped back so the
+/// lower bound always comes first.
+pub fn score_bounds(cmd: &redis::Cmd) -> Option<(ScoreBound, ScoreBound)> {
+    let mut args = cmd.args_iter().map(|arg| match arg {
+        redis::Arg::Simple(bytes) => bytes,
+        redis::Arg::Cursor => &[],
+    });
+    let name = args.next()?;
+    let _key = args.next()?;
+    let first = parse_score_bound(args.next()?)?;
+    let second = parse_score_bound(args.next()?)?;
+    if name.eq_ignore_ascii_case(b"ZREVRANGEBYSCORE") {
+        Some((second, first))
+    } else {
+        Some((first, second))
+    }
+}
+
+/// Records the score range of a `ZRANGEBYSCORE`/`ZREVRANGEBYSCORE` command on a span.
+///
+/// - `redis.zset.min` / `redis.zset.max`: The bounds as numbers, infinite for `-inf`/`+inf`.
+/// - `redis.zset.min_exclusive` / `redis.zset.max_exclusive`: Whether the bound used
+///   the exclusive `(` form.
+///
+/// The span must declare these fields for the values to be recorded.
+pub fn record_score_bounds(span: &tracing::Span, cmd: &redis::Cmd) {
+    if let Some((min, max)) = score_bounds(cmd) {
+        span.record("redis.zset.min", min.value);
+        span.record("redis.zset.min_exclusive", min.exclusive);
+        span.record("redis.zset.max", max.value);
+        span.record("redis.zset.max_exclusive", max.exclusive);
+    }
+}
+
+/// A condition flag accepted by `ZADD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZAddFlag {
+    /// Only add new members
+    Nx,
+    /// Only update existing members
+    Xx,
+    /// Only update when the new score is greater
+    Gt,
+    /// Only update when the new score is less
+    Lt,
+}
+
+impl ZAddFlag {
+    /// Returns the flag as sent to the server
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZAddFlag::Nx => "NX",
+            ZAddFlag::Xx => "XX",
+            ZAddFlag::Gt => "GT",
+            ZAddFlag::Lt => "LT",
+        }
+    }
+}
+
+impl redis::ToRedisArgs for ZAddFlag {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + redis::RedisWrite,
+    {
+        out.write_arg(self.as_str().as_bytes());
+    }
+}
+
+/// Records `redis.zset.flags`, the `ZADD` condition flags joined by spaces, e.g. `XX GT`.
+///
+/// Nothing is recorded for a plain `ZADD`. The span must declare the field.
+pub fn record_zadd_flags(span: &tracing::Span, flags: &[ZAddFlag]) {
+    if !flags.is_empty() {
+        let flags: Vec<&str> = flags.iter().map(ZAddFlag::as_str).collect();
+        span.record("redis.zset.flags", flags.join(" "));
+    }
+}
+
+/// Records `redis.command_count`, the number of commands sent by a composite operation.
+///
+/// Every helper that issues more than one command (pipelines, transactions,
+/// `scan_and`) records this same attribute on its parent span, so dashboards can
+/// compare fan-out across helper types. Commands are counted individually even when
+/// they share a round trip: a pipeline of three GETs records `3`, not `1`.
+///
+/// The span must declare the field for the value to be recorded.
+pub fn record_command_count(span: &tracing::Span, count: usize) {
+    span.record("redis.command_count", count as i64);
+}
+
+/// Records how many replies a pipeline returned as `redis.pipeline.result_count`.
+///
+/// `redis.pipeline.count` is the number of replies asked of the connection, which a
+/// misbehaving connection may not honour. Failed pipelines leave this unset: redis-rs
+/// errors don't tell how many replies were read before the failure.
+///
+/// The span must declare the field for the value to be recorded.
+pub fn record_pipeline_result_count(span: &tracing::Span, count: usize) {
+    span.record("redis.pipeline.result_count", count as i64);
+}
+
+/// Returns the number of commands a pipeline sends, counting the `MULTI` and `EXEC`
+/// that wrap a transaction.
+#[cfg(feature = "aio")]
+pub fn pipeline_command_count(pipeline: &redis::Pipeline) -> usize {
+    if pipeline.is_transaction() {
+        pipeline.len() + 2
+    } else {
+        pipeline.len()
+    }
+}
+
+/// Records a transaction that `EXEC` aborted because a `WATCH`ed key changed.
+///
+/// redis-rs hands the nil `EXEC` reply back as a successful result, so the caller
+/// detects the abort and this records it. By default the span is marked as failed
+/// with `error.type = watch_conflict`; with
+/// [`InstrumentationConfig::with_watch_conflict_attribute`] it keeps its status and
+/// records `redis.transaction.conflict = true` instead.
+///
+/// The span must declare these fields for the values to be recorded.
+pub fn record_watch_conflict(span: &tracing::Span, config: &InstrumentationConfig) {
+    if config.watch_conflict_attribute() {
+        span.record("redis.transaction.conflict", true);
+        return;
+    }
+    span.record("otel.status_code", "ERROR");
+    span.record(
+        "otel.status_description",
+        "transaction aborted by a WATCH conflict",
+    );
+    span.record("error", true);
+    span.record("error.type", "watch_conflict");
+}
+
+/// Opens the `redis MULTI` span grouping the commands of a transaction.
+///
+/// The wrappers' `transaction` helpers send `MULTI`, the queued commands and `EXEC`
+/// under this span, so their command spans nest under it.
+pub fn transaction_span(config: &InstrumentationConfig) -> tracing::Span {
+    tracing::info_span!(
+        "redis_transaction",
+        otel.name = "redis MULTI",
+        otel.kind = "client",
+        db.system = config.db_system_attribute().then_some("redis"),
+        db.operation = "MULTI",
+        db.redis.transaction.command_count = tracing::field::Empty,
+        redis.transaction.conflict = tracing::field::Empty,
+        otel.status_code = tracing::field::Empty,
+        otel.status_description = tracing::field::Empty,
+        error = tracing::field::Empty,
+        error.message = tracing::field::Empty,
+        error.type = tracing::field::Empty
+    )
+}
+
+/// Opens the `redis transaction` span of a `WATCH`/`MULTI`/`EXEC` retry loop.
+///
+/// Every attempt's `WATCH`, the commands the closure sends and the transaction's
+/// pipeline nest under this span.
+pub fn watch_transaction_span(config: &InstrumentationConfig) -> tracing::Span {
+    tracing::info_span!(
+        "redis_watch_transaction",
+        otel.name = "redis transaction",
+        otel.kind = "client",
+        db.system = config.db_system_attribute().then_some("redis"),
+        redis.transaction.attempts = tracing::field::Empty,
+        redis.transaction.watched_keys = tracing::field::Empty,
+        otel.status_code = tracing::field::Empty,
+        otel.status_description = tracing::field::Empty,
+        error = tracing::field::Empty,
+        error.message = tracing::field::Empty,
+        error.type = tracing::field::Empty
+    )
+}
+
+/// Records the keys a transaction watches on its [`watch_transaction_span`] as
+/// `redis.transaction.watched_keys`.
+///
+/// Keys pass through [`InstrumentationConfig::redact_key`] and render comma-joined,
+/// like [`record_subscription_names`]; only the first [`MAX_PUBSUB_NAMES`] are recorded.
+pub fn record_watched_keys(
+    span: &impl RecordFields,
+    keys: &[Vec<u8>],
+    config: &InstrumentationConfig,
+) {
+    if !keys.is_empty() {
+        record_key_names(span, "redis.transaction.watched_keys", keys, config);
+    }
+}
+
+/// Records the outcome of a transaction's `EXEC` on its [`transaction_span`].
+///
+/// - `db.redis.transaction.command_count`: The number of commands the server queued.
+/// - An `EXECABORT` reply, sent when a command was rejected while queuing, marks the
+///   span as failed with `error.type = exec_abort_error`.
+/// - A nil reply, a `WATCH` conflict, is recorded by [`record_watch_conflict`].
+pub fn record_transaction_result(
+    span: &tracing::Span,
+    queued: usize,
+    result: &redis::RedisResult<redis::Value>,
+    config: &InstrumentationConfig,
+) {
+    span.record("db.redis.transaction.command_count", queued as i64);
+    record_command_result(span, result);
+    if matches!(result, Ok(redis::Value::Nil)) {
+        record_watch_conflict(span, config);
+    }
+}
+
+/// Emits one span event per pipeline sub-result, as configured by
+/// [`InstrumentationConfig::with_pipeline_result_events`].
+///
+/// Each event carries `redis.pipeline.index` and `redis.pipeline.status`, which is
+/// `ERROR` for a server error reply and `OK` otherwise. When there are more results
+/// than the configured cap, only the first ones are emitted and
+/// `redis.pipeline.events_truncated = true` is recorded on the span, which must
+/// declare that field.
+pub fn record_pipeline_result_events(
+    span: &tracing::Span,
+    results: &[redis::Value],
+    config: &InstrumentationConfig,
+) {
+    let Some(max_events) = config.pipeline_result_events() else {
+        return;
+    };
+    for (index, value) in results.iter().take(max_events).enumerate() {
+        let status = match value {
+            redis::Value::ServerError(_) => "ERROR",
+            _ => "OK",
+        };
+        tracing::info!(
+            parent: span,
+            redis.pipeline.index = index as i64,
+            redis.pipeline.status = status,
+            "redis.pipeline.result"
+        );
+    }
+    if results.len() > max_events {
+        span.record("redis.pipeline.events_truncated", true);
+    }
+}
+
+/// Emits a `redis.progress` event on the current span.
+///
+/// For long-running work such as the closure of `scan_and` or a loop over a large
+/// `SORT` result: called from inside the helper, the event lands on its span and
+/// shows how far the work got. The event carries `redis.progress.processed` and,
+/// when known, `redis.progress.total`.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::record_progress;
+///
+/// let span = tracing::info_span!("batch");
+/// let _enter = span.enter();
+/// for processed in (100..=1000).step_by(100) {
+///     record_progress(processed, Some(1000));
+/// }
+/// ```
+pub fn record_progress(processed: u64, total: Option<u64>) {
+    tracing::info!(
+        redis.progress.processed = processed,
+        redis.progress.total = total,
+        "redis.progress"
+    );
+}
+
+/// Returns the label of the latency bucket `elapsed` falls into.
+///
+/// `boundaries` must be sorted in ascending order. A duration below the first
+/// boundary is labelled `<first`, one at or above the last boundary `>last`, and
+/// anything in between `lower-upper`, e.g. `1-10ms` for the default boundaries.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::latency_bucket;
+/// use otel_instrumentation_redis::config::DEFAULT_LATENCY_BUCKETS;
+/// use std::time::Duration;
+///
+/// assert_eq!(latency_bucket(Duration::from_micros(300), &DEFAULT_LATENCY_BUCKETS), "<1ms");
+/// assert_eq!(latency_bucket(Duration::from_millis(4), &DEFAULT_LATENCY_BUCKETS), "1-10ms");
+/// assert_eq!(latency_bucket(Duration::from_secs(1), &DEFAULT_LATENCY_BUCKETS), ">100ms");
+/// ```
+pub fn latency_bucket(elapsed: Duration, boundaries: &[Duration]) -> String {
+    match boundaries.iter().position(|boundary| elapsed < *boundary) {
+        Some(0) => format!("<{}", format_duration(boundaries[0])),
+        Some(index) => {
+            let (lower_value, lower_unit) = duration_parts(boundaries[index - 1]);
+            let (upper_value, upper_unit) = duration_parts(boundaries[index]);
+            if lower_unit == upper_unit {
+                format!("{lower_value}-{upper_value}{upper_unit}")
+            } else {
+                format!("{lower_value}{lower_unit}-{upper_value}{upper_unit}")
+            }
+        }
+        None => match boundaries.last() {
+            Some(last) => format!(">{}", format_duration(*last)),
+            None => "all".to_string(),
+        },
+    }
+}
+
+/// Returns the wall-clock time a command starts at, if
+/// [`InstrumentationConfig::with_unix_timestamps`] is enabled.
+pub fn command_start_time(config: &InstrumentationConfig) -> Option<SystemTime> {
+    config.unix_timestamps().then(SystemTime::now)
+}
+
+/// Records `redis.start_unix_nanos` from `started_at`, as returned by
+/// [`command_start_time`], and `redis.end_unix_nanos` from the current time.
+///
+/// Does nothing if `started_at` is `None`. Times before the Unix epoch are recorded
+/// as 0.
+pub fn record_unix_timestamps(span: &impl RecordFields, started_at: Option<SystemTime>) {
+    fn unix_nanos(time: SystemTime) -> i64 {
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| {
+                i64::try_from(since.as_nanos()).unwrap_or(i64::MAX)
+            })
+    }
+
+    let Some(started_at) = started_at else {
+        return;
+    };
+    span.record_field("redis.start_unix_nanos", unix_nanos(started_at));
+    span.record_field("redis.end_unix_nanos", unix_nanos(SystemTime::now()));
+}
+
+/// Records the split of an async command's latency, as configured by
+/// [`InstrumentationConfig::with_dispatch_timing`].
+///
+/// - `redis.queue_wait_ms`: From `started` until the command was `dispatched`.
+/// - `redis.server_wait_ms`: From `dispatched` until now, when the reply arrived.
+///
+/// Nothing is recorded when `dispatched` is `None`, i.e. when the split is disabled.
+pub fn record_dispatch_timing(span: &tracing::Span, started: Instant, dispatched: Option<Instant>) {
+    if let Some(dispatched) = dispatched {
+        let queue_wait = dispatched.saturating_duration_since(started);
+        span.record("redis.queue_wait_ms", queue_wait.as_secs_f64() * 1000.0);
+        span.record(
+            "redis.server_wait_ms",
+            dispatched.elapsed().as_secs_f64() * 1000.0,
+        );
+    }
+}
+
+/// Records `redis.latency_bucket` on a span if latency bucketing is configured.
+pub fn record_latency_bucket(
+    span: &impl RecordFields,
+    elapsed: Duration,
+    config: &InstrumentationConfig,
+) {
+    if let Some(boundaries) = config.latency_buckets() {
+        span.record_field(
+            "redis.latency_bucket",
+            latency_bucket(elapsed, boundaries).as_str(),
+        );
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let (value, unit) = duration_parts(duration);
+    format!("{value}{unit}")
+}
+
+/// Splits a duration into the largest unit that represents it exactly.
+fn duration_parts(duration: Duration) -> (u128, &'static str) {
+    let nanos = duration.as_nanos();
+    if nanos == 0 {
+        (0, "ms")
+    } else if nanos.is_multiple_of(1_000_000_000) {
+        (nanos / 1_000_000_000, "s")
+    } else if nanos.is_multiple_of(1_000_000) {
+        (nanos / 1_000_000, "ms")
+    } else if nanos.is_multiple_of(1_000) {
+        (nanos / 1_000, "us")
+    } else {
+        (nanos, "ns")
+    }
+}
+
+/// The effect a Redis command has on the keyspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandKind {
+    /// The command only reads keys.
+    Read,
+    /// The command creates, modifies, expires or deletes keys.
+    Write,
+    /// The command doesn't operate on keys, e.g. `PING`, `SELECT` or `CLIENT`.
+    Other,
+}
+
+/// Classifies a normalized (uppercase) operation name as a read, a write or neither.
+///
+/// Commands that can both read and write depending on their arguments, such as
+/// `SORT` with `STORE`, are classified as writes. Unknown commands are `Other`.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::{classify_command, CommandKind};
+///
+/// assert_eq!(classify_command("GET"), CommandKind::Read);
+/// assert_eq!(classify_command("HSET"), CommandKind::Write);
+/// assert_eq!(classify_command("PING"), CommandKind::Other);
+/// ```
+pub fn classify_command(operation: &str) -> CommandKind {
+    match operation {
+        "GET"
+        | "MGET"
+        | "GETRANGE"
+        | "SUBSTR"
+        | "STRLEN"
+        | "LCS"
+        | "EXISTS"
+        | "TYPE"
+        | "TTL"
+        | "PTTL"
+        | "EXPIRETIME"
+        | "PEXPIRETIME"
+        | "DUMP"
+        | "TOUCH"
+        | "KEYS"
+        | "SCAN"
+        | "RANDOMKEY"
+        | "DBSIZE"
+        | "HGET"
+        | "HMGET"
+        | "HGETALL"
+        | "HKEYS"
+        | "HVALS"
+        | "HLEN"
+        | "HEXISTS"
+        | "HSTRLEN"
+        | "HSCAN"
+        | "HRANDFIELD"
+        | "LRANGE"
+        | "LLEN"
+        | "LINDEX"
+        | "LPOS"
+        | "SMEMBERS"
+        | "SISMEMBER"
+        | "SMISMEMBER"
+        | "SCARD"
+        | "SRANDMEMBER"
+        | "SSCAN"
+        | "SINTER"
+        | "SINTERCARD"
+        | "SUNION"
+        | "SDIFF"
+        | "ZRANGE"
+        | "ZRANGEBYSCORE"
+        | "ZREVRANGE"
+        | "ZREVRANGEBYSCORE"
+        | "ZRANGEBYLEX"
+        | "ZREVRANGEBYLEX"
+        | "ZSCORE"
+        | "ZMSCORE"
+        | "ZCARD"
+        | "ZCOUNT"
+        | "ZLEXCOUNT"
+        | "ZRANK"
+        | "ZREVRANK"
+        | "ZSCAN"
+        | "ZRANDMEMBER"
+        | "ZINTER"
+        | "ZUNION"
+        | "ZDIFF"
+        | "XRANGE"
+        | "XREVRANGE"
+        | "XLEN"
+        | "XREAD"
+        | "BITCOUNT"
+        | "BITPOS"
+        | "GETBIT"
+        | "PFCOUNT"
+        | "GEOPOS"
+        | "GEODIST"
+        | "GEOHASH"
+        | "GEOSEARCH"
+        | "GEORADIUS_RO"
+        | "GEORADIUSBYMEMBER_RO"
+        | "SORT_RO"
+        | "EVAL_RO"
+        | "EVALSHA_RO"
+        | "FCALL_RO" => CommandKind::Read,
+        "SET" | "SETNX" | "SETEX" | "PSETEX" | "MSET" | "MSETNX" | "GETSET" | "GETDEL"
+        | "GETEX" | "APPEND" | "SETRANGE" | "INCR" | "DECR" | "INCRBY" | "DECRBY"
+        | "INCRBYFLOAT" | "DEL" | "UNLINK" | "EXPIRE" | "PEXPIRE" | "EXPIREAT" | "PEXPIREAT"
+        | "PERSIST" | "RENAME" | "RENAMENX" | "COPY" | "MOVE" | "RESTORE" | "HSET" | "HSETNX"
+        | "HMSET" | "HDEL" | "HINCRBY" | "HINCRBYFLOAT" | "LPUSH" | "RPUSH" | "LPUSHX"
+        | "RPUSHX" | "LPOP" | "RPOP" | "LSET" | "LREM" | "LTRIM" | "LINSERT" | "LMOVE"
+        | "RPOPLPUSH" | "LMPOP" | "BLPOP" | "BRPOP" | "BLMOVE" | "BRPOPLPUSH" | "BLMPOP"
+        | "SADD" | "SREM" | "SPOP" | "SMOVE" | "SINTERSTORE" | "SUNIONSTORE" | "SDIFFSTORE"
+        | "ZADD" | "ZREM" | "ZINCRBY" | "ZPOPMIN" | "ZPOPMAX" | "BZPOPMIN" | "BZPOPMAX"
+        | "ZMPOP" | "BZMPOP" | "ZREMRANGEBYSCORE" | "ZREMRANGEBYRANK" | "ZREMRANGEBYLEX"
+        | "ZUNIONSTORE" | "ZINTERSTORE" | "ZDIFFSTORE" | "ZRANGESTORE" | "XADD" | "XDEL"
+        | "XTRIM" | "XACK" | "XCLAIM" | "XAUTOCLAIM" | "XREADGROUP" | "SETBIT" | "BITOP"
+        | "BITFIELD" | "PFADD" | "PFMERGE" | "GEOADD" | "GEORADIUS" | "GEORADIUSBYMEMBER"
+        | "GEOSEARCHSTORE" | "SORT" | "FLUSHDB" | "FLUSHALL" | "SWAPDB" => CommandKind::Write,
+        _ => CommandKind::Other,
+    }
+}
+
+/// Returns `true` if sending `cmd` again leaves the keyspace as the first send did, so a
+/// retry layer may resend it after an ambiguous failure such as a timeout.
+///
+/// Reads are idempotent, as are writes that set keys to values given in the command,
+/// such as `DEL`, `HSET`, `SADD` or `EXPIREAT`. Writes relative to the current state,
+/// such as `INCR`, `LPUSH`, `SPOP` or `EXPIRE`, aren't, and neither are unknown commands
+/// and scripts, since their effect can't be known from their name.
+///
+/// `SET` is classified by its arguments: it is idempotent when it writes a fixed value,
+/// and not with `GET`, whose reply on a retry would be the value it just wrote rather
+/// than the previous one. Whether the value itself was computed from an earlier read
+/// can't be seen here, so read-modify-write sequences should not be retried on the
+/// strength of this alone. `ZADD` with `INCR` is an increment and isn't idempotent.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::is_idempotent;
+///
+/// assert!(is_idempotent(redis::cmd("GET").arg("key")));
+/// assert!(is_idempotent(redis::cmd("SET").arg("key").arg("value")));
+/// assert!(!is_idempotent(redis::cmd("SET").arg("key").arg("value").arg("GET")));
+/// assert!(!is_idempotent(redis::cmd("INCR").arg("counter")));
+/// ```
+pub fn is_idempotent(cmd: &redis::Cmd) -> bool {
+    let Some(operation) = get_command_name(cmd) else {
+        return false;
+    };
+    let has_option = |option: &[u8]| {
+        cmd.args_iter().skip(1).any(
+            |arg| matches!(arg, redis::Arg::Simple(bytes) if bytes.eq_ignore_ascii_case(option)),
+        )
+    };
+    match operation.as_str() {
+        "SET" => !has_option(b"GET"),
+        "ZADD" => !has_option(b"INCR"),
+        "SETNX" | "SETEX" | "PSETEX" | "MSET" | "MSETNX" | "DEL" | "UNLINK" | "EXPIREAT"
+        | "PEXPIREAT" | "PERSIST" | "HSET" | "HSETNX" | "HMSET" | "HDEL" | "LSET" | "SADD"
+        | "SREM" | "ZREM" | "XACK" | "SETBIT" | "PFADD" | "GEOADD" | "FLUSHDB" | "FLUSHALL"
+        | "PING" | "ECHO" | "SELECT" => true,
+        operation => classify_command(operation) == CommandKind::Read,
+    }
+}
+
+/// Records connection-scoped state on a command span before the command executes.
+///
+/// - `redis.tracking.enabled`: Set to `true` while `CLIENT TRACKING` is on for the
+///   connection; omitted otherwise.
+/// - `redis.tracking.key_tracked`: Set to `true` on reads issued while tracking is on,
+///   since the server starts tracking every key the client reads in that mode.
+/// - `redis.routing`: `replica` while `READONLY` is in effect for the connection,
+///   letting cluster replicas serve its reads, and `primary` otherwise. This is the
+///   client's routing intent, not the role of the node that answered.
+/// - `server.address` and `server.port`: The server the connection talks to, when
+///   known; see [`ConnectionState::set_server_address`]. Unix socket connections
+///   record the socket path and no port.
+pub fn record_connection_state(
+    span: &impl RecordFields,
+    cmd: &redis::Cmd,
+    state: &ConnectionState,
+) {
+    let routing = if state.replica_reads() {
+        "replica"
+    } else {
+        "primary"
+    };
+    span.record_field("redis.routing", routing);
+    if let Some(server) = state.server_address() {
+        span.record_field("server.address", server.address.as_str());
+        if let Some(port) = server.port {
+            span.record_field("server.port", i64::from(port));
+        }
+    }
+    if state.tracking_enabled() {
+        span.record_field("redis.tracking.enabled", true);
+        let operation = get_command_name(cmd);
+        if operation.is_some_and(|op| classify_command(&op) == CommandKind::Read) {
+            span.record_field("redis.tracking.key_tracked", true);
+        }
+    }
+}
+
+/// Records `redis.command.weight` and adds it to the connection's running total, if
+/// [`InstrumentationConfig::with_command_weights`] is set.
+///
+/// Commands whose name can't be read use the default weight.
+pub fn record_command_weight(
+    span: &impl RecordFields,
+    cmd: &redis::Cmd,
+    config: &InstrumentationConfig,
+    state: &ConnectionState,
+) {
+    let name = get_command_name(cmd);
+    let Some(weight) = config.command_weight(name.as_deref().unwrap_or_default()) else {
+        return;
+    };
+    span.record_field("redis.command.weight", i64::from(weight));
+    state.add_command_weight(weight);
+}
+
+/// Adds a command that took `elapsed` to the connection's window aggregates, if
+/// [`InstrumentationConfig::with_window_aggregation`] is enabled.
+pub fn record_window_aggregate(cmd: &redis::Cmd, elapsed: Duration, state: &ConnectionState) {
+    if let Some(aggregator) = state.window_aggregator() {
+        let operation = get_command_name(cmd);
+        aggregator.record(
+            operation.as_deref().unwrap_or("unknown"),
+            elapsed,
+            Instant::now(),
+        );
+    }
+}
+
+/// Records the command's duration metric, see [`crate::metrics::record_operation`].
+#[cfg(feature = "metrics")]
+pub fn record_command_metrics(
+    cmd: &redis::Cmd,
+    elapsed: Duration,
+    result: &redis::RedisResult<redis::Value>,
+    config: &InstrumentationConfig,
+) {
+    let operation = get_operation_name(cmd, config);
+    crate::metrics::record_operation(operation.as_deref().unwrap_or("unknown"), elapsed, result);
+}
+
+/// Records `redis.connection.state` from the inferred reconnect state, if
+/// [`InstrumentationConfig::with_reconnect_state`] is enabled.
+pub fn record_reconnect_state(
+    span: &impl RecordFields,
+    config: &InstrumentationConfig,
+    state: &ConnectionState,
+) {
+    if config.reconnect_state() {
+        span.record_field("redis.connection.state", state.reconnect_state().as_str());
+    }
+}
+
+/// Records `db.redis.database_index` from the connection state.
+///
+/// Database 0 is omitted unless [`InstrumentationConfig::with_include_db_index_always`]
+/// is set, so dashboards can opt into a uniform attribute on every span. When the
+/// current database has a name configured through
+/// [`InstrumentationConfig::with_db_names`], it is recorded as `db.redis.database_name`.
+pub fn record_database_index(
+    span: &impl RecordFields,
+    config: &InstrumentationConfig,
+    state: &ConnectionState,
+) {
+    let db = state.database_index();
+    if db != 0 || config.include_db_index_always() {
+        span.record_field("db.redis.database_index", db);
+    }
+    if let Some(name) = config.db_name(db) {
+        span.record_field("db.redis.database_name", name);
+    }
+}
+
+/// Counts `cmd` in the local operation counters, if they are enabled.
+///
+/// Operations are counted under their uppercase name, whatever
+/// [`InstrumentationConfig::with_operation_case`] says.
+pub fn count_operation(cmd: &redis::Cmd, config: &InstrumentationConfig) {
+    if let Some(counters) = config.local_counters() {
+        let operation = get_command_name(cmd);
+        let operation = operation.as_deref().unwrap_or("command");
+        counters.increment(if config.collapse_subcommands() {
+            collapse_operation(operation)
+        } else {
+            operation
+        });
+    }
+}
+
+/// Records the thread, and the tokio task if any, running a command.
+///
+/// - `thread.id`: The numeric id of the current thread.
+/// - `thread.name`: The thread's name, omitted for unnamed threads.
+/// - `tokio.task.id`: The id of the current tokio task, omitted outside a task or
+///   without the `aio` feature.
+///
+/// Nothing is recorded unless [`InstrumentationConfig::with_execution_context`] is set.
+pub fn record_execution_context(span: &impl RecordFields, config: &InstrumentationConfig) {
+    if !config.execution_context() {
+        return;
+    }
+    let thread = std::thread::current();
+    if let Some(id) = thread_id_number(thread.id()) {
+        span.record_field("thread.id", id);
+    }
+    if let Some(name) = thread.name() {
+        span.record_field("thread.name", name);
+    }
+    #[cfg(feature = "aio")]
+    if let Some(id) = tokio::task::try_id() {
+        span.record_field("tokio.task.id", tracing::field::display(id));
+    }
+}
+
+/// Extracts the number from a `ThreadId`, whose integer accessor isn't stable yet.
+fn thread_id_number(id: std::thread::ThreadId) -> Option<u64> {
+    format!("{id:?}")
+        .strip_prefix("ThreadId(")?
+        .strip_suffix(')')?
+        .parse()
+        .ok()
+}
+
+/// Counts a `GET`, `GETEX`, `GETDEL` or `HGET` reply in the hit counters, if they are enabled.
+///
+/// A nil reply is a miss, anything else a hit; failed commands aren't counted. With
+/// [`InstrumentationConfig::with_hit_ratio_attribute`] the running ratio is also
+/// recorded on the span as `redis.cache.hit_ratio`.
+pub fn record_keyspace_lookup(
+    span: &impl RecordFields,
+    cmd: &redis::Cmd,
+    result: &Result<redis::Value, redis::RedisError>,
+    config: &InstrumentationConfig,
+) {
+    let (Some(counters), Ok(reply)) = (config.hit_counters(), result) else {
+        return;
+    };
+    if !matches!(
+        get_command_name(cmd).as_deref(),
+        Some("GET" | "GETEX" | "GETDEL" | "HGET")
+    ) {
+        return;
+    }
+    counters.record(!matches!(reply, redis::Value::Nil));
+    if config.hit_ratio_attribute() {
+        if let Some(ratio) = counters.ratio() {
+            span.record_field("redis.cache.hit_ratio", ratio);
+        }
+    }
+}
+
+/// Returns the bytes of string data in `reply`, summed over nested aggregates.
+///
+/// Only bulk, simple and verbatim strings count; integers, doubles and other
+/// scalars add nothing. Returns `None` if `reply` is itself such a scalar, since it
+/// carries no payload worth sizing.
+pub fn response_size(reply: &redis::Value) -> Option<u64> {
+    use redis::Value;
+
+    fn payload_bytes(value: &Value) -> u64 {
+        match value {
+            Value::BulkString(bytes) => bytes.len() as u64,
+            Value::SimpleString(text) => text.len() as u64,
+            Value::VerbatimString { text, .. } => text.len() as u64,
+            Value::Array(values) | Value::Set(values) | Value::Push { data: values, .. } => {
+                values.iter().map(payload_bytes).sum()
+            }
+            Value::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| payload_bytes(key) + payload_bytes(value))
+                .sum(),
+            Value::Attribute { data, .. } => payload_bytes(data),
+            _ => 0,
+        }
+    }
+
+    match reply {
+        Value::BulkString(_)
+        | Value::SimpleString(_)
+        | Value::VerbatimString { .. }
+        | Value::Array(_)
+        | Value::Set(_)
+        | Value::Map(_)
+        | Value::Push { .. }
+        | Value::Attribute { .. } => Some(payload_bytes(reply)),
+        _ => None,
+    }
+}
+
+/// Records `db.redis.response.size` for a successful reply, if
+/// [`InstrumentationConfig::with_response_size`] is enabled.
+pub fn record_response_size(
+    span: &impl RecordFields,
+    result: &Result<redis::Value, redis::RedisError>,
+    config: &InstrumentationConfig,
+) {
+    if !config.response_size() {
+        return;
+    }
+    if let Some(size) = result.as_ref().ok().and_then(response_size) {
+        span.record_field("db.redis.response.size", size as i64);
+    }
+}
+
+/// Records `redis.resp.reply_bytes`, the on-wire size of a command's RESP reply.
+///
+/// Unlike `db.redis.response.size`, estimated from the parsed value, this is the
+/// exact byte count, as taken from a [`ReplyByteCounter`] wrapping the stream the
+/// reply was read from. The wrappers can't see the bytes read by redis-rs
+/// connections and never record it: it is only set by callers that read replies
+/// themselves and call this, e.g. with the span returned by `begin_command`.
+pub fn record_resp_reply_bytes(span: &impl RecordFields, bytes: u64) {
+    span.record_field("redis.resp.reply_bytes", bytes as i64);
+}
+
+/// Returns `true` if the wrapper should sample `INFO stats` after `cmd`.
+///
+/// Only writes are sampled, only when eviction sampling is enabled, and at most once
+/// per configured interval per connection. Nothing is sampled while a transaction is
+/// open on the connection, where `INFO` would be queued into it.
+pub fn eviction_sample_due(
+    cmd: &redis::Cmd,
+    config: &InstrumentationConfig,
+    state: &ConnectionState,
+) -> bool {
+    let Some(interval) = config.eviction_sampling() else {
+        return false;
+    };
+    !state.in_transaction()
+        && get_command_name(cmd).is_some_and(|op| classify_command(&op) == CommandKind::Write)
+        && state.eviction().claim(Instant::now(), interval)
+}
+
+/// Number of entries requested with `SLOWLOG GET` when correlating a slow command
+pub const SLOWLOG_LOOKUP_ENTRIES: usize = 10;
+
+/// Returns `true` if the wrapper should look up `cmd`, which took `elapsed`, in
+/// `SLOWLOG GET`.
+///
+/// Only commands at or above the configured threshold are looked up, and at most
+/// once per configured interval per connection; see
+/// [`InstrumentationConfig::with_slowlog_correlation`]. Nothing is looked up while a
+/// transaction is open on the connection: the command was only queued, and
+/// `SLOWLOG GET` would be queued too.
+pub fn slowlog_lookup_due(
+    cmd: &redis::Cmd,
+    elapsed: Duration,
+    config: &InstrumentationConfig,
+    state: &ConnectionState,
+) -> bool {
+    let Some((threshold, interval)) = config.slowlog_correlation() else {
+        return false;
+    };
+    elapsed >= threshold
+        && !state.in_transaction()
+        && get_command_name(cmd).is_some_and(|op| op != "SLOWLOG")
+        && state.slowlog().claim(Instant::now(), interval)
+}
+
+/// Records `redis.server.exec_us` from the `SLOWLOG GET` entry matching `cmd`.
+///
+/// `elapsed` is the client-side duration of `cmd`, used to skip entries logged
+/// before it started. See [`find_slowlog_duration`] for how the entry is matched.
+pub fn record_server_exec_time(
+    span: &impl RecordFields,
+    cmd: &redis::Cmd,
+    slowlog: &redis::Value,
+    elapsed: Duration,
+) {
+    let started = std::time::SystemTime::now()
+        .checked_sub(elapsed)
+        .and_then(|started| started.duration_since(std::time::UNIX_EPOCH).ok());
+    let Some(started) = started else {
+        return;
+    };
+    // Entries are timestamped in whole seconds, so allow for one second of rounding
+    let not_before = started.as_secs().saturating_sub(1) as i64;
+    if let Some(micros) = find_slowlog_duration(slowlog, cmd, not_before) {
+        span.record_field("redis.server.exec_us", micros);
+    }
+}
+
+/// Finds the execution time, in microseconds, of `cmd` in a `SLOWLOG GET` reply.
+///
+/// Each entry is `[id, timestamp, duration, [args...], ...]`, newest first. The
+/// first entry logged at or after the Unix time `not_before` whose command name
+/// matches, and whose first argument matches when `cmd` has one, is used. Older
+/// entries are ignored so an earlier run of the same command isn't picked up.
+pub fn find_slowlog_duration(
+    slowlog: &redis::Value,
+    cmd: &redis::Cmd,
+    not_before: i64,
+) -> Option<i64> {
+    let redis::Value::Array(entries) = slowlog else {
+        return None;
+    };
+    let name = get_command_name(cmd)?;
+    let key = command_arg(cmd, 1);
+
+    entries.iter().find_map(|entry| {
+        let redis::Value::Array(fields) = entry else {
+            return None;
+        };
+        let [_, redis::Value::Int(timestamp), redis::Value::Int(duration), redis::Value::Array(args), ..] =
+            fields.as_slice()
+        else {
+            return None;
+        };
+        if *timestamp < not_before {
+            return None;
+        }
+        let arg = |index: usize| match args.get(index) {
+            Some(redis::Value::BulkString(bytes)) => Some(String::from_utf8_lossy(bytes)),
+            _ => None,
+        };
+        let same_name = arg(0).is_some_and(|logged| logged.eq_ignore_ascii_case(&name));
+        let same_key = key.as_deref().is_none_or(|key| arg(1).as_deref() == Some(key));
+        (same_name && same_key).then_some(*duration)
+    })
+}
+
+/// Reads the `evicted_keys` counter from an `INFO stats` reply.
+pub fn parse_evicted_keys(info: &str) -> Option<u64> {
+    info.lines()
+        .find_map(|line| line.trim_end().strip_prefix("evicted_keys:"))
+        .and_then(|value| value.parse().ok())
+}
+
+/// Reads the `cluster_state` field from a `CLUSTER INFO` reply, `ok` or `fail`.
+pub fn parse_cluster_state(info: &str) -> Option<&str> {
+    info.lines()
+        .find_map(|line| line.trim_end().strip_prefix("cluster_state:"))
+}
+
+/// Records `redis.eviction_pressure` on write spans once two samples have been taken.
+pub fn record_eviction_pressure(
+    span: &impl RecordFields,
+    cmd: &redis::Cmd,
+    state: &ConnectionState,
+) {
+    let Some(pressure) = state.eviction().pressure() else {
+        return;
+    };
+    if get_command_name(cmd).is_some_and(|op| classify_command(&op) == CommandKind::Write) {
+        span.record_field("redis.eviction_pressure", pressure);
+    }
+}
+
+/// Returns `true` for commands that set up or reset a connection rather than operate on data.
+///
+/// `HELLO`, `RESET`, `AUTH`, `CLIENT SETNAME` and `CLIENT SETINFO` are tagged with
+/// `redis.connection_setup = true` so they can be filtered out of command dashboards.
+/// Only the command name is inspected, so credentials passed to `AUTH` or
+/// `HELLO ... AUTH` are never read.
+pub fn is_connection_setup_command(cmd: &redis::Cmd) -> bool {
+    let mut args = cmd.args_iter().map(|arg| match arg {
+        redis::Arg::Simple(bytes) => bytes,
+        redis::Arg::Cursor => &[],
+    });
+    let Some(name) = args.next() else {
+        return false;
+    };
+
+    if name.eq_ignore_ascii_case(b"HELLO")
+        || name.eq_ignore_ascii_case(b"RESET")
+        || name.eq_ignore_ascii_case(b"AUTH")
+    {
+        return true;
+    }
+    name.eq_ignore_ascii_case(b"CLIENT")
+        && args.next().is_some_and(|subcommand| {
+            subcommand.eq_ignore_ascii_case(b"SETNAME")
+                || subcommand.eq_ignore_ascii_case(b"SETINFO")
+        })
+}
+
+/// Extracts the negotiated protocol version from a `HELLO` reply.
+///
+/// `HELLO` replies with a map of server properties: a RESP3 map, or a flat array of
+/// alternating keys and values under RESP2. The version is the value of `proto`.
+pub fn hello_protocol_version(reply: &redis::Value) -> Option<i64> {
+    let is_proto = |key: &redis::Value| match key {
+        redis::Value::BulkString(bytes) => bytes.as_slice() == b"proto",
+        redis::Value::SimpleString(text) => text == "proto",
+        _ => false,
+    };
+    let as_int = |value: &redis::Value| match value {
+        redis::Value::Int(version) => Some(*version),
+        _ => None,
+    };
+
+    match reply {
+        redis::Value::Map(entries) => entries
+            .iter()
+            .find(|(key, _)| is_proto(key))
+            .and_then(|(_, value)| as_int(value)),
+        redis::Value::Array(items) => items
+            .chunks_exact(2)
+            .find(|pair| is_proto(&pair[0]))
+            .and_then(|pair| as_int(&pair[1])),
+        _ => None,
+    }
+}
+
+/// Returns whether a conditional command actually changed anything.
+///
+/// Conditional commands succeed even when their condition fails, replying `0` or
+/// nil instead. This covers `SETNX`, `HSETNX`, `MSETNX`, `RENAMENX`, `SET` with
+/// `NX`/`XX`, the `EXPIRE` family with `NX`/`XX`/`GT`/`LT`, and `ZADD` with `NX`,
+/// `INCR`, or `XX`/`GT`/`LT` combined with `CH`. Returns `None` for other commands
+/// and for forms whose reply doesn't tell, such as `SET ... GET` or `ZADD XX`
+/// without `CH`, which always replies with the number of added members.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::conditional_applied;
+/// use redis::Value;
+///
+/// let cmd = redis::cmd("HSETNX").arg("user:1").arg("name").arg("Alice").clone();
+/// assert_eq!(conditional_applied(&cmd, &Value::Int(0)), Some(false));
+///
+/// let cmd = redis::cmd("EXPIRE").arg("session").arg(60).arg("NX").clone();
+/// assert_eq!(conditional_applied(&cmd, &Value::Int(1)), Some(true));
+///
+/// let cmd = redis::cmd("EXPIRE").arg("session").arg(60).clone();
+/// assert_eq!(conditional_applied(&cmd, &Value::Int(0)), None);
+/// ```
+pub fn conditional_applied(cmd: &redis::Cmd, reply: &redis::Value) -> Option<bool> {
+    let args: Vec<&[u8]> = cmd
+        .args_iter()
+        .map(|arg| match arg {
+            redis::Arg::Simple(bytes) => bytes,
+            redis::Arg::Cursor => &[],
+        })
+        .collect();
+    let has_flag = |options: &[&[u8]], flags: &[&str]| {
+        options.iter().any(|arg| {
+            flags
+                .iter()
+                .any(|flag| arg.eq_ignore_ascii_case(flag.as_bytes()))
+        })
+    };
+    let int_applied = |reply: &redis::Value| match reply {
+        redis::Value::Int(n) => Some(*n > 0),
+        _ => None,
+    };
+
+    match get_command_name(cmd)?.as_str() {
+        "SETNX" | "HSETNX" | "MSETNX" | "RENAMENX" => int_applied(reply),
+        "SET" => {
+            let options = args.get(3..)?;
+            if !has_flag(options, &["NX", "XX"]) || has_flag(options, &["GET"]) {
+                return None;
+            }
+            Some(!matches!(reply, redis::Value::Nil))
+        }
+        "EXPIRE" | "PEXPIRE" | "EXPIREAT" | "PEXPIREAT" => {
+            if !has_flag(args.get(3..)?, &["NX", "XX", "GT", "LT"]) {
+                return None;
+            }
+            int_applied(reply)
+        }
+        "ZADD" => {
+            // Flags sit between the key and the first score
+            let flags: Vec<&[u8]> = args
+                .get(2..)?
+                .iter()
+                .copied()
+                .take_while(|arg| {
+                    ["NX", "XX", "GT", "LT", "CH", "INCR"]
+                        .iter()
+                        .any(|flag| arg.eq_ignore_ascii_case(flag.as_bytes()))
+                })
+                .collect();
+            if has_flag(&flags, &["INCR"]) && has_flag(&flags, &["NX", "XX", "GT", "LT"]) {
+                Some(!matches!(reply, redis::Value::Nil))
+            } else if has_flag(&flags, &["XX", "GT", "LT"]) {
+                if has_flag(&flags, &["CH"]) {
+                    int_applied(reply)
+                } else {
+                    None
+                }
+            } else if has_flag(&flags, &["NX"]) {
+                int_applied(reply)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Records attributes derived from a command's reply on its span.
+///
+/// - `db.redis.protocol_version`: The protocol negotiated by a successful `HELLO`.
+/// - `redis.conditional.applied`: Whether a conditional command changed anything,
+///   as interpreted by [`conditional_applied`].
+pub fn record_reply_attributes(
+    span: &impl RecordFields,
+    cmd: &redis::Cmd,
+    result: &Result<redis::Value, redis::RedisError>,
+) {
+    let Ok(reply) = result else {
+        return;
+    };
+    if get_command_name(cmd).as_deref() == Some("HELLO") {
+        if let Some(version) = hello_protocol_version(reply) {
+            span.record_field("db.redis.protocol_version", version);
+        }
+    }
+    if let Some(applied) = conditional_applied(cmd, reply) {
+        span.record_field("redis.conditional.applied", applied);
+    }
+}
@@ -0,0 +1,192 @@
+//! On-wire sizes of RESP replies read from a connection
+//!
+//! redis-rs parses replies from a socket it owns and keeps no count of the bytes
+//! behind each value, and its parser reads ahead of the reply it returns. The exact
+//! size is recovered by framing the bytes read from the socket independently of the
+//! parser, which also splits one read covering several pipelined replies.
+
+use std::io::{self, Read};
+
+/// Returns the on-wire length of the first complete RESP2 or RESP3 reply in `bytes`.
+///
+/// `None` if `bytes` ends before the reply does or doesn't start with a reply.
+///
+/// # Examples
+///
+/// ```rust
+/// use otel_instrumentation_redis::common::resp_reply_len;
+///
+/// assert_eq!(resp_reply_len(b"+OK\r\n"), Some(5));
+/// assert_eq!(resp_reply_len(b"*2\r\n$1\r\na\r\n:1\r\n+OK\r\n"), Some(15));
+/// assert_eq!(resp_reply_len(b"$5\r\nhel"), None);
+/// ```
+pub fn resp_reply_len(bytes: &[u8]) -> Option<usize> {
+    frame_reply(bytes).ok().flatten()
+}
+
+/// The bytes framed by [`ReplyByteCounter`] don't start with a RESP reply
+#[derive(Debug)]
+struct Malformed;
+
+/// Frames the first reply in `bytes`, telling a reply that isn't complete yet,
+/// `Ok(None)`, from bytes that can't be one, `Err`.
+fn frame_reply(bytes: &[u8]) -> Result<Option<usize>, Malformed> {
+    let mut pos = 0;
+    let mut remaining: usize = 1;
+    while remaining > 0 {
+        remaining -= 1;
+        let Some(&kind) = bytes.get(pos) else {
+            return Ok(None);
+        };
+        if !b"+-:_,#($!=*~>%|".contains(&kind) {
+            return Err(Malformed);
+        }
+        let Some(line_len) = bytes[pos + 1..].windows(2).position(|w| w == b"\r\n") else {
+            return Ok(None);
+        };
+        let line_end = pos + 1 + line_len;
+        let header = &bytes[pos + 1..line_end];
+        pos = line_end + 2;
+        let len = || -> Result<i64, Malformed> {
+            std::str::from_utf8(header)
+                .ok()
+                .and_then(|header| header.parse().ok())
+                .ok_or(Malformed)
+        };
+        let count = |n: i64| usize::try_from(n).map_err(|_| Malformed);
+        match kind {
+            b'$' | b'!' | b'=' => {
+                if let Ok(len) = usize::try_from(len()?) {
+                    pos = pos
+                        .checked_add(len)
+                        .and_then(|pos| pos.checked_add(2))
+                        .ok_or(Malformed)?;
+                    if bytes.len() < pos {
+                        return Ok(None);
+                    }
+                }
+            }
+            b'*' | b'~' | b'>' => {
+                if let Ok(len) = usize::try_from(len()?) {
+                    remaining = remaining.checked_add(len).ok_or(Malformed)?;
+                }
+            }
+            b'%' => {
+                let pairs = count(len()?)?;
+                remaining = pairs
+                    .checked_mul(2)
+                    .and_then(|n| remaining.checked_add(n))
+                    .ok_or(Malformed)?;
+            }
+            // Attributes precede the reply they annotate
+            b'|' => {
+                let pairs = count(len()?)?;
+                remaining = pairs
+                    .checked_mul(2)
+                    .and_then(|n| n.checked_add(1))
+                    .and_then(|n| remaining.checked_add(n))
+                    .ok_or(Malformed)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(Some(pos))
+}
+
+/// The default limit on the bytes [`ReplyByteCounter`] holds for an incomplete reply,
+/// the default `proto-max-bulk-len` of Redis.
+pub const DEFAULT_MAX_REPLY_LEN: usize = 512 * 1024 * 1024;
+
+/// A reader counting the bytes of each RESP reply read through it.
+///
+/// Wrap the stream a connection reads replies from, e.g. in a custom
+/// `redis::ConnectionLike` parsing with `redis::Parser`, and take the sizes of the
+/// replies read so far with [`ReplyByteCounter::take_reply_sizes`]. The bytes are
+/// passed through unchanged. Replies are framed as the bytes arrive, so a read
+/// covering several pipelined replies yields one size per reply, and a reply split
+/// across reads is only counted once complete.
+///
+/// No connection wrapper reads through a counter: record the sizes on command spans
+/// with [`crate::common::record_resp_reply_bytes`].
+///
+/// Counting stops for good, see [`ReplyByteCounter::is_counting`], once the bytes
+/// read don't frame as RESP or a reply outgrows the limit set with
+/// [`ReplyByteCounter::with_max_reply_len`], as the next reply can't be found
+/// after either. The buffered bytes are dropped then, so they never exceed the limit.
+#[derive(Debug)]
+pub struct ReplyByteCounter<R> {
+    inner: R,
+    pending: Vec<u8>,
+    sizes: Vec<u64>,
+    max_reply_len: usize,
+    counting: bool,
+}
+
+impl<R> ReplyByteCounter<R> {
+    /// Wraps `inner`, buffering up to [`DEFAULT_MAX_REPLY_LEN`] bytes of a reply
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            sizes: Vec::new(),
+            max_reply_len: DEFAULT_MAX_REPLY_LEN,
+            counting: true,
+        }
+    }
+
+    /// Sets how many bytes of an incomplete reply are buffered before counting stops
+    pub fn with_max_reply_len(mut self, limit: usize) -> Self {
+        self.max_reply_len = limit;
+        self
+    }
+
+    /// Returns the sizes of the replies completed since the last call, in order
+    pub fn take_reply_sizes(&mut self) -> Vec<u64> {
+        std::mem::take(&mut self.sizes)
+    }
+
+    /// Returns `false` once counting stopped on malformed or oversized replies
+    pub fn is_counting(&self) -> bool {
+        self.counting
+    }
+
+    /// Returns the wrapped reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn frame(&mut self, read: &[u8]) {
+        if !self.counting {
+            return;
+        }
+        self.pending.extend_from_slice(read);
+        let mut start = 0;
+        loop {
+            match frame_reply(&self.pending[start..]) {
+                Ok(Some(len)) => {
+                    self.sizes.push(len as u64);
+                    start += len;
+                }
+                Ok(None) => break,
+                Err(Malformed) => return self.stop_counting(),
+            }
+        }
+        self.pending.drain(..start);
+        if self.pending.len() > self.max_reply_len {
+            self.stop_counting();
+        }
+    }
+
+    fn stop_counting(&mut self) {
+        self.counting = false;
+        self.pending = Vec::new();
+    }
+}
+
+impl<R: Read> Read for ReplyByteCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.frame(&buf[..read]);
+        Ok(read)
+    }
+}
@@ -0,0 +1,162 @@
+//! Attributes with keys chosen at runtime by user-supplied closures
+//!
+//! A span can only record the fields it declared when it was created, and the keys a
+//! response extractor or span preprocessor returns aren't known in advance. Keys the
+//! command span declares are recorded on it; the others are emitted together on an
+//! event under the span, with a callsite registered per distinct set of keys.
+
+use super::target::DynamicCallsite;
+use opentelemetry::KeyValue;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{display, DisplayValue};
+use tracing::{Level, Span};
+use tracing_core::field::{FieldSet, Value};
+use tracing_core::metadata::Kind;
+use tracing_core::{Event, Metadata};
+
+/// Most attributes one reply may add; further ones are dropped
+pub const MAX_RESPONSE_ATTRIBUTES: usize = 31;
+
+/// Records `attributes` on `span`, or on a `redis.response` event under it for keys
+/// the span doesn't declare.
+///
+/// Only the first [`MAX_RESPONSE_ATTRIBUTES`] attributes are recorded.
+pub(crate) fn record_response_attributes(span: &Span, attributes: &[KeyValue]) {
+    let attributes = &attributes[..attributes.len().min(MAX_RESPONSE_ATTRIBUTES)];
+    super::apply_span_attributes(span, attributes);
+    emit_undeclared_attributes(span, attributes, "redis.response");
+}
+
+/// Emits the attributes whose keys `span` doesn't declare on an event under it, with
+/// `message` as the event's message.
+///
+/// Attributes beyond the first [`MAX_RESPONSE_ATTRIBUTES`] undeclared ones are dropped.
+pub(crate) fn emit_undeclared_attributes(
+    span: &Span,
+    attributes: &[KeyValue],
+    message: &'static str,
+) {
+    let Some(span_fields) = span.metadata().map(Metadata::fields) else {
+        return;
+    };
+    let undeclared: Vec<&KeyValue> = attributes
+        .iter()
+        .filter(|attribute| span_fields.field(attribute.key.as_str()).is_none())
+        .take(MAX_RESPONSE_ATTRIBUTES)
+        .collect();
+    if undeclared.is_empty() {
+        return;
+    }
+
+    let mut keys: Vec<&'static str> = undeclared
+        .iter()
+        .map(|attribute| intern(attribute.key.as_str()))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    let site = callsite_for(keys);
+    if !site.is_enabled() {
+        return;
+    }
+    let metadata = tracing_core::Callsite::metadata(site);
+    let fields = metadata.fields();
+    let Some(message_field) = fields.field("message") else {
+        return;
+    };
+
+    let values: Vec<_> = undeclared
+        .iter()
+        .filter_map(|attribute| {
+            let field = fields.field(attribute.key.as_str())?;
+            Some((field, owned_value(&attribute.value)))
+        })
+        .collect();
+    let message = display(message);
+    let entries: [(&_, Option<&dyn Value>); MAX_RESPONSE_ATTRIBUTES + 1] =
+        std::array::from_fn(|i| match i {
+            0 => (&message_field, Some(&message as &dyn Value)),
+            i => match values.get(i - 1) {
+                Some((field, value)) => (field, Some(value.as_value())),
+                None => (&message_field, None),
+            },
+        });
+    Event::child_of(span.id(), metadata, &fields.value_set(&entries));
+}
+
+/// Returns a `'static` copy of `key`, leaking each distinct key once
+pub(super) fn intern(key: &str) -> &'static str {
+    static KEYS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+    let mut keys = KEYS.get_or_init(Default::default).lock().unwrap();
+    match keys.get(key) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(key.to_owned().into_boxed_str());
+            keys.insert(interned);
+            interned
+        }
+    }
+}
+
+/// Returns the event callsite for a sorted set of attribute keys, registering it on
+/// first use
+fn callsite_for(keys: Vec<&'static str>) -> &'static DynamicCallsite {
+    static CALLSITES: OnceLock<Mutex<HashMap<Vec<&'static str>, &'static DynamicCallsite>>> =
+        OnceLock::new();
+
+    let mut callsites = CALLSITES.get_or_init(Default::default).lock().unwrap();
+    callsites.entry(keys).or_insert_with_key(|keys| {
+        let names: &'static [&'static str] = Box::leak(
+            std::iter::once("message")
+                .chain(keys.iter().copied())
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        );
+        DynamicCallsite::register(|id| {
+            Metadata::new(
+                "redis.attributes",
+                module_path!(),
+                Level::INFO,
+                Some(file!()),
+                Some(line!()),
+                Some(module_path!()),
+                FieldSet::new(names, id),
+                Kind::EVENT,
+            )
+        })
+    })
+}
+
+/// An attribute value in a form `tracing` can record
+enum OwnedValue {
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    Str(String),
+    Formatted(DisplayValue<String>),
+}
+
+impl OwnedValue {
+    fn as_value(&self) -> &dyn Value {
+        match self {
+            OwnedValue::Bool(value) => value,
+            OwnedValue::I64(value) => value,
+            OwnedValue::F64(value) => value,
+            OwnedValue::Str(value) => value,
+            OwnedValue::Formatted(value) => value,
+        }
+    }
+}
+
+fn owned_value(value: &opentelemetry::Value) -> OwnedValue {
+    match value {
+        opentelemetry::Value::Bool(value) => OwnedValue::Bool(*value),
+        opentelemetry::Value::I64(value) => OwnedValue::I64(*value),
+        opentelemetry::Value::F64(value) => OwnedValue::F64(*value),
+        opentelemetry::Value::String(value) => OwnedValue::Str(value.to_string()),
+        opentelemetry::Value::Array(array) => OwnedValue::Str(super::join_array(array)),
+        other => OwnedValue::Formatted(display(other.to_string())),
+    }
+}
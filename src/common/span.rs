@@ -0,0 +1,83 @@
+//! Command spans handed to the caller, for commands executed outside the wrappers
+
+use super::{
+    apply_span_attributes, record_command_result, record_latency_bucket, response, RecordFields,
+};
+use crate::config::InstrumentationConfig;
+use opentelemetry::KeyValue;
+use redis::RedisResult;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::field::Value;
+use tracing::Span;
+
+/// The span of one command, returned by the wrappers' `begin_command`.
+///
+/// It carries the same attributes as the span `req_command` creates, but leaves
+/// executing the command to the caller, e.g. through the wrapper's `inner_mut`.
+/// Once the command completed, [`RedisSpan::record_result`] records its outcome and
+/// latency, and [`RedisSpan::finish`] closes the span. Dropping it closes the span
+/// too.
+///
+/// Commands executed this way don't update the wrapper's connection state, such as
+/// the database a `SELECT` switches to.
+#[derive(Debug)]
+pub struct RedisSpan {
+    span: Span,
+    attributes: Vec<KeyValue>,
+    config: Arc<InstrumentationConfig>,
+    started: Instant,
+}
+
+impl RedisSpan {
+    /// Wraps a command span and the attributes extracted from its command
+    #[cfg(any(feature = "sync", feature = "aio"))]
+    pub(crate) fn new(
+        span: Span,
+        attributes: Vec<KeyValue>,
+        config: Arc<InstrumentationConfig>,
+    ) -> Self {
+        Self {
+            span,
+            attributes,
+            config,
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns the underlying span, e.g. to instrument the command's future with it
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Returns the attributes extracted from the command
+    pub fn attributes(&self) -> &[KeyValue] {
+        &self.attributes
+    }
+
+    /// Adds `attribute` to the span.
+    ///
+    /// Keys the command span declares are recorded on it; others are emitted on a
+    /// `redis.attributes` event under it.
+    pub fn set_attribute(&self, attribute: KeyValue) {
+        let attributes = [attribute];
+        apply_span_attributes(&self.span, &attributes);
+        response::emit_undeclared_attributes(&self.span, &attributes, "redis.attributes");
+    }
+
+    /// Records the command's outcome, and its latency bucket when configured, measured
+    /// from `begin_command`
+    pub fn record_result<T>(&self, result: &RedisResult<T>) {
+        record_latency_bucket(&self.span, self.started.elapsed(), &self.config);
+        record_command_result(&self.span, result);
+    }
+
+    /// Closes the span
+    pub fn finish(self) {}
+}
+
+impl RecordFields for RedisSpan {
+    fn record_field<V: Value>(&self, field: &str, value: V) {
+        self.span.record(field, value);
+    }
+}
@@ -0,0 +1,382 @@
+//! Connection-scoped state observed from the commands passing through a wrapper
+
+use super::WindowAggregator;
+use crate::config::ConnectionSpanMode;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// State that a command changes for the rest of the connection's lifetime.
+///
+/// Some commands, such as `CLIENT TRACKING ON`, alter how the server treats every
+/// later command on the same connection. The wrappers feed each successful command
+/// to [`ConnectionState::observe_command`] and record the resulting state on the
+/// spans of subsequent commands.
+///
+/// The state uses atomics so it can be shared behind an `Arc` between clones of a
+/// multiplexed connection, which all talk to the server over the same socket.
+#[derive(Debug, Default)]
+pub struct ConnectionState {
+    tracking_enabled: AtomicBool,
+    replica_reads: AtomicBool,
+    database_index: AtomicI64,
+    in_transaction: AtomicBool,
+    // Arguments of the state-changing commands queued in the open transaction
+    queued_commands: Mutex<Vec<Vec<Vec<u8>>>>,
+    eviction: EvictionSampler,
+    slowlog: Throttle,
+    connection_failures: AtomicU32,
+    command_weight_total: AtomicU64,
+    aggregator: Option<WindowAggregator>,
+    server: OnceLock<ServerAddress>,
+    connection_span: Option<(tracing::Span, ConnectionSpanMode)>,
+}
+
+impl ConnectionState {
+    /// Creates the state of a freshly opened connection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the state of a freshly opened connection to database `db`.
+    pub fn with_database_index(db: i64) -> Self {
+        let state = Self::default();
+        state.database_index.store(db, Ordering::Relaxed);
+        state
+    }
+
+    /// Attaches the span covering the connection's lifetime.
+    ///
+    /// The span stays open for as long as the state, i.e. until the last clone of
+    /// the wrapper is dropped. Command spans are tied to it according to `mode`.
+    pub fn with_connection_span(mut self, span: tracing::Span, mode: ConnectionSpanMode) -> Self {
+        self.connection_span = Some((span, mode));
+        self
+    }
+
+    /// Summarizes commands over `window` instead of giving each its own span.
+    ///
+    /// The last window is flushed when the state is dropped with the connection.
+    pub fn with_window_aggregation(mut self, window: Duration) -> Self {
+        self.aggregator = Some(WindowAggregator::new(window));
+        self
+    }
+
+    /// Returns the aggregator summarizing the connection's commands, if enabled
+    pub fn window_aggregator(&self) -> Option<&WindowAggregator> {
+        self.aggregator.as_ref()
+    }
+
+    /// Returns the connection's lifetime span and how command spans are tied to it
+    pub fn connection_span(&self) -> Option<(&tracing::Span, ConnectionSpanMode)> {
+        self.connection_span
+            .as_ref()
+            .map(|(span, mode)| (span, *mode))
+    }
+
+    /// Sets the server the connection talks to, recorded on every command span.
+    ///
+    /// Only the first address set is kept.
+    pub fn set_server_address(&self, server: ServerAddress) {
+        let _ = self.server.set(server);
+    }
+
+    /// Returns the server the connection talks to, if it was set
+    pub fn server_address(&self) -> Option<&ServerAddress> {
+        self.server.get()
+    }
+
+    /// Returns the database the connection currently operates on.
+    ///
+    /// Starts at the database the connection was opened with and follows every
+    /// successful `SELECT` issued through the wrapper.
+    pub fn database_index(&self) -> i64 {
+        self.database_index.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` between a `MULTI` issued through the wrapper and the `EXEC` or
+    /// `DISCARD` ending it.
+    ///
+    /// The server queues every command sent in between, so the wrappers don't send
+    /// commands of their own, such as `INFO stats`, while a transaction is open.
+    pub fn in_transaction(&self) -> bool {
+        self.in_transaction.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if `CLIENT TRACKING ON` was issued through the wrapper and not
+    /// switched off since.
+    pub fn tracking_enabled(&self) -> bool {
+        self.tracking_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if `READONLY` was issued through the wrapper and not undone by
+    /// `READWRITE` since, i.e. the client allows cluster replicas to serve its reads.
+    pub fn replica_reads(&self) -> bool {
+        self.replica_reads.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total weight of the commands sent through the wrapper, e.g. for
+    /// client-side rate limiting.
+    ///
+    /// Stays `0` unless [`crate::InstrumentationConfig::with_command_weights`] is set.
+    pub fn command_weight_total(&self) -> u64 {
+        self.command_weight_total.load(Ordering::Relaxed)
+    }
+
+    /// Adds the weight of a command sent over the connection to its total
+    pub fn add_command_weight(&self, weight: u32) {
+        self.command_weight_total
+            .fetch_add(u64::from(weight), Ordering::Relaxed);
+    }
+
+    /// Returns the throttled `INFO stats` sampler used to detect eviction pressure
+    pub fn eviction(&self) -> &EvictionSampler {
+        &self.eviction
+    }
+
+    /// Returns the throttle limiting `SLOWLOG GET` lookups on the connection
+    pub fn slowlog(&self) -> &Throttle {
+        &self.slowlog
+    }
+
+    /// Returns the reconnect state inferred from the outcomes passed to
+    /// [`ConnectionState::observe_outcome`].
+    pub fn reconnect_state(&self) -> ReconnectState {
+        match self.connection_failures.load(Ordering::Relaxed) {
+            0 => ReconnectState::Ready,
+            1 => ReconnectState::Connecting,
+            _ => ReconnectState::Backoff,
+        }
+    }
+
+    /// Updates the reconnect state from the outcome of a command.
+    ///
+    /// A reply, even an error reply, means the connection is up. Dropped, refused and
+    /// other I/O failures count towards the consecutive failures since the last reply;
+    /// other errors leave the count alone.
+    pub fn observe_outcome<T>(&self, result: &redis::RedisResult<T>) {
+        match result {
+            Err(err)
+                if err.is_connection_dropped()
+                    || err.is_connection_refusal()
+                    || err.is_io_error() =>
+            {
+                self.connection_failures.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(err) if err.code().is_none() => {}
+            _ => self.connection_failures.store(0, Ordering::Relaxed),
+        }
+    }
+
+    /// Opens or closes a transaction from a command and its outcome.
+    ///
+    /// A successful `MULTI` opens it; `EXEC`, `DISCARD` and `RESET` close it whatever
+    /// their outcome, as does losing the connection, which discards it on the server.
+    /// The state changes of the commands queued in between apply only once `EXEC`
+    /// has run them, see [`ConnectionState::observe_command`].
+    pub fn observe_transaction(&self, cmd: &redis::Cmd, result: &redis::RedisResult<redis::Value>) {
+        if matches!(result, Err(err) if err.is_connection_dropped() || err.is_io_error()) {
+            self.close_transaction(false);
+            return;
+        }
+        let Some(redis::Arg::Simple(name)) = cmd.args_iter().next() else {
+            return;
+        };
+        if name.eq_ignore_ascii_case(b"MULTI") {
+            if result.is_ok() {
+                self.in_transaction.store(true, Ordering::Relaxed);
+            }
+        } else if name.eq_ignore_ascii_case(b"EXEC") {
+            // A nil reply means a WATCH conflict aborted the transaction
+            self.close_transaction(matches!(result, Ok(redis::Value::Array(_))));
+        } else if name.eq_ignore_ascii_case(b"DISCARD") || name.eq_ignore_ascii_case(b"RESET") {
+            self.close_transaction(false);
+        }
+    }
+
+    /// Closes the open transaction, applying its queued commands if it `ran`
+    fn close_transaction(&self, ran: bool) {
+        self.in_transaction.store(false, Ordering::Relaxed);
+        let queued = std::mem::take(&mut *self.queued_commands.lock().unwrap());
+        if ran {
+            for args in &queued {
+                self.observe_args(args.iter().map(Vec::as_slice));
+            }
+        }
+    }
+
+    /// Updates the state from a command that completed successfully.
+    ///
+    /// Commands that don't affect connection state are ignored. Inside a transaction
+    /// the reply only says the command was queued, so its effect is held back until
+    /// [`ConnectionState::observe_transaction`] sees the `EXEC` that runs it.
+    pub fn observe_command(&self, cmd: &redis::Cmd) {
+        let args = cmd.args_iter().map(|arg| match arg {
+            redis::Arg::Simple(bytes) => bytes,
+            redis::Arg::Cursor => &[],
+        });
+        if !self.in_transaction() {
+            self.observe_args(args);
+            return;
+        }
+
+        let mut queued = args.clone();
+        if queued.next().is_some_and(|name| {
+            [&b"READONLY"[..], b"READWRITE", b"SELECT", b"CLIENT"]
+                .iter()
+                .any(|changes_state| name.eq_ignore_ascii_case(changes_state))
+        }) {
+            let args = args.map(<[u8]>::to_vec).collect();
+            self.queued_commands.lock().unwrap().push(args);
+        }
+    }
+
+    /// Updates the state from the arguments of a command that ran
+    fn observe_args<'a>(&self, mut args: impl Iterator<Item = &'a [u8]>) {
+        let Some(name) = args.next() else {
+            return;
+        };
+        if name.eq_ignore_ascii_case(b"READONLY") {
+            self.replica_reads.store(true, Ordering::Relaxed);
+            return;
+        }
+        if name.eq_ignore_ascii_case(b"READWRITE") {
+            self.replica_reads.store(false, Ordering::Relaxed);
+            return;
+        }
+        let Some(subcommand) = args.next() else {
+            return;
+        };
+        if name.eq_ignore_ascii_case(b"SELECT") {
+            if let Some(db) = std::str::from_utf8(subcommand)
+                .ok()
+                .and_then(|db| db.parse().ok())
+            {
+                self.database_index.store(db, Ordering::Relaxed);
+            }
+            return;
+        }
+        if name.eq_ignore_ascii_case(b"CLIENT") && subcommand.eq_ignore_ascii_case(b"TRACKING") {
+            match args.next() {
+                Some(mode) if mode.eq_ignore_ascii_case(b"ON") => {
+                    self.tracking_enabled.store(true, Ordering::Relaxed);
+                }
+                Some(mode) if mode.eq_ignore_ascii_case(b"OFF") => {
+                    self.tracking_enabled.store(false, Ordering::Relaxed);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The server a connection talks to, recorded as `server.address` and `server.port`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerAddress {
+    /// Host name or IP address, or the socket path of a Unix socket connection
+    pub address: String,
+    /// Port of a TCP connection
+    pub port: Option<u16>,
+}
+
+impl ServerAddress {
+    /// Returns the server address a client with `info` connects to
+    pub fn from_connection_info(info: &redis::ConnectionInfo) -> Self {
+        match &info.addr {
+            redis::ConnectionAddr::Tcp(host, port)
+            | redis::ConnectionAddr::TcpTls { host, port, .. } => Self {
+                address: host.clone(),
+                port: Some(*port),
+            },
+            redis::ConnectionAddr::Unix(path) => Self {
+                address: path.display().to_string(),
+                port: None,
+            },
+        }
+    }
+}
+
+/// How a reconnecting connection is doing, see [`ConnectionState::reconnect_state`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectState {
+    /// The last command got a reply
+    Ready,
+    /// The connection failed once and is being replaced
+    Connecting,
+    /// Reconnect attempts keep failing and are retried after a delay
+    Backoff,
+}
+
+impl ReconnectState {
+    /// Returns the value recorded as `redis.connection.state`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReconnectState::Ready => "ready",
+            ReconnectState::Connecting => "connecting",
+            ReconnectState::Backoff => "backoff",
+        }
+    }
+}
+
+/// Limits an extra round trip made on behalf of the instrumentation to once per interval.
+#[derive(Debug, Default)]
+pub struct Throttle {
+    claimed_at: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    /// Claims the next round trip if `interval` has passed since the previous claim.
+    pub fn claim(&self, now: Instant, interval: Duration) -> bool {
+        let mut claimed_at = self.claimed_at.lock().unwrap();
+        let due =
+            claimed_at.is_none_or(|claimed| now.saturating_duration_since(claimed) >= interval);
+        if due {
+            *claimed_at = Some(now);
+        }
+        due
+    }
+}
+
+/// Throttled samples of the server's `evicted_keys` counter.
+///
+/// Write spans report `redis.eviction_pressure` when keys were evicted between the
+/// last two samples. Sampling issues an extra `INFO stats` round trip, so at most one
+/// sample is taken per interval; spans in between reuse the cached result.
+#[derive(Debug, Default)]
+pub struct EvictionSampler {
+    throttle: Throttle,
+    sample: Mutex<EvictionSample>,
+}
+
+#[derive(Debug, Default)]
+struct EvictionSample {
+    evicted_keys: Option<u64>,
+    pressure: Option<bool>,
+}
+
+impl EvictionSampler {
+    /// Claims the next sample if `interval` has passed since the previous one.
+    ///
+    /// Returns `true` at most once per interval, even when several commands on a
+    /// shared connection finish at the same time, so only one of them pays for
+    /// the `INFO stats` call.
+    pub fn claim(&self, now: Instant, interval: Duration) -> bool {
+        self.throttle.claim(now, interval)
+    }
+
+    /// Stores a new `evicted_keys` reading and compares it with the previous one
+    pub fn record(&self, evicted_keys: u64) {
+        let mut sample = self.sample.lock().unwrap();
+        if let Some(previous) = sample.evicted_keys {
+            sample.pressure = Some(evicted_keys > previous);
+        }
+        sample.evicted_keys = Some(evicted_keys);
+    }
+
+    /// Returns whether keys were evicted between the last two samples.
+    ///
+    /// `None` until two samples have been taken.
+    pub fn pressure(&self) -> Option<bool> {
+        self.sample.lock().unwrap().pressure
+    }
+}
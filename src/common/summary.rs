@@ -0,0 +1,224 @@
+//! Per-connection summary spans replacing individual command spans
+//!
+//! With window aggregation enabled, commands only add their latency to the current
+//! window, and a `redis summary` span carrying per-operation counts and p99
+//! latencies is emitted once the window has elapsed. Its fields depend on the
+//! operations seen, so the span uses a runtime callsite declaring every operation
+//! summarized so far in the process. Operations are capped process-wide, so both
+//! the field names and the callsites registered as new operations appear stay
+//! bounded.
+
+use super::response::intern;
+use super::target::DynamicCallsite;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{Level, Span};
+use tracing_core::field::{FieldSet, Value};
+use tracing_core::metadata::Kind;
+use tracing_core::Metadata;
+
+/// Most distinct operations summaries break down across the process; later ones
+/// are counted together as `other`
+pub const MAX_SUMMARY_OPERATIONS: usize = 64;
+
+/// Aggregates command latencies by operation over fixed windows.
+///
+/// Enabled with [`crate::InstrumentationConfig::with_window_aggregation`]. A window
+/// starts with its first command and is flushed as a `redis summary` span by the
+/// first command completing after it elapsed, so an idle connection emits nothing.
+/// The last window is flushed when the aggregator is dropped with the connection.
+#[derive(Debug)]
+pub struct WindowAggregator {
+    window: Duration,
+    current: Mutex<Window>,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    started: Option<Instant>,
+    latencies: BTreeMap<&'static str, Vec<Duration>>,
+    /// Attribute key form of the operations this connection summarized so far
+    keys: HashMap<String, &'static str>,
+}
+
+impl WindowAggregator {
+    /// Creates an aggregator summarizing every `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            current: Mutex::default(),
+        }
+    }
+
+    /// Adds a command of `operation` that took `elapsed`, completing at `now`.
+    ///
+    /// Flushes the current window first if it elapsed before `now`.
+    pub fn record(&self, operation: &str, elapsed: Duration, now: Instant) {
+        let mut current = self.current.lock().unwrap();
+        let started = *current.started.get_or_insert(now);
+        if now.saturating_duration_since(started) >= self.window {
+            current.flush(now);
+            current.started = Some(now);
+        }
+
+        let operation = current.operation_key(operation);
+        current
+            .latencies
+            .entry(operation)
+            .or_default()
+            .push(elapsed);
+    }
+
+    /// Emits a summary of the current window now, if it has any commands
+    pub fn flush(&self) {
+        let mut current = self.current.lock().unwrap();
+        current.flush(Instant::now());
+        current.started = None;
+    }
+}
+
+impl Drop for WindowAggregator {
+    fn drop(&mut self) {
+        if let Ok(current) = self.current.get_mut() {
+            current.flush(Instant::now());
+        }
+    }
+}
+
+impl Window {
+    /// Returns the attribute key form of `operation`, e.g. `client_setname` for
+    /// `CLIENT SETNAME`, or `other` once [`MAX_SUMMARY_OPERATIONS`] were seen
+    fn operation_key(&mut self, operation: &str) -> &'static str {
+        if let Some(key) = self.keys.get(operation) {
+            return key;
+        }
+        let key = REGISTRY.lock().unwrap().operation_key(operation);
+        // Operations counted as `other` aren't cached, as their number isn't bounded
+        if key != OTHER {
+            self.keys.insert(operation.to_owned(), key);
+        }
+        key
+    }
+
+    /// Emits the summary span of the window ending at `now` and clears its latencies
+    fn flush(&mut self, now: Instant) {
+        let Some(started) = self.started else {
+            return;
+        };
+        if self.latencies.is_empty() {
+            return;
+        }
+        let latencies = std::mem::take(&mut self.latencies);
+
+        let site = REGISTRY.lock().unwrap().callsite();
+        if !site.is_enabled() {
+            return;
+        }
+        let metadata = tracing_core::Callsite::metadata(site);
+        let fields = metadata.fields();
+        let (Some(otel_name), Some(db_system)) =
+            (fields.field("otel.name"), fields.field("db.system"))
+        else {
+            return;
+        };
+        let values: [(&_, Option<&dyn Value>); 2] = [
+            (&otel_name, Some(&"redis summary")),
+            (&db_system, Some(&"redis")),
+        ];
+        let span = Span::new_root(metadata, &fields.value_set(&values));
+
+        let window = now.saturating_duration_since(started);
+        span.record("redis.summary.window_ms", window.as_secs_f64() * 1000.0);
+        for (operation, mut latencies) in latencies {
+            latencies.sort_unstable();
+            span.record(count_key(operation), latencies.len() as i64);
+            span.record(p99_key(operation), p99(&latencies).as_secs_f64() * 1000.0);
+        }
+    }
+}
+
+/// Returns the 99th percentile of sorted, non-empty `latencies`, by nearest rank
+fn p99(latencies: &[Duration]) -> Duration {
+    let rank = (latencies.len() * 99).div_ceil(100);
+    latencies[rank.saturating_sub(1)]
+}
+
+fn count_key(operation: &str) -> &'static str {
+    intern(&format!("redis.summary.{operation}.count"))
+}
+
+fn p99_key(operation: &str) -> &'static str {
+    intern(&format!("redis.summary.{operation}.p99_ms"))
+}
+
+/// Operation key that the operations past [`MAX_SUMMARY_OPERATIONS`] share
+const OTHER: &str = "other";
+
+/// Operations summarized across the process, and the summary span callsite
+/// declaring their fields
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    operations: Vec::new(),
+    callsite: None,
+});
+
+struct Registry {
+    /// Operations in attribute key form, in the order they were first summarized
+    operations: Vec<&'static str>,
+    /// Callsite declaring the fields of `operations`, until another one is added
+    callsite: Option<&'static DynamicCallsite>,
+}
+
+impl Registry {
+    /// Returns the attribute key form of `operation`, adding it to the summarized
+    /// operations unless there are [`MAX_SUMMARY_OPERATIONS`] already
+    fn operation_key(&mut self, operation: &str) -> &'static str {
+        let key = operation.to_ascii_lowercase().replace(' ', "_");
+        if let Some(known) = self.operations.iter().find(|known| **known == key) {
+            return known;
+        }
+        if self.operations.len() >= MAX_SUMMARY_OPERATIONS {
+            return OTHER;
+        }
+        let key = intern(&key);
+        self.operations.push(key);
+        self.callsite = None;
+        key
+    }
+
+    /// Returns the summary span callsite, registering it if an operation was added
+    /// since the last one.
+    ///
+    /// A callsite is registered at most once per operation, and declares the fields
+    /// of every operation so far, which summaries of fewer operations leave empty.
+    fn callsite(&mut self) -> &'static DynamicCallsite {
+        let operations = &self.operations;
+        self.callsite.get_or_insert_with(|| {
+            let names: &'static [&'static str] = Box::leak(
+                ["otel.name", "db.system", "redis.summary.window_ms"]
+                    .into_iter()
+                    .chain(
+                        operations
+                            .iter()
+                            .copied()
+                            .chain([OTHER])
+                            .flat_map(|operation| [count_key(operation), p99_key(operation)]),
+                    )
+                    .collect::<Vec<_>>()
+                    .into_boxed_slice(),
+            );
+            DynamicCallsite::register(|id| {
+                Metadata::new(
+                    "redis summary",
+                    module_path!(),
+                    Level::INFO,
+                    Some(file!()),
+                    Some(line!()),
+                    Some(module_path!()),
+                    FieldSet::new(names, id),
+                    Kind::SPAN,
+                )
+            })
+        })
+    }
+}
@@ -0,0 +1,186 @@
+//! Command spans emitted under a tracing target chosen at runtime
+//!
+//! The span macros fix the target at compile time, so a configured target needs its
+//! own callsite. One is registered per distinct target and lives for the rest of the
+//! process, the same as a macro's static callsite. [`DynamicCallsite`] is also used
+//! for other spans and events whose metadata is only known at runtime.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::level_filters::LevelFilter;
+use tracing::{Level, Span};
+use tracing_core::callsite::{self, Callsite, Identifier};
+use tracing_core::field::{FieldSet, Value};
+use tracing_core::metadata::Kind;
+use tracing_core::{dispatcher, Interest, Metadata};
+
+/// Fields of the command span, in the order `command_span` declares them
+pub(crate) const COMMAND_SPAN_FIELDS: [&str; 47] = [
+    "otel.name",
+    "otel.kind",
+    "db.system",
+    "db.operation",
+    "redis.latency_bucket",
+    "redis.tracking.enabled",
+    "redis.tracking.key_tracked",
+    "redis.routing",
+    "redis.connection.state",
+    "server.address",
+    "server.port",
+    "redis.command.raw_verb",
+    "db.redis.key_count",
+    "db.redis.numkeys",
+    "redis.shard",
+    "redis.command.weight",
+    "redis.expire.unit",
+    "redis.expire.absolute",
+    "redis.expire.ttl_ms",
+    "db.statement",
+    "redis.connection_setup",
+    "redis.idempotent",
+    "redis.command.fingerprint",
+    "db.redis.protocol_version",
+    "redis.criticality",
+    "redis.eviction_pressure",
+    "db.redis.database_index",
+    "db.redis.database_name",
+    "redis.conditional.applied",
+    "redis.cache.hit_ratio",
+    "thread.id",
+    "thread.name",
+    "tokio.task.id",
+    "redis.error.statement",
+    "redis.error.reply",
+    "redis.redirect.node",
+    "redis.redirect.slot",
+    "db.redis.response.size",
+    "redis.resp.reply_bytes",
+    "redis.server.exec_us",
+    "redis.start_unix_nanos",
+    "redis.end_unix_nanos",
+    "redis.queue_wait_ms",
+    "redis.server_wait_ms",
+    "otel.status_code",
+    "otel.status_description",
+    "error.type",
+];
+
+const INTEREST_NEVER: u8 = 0;
+const INTEREST_SOMETIMES: u8 = 1;
+const INTEREST_ALWAYS: u8 = 2;
+
+/// A callsite whose metadata is only known at runtime
+pub(crate) struct DynamicCallsite {
+    metadata: OnceLock<Metadata<'static>>,
+    interest: AtomicU8,
+}
+
+impl DynamicCallsite {
+    /// Leaks and registers a callsite with the metadata built by `metadata`, which
+    /// receives the callsite's identifier.
+    pub(crate) fn register(
+        metadata: impl FnOnce(Identifier) -> Metadata<'static>,
+    ) -> &'static DynamicCallsite {
+        let site: &'static DynamicCallsite = Box::leak(Box::new(DynamicCallsite {
+            metadata: OnceLock::new(),
+            interest: AtomicU8::new(INTEREST_SOMETIMES),
+        }));
+        let _ = site.metadata.set(metadata(Identifier(site)));
+        callsite::register(site);
+        site
+    }
+
+    /// Returns `true` if the current subscriber wants spans or events from this callsite
+    pub(crate) fn is_enabled(&self) -> bool {
+        if *self.metadata().level() > LevelFilter::current() {
+            return false;
+        }
+        match self.interest.load(Ordering::Relaxed) {
+            INTEREST_NEVER => false,
+            INTEREST_ALWAYS => true,
+            _ => dispatcher::get_default(|dispatch| dispatch.enabled(self.metadata())),
+        }
+    }
+}
+
+impl Callsite for DynamicCallsite {
+    fn set_interest(&self, interest: Interest) {
+        let interest = if interest.is_never() {
+            INTEREST_NEVER
+        } else if interest.is_always() {
+            INTEREST_ALWAYS
+        } else {
+            INTEREST_SOMETIMES
+        };
+        self.interest.store(interest, Ordering::Relaxed);
+    }
+
+    fn metadata(&self) -> &Metadata<'_> {
+        self.metadata
+            .get()
+            .expect("callsite metadata is set before registration")
+    }
+}
+
+/// Returns the callsite for `target`, registering it on first use
+fn callsite_for(target: &'static str) -> &'static DynamicCallsite {
+    static CALLSITES: OnceLock<Mutex<HashMap<&'static str, &'static DynamicCallsite>>> =
+        OnceLock::new();
+
+    let mut callsites = CALLSITES.get_or_init(Default::default).lock().unwrap();
+    callsites.entry(target).or_insert_with(|| {
+        DynamicCallsite::register(|id| {
+            Metadata::new(
+                "redis_command",
+                target,
+                Level::INFO,
+                Some(file!()),
+                Some(line!()),
+                Some(module_path!()),
+                FieldSet::new(&COMMAND_SPAN_FIELDS, id),
+                Kind::SPAN,
+            )
+        })
+    })
+}
+
+/// Creates the command span under `target`, with the same fields as `command_span`
+pub(crate) fn command_span(
+    target: &'static str,
+    name: &str,
+    operation: &str,
+    db_system: Option<&'static str>,
+) -> Span {
+    if Level::INFO > LevelFilter::current() {
+        return Span::none();
+    }
+
+    let site = callsite_for(target);
+    if !site.is_enabled() {
+        return Span::none();
+    }
+    let metadata = site.metadata();
+
+    let fields = metadata.fields();
+    let (Some(otel_name), Some(otel_kind), Some(db_system_field), Some(db_operation)) = (
+        fields.field("otel.name"),
+        fields.field("otel.kind"),
+        fields.field("db.system"),
+        fields.field("db.operation"),
+    ) else {
+        return Span::none();
+    };
+    let name = tracing::field::display(name);
+    let operation = tracing::field::display(operation);
+    let values: [(&_, Option<&dyn Value>); 4] = [
+        (&otel_name, Some(&name)),
+        (&otel_kind, Some(&"client")),
+        (
+            &db_system_field,
+            db_system.as_ref().map(|value| value as &dyn Value),
+        ),
+        (&db_operation, Some(&operation)),
+    ];
+    Span::new(metadata, &fields.value_set(&values))
+}
@@ -0,0 +1,1071 @@
+//! Configuration shared by an instrumented client and the connections it hands out.
+
+use crate::common::{CommandKind, HitCounters, OperationCounters};
+use opentelemetry::KeyValue;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Latency bucket boundaries used by [`InstrumentationConfig::with_default_latency_buckets`].
+///
+/// They produce the buckets `<1ms`, `1-10ms`, `10-100ms` and `>100ms`.
+pub const DEFAULT_LATENCY_BUCKETS: [Duration; 3] = [
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+];
+
+/// Longest `db.statement` recorded unless [`InstrumentationConfig::with_statement_max_length`]
+/// sets another limit, in characters.
+pub const DEFAULT_STATEMENT_MAX_LENGTH: usize = 1024;
+
+/// Environment variable read by [`InstrumentationConfig::from_env`]: comma-separated
+/// latency bucket boundaries in milliseconds, e.g. `1,10,100`.
+pub const ENV_LATENCY_BUCKETS_MS: &str = "OTEL_REDIS_LATENCY_BUCKETS_MS";
+
+/// Environment variable read by [`InstrumentationConfig::from_env`]: `true` or `false`
+/// to toggle [`InstrumentationConfig::with_raw_verb_capture`].
+pub const ENV_CAPTURE_RAW_VERB: &str = "OTEL_REDIS_CAPTURE_RAW_VERB";
+
+/// Environment variable read by [`InstrumentationConfig::from_env`]: `upper`, `lower`
+/// or `preserve`, see [`OperationCase`].
+pub const ENV_OPERATION_CASE: &str = "OTEL_REDIS_OPERATION_CASE";
+
+/// Environment variable read by [`InstrumentationConfig::from_env`]: the `INFO stats`
+/// sampling interval in milliseconds, see [`InstrumentationConfig::with_eviction_sampling`].
+pub const ENV_EVICTION_SAMPLING_MS: &str = "OTEL_REDIS_EVICTION_SAMPLING_MS";
+
+/// Environment variable read by [`InstrumentationConfig::from_env`]: `true` or `false`
+/// to toggle [`InstrumentationConfig::with_include_db_index_always`].
+pub const ENV_INCLUDE_DB_INDEX_ALWAYS: &str = "OTEL_REDIS_INCLUDE_DB_INDEX_ALWAYS";
+
+/// How operation names are cased in `db.operation` and span names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OperationCase {
+    /// `GET`, regardless of how the command was sent. Span names stay lowercase
+    /// (`redis get`) for backward compatibility.
+    #[default]
+    Upper,
+    /// `get`, in both `db.operation` and the span name.
+    Lower,
+    /// The command name exactly as sent, e.g. `Get`, in both `db.operation` and the span name.
+    Preserve,
+}
+
+/// How command spans are named, see [`InstrumentationConfig::with_span_name_strategy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpanNameStrategy {
+    /// `redis get`, the name used before strategies were configurable.
+    #[default]
+    RedisPrefixed,
+    /// The operation alone, e.g. `GET`, as the database semantic conventions recommend
+    /// when there's no target to add.
+    OperationOnly,
+    /// The operation followed by the first key, e.g. `HSET users:1`, or the operation
+    /// alone for commands without keys such as `PING`.
+    ///
+    /// Keys are often unique per request, so this can create many distinct span
+    /// names; prefer it when keys name collections rather than individual records.
+    OperationAndCollection,
+}
+
+/// What `db.statement` records, see [`InstrumentationConfig::with_statement_capture`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatementCapture {
+    /// No `db.statement`.
+    #[default]
+    Disabled,
+    /// The operation name alone, e.g. `GET`.
+    OperationOnly,
+    /// The whole command with its arguments, e.g. `GET user:123`. The arguments of
+    /// commands carrying credentials are masked.
+    Full,
+}
+
+/// How command spans are tied to the span covering their connection's lifetime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionSpanMode {
+    /// Command spans are children of the connection span, so the trace UI groups
+    /// them under it. They are no longer children of the caller's current span.
+    Parent,
+    /// Command spans keep their usual parent and link to the connection span.
+    ///
+    /// Multiplexed connections always use this mode: they serve many unrelated
+    /// requests at once, so parenting every command to the connection would merge
+    /// those requests into one trace.
+    Link,
+}
+
+/// A callback run for every failed command, see [`InstrumentationConfig::with_error_hook`].
+pub type ErrorHook = dyn Fn(&redis::Cmd, &redis::RedisError) + Send + Sync;
+
+/// A callback deriving attributes from a successful reply, see
+/// [`InstrumentationConfig::with_response_attribute_extractor`].
+pub type ResponseAttributeExtractor = dyn Fn(&str, &redis::Value) -> Vec<KeyValue> + Send + Sync;
+
+/// What a span preprocessor decided for a command, see
+/// [`InstrumentationConfig::with_span_preprocessor`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpanDecision {
+    /// Create the command span with the preprocessed attributes
+    #[default]
+    Create,
+    /// Send the command without a span
+    Skip,
+}
+
+/// A callback run on the attributes of every command before its span is created, see
+/// [`InstrumentationConfig::with_span_preprocessor`].
+pub type SpanPreprocessor = dyn Fn(&mut Vec<KeyValue>, &str) -> SpanDecision + Send + Sync;
+
+/// Adds application-specific attributes, such as a tenant id, to every command span.
+///
+/// Registered with [`InstrumentationConfig::with_span_enricher`].
+///
+/// # Example
+///
+/// ```rust
+/// use opentelemetry::KeyValue;
+/// use otel_instrumentation_redis::{InstrumentationConfig, SpanEnricher};
+///
+/// struct Tenant(&'static str);
+///
+/// impl SpanEnricher for Tenant {
+///     fn enrich(&self, _cmd: &redis::Cmd, attributes: &mut Vec<KeyValue>) {
+///         attributes.push(KeyValue::new("tenant.id", self.0));
+///     }
+/// }
+///
+/// let config = InstrumentationConfig::new().with_span_enricher(Tenant("acme"));
+/// ```
+pub trait SpanEnricher {
+    /// Appends the attributes for `cmd` to `attributes`, which holds the ones
+    /// extracted from the command so far.
+    fn enrich(&self, cmd: &redis::Cmd, attributes: &mut Vec<KeyValue>);
+}
+
+/// A callback mapping a command's first key to the shard serving it, see
+/// [`InstrumentationConfig::with_shard_resolver`].
+pub type ShardResolver = dyn Fn(&str) -> u32 + Send + Sync;
+
+/// Options controlling what the instrumentation records.
+///
+/// The configuration is built once, handed to [`crate::InstrumentedClient::with_config`],
+/// and shared with every connection the client creates. All options are off by
+/// default, so `InstrumentationConfig::default()` records the same attributes as
+/// wrappers created without a configuration.
+///
+/// # Example
+///
+/// ```rust
+/// use otel_instrumentation_redis::{InstrumentationConfig, InstrumentedClient};
+/// use std::time::Duration;
+///
+/// let config = InstrumentationConfig::new()
+///     .with_latency_buckets(&[Duration::from_millis(5), Duration::from_millis(50)]);
+///
+/// let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+/// let instrumented = InstrumentedClient::with_config(client, config);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentationConfig {
+    latency_buckets: Option<Vec<Duration>>,
+    raw_verb_capture: bool,
+    operation_case: OperationCase,
+    span_name_strategy: SpanNameStrategy,
+    collapse_subcommands: bool,
+    omit_db_system: bool,
+    statement_capture: StatementCapture,
+    statement_max_length: Option<usize>,
+    eviction_sampling: Option<Duration>,
+    slowlog_correlation: Option<(Duration, Duration)>,
+    include_db_index_always: bool,
+    db_names: HashMap<i64, &'static str>,
+    command_weights: Option<HashMap<String, u32>>,
+    default_command_weight: Option<u32>,
+    pipeline_result_events: Option<usize>,
+    local_counters: Option<Arc<OperationCounters>>,
+    tracing_target: Option<&'static str>,
+    connection_span: Option<ConnectionSpanMode>,
+    writes_only: bool,
+    writes_only_includes_other: bool,
+    error_hook: Option<SharedErrorHook>,
+    response_attribute_extractor: Option<SharedResponseExtractor>,
+    span_preprocessor: Option<SharedSpanPreprocessor>,
+    span_enricher: Option<SharedSpanEnricher>,
+    shard_resolver: Option<SharedShardResolver>,
+    hit_counters: Option<Arc<HitCounters>>,
+    hit_ratio_attribute: bool,
+    response_size: bool,
+    unix_timestamps: bool,
+    dispatch_timing: bool,
+    deferred_recording: bool,
+    execution_context: bool,
+    error_statement_capture: bool,
+    watch_conflict_attribute: bool,
+    reconnect_state: bool,
+    window_aggregation: Option<Duration>,
+    #[cfg(feature = "metrics")]
+    batch_efficiency: Option<usize>,
+    #[cfg(feature = "regex")]
+    key_redactions: Vec<(regex::Regex, String)>,
+}
+
+/// The configured error hook, shared between clones of the configuration
+#[derive(Clone)]
+struct SharedErrorHook(Arc<ErrorHook>);
+
+impl fmt::Debug for SharedErrorHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErrorHook")
+    }
+}
+
+/// The configured response attribute extractor, shared between clones of the configuration
+#[derive(Clone)]
+struct SharedResponseExtractor(Arc<ResponseAttributeExtractor>);
+
+impl fmt::Debug for SharedResponseExtractor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ResponseAttributeExtractor")
+    }
+}
+
+/// The configured span preprocessor, shared between clones of the configuration
+#[derive(Clone)]
+struct SharedSpanPreprocessor(Arc<SpanPreprocessor>);
+
+impl fmt::Debug for SharedSpanPreprocessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SpanPreprocessor")
+    }
+}
+
+/// The configured span enricher, shared between clones of the configuration
+#[derive(Clone)]
+struct SharedSpanEnricher(Arc<dyn SpanEnricher + Send + Sync>);
+
+impl fmt::Debug for SharedSpanEnricher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SpanEnricher")
+    }
+}
+
+/// The configured shard resolver, shared between clones of the configuration
+#[derive(Clone)]
+struct SharedShardResolver(Arc<ShardResolver>);
+
+impl fmt::Debug for SharedShardResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ShardResolver")
+    }
+}
+
+impl InstrumentationConfig {
+    /// Creates a configuration with every option disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a configuration from `OTEL_REDIS_*` environment variables.
+    ///
+    /// This lets options be flipped during an incident without recompiling. Builder
+    /// calls made on the result take precedence over the environment:
+    ///
+    /// | Variable | Option |
+    /// |---|---|
+    /// | `OTEL_REDIS_LATENCY_BUCKETS_MS` | [`Self::with_latency_buckets`], e.g. `1,10,100` |
+    /// | `OTEL_REDIS_CAPTURE_RAW_VERB` | [`Self::with_raw_verb_capture`] |
+    /// | `OTEL_REDIS_OPERATION_CASE` | [`Self::with_operation_case`]: `upper`, `lower` or `preserve` |
+    /// | `OTEL_REDIS_EVICTION_SAMPLING_MS` | [`Self::with_eviction_sampling`] |
+    /// | `OTEL_REDIS_INCLUDE_DB_INDEX_ALWAYS` | [`Self::with_include_db_index_always`] |
+    ///
+    /// Booleans accept `true`/`false`, `1`/`0`, `yes`/`no` and `on`/`off`. A malformed
+    /// value is logged as a warning and the option keeps its default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use otel_instrumentation_redis::InstrumentationConfig;
+    ///
+    /// // Raw verb capture stays on even if the environment turns it off
+    /// let config = InstrumentationConfig::from_env().with_raw_verb_capture(true);
+    /// assert!(config.raw_verb_capture());
+    /// ```
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Builds a configuration from variables returned by `lookup`, as [`Self::from_env`] does.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = Self::default();
+
+        if let Some(value) = lookup(ENV_LATENCY_BUCKETS_MS) {
+            let boundaries: Option<Vec<Duration>> = value
+                .split(',')
+                .map(|ms| ms.trim().parse().ok().map(Duration::from_millis))
+                .collect();
+            match boundaries {
+                Some(boundaries) => config = config.with_latency_buckets(&boundaries),
+                None => warn_malformed(ENV_LATENCY_BUCKETS_MS, &value),
+            }
+        }
+        if let Some(value) = lookup(ENV_CAPTURE_RAW_VERB) {
+            match parse_bool(&value) {
+                Some(enabled) => config = config.with_raw_verb_capture(enabled),
+                None => warn_malformed(ENV_CAPTURE_RAW_VERB, &value),
+            }
+        }
+        if let Some(value) = lookup(ENV_OPERATION_CASE) {
+            let case = match value.trim().to_ascii_lowercase().as_str() {
+                "upper" => Some(OperationCase::Upper),
+                "lower" => Some(OperationCase::Lower),
+                "preserve" => Some(OperationCase::Preserve),
+                _ => None,
+            };
+            match case {
+                Some(case) => config = config.with_operation_case(case),
+                None => warn_malformed(ENV_OPERATION_CASE, &value),
+            }
+        }
+        if let Some(value) = lookup(ENV_EVICTION_SAMPLING_MS) {
+            match value.trim().parse() {
+                Ok(ms) => config = config.with_eviction_sampling(Duration::from_millis(ms)),
+                Err(_) => warn_malformed(ENV_EVICTION_SAMPLING_MS, &value),
+            }
+        }
+        if let Some(value) = lookup(ENV_INCLUDE_DB_INDEX_ALWAYS) {
+            match parse_bool(&value) {
+                Some(enabled) => config = config.with_include_db_index_always(enabled),
+                None => warn_malformed(ENV_INCLUDE_DB_INDEX_ALWAYS, &value),
+            }
+        }
+
+        config
+    }
+
+    /// Records `redis.latency_bucket` on command spans using the given bucket boundaries.
+    ///
+    /// Each boundary closes one bucket and opens the next, so `n` boundaries produce
+    /// `n + 1` buckets. Boundaries are sorted and deduplicated; an empty slice
+    /// disables bucketing again.
+    pub fn with_latency_buckets(mut self, boundaries: &[Duration]) -> Self {
+        let mut boundaries = boundaries.to_vec();
+        boundaries.sort();
+        boundaries.dedup();
+        self.latency_buckets = (!boundaries.is_empty()).then_some(boundaries);
+        self
+    }
+
+    /// Records `redis.latency_bucket` using [`DEFAULT_LATENCY_BUCKETS`].
+    pub fn with_default_latency_buckets(self) -> Self {
+        self.with_latency_buckets(&DEFAULT_LATENCY_BUCKETS)
+    }
+
+    /// Returns the configured latency bucket boundaries, if bucketing is enabled.
+    pub fn latency_buckets(&self) -> Option<&[Duration]> {
+        self.latency_buckets.as_deref()
+    }
+
+    /// Records `redis.command.raw_verb`, the command name exactly as sent.
+    ///
+    /// `db.operation` is always normalized to uppercase; the raw verb preserves the
+    /// original casing to help debug proxies that reject oddly cased commands.
+    pub fn with_raw_verb_capture(mut self, enabled: bool) -> Self {
+        self.raw_verb_capture = enabled;
+        self
+    }
+
+    /// Returns `true` if the raw command verb is recorded.
+    pub fn raw_verb_capture(&self) -> bool {
+        self.raw_verb_capture
+    }
+
+    /// Controls how the operation name is cased in `db.operation` and span names.
+    ///
+    /// Defaults to [`OperationCase::Upper`].
+    pub fn with_operation_case(mut self, case: OperationCase) -> Self {
+        self.operation_case = case;
+        self
+    }
+
+    /// Returns how operation names are cased.
+    pub fn operation_case(&self) -> OperationCase {
+        self.operation_case
+    }
+
+    /// Controls whether `db.system = "redis"` is added to command attributes and spans.
+    ///
+    /// On by default. Deployments where every span comes from Redis can turn it off to
+    /// shave the constant attribute off each command; backends then can't tell Redis
+    /// spans apart by `db.system`.
+    pub fn with_db_system_attribute(mut self, enabled: bool) -> Self {
+        self.omit_db_system = !enabled;
+        self
+    }
+
+    /// Returns `true` if `db.system` is recorded on commands.
+    pub fn db_system_attribute(&self) -> bool {
+        !self.omit_db_system
+    }
+
+    /// Reduces `db.operation` and span names to the command family.
+    ///
+    /// Subcommands and module command names are stripped, so `CONFIG GET` and
+    /// `CONFIG SET` both become `CONFIG`, and `FT.SEARCH` and `FT.AGGREGATE` become
+    /// `FT`. This suits dashboards grouping by family; operation counters and metrics
+    /// use the collapsed name too.
+    pub fn with_collapse_subcommands(mut self, enabled: bool) -> Self {
+        self.collapse_subcommands = enabled;
+        self
+    }
+
+    /// Returns `true` if operation names are reduced to the command family.
+    pub fn collapse_subcommands(&self) -> bool {
+        self.collapse_subcommands
+    }
+
+    /// Records the command as `db.statement` on command spans, as `capture` selects.
+    ///
+    /// With [`StatementCapture::Full`], argument values are recorded as sent, so
+    /// only enable it where they are safe to export. The arguments of commands that
+    /// may carry credentials are replaced with `?`: `AUTH`, `HELLO` after its `AUTH`
+    /// option, and `MIGRATE`, `ACL` and `CONFIG`. Keys pass through
+    /// [`Self::redact_key`]. Statements longer than [`Self::statement_max_length`] are
+    /// truncated.
+    pub fn with_statement_capture(mut self, capture: StatementCapture) -> Self {
+        self.statement_capture = capture;
+        self
+    }
+
+    /// Returns what `db.statement` records.
+    pub fn statement_capture(&self) -> StatementCapture {
+        self.statement_capture
+    }
+
+    /// Truncates `db.statement` to `max_length` characters, ending it with `…`.
+    ///
+    /// Defaults to [`DEFAULT_STATEMENT_MAX_LENGTH`]; `0` records statements whole.
+    pub fn with_statement_max_length(mut self, max_length: usize) -> Self {
+        self.statement_max_length = Some(max_length);
+        self
+    }
+
+    /// Returns the longest `db.statement` recorded, in characters.
+    pub fn statement_max_length(&self) -> usize {
+        self.statement_max_length
+            .unwrap_or(DEFAULT_STATEMENT_MAX_LENGTH)
+    }
+
+    /// Names command spans according to `strategy`.
+    ///
+    /// The operation part follows [`Self::with_operation_case`], so the default
+    /// casing yields `GET` with [`SpanNameStrategy::OperationOnly`].
+    pub fn with_span_name_strategy(mut self, strategy: SpanNameStrategy) -> Self {
+        self.span_name_strategy = strategy;
+        self
+    }
+
+    /// Returns how command spans are named.
+    pub fn span_name_strategy(&self) -> SpanNameStrategy {
+        self.span_name_strategy
+    }
+
+    /// Records `redis.eviction_pressure` on write spans.
+    ///
+    /// After a write, the wrapper reads `evicted_keys` from `INFO stats` at most once
+    /// per `interval` and flags the span when the counter grew since the previous
+    /// sample. Writes between samples report the cached result.
+    pub fn with_eviction_sampling(mut self, interval: Duration) -> Self {
+        self.eviction_sampling = Some(interval);
+        self
+    }
+
+    /// Returns the `INFO stats` sampling interval, if eviction sampling is enabled.
+    pub fn eviction_sampling(&self) -> Option<Duration> {
+        self.eviction_sampling
+    }
+
+    /// Records the server-side execution time of slow commands as `redis.server.exec_us`.
+    ///
+    /// When a command takes at least `threshold` on the client, the wrapper issues
+    /// `SLOWLOG GET` and attaches the duration of the most recent entry for the same
+    /// command and key. At most one lookup is made per `interval` per connection, so
+    /// a burst of slow commands doesn't add to the load. Only commands the server
+    /// logged, i.e. slower than its `slowlog-log-slower-than`, can be matched.
+    pub fn with_slowlog_correlation(mut self, threshold: Duration, interval: Duration) -> Self {
+        self.slowlog_correlation = Some((threshold, interval));
+        self
+    }
+
+    /// Returns the latency threshold and lookup interval of SLOWLOG correlation, if
+    /// it is enabled.
+    pub fn slowlog_correlation(&self) -> Option<(Duration, Duration)> {
+        self.slowlog_correlation
+    }
+
+    /// Records `db.redis.database_index` on every command span, including database 0.
+    ///
+    /// By default the attribute is only recorded when the connection is on a database
+    /// other than 0, the server default.
+    pub fn with_include_db_index_always(mut self, enabled: bool) -> Self {
+        self.include_db_index_always = enabled;
+        self
+    }
+
+    /// Returns `true` if the database index is recorded on every command span.
+    pub fn include_db_index_always(&self) -> bool {
+        self.include_db_index_always
+    }
+
+    /// Records `db.redis.database_name` using logical names for database indexes.
+    ///
+    /// Deployments that dedicate databases to purposes, such as `0` for sessions and
+    /// `1` for cache, get the readable name next to `db.redis.database_index`. An
+    /// index without a name records no `db.redis.database_name`.
+    pub fn with_db_names(mut self, names: HashMap<i64, &'static str>) -> Self {
+        self.db_names = names;
+        self
+    }
+
+    /// Returns the logical name of database `index`, if one was configured.
+    pub fn db_name(&self, index: i64) -> Option<&'static str> {
+        self.db_names.get(&index).copied()
+    }
+
+    /// Records `redis.command.weight` from a weight per command name.
+    ///
+    /// Weights let load budgets tell cheap commands such as `GET` from expensive ones
+    /// such as `KEYS`. Names are matched case-insensitively against the command name,
+    /// e.g. `CLIENT` for `CLIENT SETNAME`. Commands without a weight use the default
+    /// weight, see [`InstrumentationConfig::with_default_command_weight`]. Every
+    /// wrapper also adds the weights of its commands to
+    /// [`crate::common::ConnectionState::command_weight_total`].
+    pub fn with_command_weights(mut self, weights: HashMap<&'static str, u32>) -> Self {
+        self.command_weights = Some(
+            weights
+                .into_iter()
+                .map(|(name, weight)| (name.to_ascii_uppercase(), weight))
+                .collect(),
+        );
+        self
+    }
+
+    /// Sets the weight of commands without one in
+    /// [`InstrumentationConfig::with_command_weights`], `1` unless set.
+    pub fn with_default_command_weight(mut self, weight: u32) -> Self {
+        self.default_command_weight = Some(weight);
+        self
+    }
+
+    /// Returns the weight of command `name`, or `None` unless command weights are
+    /// configured.
+    pub fn command_weight(&self, name: &str) -> Option<u32> {
+        let weights = self.command_weights.as_ref()?;
+        let weight = weights
+            .get(name)
+            .or_else(|| weights.get(&name.to_ascii_uppercase()))
+            .copied();
+        Some(weight.unwrap_or(self.default_command_weight.unwrap_or(1)))
+    }
+
+    /// Emits a span event per pipeline or transaction sub-result, up to `max_events`.
+    ///
+    /// Each event records the result's `redis.pipeline.index` and `redis.pipeline.status`,
+    /// giving trace UIs a timeline of the batch. Results beyond `max_events` are not
+    /// emitted and the pipeline span records `redis.pipeline.events_truncated = true`.
+    pub fn with_pipeline_result_events(mut self, max_events: usize) -> Self {
+        self.pipeline_result_events = Some(max_events);
+        self
+    }
+
+    /// Returns the maximum number of pipeline result events, if they are enabled.
+    pub fn pipeline_result_events(&self) -> Option<usize> {
+        self.pipeline_result_events
+    }
+
+    /// Keeps an in-process count of commands per operation, readable through
+    /// [`crate::InstrumentedClient::operation_counts`] without any OpenTelemetry setup.
+    ///
+    /// The counters live in the configuration, so every connection of a client and
+    /// every clone of the configuration share them.
+    pub fn with_local_counters(mut self, enabled: bool) -> Self {
+        self.local_counters = enabled.then(|| Arc::new(OperationCounters::new()));
+        self
+    }
+
+    /// Returns the local operation counters, if they are enabled.
+    pub fn local_counters(&self) -> Option<&OperationCounters> {
+        self.local_counters.as_deref()
+    }
+
+    /// Emits command spans under `target` instead of this crate's module path.
+    ///
+    /// Subscribers filter on the target, so this lets an application route Redis
+    /// spans with the rest of its own, e.g. `"myapp::redis"`.
+    pub fn with_tracing_target(mut self, target: &'static str) -> Self {
+        self.tracing_target = Some(target);
+        self
+    }
+
+    /// Returns the target command spans are emitted under, if one was set.
+    pub fn tracing_target(&self) -> Option<&'static str> {
+        self.tracing_target
+    }
+
+    /// Opens a `redis connection` span for the lifetime of each connection and ties
+    /// every command span to it according to `mode`.
+    ///
+    /// The connection span ends when the last handle to the connection is dropped.
+    pub fn with_connection_span(mut self, mode: ConnectionSpanMode) -> Self {
+        self.connection_span = Some(mode);
+        self
+    }
+
+    /// Returns how command spans are tied to their connection's span, if at all.
+    pub fn connection_span(&self) -> Option<ConnectionSpanMode> {
+        self.connection_span
+    }
+
+    /// Records command spans only for commands that modify keys.
+    ///
+    /// Reads get a disabled span, which keeps an audit trail of writes while cutting
+    /// span volume for read-heavy workloads. Commands that are neither reads nor
+    /// writes, such as `PING` or `SELECT`, are skipped too unless
+    /// [`Self::with_writes_only_including_other`] says otherwise. See
+    /// [`crate::common::classify_command`] for the classification.
+    pub fn with_writes_only(mut self, enabled: bool) -> Self {
+        self.writes_only = enabled;
+        self
+    }
+
+    /// Returns `true` if only writes get command spans.
+    pub fn writes_only(&self) -> bool {
+        self.writes_only
+    }
+
+    /// Keeps spans for commands that are neither reads nor writes when
+    /// [`Self::with_writes_only`] is enabled.
+    pub fn with_writes_only_including_other(mut self, enabled: bool) -> Self {
+        self.writes_only_includes_other = enabled;
+        self
+    }
+
+    /// Returns `true` if commands that are neither reads nor writes keep their
+    /// spans in writes-only mode.
+    pub fn writes_only_includes_other(&self) -> bool {
+        self.writes_only_includes_other
+    }
+
+    /// Returns `true` if commands of `kind` get a command span.
+    pub fn instruments(&self, kind: CommandKind) -> bool {
+        if !self.writes_only {
+            return true;
+        }
+        match kind {
+            CommandKind::Write => true,
+            CommandKind::Read => false,
+            CommandKind::Other => self.writes_only_includes_other,
+        }
+    }
+
+    /// Calls `hook` with the command and error whenever a command fails.
+    ///
+    /// The hook runs on the command path after the error is recorded on the span,
+    /// e.g. to bump a custom counter or feed a circuit breaker, so it should return
+    /// quickly. A panicking hook is caught and logged; the command still returns
+    /// its original error.
+    pub fn with_error_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&redis::Cmd, &redis::RedisError) + Send + Sync + 'static,
+    {
+        self.error_hook = Some(SharedErrorHook(Arc::new(hook)));
+        self
+    }
+
+    /// Returns the hook called for every failed command, if one was set.
+    pub fn error_hook(&self) -> Option<&ErrorHook> {
+        self.error_hook.as_ref().map(|hook| &*hook.0)
+    }
+
+    /// Calls `extractor` with the operation name and reply of every successful command
+    /// and records the attributes it returns.
+    ///
+    /// This covers replies the instrumentation has no built-in attribute for, e.g.
+    /// the length returned by `LLEN`. Return an empty `Vec` for operations of no
+    /// interest. Attributes the command span declares are recorded on it; the others
+    /// are recorded together on a `redis.response` event under the span. At most
+    /// [`crate::common::MAX_RESPONSE_ATTRIBUTES`] attributes are kept per reply.
+    /// Server error replies are not passed to the extractor, and a panicking
+    /// extractor is caught and logged like an error hook.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use opentelemetry::KeyValue;
+    /// use otel_instrumentation_redis::InstrumentationConfig;
+    ///
+    /// let config = InstrumentationConfig::new().with_response_attribute_extractor(
+    ///     |operation, reply| match (operation, reply) {
+    ///         ("LLEN", redis::Value::Int(len)) => vec![KeyValue::new("app.queue.length", *len)],
+    ///         _ => Vec::new(),
+    ///     },
+    /// );
+    /// ```
+    pub fn with_response_attribute_extractor<F>(mut self, extractor: F) -> Self
+    where
+        F: Fn(&str, &redis::Value) -> Vec<KeyValue> + Send + Sync + 'static,
+    {
+        self.response_attribute_extractor = Some(SharedResponseExtractor(Arc::new(extractor)));
+        self
+    }
+
+    /// Returns the response attribute extractor, if one was set.
+    pub fn response_attribute_extractor(&self) -> Option<&ResponseAttributeExtractor> {
+        self.response_attribute_extractor
+            .as_ref()
+            .map(|extractor| &*extractor.0)
+    }
+
+    /// Calls `preprocessor` with the attributes and operation name of every command
+    /// before its span is created.
+    ///
+    /// The preprocessor may add, rewrite or remove attributes in place, and returns
+    /// [`SpanDecision::Skip`] to send the command without a span, e.g. for dynamic
+    /// sampling. It runs after [`Self::with_writes_only`] filtering, on the
+    /// operation name as it will appear in `db.operation`. Attributes the command
+    /// span declares are recorded on it; the others are recorded together on a
+    /// `redis.attributes` event under the span. A panicking preprocessor is caught
+    /// and logged, and the span is created with the attributes as they were left.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use opentelemetry::KeyValue;
+    /// use otel_instrumentation_redis::{InstrumentationConfig, SpanDecision};
+    ///
+    /// let config = InstrumentationConfig::new().with_span_preprocessor(|attributes, operation| {
+    ///     if operation == "PING" {
+    ///         return SpanDecision::Skip;
+    ///     }
+    ///     attributes.push(KeyValue::new("app.tenant", "acme"));
+    ///     SpanDecision::Create
+    /// });
+    /// ```
+    pub fn with_span_preprocessor<F>(mut self, preprocessor: F) -> Self
+    where
+        F: Fn(&mut Vec<KeyValue>, &str) -> SpanDecision + Send + Sync + 'static,
+    {
+        self.span_preprocessor = Some(SharedSpanPreprocessor(Arc::new(preprocessor)));
+        self
+    }
+
+    /// Returns the span preprocessor, if one was set.
+    pub fn span_preprocessor(&self) -> Option<&SpanPreprocessor> {
+        self.span_preprocessor
+            .as_ref()
+            .map(|preprocessor| &*preprocessor.0)
+    }
+
+    /// Calls `enricher` with every command and its attributes before its span is created.
+    ///
+    /// The enricher runs before [`Self::with_span_preprocessor`], which sees the
+    /// attributes it added. Attributes the command span declares are recorded on it;
+    /// the others are recorded together on a `redis.attributes` event under the span.
+    /// The enricher is shared by every connection and configuration clone, and a
+    /// panicking enricher is caught and logged like an error hook.
+    pub fn with_span_enricher<E>(mut self, enricher: E) -> Self
+    where
+        E: SpanEnricher + Send + Sync + 'static,
+    {
+        self.span_enricher = Some(SharedSpanEnricher(Arc::new(enricher)));
+        self
+    }
+
+    /// Returns the span enricher, if one was set.
+    pub fn span_enricher(&self) -> Option<&(dyn SpanEnricher + Send + Sync)> {
+        self.span_enricher.as_ref().map(|enricher| &*enricher.0)
+    }
+
+    /// Calls `resolver` with the first key of every command and records the shard it
+    /// returns as `redis.shard`.
+    ///
+    /// For deployments sharding keys across several Redis instances on the client
+    /// side, this shows which shard each command hit. `resolver` should apply the same
+    /// hash as the sharding client. Commands without a key, such as `PING` or `SCAN`,
+    /// get no shard. A panicking resolver is caught and logged like an error hook.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use otel_instrumentation_redis::InstrumentationConfig;
+    ///
+    /// let config = InstrumentationConfig::new()
+    ///     .with_shard_resolver(|key| key.bytes().map(u32::from).sum::<u32>() % 4);
+    /// ```
+    pub fn with_shard_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&str) -> u32 + Send + Sync + 'static,
+    {
+        self.shard_resolver = Some(SharedShardResolver(Arc::new(resolver)));
+        self
+    }
+
+    /// Returns the shard resolver, if one was set.
+    pub fn shard_resolver(&self) -> Option<&ShardResolver> {
+        self.shard_resolver.as_ref().map(|resolver| &*resolver.0)
+    }
+
+    /// Counts keyspace hits and misses of `GET`, `GETEX`, `GETDEL` and `HGET` replies.
+    ///
+    /// A lightweight alternative to scraping `INFO stats` for cache effectiveness,
+    /// read through [`crate::InstrumentedClient::hit_ratio`]. Like the local operation
+    /// counters, the counts are shared by every connection and configuration clone.
+    pub fn with_hit_ratio(mut self, enabled: bool) -> Self {
+        self.hit_counters = enabled.then(|| Arc::new(HitCounters::new()));
+        self
+    }
+
+    /// Returns the keyspace hit counters, if they are enabled.
+    pub fn hit_counters(&self) -> Option<&HitCounters> {
+        self.hit_counters.as_deref()
+    }
+
+    /// Records the running hit ratio as `redis.cache.hit_ratio` on every counted
+    /// lookup. Has no effect unless [`Self::with_hit_ratio`] is enabled.
+    pub fn with_hit_ratio_attribute(mut self, enabled: bool) -> Self {
+        self.hit_ratio_attribute = enabled;
+        self
+    }
+
+    /// Returns `true` if lookups record the running hit ratio on their span.
+    pub fn hit_ratio_attribute(&self) -> bool {
+        self.hit_ratio_attribute
+    }
+
+    /// Records `db.redis.response.size`, the bytes of string data in a successful
+    /// reply, to spot oversized values returned by commands like `GET` or `HGETALL`.
+    ///
+    /// Off by default, since it walks every reply.
+    pub fn with_response_size(mut self, enabled: bool) -> Self {
+        self.response_size = enabled;
+        self
+    }
+
+    /// Returns `true` if reply payload sizes are recorded.
+    pub fn response_size(&self) -> bool {
+        self.response_size
+    }
+
+    /// Records `redis.start_unix_nanos` and `redis.end_unix_nanos`, the wall-clock
+    /// times around a command's execution in nanoseconds since the Unix epoch.
+    ///
+    /// They are read from [`std::time::SystemTime`] independently of the subscriber's
+    /// own timestamps, for exact replay and clock skew analysis. Off by default, since
+    /// it reads the clock twice more per command.
+    pub fn with_unix_timestamps(mut self, enabled: bool) -> Self {
+        self.unix_timestamps = enabled;
+        self
+    }
+
+    /// Returns `true` if commands record their start and end Unix timestamps.
+    pub fn unix_timestamps(&self) -> bool {
+        self.unix_timestamps
+    }
+
+    /// Splits the latency of async commands into `redis.queue_wait_ms`, the time
+    /// from the call until the command is handed to the connection, and
+    /// `redis.server_wait_ms`, the time from then until the reply arrives.
+    ///
+    /// redis-rs doesn't report when a command is written, so the split is
+    /// approximated: the command counts as dispatched once redis-rs's future for it
+    /// has been polled for the first time, by which point a multiplexed connection
+    /// has queued it for its writer task. Waiting for room in that queue, and the
+    /// write itself, therefore count as server wait. Off by default; synchronous
+    /// commands never record it.
+    pub fn with_dispatch_timing(mut self, enabled: bool) -> Self {
+        self.dispatch_timing = enabled;
+        self
+    }
+
+    /// Returns `true` if async commands split their latency into queue and server wait.
+    pub fn dispatch_timing(&self) -> bool {
+        self.dispatch_timing
+    }
+
+    /// Buffers the attributes of a synchronous command and records them on its span
+    /// in one batch once the command completes.
+    ///
+    /// Each attribute recorded directly costs one call into the subscriber; batching
+    /// replaces them with a single call at the price of copying the values. Whether
+    /// that is a win depends on the subscriber, see [`crate::common::DeferredRecorder`]
+    /// for measurements. The batch is recorded even if the command fails or panics.
+    pub fn with_deferred_recording(mut self, enabled: bool) -> Self {
+        self.deferred_recording = enabled;
+        self
+    }
+
+    /// Returns `true` if synchronous commands record their attributes in one batch.
+    pub fn deferred_recording(&self) -> bool {
+        self.deferred_recording
+    }
+
+    /// Records which thread, and on async connections which task, ran each command.
+    ///
+    /// Command spans get `thread.id` and, for named threads, `thread.name`. Async
+    /// commands running inside a tokio task also get `tokio.task.id`; outside a task
+    /// it is omitted. Useful when tracking down contention between callers.
+    pub fn with_execution_context(mut self, enabled: bool) -> Self {
+        self.execution_context = enabled;
+        self
+    }
+
+    /// Returns `true` if command spans record the executing thread and task.
+    pub fn execution_context(&self) -> bool {
+        self.execution_context
+    }
+
+    /// Records a redacted rendering of the command on the span when it fails.
+    ///
+    /// Failed command spans get `redis.error.statement`, e.g. `GET user:1`, and, for
+    /// errors replied by the server, the reply as `redis.error.reply`. Successful
+    /// commands are never rendered. The key goes through the key redactions and every
+    /// other argument is replaced by `?`; commands carrying credentials, such as
+    /// `AUTH` and `HELLO`, have all their arguments replaced.
+    pub fn with_error_statement_capture(mut self, enabled: bool) -> Self {
+        self.error_statement_capture = enabled;
+        self
+    }
+
+    /// Returns `true` if failed commands record their redacted statement.
+    pub fn error_statement_capture(&self) -> bool {
+        self.error_statement_capture
+    }
+
+    /// Reports how well multiplexed connections batch their writes as the
+    /// `redis.batch.efficiency` gauge, estimated over every `window` commands.
+    ///
+    /// The estimate is the number of commands sent per flush, approximated from the
+    /// commands in flight on the connection; see [`crate::aio::BatchEstimator`].
+    /// Clones of a multiplexed connection share one estimate.
+    #[cfg(feature = "metrics")]
+    pub fn with_batch_efficiency(mut self, window: usize) -> Self {
+        self.batch_efficiency = Some(window);
+        self
+    }
+
+    /// Returns the window of the batching estimate, if it is enabled.
+    #[cfg(feature = "metrics")]
+    pub fn batch_efficiency(&self) -> Option<usize> {
+        self.batch_efficiency
+    }
+
+    /// Records transactions aborted by a `WATCH` conflict as
+    /// `redis.transaction.conflict = true` instead of as errors.
+    ///
+    /// By default a nil `EXEC` reply marks the transaction span as failed with
+    /// `error.type = watch_conflict`, so optimistic-lock contention shows up on error
+    /// dashboards. Enable this where retrying on conflict is routine and the aborts
+    /// shouldn't count as errors.
+    pub fn with_watch_conflict_attribute(mut self, enabled: bool) -> Self {
+        self.watch_conflict_attribute = enabled;
+        self
+    }
+
+    /// Returns `true` if `WATCH` conflicts are recorded as an attribute, not an error.
+    pub fn watch_conflict_attribute(&self) -> bool {
+        self.watch_conflict_attribute
+    }
+
+    /// Records `redis.connection.state` on command spans, as `ready`, `connecting` or
+    /// `backoff`.
+    ///
+    /// Meant for async wrappers around a reconnecting connection such as redis-rs's
+    /// `ConnectionManager`, which doesn't expose its reconnect state. The state is
+    /// inferred from the connection-level failures of the preceding commands: none
+    /// since the last success is `ready`, one is `connecting` while the connection is
+    /// replaced, and more mean reconnect attempts are failing and being retried with
+    /// `backoff`. It explains the latency spikes and error bursts of an outage.
+    pub fn with_reconnect_state(mut self, enabled: bool) -> Self {
+        self.reconnect_state = enabled;
+        self
+    }
+
+    /// Returns `true` if the inferred reconnect state is recorded on command spans.
+    pub fn reconnect_state(&self) -> bool {
+        self.reconnect_state
+    }
+
+    /// Replaces command spans with one `redis summary` span per connection and `window`.
+    ///
+    /// For very high-throughput connections where even disabled spans cost too much.
+    /// Commands sent through the connection wrappers create no span; their latencies
+    /// are aggregated by operation and summarized as `redis.summary.<op>.count` and
+    /// `redis.summary.<op>.p99_ms`, with `op` the lowercased operation name, plus
+    /// `redis.summary.window_ms`. Operations past the first
+    /// [`MAX_SUMMARY_OPERATIONS`](crate::common::MAX_SUMMARY_OPERATIONS) seen in the
+    /// process are summarized together as `other`. A window is flushed by the first command after it
+    /// elapsed, and the last one when the connection is dropped. Pipelines and the
+    /// convenience methods' own spans are unaffected.
+    pub fn with_window_aggregation(mut self, window: Duration) -> Self {
+        self.window_aggregation = Some(window);
+        self
+    }
+
+    /// Returns the summary window, if commands are aggregated instead of traced.
+    pub fn window_aggregation(&self) -> Option<Duration> {
+        self.window_aggregation
+    }
+
+    /// Rewrites keys recorded on spans with regex replacements, in order.
+    ///
+    /// Each pattern replaces every match in the key with its replacement string, so
+    /// `(Regex::new(r"\d+")?, "#")` turns `user:12345` into `user:#`. Replacements may
+    /// refer to capture groups as in [`regex::Regex::replace_all`]. Keys no pattern
+    /// matches are recorded unchanged.
+    #[cfg(feature = "regex")]
+    pub fn with_key_redaction_patterns(mut self, patterns: Vec<(regex::Regex, &str)>) -> Self {
+        self.key_redactions = patterns
+            .into_iter()
+            .map(|(pattern, replacement)| (pattern, replacement.to_string()))
+            .collect();
+        self
+    }
+
+    /// Applies the configured key redactions to `key`.
+    ///
+    /// Returns the key unchanged when no redaction applies, or without the `regex`
+    /// feature.
+    pub fn redact_key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        #[cfg(feature = "regex")]
+        {
+            let mut key = Cow::Borrowed(key);
+            for (pattern, replacement) in &self.key_redactions {
+                if let Cow::Owned(redacted) = pattern.replace_all(&key, replacement.as_str()) {
+                    key = Cow::Owned(redacted);
+                }
+            }
+            key
+        }
+        #[cfg(not(feature = "regex"))]
+        Cow::Borrowed(key)
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn warn_malformed(name: &str, value: &str) {
+    tracing::warn!(
+        variable = name,
+        value,
+        "ignoring malformed Redis instrumentation setting"
+    );
+}
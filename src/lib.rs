@@ -17,6 +17,15 @@
 //!
 //! - `sync` (default): Synchronous Redis client instrumentation
 //! - `aio`: Asynchronous Redis client instrumentation  
+//! - `connection-manager`: Instrumented `redis::aio::ConnectionManager`, see
+//!   `InstrumentedClient::get_connection_manager`
+//! - `cluster`: Redis Cluster instrumentation, see `InstrumentedClusterClient`; with
+//!   `sync` for synchronous cluster connections
+//! - `cluster-async`: Asynchronous cluster connections, implies `cluster` and `aio`
+//! - `regex`: Regex-based key redaction, see `InstrumentationConfig::with_key_redaction_patterns`
+//! - `metrics`: OpenTelemetry metrics: the `db.client.operation.duration` histogram and
+//!   `db.client.operations` counter of every command, and the batching estimate enabled with
+//!   `InstrumentationConfig::with_batch_efficiency`
 //!
 //! # Examples
 //!
@@ -96,17 +105,86 @@
 //!
 //! The following attributes are automatically added to spans:
 //!
-//! - `db.system`: Always set to "redis"
+//! - `otel.kind`: Always set to "client" on command spans
+//! - `db.system`: Always set to "redis", unless turned off with
+//!   `with_db_system_attribute(false)`
 //! - `db.operation`: The Redis command name (GET, SET, HGET, etc.)
-//! - `db.redis.database_index`: Database index for SELECT operations
+//! - `db.redis.database_index`: The connection's current database, starting at the one
+//!   in the connection URL and following SELECT; omitted for database 0 unless
+//!   `with_include_db_index_always` is set. A SELECT span records the database it
+//!   switches to
+//! - `db.redis.database_name`: The logical name of the current database, when one is
+//!   configured with `with_db_names`
+//! - `db.redis.key_count`: The keys of multi-key commands such as `DEL` and `MSET`, or
+//!   the members added to or read from a collection by commands such as `SADD`
+//! - `db.redis.numkeys`: The `numkeys` argument of commands such as `EVAL`, `FCALL`
+//!   and `ZUNION`, which also gives their key count
+//! - `redis.expire.unit`, `redis.expire.ttl_ms`, `redis.expire.absolute`: The unit an
+//!   expire-family command (`EXPIRE`, `PEXPIREAT`, `SET ... EX`, ...) gave its expiry
+//!   in, the TTL in milliseconds, and whether it was a Unix time
+//! - `redis.command.fingerprint`: A hash of the command name and the number and kind
+//!   of its arguments, shared by commands that differ only in their keys and values
+//! - `redis.shard`: The shard a client-side sharding resolver maps the first key to,
+//!   when `with_shard_resolver` is set
+//! - `redis.start_unix_nanos`, `redis.end_unix_nanos`: Wall-clock times around the
+//!   command's execution, in nanoseconds since the Unix epoch, when
+//!   `with_unix_timestamps` is enabled
+//! - `redis.queue_wait_ms`, `redis.server_wait_ms`: An async command's latency split
+//!   at the point it was handed to the connection, approximated as the first poll of
+//!   redis-rs's future, when `with_dispatch_timing` is enabled
+//! - `db.redis.response.size`: The bytes of string data in a successful reply, when
+//!   `with_response_size` is enabled
 //! - `error`: Set to true when operations fail
 //! - `error.message`: Error message when operations fail
 //! - `otel.status_code`: "OK" or "ERROR"
 //! - `otel.status_description`: Error description for failures
+//! - `redis.connection_setup`: Set on HELLO, RESET, AUTH and CLIENT SETNAME/SETINFO
+//! - `db.redis.protocol_version`: Protocol version negotiated by HELLO
+//! - `redis.conditional.applied`: Whether a conditional command such as `HSETNX`,
+//!   `SET NX` or `EXPIRE NX` changed anything
+//!
+//! Optional attributes, enabled through [`InstrumentationConfig`]:
+//!
+//! - `redis.latency_bucket`: Latency bucket label such as `1-10ms`
+//! - `redis.command.raw_verb`: The command name exactly as sent, before uppercasing
+//! - `db.statement`: The operation or the whole command, with credentials masked and
+//!   long statements truncated
+//! - `redis.eviction_pressure`: Set on writes when `evicted_keys` grew between the last
+//!   two throttled `INFO stats` samples
+//! - `redis.connection.state`: `ready`, `connecting` or `backoff`, inferred on async
+//!   connections from the connection failures of preceding commands
+//!
+//! When a convenience method can't convert a reply to the requested type, its span
+//! gets `redis.conversion.target_type` and `redis.conversion.actual_type` on a
+//! `redis.conversion` event.
+//!
+//! Every command span also records `redis.idempotent`, whether the command can be
+//! safely resent after an ambiguous failure, from
+//! [`common::is_idempotent`]'s classification.
+//!
+//! Connection-scoped attributes, derived from earlier commands on the same connection:
+//!
+//! - `redis.tracking.enabled`: Set while `CLIENT TRACKING` is on
+//! - `redis.tracking.key_tracked`: Set on reads whose keys the server starts tracking
+//! - `redis.routing`: `replica` after `READONLY`, `primary` by default or after
+//!   `READWRITE`
+//! - `server.address`, `server.port`: The server from the client's connection info, on
+//!   connections created through `InstrumentedClient`; Unix sockets record the path
+//!
+//! Spans of helpers sending several commands (pipelines, transactions, `scan_and`,
+//! `migrate_keys`) record `redis.command_count`, counting every command even when they
+//! share a round trip.
+//!
+//! Async command spans also carry `redis.criticality` (`critical` or `best_effort`)
+//! when issued inside `aio::with_criticality` or through `req_command_critical`.
 //!
 //! Service name attributes are set at the application level through the OpenTelemetry
 //! SDK resource configuration, not by this instrumentation library.
 //!
+//! Spans created directly through OpenTelemetry should use [`tracer`], or a tracer
+//! obtained with [`instrumentation_scope`], so they are attributed to the
+//! `otel-instrumentation-redis` instrumentation scope and this crate's version.
+//!
 //! # Performance Considerations
 //!
 //! This instrumentation adds minimal overhead:
@@ -116,7 +194,17 @@
 //! - Error information is captured without affecting performance of successful operations
 
 pub mod client;
+#[cfg(feature = "cluster")]
+pub mod cluster;
 pub mod common;
+pub mod config;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(any(feature = "sync", feature = "aio"))]
+pub mod pipeline;
+#[cfg(any(feature = "sync", feature = "aio"))]
+pub mod query;
+pub mod scope;
 
 #[cfg(feature = "sync")]
 pub mod sync;
@@ -124,11 +212,35 @@ pub mod sync;
 #[cfg(feature = "aio")]
 pub mod aio;
 
+#[cfg(test)]
+mod test_support;
+
 pub use client::InstrumentedClient;
+#[cfg(feature = "cluster")]
+pub use cluster::InstrumentedClusterClient;
+pub use config::{
+    ConnectionSpanMode, InstrumentationConfig, OperationCase, SpanDecision, SpanEnricher,
+    SpanNameStrategy, StatementCapture,
+};
+#[cfg(any(feature = "sync", feature = "aio"))]
+pub use pipeline::InstrumentedPipeline;
+#[cfg(any(feature = "sync", feature = "aio"))]
+pub use query::InstrumentedQuery;
+pub use scope::{instrumentation_scope, tracer};
 
 /// Re-export commonly used types
 pub mod prelude {
     pub use crate::client::InstrumentedClient;
+    #[cfg(feature = "cluster")]
+    pub use crate::cluster::InstrumentedClusterClient;
+    pub use crate::config::{
+        ConnectionSpanMode, InstrumentationConfig, OperationCase, SpanDecision, SpanEnricher,
+        SpanNameStrategy, StatementCapture,
+    };
+    #[cfg(any(feature = "sync", feature = "aio"))]
+    pub use crate::pipeline::InstrumentedPipeline;
+    #[cfg(any(feature = "sync", feature = "aio"))]
+    pub use crate::query::InstrumentedQuery;
 
     #[cfg(feature = "sync")]
     pub use crate::sync::*;
@@ -335,4 +447,5345 @@ mod tests {
         // We expect this to fail without a Redis server, but the method should exist
         assert!(result.is_err());
     }
+
+    #[cfg(feature = "aio")]
+    #[test]
+    fn test_pubsub_active_channels_shared_between_halves() {
+        use crate::aio::ActiveChannels;
+
+        let sink_side = ActiveChannels::new();
+        let stream_side = sink_side.clone();
+
+        sink_side.add(3);
+        assert_eq!(stream_side.get(), 3);
+
+        sink_side.remove(1);
+        assert_eq!(stream_side.get(), 2);
+
+        // Removing more than are active saturates at zero
+        sink_side.remove(5);
+        assert_eq!(stream_side.get(), 0);
+
+        sink_side.add(2);
+        stream_side.clear();
+        assert_eq!(sink_side.get(), 0);
+    }
+
+    #[cfg(feature = "aio")]
+    #[test]
+    fn test_pubsub_split_wrapper_types() {
+        use crate::aio::{InstrumentedPubSubSink, InstrumentedPubSubStream};
+        use futures_util::Stream;
+
+        fn assert_clone<T: Clone>() {}
+        fn assert_message_stream<T: Stream<Item = redis::Msg>>() {}
+
+        assert_clone::<InstrumentedPubSubSink>();
+        assert_message_stream::<InstrumentedPubSubStream>();
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_pubsub_split_wrapper() {
+        use futures_util::StreamExt;
+
+        fn respond(command: &str) -> Vec<u8> {
+            match command {
+                // The subscription is confirmed, then a message is published to it
+                "SUBSCRIBE" => [
+                    &b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n"[..],
+                    b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n",
+                ]
+                .concat(),
+                "UNSUBSCRIBE" => b"*3\r\n$11\r\nunsubscribe\r\n$4\r\nnews\r\n:0\r\n".to_vec(),
+                _ => b"+OK\r\n".to_vec(),
+            }
+        }
+
+        let client = redis::Client::open(test_support::spawn_scripted_server(respond)).unwrap();
+        let instrumented = InstrumentedClient::new(client);
+        let (mut sink, mut stream) = instrumented.get_async_pubsub_split().await.unwrap();
+
+        let (_guard, capture) = test_support::capture_default();
+        sink.subscribe("news").await.unwrap();
+        let msg = stream.next().await.unwrap();
+        assert_eq!(msg.get_channel_name(), "news");
+        // Subscribing again to the same channel doesn't count it twice
+        sink.subscribe("news").await.unwrap();
+        assert_eq!(sink.active_channels().get(), 1);
+        // Neither does unsubscribing from a channel never subscribed
+        sink.unsubscribe("sports").await.unwrap();
+        assert_eq!(stream.active_channels().get(), 1);
+
+        let pubsub = instrumented.inner().get_async_pubsub().await.unwrap();
+        let stream = sink.reconnect(pubsub).await.unwrap();
+        assert_eq!(stream.active_channels().get(), 1);
+        sink.unsubscribe("news").await.unwrap();
+        assert_eq!(stream.active_channels().get(), 0);
+
+        let spans = capture.spans();
+        let subscribes: Vec<_> = spans
+            .iter()
+            .filter(|span| span.field("otel.name") == Some("redis subscribe"))
+            .collect();
+        assert_eq!(subscribes.len(), 2);
+        assert_eq!(subscribes[0].field("redis.pubsub.channels"), Some("news"));
+        assert_eq!(
+            subscribes[1].field("redis.pubsub.active_channels"),
+            Some("1")
+        );
+        let message = capture.by_otel_name("redis message").unwrap();
+        assert_eq!(message.field("redis.pubsub.channel"), Some("news"));
+        assert_eq!(message.field("redis.pubsub.payload_size"), Some("5"));
+        assert_eq!(message.field("redis.pubsub.active_channels"), Some("1"));
+        let unsubscribes: Vec<_> = spans
+            .iter()
+            .filter(|span| span.field("otel.name") == Some("redis unsubscribe"))
+            .collect();
+        assert_eq!(unsubscribes.len(), 2);
+        assert_eq!(
+            unsubscribes[0].field("redis.pubsub.active_channels"),
+            Some("1")
+        );
+        assert_eq!(
+            unsubscribes[1].field("redis.pubsub.active_channels"),
+            Some("0")
+        );
+        let reconnect = capture.by_otel_name("redis reconnect").unwrap();
+        assert_eq!(
+            reconnect.field("redis.pubsub.resubscribed_channels"),
+            Some("1")
+        );
+        assert_eq!(reconnect.field("otel.status_code"), Some("OK"));
+    }
+
+    #[test]
+    fn test_hash_fields_set_counts_pairs() {
+        use crate::common::hash_fields_set;
+
+        let mut cmd = Cmd::new();
+        cmd.arg("HSET")
+            .arg("user:1")
+            .arg("name")
+            .arg("Alice")
+            .arg("age")
+            .arg(30)
+            .arg("city")
+            .arg("Paris");
+        assert_eq!(hash_fields_set(&cmd), Some(3));
+
+        let mut cmd = Cmd::new();
+        cmd.arg("HSET").arg("user:1").arg("name").arg("Alice");
+        assert_eq!(hash_fields_set(&cmd), Some(1));
+
+        let mut cmd = Cmd::new();
+        cmd.arg("HMSET")
+            .arg("user:1")
+            .arg("a")
+            .arg(1)
+            .arg("b")
+            .arg(2);
+        assert_eq!(hash_fields_set(&cmd), Some(2));
+
+        let mut cmd = Cmd::new();
+        cmd.arg("HSET").arg("user:1");
+        assert_eq!(hash_fields_set(&cmd), None);
+    }
+
+    #[test]
+    fn test_record_hash_set_fields() {
+        use crate::common::record_hash_set_fields;
+        use tracing::field::Empty;
+
+        let mut cmd = Cmd::new();
+        cmd.arg("HSET")
+            .arg("user:1")
+            .arg("name")
+            .arg("Alice")
+            .arg("age")
+            .arg(30);
+
+        let (_, capture) = test_support::capture(|| {
+            let span = tracing::info_span!(
+                "hset",
+                redis.hash.fields_set = Empty,
+                redis.hash.new_fields = Empty
+            );
+            record_hash_set_fields(&span, &cmd, &Ok(redis::Value::Int(1)));
+        });
+
+        let span = capture.by_name("hset").unwrap();
+        assert_eq!(span.field("redis.hash.fields_set"), Some("2"));
+        assert_eq!(span.field("redis.hash.new_fields"), Some("1"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_hset_multiple_records_field_counts() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([Ok(redis::Value::Int(2))]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let new_fields = conn
+            .hset_multiple(
+                "user:1",
+                &[("name", "Alice"), ("age", "30"), ("city", "Paris")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(new_fields, 2);
+
+        let span = capture.by_name("hset_multiple").unwrap();
+        assert_eq!(span.field("redis.hash.fields_set"), Some("3"));
+        assert_eq!(span.field("redis.hash.new_fields"), Some("2"));
+    }
+
+    #[test]
+    fn test_latency_bucket_labels() {
+        use crate::common::latency_bucket;
+        use crate::config::DEFAULT_LATENCY_BUCKETS;
+        use std::time::Duration;
+
+        let buckets = DEFAULT_LATENCY_BUCKETS;
+        assert_eq!(latency_bucket(Duration::from_micros(200), &buckets), "<1ms");
+        assert_eq!(latency_bucket(Duration::from_millis(1), &buckets), "1-10ms");
+        assert_eq!(
+            latency_bucket(Duration::from_millis(42), &buckets),
+            "10-100ms"
+        );
+        assert_eq!(
+            latency_bucket(Duration::from_millis(250), &buckets),
+            ">100ms"
+        );
+
+        let mixed = [Duration::from_micros(500), Duration::from_secs(1)];
+        assert_eq!(latency_bucket(Duration::from_millis(3), &mixed), "500us-1s");
+    }
+
+    #[test]
+    fn test_latency_buckets_configuration() {
+        use std::time::Duration;
+
+        let config = InstrumentationConfig::new();
+        assert!(config.latency_buckets().is_none());
+
+        let config = InstrumentationConfig::new()
+            .with_latency_buckets(&[Duration::from_millis(50), Duration::from_millis(5)]);
+        assert_eq!(
+            config.latency_buckets(),
+            Some(&[Duration::from_millis(5), Duration::from_millis(50)][..])
+        );
+
+        let config = config.with_latency_buckets(&[]);
+        assert!(config.latency_buckets().is_none());
+    }
+
+    #[test]
+    fn test_slow_command_gets_higher_latency_bucket() {
+        use crate::common::record_latency_bucket;
+        use std::time::Duration;
+
+        let config = InstrumentationConfig::new()
+            .with_latency_buckets(&[Duration::from_millis(1), Duration::from_millis(5)]);
+
+        let (_, capture) = test_support::capture(|| {
+            let mut cmd = Cmd::new();
+            cmd.arg("GET").arg("test_key");
+            let (span, _attributes) = create_command_span(&cmd);
+            record_latency_bucket(&span, Duration::from_millis(20), &config);
+        });
+
+        let span = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(span.field("redis.latency_bucket"), Some(">5ms"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_fast_command_gets_lowest_latency_bucket() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use std::sync::Arc;
+
+        let (_guard, capture) = test_support::capture_default();
+        let config = Arc::new(InstrumentationConfig::new().with_default_latency_buckets());
+        let mock = MockAsyncConnection::with_replies([Ok(redis::Value::Okay)]);
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, config);
+
+        conn.set("key", "value").await.unwrap();
+
+        let span = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(span.field("redis.latency_bucket"), Some("<1ms"));
+    }
+
+    #[test]
+    fn test_instrumented_client_shares_config() {
+        let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+        let config = InstrumentationConfig::new().with_default_latency_buckets();
+        let instrumented = InstrumentedClient::with_config(client, config);
+
+        assert_eq!(
+            instrumented.clone().config().latency_buckets(),
+            Some(&config::DEFAULT_LATENCY_BUCKETS[..])
+        );
+    }
+
+    #[test]
+    fn test_get_packed_command_name() {
+        use crate::common::get_packed_command_name;
+
+        let packed = redis::cmd("GET").arg("test_key").get_packed_command();
+        assert_eq!(get_packed_command_name(&packed), Some("GET".to_string()));
+
+        let packed = redis::cmd("hset")
+            .arg("h")
+            .arg("f")
+            .arg("v")
+            .get_packed_command();
+        assert_eq!(get_packed_command_name(&packed), Some("HSET".to_string()));
+
+        // Inline commands and truncated buffers are not recognized
+        assert_eq!(get_packed_command_name(b"PING\r\n"), None);
+        assert_eq!(get_packed_command_name(b"*1\r\n$4\r\nPI"), None);
+        assert_eq!(get_packed_command_name(b""), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_connection_is_connection_like() {
+        fn assert_connection_like<T: redis::ConnectionLike>() {}
+        assert_connection_like::<crate::sync::InstrumentedConnection>();
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_packed_command_span_records_recovered_operation() {
+        use crate::test_support::capture_sync;
+        use redis::{ConnectionLike, Value};
+
+        let packed = redis::cmd("GET").arg("test_key").get_packed_command();
+        let replies = [Ok(Value::BulkString(b"1".to_vec()))];
+        let (_, capture) = capture_sync(InstrumentationConfig::new(), replies, |conn| {
+            ConnectionLike::req_packed_command(conn, &packed).unwrap();
+        });
+
+        let span = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(span.field("db.operation"), Some("GET"));
+    }
+
+    #[test]
+    fn test_connection_state_tracks_client_tracking() {
+        use crate::common::ConnectionState;
+
+        let state = ConnectionState::new();
+        assert!(!state.tracking_enabled());
+
+        state.observe_command(redis::cmd("CLIENT").arg("TRACKING").arg("on"));
+        assert!(state.tracking_enabled());
+
+        // Unrelated commands leave the state untouched
+        state.observe_command(redis::cmd("GET").arg("key"));
+        state.observe_command(redis::cmd("CLIENT").arg("SETNAME").arg("app"));
+        assert!(state.tracking_enabled());
+
+        state.observe_command(redis::cmd("CLIENT").arg("TRACKING").arg("OFF"));
+        assert!(!state.tracking_enabled());
+    }
+
+    #[test]
+    fn test_classify_command() {
+        use crate::common::{classify_command, CommandKind};
+
+        assert_eq!(classify_command("GET"), CommandKind::Read);
+        assert_eq!(classify_command("HGETALL"), CommandKind::Read);
+        assert_eq!(classify_command("SET"), CommandKind::Write);
+        assert_eq!(classify_command("DEL"), CommandKind::Write);
+        assert_eq!(classify_command("PING"), CommandKind::Other);
+        assert_eq!(classify_command("SELECT"), CommandKind::Other);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_tracking_state_recorded_on_spans() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(redis::Value::Okay),
+            Ok(redis::Value::Nil),
+            Ok(redis::Value::Okay),
+            Ok(redis::Value::Nil),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        conn.req_command(redis::cmd("CLIENT").arg("TRACKING").arg("ON"))
+            .await
+            .unwrap();
+        assert!(conn.state().tracking_enabled());
+        let _: Option<String> = conn.get("tracked").await.unwrap();
+
+        conn.req_command(redis::cmd("CLIENT").arg("TRACKING").arg("OFF"))
+            .await
+            .unwrap();
+        assert!(!conn.state().tracking_enabled());
+        let _: Option<String> = conn.get("untracked").await.unwrap();
+
+        let gets: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.field("otel.name") == Some("redis get"))
+            .collect();
+        assert_eq!(gets.len(), 2);
+        assert_eq!(gets[0].field("redis.tracking.enabled"), Some("true"));
+        assert_eq!(gets[0].field("redis.tracking.key_tracked"), Some("true"));
+        assert_eq!(gets[1].field("redis.tracking.enabled"), None);
+        assert_eq!(gets[1].field("redis.tracking.key_tracked"), None);
+    }
+
+    #[test]
+    fn test_raw_verb_capture() {
+        use crate::common::{
+            create_command_span_with_config, extract_command_attributes_with_config,
+        };
+
+        let mut cmd = Cmd::new();
+        cmd.arg("get").arg("test_key");
+
+        // Off by default
+        let attributes =
+            extract_command_attributes_with_config(&cmd, &InstrumentationConfig::new());
+        assert!(!attributes
+            .iter()
+            .any(|attr| attr.key.as_str() == "redis.command.raw_verb"));
+
+        let config = InstrumentationConfig::new().with_raw_verb_capture(true);
+        let attributes = extract_command_attributes_with_config(&cmd, &config);
+        let value_of = |key: &str| {
+            attributes
+                .iter()
+                .find(|attr| attr.key.as_str() == key)
+                .map(|attr| attr.value.as_str().into_owned())
+        };
+        assert_eq!(
+            value_of(opentelemetry_semantic_conventions::attribute::DB_OPERATION_NAME).as_deref(),
+            Some("GET")
+        );
+        assert_eq!(value_of("redis.command.raw_verb").as_deref(), Some("get"));
+
+        let (_, capture) = test_support::capture(|| {
+            let (span, attributes) = create_command_span_with_config(&cmd, &config);
+            common::apply_span_attributes(&span, &attributes);
+        });
+        let span = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(span.field("db.operation"), Some("GET"));
+        assert_eq!(span.field("redis.command.raw_verb"), Some("get"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_instrument_redis_operation_records_error() {
+        use crate::aio::{instrument_redis_operation, instrument_redis_result};
+
+        let (_guard, capture) = test_support::capture_default();
+
+        let result: redis::RedisResult<()> = instrument_redis_result("lock_acquire", async {
+            Err(redis::RedisError::from((
+                redis::ErrorKind::ResponseError,
+                "lock held",
+            )))
+        })
+        .await;
+        assert!(result.is_err());
+
+        let ok: redis::RedisResult<i64> =
+            instrument_redis_result("lock_release", async { Ok(1) }).await;
+        assert_eq!(ok.unwrap(), 1);
+
+        // An output of any type, whose Redis error the closure picks out
+        let nested: Result<i64, (&str, Option<redis::RedisError>)> = instrument_redis_operation(
+            "lock_extend",
+            async {
+                Err((
+                    "extend failed",
+                    Some(redis::RedisError::from((
+                        redis::ErrorKind::TypeError,
+                        "not a lock",
+                    ))),
+                ))
+            },
+            |result| result.as_ref().err().and_then(|(_, err)| err.as_ref()),
+        )
+        .await;
+        assert!(nested.is_err());
+        instrument_redis_operation("lock_noop", async {}, |_| None).await;
+
+        let failed = capture.by_otel_name("redis lock_acquire").unwrap();
+        assert_eq!(failed.field("db.system"), Some("redis"));
+        assert_eq!(failed.field("otel.status_code"), Some("ERROR"));
+        assert_eq!(failed.field("error.type"), Some("response_error"));
+
+        let succeeded = capture.by_otel_name("redis lock_release").unwrap();
+        assert_eq!(succeeded.field("otel.status_code"), Some("OK"));
+
+        let extended = capture.by_otel_name("redis lock_extend").unwrap();
+        assert_eq!(extended.field("otel.status_code"), Some("ERROR"));
+        assert_eq!(extended.field("error.type"), Some("type_error"));
+        let noop = capture.by_otel_name("redis lock_noop").unwrap();
+        assert_eq!(noop.field("otel.status_code"), Some("OK"));
+    }
+
+    #[test]
+    fn test_operation_case() {
+        use crate::common::create_command_span_with_config;
+
+        let mut cmd = Cmd::new();
+        cmd.arg("Get").arg("test_key");
+
+        let cases = [
+            (OperationCase::Upper, "GET", "redis get"),
+            (OperationCase::Lower, "get", "redis get"),
+            (OperationCase::Preserve, "Get", "redis Get"),
+        ];
+
+        for (case, expected_operation, expected_span_name) in cases {
+            let config = InstrumentationConfig::new().with_operation_case(case);
+            let (attributes, capture) =
+                test_support::capture(|| create_command_span_with_config(&cmd, &config).1);
+
+            let operation = attributes
+                .iter()
+                .find(|attr| {
+                    attr.key.as_str()
+                        == opentelemetry_semantic_conventions::attribute::DB_OPERATION_NAME
+                })
+                .map(|attr| attr.value.as_str().into_owned());
+            assert_eq!(operation.as_deref(), Some(expected_operation));
+
+            let span = capture.by_otel_name(expected_span_name).unwrap();
+            assert_eq!(span.field("db.operation"), Some(expected_operation));
+        }
+
+        assert_eq!(
+            InstrumentationConfig::default().operation_case(),
+            OperationCase::Upper
+        );
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_operation_case_on_convenience_method_spans() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let config = InstrumentationConfig::new().with_operation_case(OperationCase::Lower);
+        let replies = [Ok(Value::BulkString(b"1".to_vec()))];
+        let (_, capture) = capture_sync(config, replies, |conn| {
+            let _: String = conn.get("key").unwrap();
+        });
+
+        let method = capture.by_name("get").unwrap();
+        assert_eq!(method.field("db.operation"), Some("get"));
+        let command = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(command.field("db.operation"), Some("get"));
+    }
+
+    #[test]
+    fn test_connection_setup_commands() {
+        use crate::common::is_connection_setup_command;
+
+        assert!(is_connection_setup_command(redis::cmd("HELLO").arg(3)));
+        assert!(is_connection_setup_command(&redis::cmd("RESET")));
+        assert!(is_connection_setup_command(
+            redis::cmd("auth").arg("secret")
+        ));
+        assert!(is_connection_setup_command(
+            redis::cmd("CLIENT").arg("SETNAME").arg("app")
+        ));
+        assert!(!is_connection_setup_command(
+            redis::cmd("CLIENT").arg("TRACKING").arg("ON")
+        ));
+        assert!(!is_connection_setup_command(redis::cmd("GET").arg("key")));
+    }
+
+    #[test]
+    fn test_hello_protocol_version() {
+        use crate::common::hello_protocol_version;
+        use redis::Value;
+
+        let resp3 = Value::Map(vec![
+            (
+                Value::BulkString(b"server".to_vec()),
+                Value::BulkString(b"redis".to_vec()),
+            ),
+            (Value::BulkString(b"proto".to_vec()), Value::Int(3)),
+        ]);
+        assert_eq!(hello_protocol_version(&resp3), Some(3));
+
+        let resp2 = Value::Array(vec![
+            Value::BulkString(b"server".to_vec()),
+            Value::BulkString(b"redis".to_vec()),
+            Value::BulkString(b"proto".to_vec()),
+            Value::Int(2),
+        ]);
+        assert_eq!(hello_protocol_version(&resp2), Some(2));
+
+        assert_eq!(hello_protocol_version(&Value::Okay), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_hello_records_setup_without_credentials() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let reply = Value::Map(vec![(Value::SimpleString("proto".into()), Value::Int(3))]);
+        let mock = MockAsyncConnection::with_replies([Ok(reply)]);
+        let config = std::sync::Arc::new(InstrumentationConfig::new().with_raw_verb_capture(true));
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, config);
+
+        conn.req_command(
+            redis::cmd("HELLO")
+                .arg(3)
+                .arg("AUTH")
+                .arg("default")
+                .arg("hunter2"),
+        )
+        .await
+        .unwrap();
+
+        let span = capture.by_otel_name("redis hello").unwrap();
+        assert_eq!(span.field("redis.connection_setup"), Some("true"));
+        assert_eq!(span.field("db.redis.protocol_version"), Some("3"));
+        assert!(span.fields.values().all(|value| !value.contains("hunter2")));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_scan_and_empty_scan() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let reply = Value::Array(vec![Value::BulkString(b"0".to_vec()), Value::Array(vec![])]);
+        let mut conn =
+            InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies([Ok(reply)]));
+
+        let processed = conn
+            .scan_and("session:*", |_key| async { Ok::<_, redis::RedisError>(()) })
+            .await
+            .unwrap();
+        assert_eq!(processed, 0);
+        assert_eq!(
+            conn.inner().commands,
+            vec![redis::cmd("SCAN")
+                .arg(0)
+                .arg("MATCH")
+                .arg("session:*")
+                .get_packed_command()]
+        );
+
+        let scan = capture.by_name("redis_scan").unwrap();
+        assert_eq!(scan.field("otel.name"), Some("redis scan"));
+        assert_eq!(scan.field("redis.scan.processed"), Some("0"));
+        assert_eq!(scan.field("redis.command_count"), Some("1"));
+        assert_eq!(scan.field("otel.status_code"), Some("OK"));
+        let command = capture.by_name("redis_command").unwrap();
+        assert_eq!(command.parent, Some(scan.id));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_scan_and_error_policy() {
+        use crate::aio::{InstrumentedAsyncConnection, ScanErrorPolicy};
+        use crate::test_support::MockAsyncConnection;
+        use redis::{ErrorKind, RedisError, Value};
+
+        let batch = || {
+            Ok(Value::Array(vec![
+                Value::BulkString(b"0".to_vec()),
+                Value::Array(vec![
+                    Value::BulkString(b"bad".to_vec()),
+                    Value::BulkString(b"good".to_vec()),
+                ]),
+            ]))
+        };
+        let apply = |key: String| async move {
+            if key == "bad" {
+                Err(RedisError::from((ErrorKind::TypeError, "wrong type")))
+            } else {
+                Ok(())
+            }
+        };
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn =
+            InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies([batch()]));
+        assert_eq!(conn.scan_and("*", apply).await.unwrap(), 2);
+        let scan = capture.by_name("redis_scan").unwrap();
+        assert_eq!(scan.field("redis.scan.processed"), Some("2"));
+        assert_eq!(scan.field("redis.scan.failed"), Some("1"));
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn =
+            InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies([batch()]));
+        let result = conn
+            .scan_and_with_policy("*", ScanErrorPolicy::Abort, apply)
+            .await;
+        assert!(result.is_err());
+        let scan = capture.by_name("redis_scan").unwrap();
+        assert_eq!(scan.field("redis.scan.processed"), Some("1"));
+        assert_eq!(scan.field("otel.status_code"), Some("ERROR"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_criticality_recorded_on_command_span() {
+        use crate::aio::{with_criticality, Criticality, InstrumentedAsyncConnection};
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::BulkString(b"Alice".to_vec())),
+            Ok(Value::Okay),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let name: String = with_criticality(Criticality::Critical, conn.get("user:1:name"))
+            .await
+            .unwrap();
+        assert_eq!(name, "Alice");
+        conn.req_command_critical(redis::cmd("SET").arg("k").arg("v"), Criticality::BestEffort)
+            .await
+            .unwrap();
+
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("redis.criticality"), Some("critical"));
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("redis.criticality"), Some("best_effort"));
+    }
+
+    #[test]
+    fn test_eviction_sampler_throttles_samples() {
+        use crate::common::{parse_evicted_keys, EvictionSampler};
+        use std::time::{Duration, Instant};
+
+        let sampler = EvictionSampler::default();
+        let interval = Duration::from_secs(10);
+        let start = Instant::now();
+
+        assert!(sampler.claim(start, interval));
+        sampler.record(4);
+        assert_eq!(sampler.pressure(), None);
+
+        assert!(!sampler.claim(start + Duration::from_secs(5), interval));
+        assert!(sampler.claim(start + Duration::from_secs(10), interval));
+        sampler.record(9);
+        assert_eq!(sampler.pressure(), Some(true));
+
+        assert!(sampler.claim(start + Duration::from_secs(20), interval));
+        sampler.record(9);
+        assert_eq!(sampler.pressure(), Some(false));
+
+        let info = "# Stats\r\nexpired_keys:2\r\nevicted_keys:17\r\nkeyspace_hits:3\r\n";
+        assert_eq!(parse_evicted_keys(info), Some(17));
+        assert_eq!(parse_evicted_keys("# Stats\r\n"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_eviction_pressure_on_write_spans() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+        use std::time::Duration;
+
+        let (_guard, capture) = test_support::capture_default();
+        let info = |evicted: u64| {
+            Ok(Value::BulkString(
+                format!("evicted_keys:{evicted}\r\n").into_bytes(),
+            ))
+        };
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            info(1),
+            Ok(Value::Nil),
+            Ok(Value::Int(1)),
+            info(3),
+        ]);
+        let config = InstrumentationConfig::new().with_eviction_sampling(Duration::ZERO);
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        conn.req_command(redis::cmd("SET").arg("a").arg(1))
+            .await
+            .unwrap();
+        conn.req_command(redis::cmd("GET").arg("a")).await.unwrap();
+        conn.req_command(redis::cmd("DEL").arg("a")).await.unwrap();
+
+        // The GET isn't a write, so it neither samples nor reports pressure
+        assert_eq!(conn.inner().commands.len(), 5);
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("redis.eviction_pressure"), None);
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("redis.eviction_pressure"), None);
+        let del = capture.by_otel_name("redis del").unwrap();
+        assert_eq!(del.field("redis.eviction_pressure"), Some("true"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_eviction_sampling_skipped_inside_multi() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+        use std::time::Duration;
+
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::Array(vec![Value::Okay])),
+        ]);
+        let config = InstrumentationConfig::new().with_eviction_sampling(Duration::ZERO);
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        conn.req_command(&redis::cmd("MULTI")).await.unwrap();
+        assert!(conn.state().in_transaction());
+        conn.req_command(redis::cmd("SET").arg("a").arg(1))
+            .await
+            .unwrap();
+        let exec = conn.req_command(&redis::cmd("EXEC")).await.unwrap();
+        assert!(!conn.state().in_transaction());
+
+        // INFO would have been queued into the transaction and replied to by EXEC
+        assert_eq!(exec, Value::Array(vec![Value::Okay]));
+        assert_eq!(conn.inner().commands.len(), 3);
+    }
+
+    #[test]
+    fn test_connection_state_follows_select() {
+        use crate::common::ConnectionState;
+
+        let state = ConnectionState::with_database_index(2);
+        assert_eq!(state.database_index(), 2);
+
+        state.observe_command(redis::cmd("SELECT").arg(5));
+        assert_eq!(state.database_index(), 5);
+
+        state.observe_command(redis::cmd("select").arg("not-a-number"));
+        assert_eq!(state.database_index(), 5);
+    }
+
+    #[test]
+    fn test_connection_state_applies_queued_select_on_exec() {
+        use crate::common::ConnectionState;
+        use redis::Value;
+
+        let state = ConnectionState::with_database_index(2);
+        let queued = Ok(Value::SimpleString("QUEUED".into()));
+        let transaction = |select: i64, exec: redis::RedisResult<Value>| {
+            state.observe_transaction(&redis::cmd("MULTI"), &Ok(Value::Okay));
+            let select = redis::cmd("SELECT").arg(select).clone();
+            state.observe_command(&select);
+            state.observe_transaction(&select, &queued);
+            // QUEUED only means the SELECT will run with the transaction
+            assert_eq!(state.database_index(), 2);
+            state.observe_transaction(&redis::cmd("EXEC"), &exec);
+        };
+
+        // Aborted by a WATCH conflict
+        transaction(5, Ok(Value::Nil));
+        assert!(!state.in_transaction());
+        assert_eq!(state.database_index(), 2);
+
+        transaction(5, Ok(Value::Array(vec![Value::Okay])));
+        assert!(!state.in_transaction());
+        assert_eq!(state.database_index(), 5);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_include_db_index_always() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+
+        let (_guard, capture) = test_support::capture_default();
+        let config = InstrumentationConfig::new().with_include_db_index_always(true);
+        let mut conn = InstrumentedAsyncConnection::with_config(
+            MockAsyncConnection::default(),
+            std::sync::Arc::new(config),
+        );
+        conn.req_command(redis::cmd("GET").arg("key"))
+            .await
+            .unwrap();
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.redis.database_index"), Some("0"));
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::new(MockAsyncConnection::default());
+        conn.req_command(redis::cmd("GET").arg("key"))
+            .await
+            .unwrap();
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.redis.database_index"), None);
+
+        conn.req_command(redis::cmd("SELECT").arg(3)).await.unwrap();
+        conn.req_command(redis::cmd("SET").arg("key").arg(1))
+            .await
+            .unwrap();
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("db.redis.database_index"), Some("3"));
+    }
+
+    #[test]
+    fn test_instrumentation_scope_matches_package() {
+        let scope = crate::instrumentation_scope();
+        assert_eq!(scope.name(), "otel-instrumentation-redis");
+        assert_eq!(scope.name(), env!("CARGO_PKG_NAME"));
+        assert_eq!(scope.version(), Some(env!("CARGO_PKG_VERSION")));
+        assert_eq!(
+            scope.schema_url(),
+            Some(opentelemetry_semantic_conventions::SCHEMA_URL)
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_req_command_raw_uses_provided_data() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use opentelemetry::KeyValue;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([Ok(Value::Int(7))]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let attributes = [KeyValue::new("db.redis.database_index", 4_i64)];
+        let reply = conn
+            .req_command_raw(redis::cmd("INCR").arg("hits"), "CACHE_HIT", &attributes)
+            .await
+            .unwrap();
+        assert_eq!(reply, Value::Int(7));
+
+        let span = capture.by_name("redis_command").unwrap();
+        assert_eq!(span.field("otel.name"), Some("redis cache_hit"));
+        assert_eq!(span.field("db.operation"), Some("CACHE_HIT"));
+        assert_eq!(span.field("db.redis.database_index"), Some("4"));
+    }
+
+    #[test]
+    fn test_instrumentation_config_from_env() {
+        use crate::config::{
+            ENV_CAPTURE_RAW_VERB, ENV_EVICTION_SAMPLING_MS, ENV_INCLUDE_DB_INDEX_ALWAYS,
+            ENV_LATENCY_BUCKETS_MS, ENV_OPERATION_CASE,
+        };
+        use std::time::Duration;
+
+        // Only this test reads these variables, so setting them can't race other tests
+        std::env::set_var(ENV_LATENCY_BUCKETS_MS, "50, 5");
+        std::env::set_var(ENV_CAPTURE_RAW_VERB, "yes");
+        std::env::set_var(ENV_OPERATION_CASE, "Preserve");
+        std::env::set_var(ENV_EVICTION_SAMPLING_MS, "250");
+        std::env::set_var(ENV_INCLUDE_DB_INDEX_ALWAYS, "1");
+
+        let config = InstrumentationConfig::from_env();
+        assert_eq!(
+            config.latency_buckets(),
+            Some(&[Duration::from_millis(5), Duration::from_millis(50)][..])
+        );
+        assert!(config.raw_verb_capture());
+        assert_eq!(config.operation_case(), OperationCase::Preserve);
+        assert_eq!(config.eviction_sampling(), Some(Duration::from_millis(250)));
+        assert!(config.include_db_index_always());
+
+        // Explicit builder calls win over the environment
+        let config = InstrumentationConfig::from_env()
+            .with_raw_verb_capture(false)
+            .with_operation_case(OperationCase::Lower);
+        assert!(!config.raw_verb_capture());
+        assert_eq!(config.operation_case(), OperationCase::Lower);
+
+        // Malformed values fall back to the defaults
+        std::env::set_var(ENV_LATENCY_BUCKETS_MS, "1,ten");
+        std::env::set_var(ENV_CAPTURE_RAW_VERB, "maybe");
+        std::env::set_var(ENV_OPERATION_CASE, "title");
+        std::env::set_var(ENV_EVICTION_SAMPLING_MS, "-5");
+        std::env::set_var(ENV_INCLUDE_DB_INDEX_ALWAYS, "");
+
+        let config = InstrumentationConfig::from_env();
+        assert_eq!(config.latency_buckets(), None);
+        assert!(!config.raw_verb_capture());
+        assert_eq!(config.operation_case(), OperationCase::Upper);
+        assert_eq!(config.eviction_sampling(), None);
+        assert!(!config.include_db_index_always());
+
+        for name in [
+            ENV_LATENCY_BUCKETS_MS,
+            ENV_CAPTURE_RAW_VERB,
+            ENV_OPERATION_CASE,
+            ENV_EVICTION_SAMPLING_MS,
+            ENV_INCLUDE_DB_INDEX_ALWAYS,
+        ] {
+            std::env::remove_var(name);
+        }
+    }
+
+    #[test]
+    fn test_score_bounds_parsing() {
+        use crate::common::{score_bounds, ScoreBound};
+
+        let (min, max) = score_bounds(
+            redis::cmd("ZRANGEBYSCORE")
+                .arg("board")
+                .arg("(1")
+                .arg("+inf"),
+        )
+        .unwrap();
+        assert_eq!(
+            min,
+            ScoreBound {
+                value: 1.0,
+                exclusive: true
+            }
+        );
+        assert_eq!(
+            max,
+            ScoreBound {
+                value: f64::INFINITY,
+                exclusive: false
+            }
+        );
+
+        let (min, max) = score_bounds(
+            redis::cmd("ZREVRANGEBYSCORE")
+                .arg("board")
+                .arg("(+inf")
+                .arg("-inf"),
+        )
+        .unwrap();
+        assert_eq!(
+            min,
+            ScoreBound {
+                value: f64::NEG_INFINITY,
+                exclusive: false
+            }
+        );
+        assert_eq!(
+            max,
+            ScoreBound {
+                value: f64::INFINITY,
+                exclusive: true
+            }
+        );
+
+        assert_eq!(
+            score_bounds(redis::cmd("ZRANGEBYSCORE").arg("board").arg("low").arg(5)),
+            None
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_zset_score_attributes() {
+        use crate::aio::{InstrumentedAsyncConnection, ZAddFlag};
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([Ok(Value::Array(vec![])), Ok(Value::Int(0))]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let members: Vec<String> = conn.zrangebyscore("board", "(1", "+inf").await.unwrap();
+        assert!(members.is_empty());
+        conn.zadd("board", "alice", 42, &[ZAddFlag::Xx, ZAddFlag::Gt])
+            .await
+            .unwrap();
+        assert_eq!(
+            conn.inner().commands[1],
+            redis::cmd("ZADD")
+                .arg("board")
+                .arg("XX")
+                .arg("GT")
+                .arg(42)
+                .arg("alice")
+                .get_packed_command()
+        );
+
+        let range = capture.by_name("zrangebyscore").unwrap();
+        assert_eq!(range.field("redis.zset.min"), Some("1.0"));
+        assert_eq!(range.field("redis.zset.min_exclusive"), Some("true"));
+        assert_eq!(range.field("redis.zset.max"), Some("inf"));
+        assert_eq!(range.field("redis.zset.max_exclusive"), Some("false"));
+        let zadd = capture.by_name("zadd").unwrap();
+        assert_eq!(zadd.field("redis.zset.flags"), Some("XX GT"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_connections_created_counts_failed_acquisitions_as_zero() {
+        // Nothing listens on port 1, so both acquisitions fail
+        let client = redis::Client::open("redis://127.0.0.1:1/").unwrap();
+        let instrumented = InstrumentedClient::new(client);
+        let clone = instrumented.clone();
+
+        assert!(instrumented.get_connection().is_err());
+        assert!(clone.get_connection().is_err());
+        assert_eq!(instrumented.connections_created(), 0);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_connections_created_counts_successful_acquisitions() {
+        let client = redis::Client::open(test_support::spawn_ok_server()).unwrap();
+        let instrumented = InstrumentedClient::new(client);
+        let clone = instrumented.clone();
+
+        let ((), capture) = test_support::capture(|| {
+            let _first = instrumented.get_connection().unwrap();
+            let _second = clone.get_connection().unwrap();
+        });
+        assert_eq!(instrumented.connections_created(), 2);
+
+        let acquires: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "get_connection")
+            .collect();
+        assert_eq!(acquires.len(), 2);
+        assert_eq!(
+            acquires[1].field("redis.client.connections_created"),
+            Some("2")
+        );
+        assert_eq!(acquires[1].field("redis.connection.reused"), Some("false"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_set_sampling_and_move_commands() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::common::{classify_command, CommandKind};
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        assert_eq!(classify_command("SMOVE"), CommandKind::Write);
+        assert_eq!(classify_command("SPOP"), CommandKind::Write);
+        assert_eq!(classify_command("SRANDMEMBER"), CommandKind::Read);
+
+        let (_guard, capture) = test_support::capture_default();
+        let members = Value::Array(vec![Value::BulkString(b"a".to_vec())]);
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Int(1)),
+            Ok(members.clone()),
+            Ok(members.clone()),
+            Ok(Value::BulkString(b"a".to_vec())),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        assert!(conn.smove("pending", "done", "job:1").await.unwrap());
+        let _: Vec<String> = conn.spop("pending", Some(2)).await.unwrap();
+        let _: Vec<String> = conn.srandmember("pending", Some(-3)).await.unwrap();
+        let _: Option<String> = conn.srandmember("pending", None).await.unwrap();
+
+        for operation in ["smove", "spop", "srandmember"] {
+            assert!(capture
+                .by_otel_name(&format!("redis {operation}"))
+                .is_some());
+        }
+        let smove = capture.by_name("smove").unwrap();
+        assert_eq!(smove.field("redis.smove.source"), Some("pending"));
+        assert_eq!(smove.field("redis.smove.destination"), Some("done"));
+        let spop = capture.by_name("spop").unwrap();
+        assert_eq!(spop.field("redis.spop.count"), Some("2"));
+
+        let samples: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "srandmember")
+            .collect();
+        assert_eq!(samples[0].field("redis.srandmember.count"), Some("-3"));
+        assert_eq!(samples[0].field("redis.set.allow_duplicates"), Some("true"));
+        assert_eq!(samples[1].field("redis.srandmember.count"), None);
+        assert_eq!(samples[1].field("redis.set.allow_duplicates"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_pipeline_command_count() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::new(MockAsyncConnection::default());
+
+        // A batch get of three keys is one pipeline round trip but three commands
+        let mut batch = redis::pipe();
+        batch.get("a").get("b").get("c");
+        conn.execute_pipeline(&batch).await.unwrap();
+        let pipeline = capture.by_name("redis_pipeline").unwrap();
+        assert_eq!(pipeline.field("redis.command_count"), Some("3"));
+
+        let mut transaction = redis::pipe();
+        transaction.atomic().set("a", 1).incr("a", 1);
+        assert_eq!(crate::common::pipeline_command_count(&transaction), 4);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_database_name_follows_select() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use std::collections::HashMap;
+
+        let (_guard, capture) = test_support::capture_default();
+        let config = InstrumentationConfig::new().with_db_names(HashMap::from([(1, "cache")]));
+        let mut conn = InstrumentedAsyncConnection::with_config(
+            MockAsyncConnection::default(),
+            std::sync::Arc::new(config),
+        );
+
+        conn.req_command(redis::cmd("GET").arg("key"))
+            .await
+            .unwrap();
+        conn.req_command(redis::cmd("SELECT").arg(1)).await.unwrap();
+        conn.req_command(redis::cmd("SET").arg("key").arg(1))
+            .await
+            .unwrap();
+
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.redis.database_name"), None);
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("db.redis.database_index"), Some("1"));
+        assert_eq!(set.field("db.redis.database_name"), Some("cache"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_touch_and_unlink_key_counts() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::common::{classify_command, CommandKind};
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        assert_eq!(classify_command("TOUCH"), CommandKind::Read);
+        assert_eq!(classify_command("UNLINK"), CommandKind::Write);
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([Ok(Value::Int(2)), Ok(Value::Int(1))]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        assert_eq!(conn.touch(&["a", "b", "c"]).await.unwrap(), 2);
+        assert_eq!(conn.unlink("a").await.unwrap(), 1);
+
+        assert!(capture.by_otel_name("redis touch").is_some());
+        assert!(capture.by_otel_name("redis unlink").is_some());
+        let touch = capture.by_name("touch").unwrap();
+        assert_eq!(touch.field("redis.keys.count"), Some("3"));
+        assert_eq!(touch.field("redis.keys.affected"), Some("2"));
+        let unlink = capture.by_name("unlink").unwrap();
+        assert_eq!(unlink.field("redis.keys.count"), Some("1"));
+        assert_eq!(unlink.field("redis.keys.affected"), Some("1"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_pipeline_result_events_capped() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+
+        let (_guard, capture) = test_support::capture_default();
+        let config = InstrumentationConfig::new().with_pipeline_result_events(2);
+        let mut conn = InstrumentedAsyncConnection::with_config(
+            MockAsyncConnection::default(),
+            std::sync::Arc::new(config),
+        );
+
+        let mut pipeline = redis::pipe();
+        pipeline.get("a").get("b");
+        conn.execute_pipeline(&pipeline).await.unwrap();
+        pipeline.get("c");
+        conn.execute_pipeline(&pipeline).await.unwrap();
+
+        let spans: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_pipeline")
+            .collect();
+        assert_eq!(spans[0].events.len(), 2);
+        assert_eq!(spans[0].field("redis.pipeline.events_truncated"), None);
+
+        assert_eq!(spans[1].events.len(), 2);
+        for (index, event) in spans[1].events.iter().enumerate() {
+            assert_eq!(event["redis.pipeline.index"], index.to_string());
+            assert_eq!(event["redis.pipeline.status"], "OK");
+        }
+        assert_eq!(
+            spans[1].field("redis.pipeline.events_truncated"),
+            Some("true")
+        );
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_local_operation_counters() {
+        let client = redis::Client::open(test_support::spawn_ok_server()).unwrap();
+        let config = InstrumentationConfig::new().with_local_counters(true);
+        let instrumented = InstrumentedClient::with_config(client, config);
+
+        let mut first = instrumented.get_connection().unwrap();
+        let mut second = instrumented.get_connection().unwrap();
+        first.req_command(redis::cmd("GET").arg("a")).unwrap();
+        second.req_command(redis::cmd("get").arg("b")).unwrap();
+        first
+            .req_command(redis::cmd("SET").arg("a").arg(1))
+            .unwrap();
+
+        let counts = instrumented.operation_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["GET"], 2);
+        assert_eq!(counts["SET"], 1);
+
+        let disabled = InstrumentedClient::new(redis::Client::open("redis://127.0.0.1/").unwrap());
+        assert!(disabled.operation_counts().is_empty());
+    }
+
+    #[test]
+    fn test_conditional_applied() {
+        use crate::common::conditional_applied;
+        use redis::Value;
+
+        let hsetnx = redis::cmd("HSETNX").arg("h").arg("f").arg("v").clone();
+        assert_eq!(conditional_applied(&hsetnx, &Value::Int(0)), Some(false));
+        assert_eq!(conditional_applied(&hsetnx, &Value::Int(1)), Some(true));
+
+        let expire_nx = redis::cmd("EXPIRE").arg("k").arg(60).arg("nx").clone();
+        assert_eq!(conditional_applied(&expire_nx, &Value::Int(0)), Some(false));
+
+        let set_nx = redis::cmd("SET")
+            .arg("k")
+            .arg("v")
+            .arg("NX")
+            .arg("EX")
+            .arg(5)
+            .clone();
+        assert_eq!(conditional_applied(&set_nx, &Value::Nil), Some(false));
+        assert_eq!(conditional_applied(&set_nx, &Value::Okay), Some(true));
+        let set_get = redis::cmd("SET")
+            .arg("k")
+            .arg("v")
+            .arg("NX")
+            .arg("GET")
+            .clone();
+        assert_eq!(conditional_applied(&set_get, &Value::Nil), None);
+
+        let zadd_nx = redis::cmd("ZADD")
+            .arg("z")
+            .arg("NX")
+            .arg(1)
+            .arg("m")
+            .clone();
+        assert_eq!(conditional_applied(&zadd_nx, &Value::Int(0)), Some(false));
+        let zadd_xx = redis::cmd("ZADD")
+            .arg("z")
+            .arg("XX")
+            .arg(1)
+            .arg("m")
+            .clone();
+        assert_eq!(conditional_applied(&zadd_xx, &Value::Int(0)), None);
+        let zadd_xx_ch = redis::cmd("ZADD")
+            .arg("z")
+            .arg("XX")
+            .arg("CH")
+            .arg(1)
+            .arg("m")
+            .clone();
+        assert_eq!(conditional_applied(&zadd_xx_ch, &Value::Int(1)), Some(true));
+
+        assert_eq!(
+            conditional_applied(redis::cmd("GET").arg("k"), &Value::Nil),
+            None
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_conditional_applied_recorded() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([Ok(Value::Int(0))]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+        conn.req_command(redis::cmd("EXPIRE").arg("session").arg(60).arg("NX"))
+            .await
+            .unwrap();
+
+        let span = capture.by_otel_name("redis expire").unwrap();
+        assert_eq!(span.field("redis.conditional.applied"), Some("false"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_pubsub_receive_span() {
+        fn respond(command: &str) -> Vec<u8> {
+            match command {
+                "SUBSCRIBE" => b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n\
+                    *3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+                    .to_vec(),
+                "UNSUBSCRIBE" => b"*3\r\n$11\r\nunsubscribe\r\n$-1\r\n:0\r\n".to_vec(),
+                "PUNSUBSCRIBE" => b"*3\r\n$12\r\npunsubscribe\r\n$-1\r\n:0\r\n".to_vec(),
+                _ => b"+OK\r\n".to_vec(),
+            }
+        }
+
+        let client = redis::Client::open(test_support::spawn_scripted_server(respond)).unwrap();
+        let mut conn = InstrumentedClient::new(client).get_connection().unwrap();
+
+        let (msg, capture) = test_support::capture(|| {
+            let mut pubsub = conn.as_pubsub();
+            pubsub.subscribe("news").unwrap();
+            pubsub.get_message().unwrap()
+        });
+        assert_eq!(msg.get_payload::<String>().unwrap(), "hello");
+
+        assert!(capture.by_name("subscribe").is_some());
+        let receive = capture.by_otel_name("redis receive").unwrap();
+        assert_eq!(receive.field("db.operation"), Some("RECEIVE"));
+        assert_eq!(receive.field("redis.pubsub.channel"), Some("news"));
+        assert_eq!(receive.field("redis.pubsub.pattern"), Some("false"));
+        assert_eq!(receive.field("redis.pubsub.payload_size"), Some("5"));
+        assert_eq!(receive.field("otel.status_code"), Some("OK"));
+    }
+
+    #[test]
+    fn test_tracing_target_routes_command_spans() {
+        use tracing_subscriber::filter::{LevelFilter, Targets};
+        use tracing_subscriber::prelude::*;
+
+        fn field_names(span: &tracing::Span) -> Vec<&'static str> {
+            let meta = span.metadata().expect("span is enabled");
+            meta.fields().iter().map(|field| field.name()).collect()
+        }
+
+        let capture = test_support::SpanCapture::default();
+        let filter = Targets::new().with_target("myapp::redis", LevelFilter::INFO);
+        let subscriber = tracing_subscriber::registry().with(capture.clone().with_filter(filter));
+        let config = InstrumentationConfig::new().with_tracing_target("myapp::redis");
+
+        let targeted_fields = tracing::subscriber::with_default(subscriber, || {
+            let (default_span, _) = common::create_command_span(&redis::cmd("GET"));
+            assert!(default_span.is_disabled());
+
+            let (span, _) = common::create_command_span_with_config(&redis::cmd("SET"), &config);
+            span.record("db.redis.database_index", 3);
+            field_names(&span)
+        });
+
+        // Only the span under the configured target passes the filter
+        let spans = capture.spans();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.target, "myapp::redis");
+        assert_eq!(span.name, "redis_command");
+        assert_eq!(span.field("otel.name"), Some("redis set"));
+        assert_eq!(span.field("otel.kind"), Some("client"));
+        assert_eq!(span.field("db.system"), Some("redis"));
+        assert_eq!(span.field("db.operation"), Some("SET"));
+        assert_eq!(span.field("db.redis.database_index"), Some("3"));
+
+        // Both callsites declare the same fields, so later records land on either
+        let (default_fields, _) = test_support::capture(|| {
+            field_names(&common::create_command_span(&redis::cmd("GET")).0)
+        });
+        assert_eq!(default_fields, targeted_fields);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_string_range_commands() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::common::{classify_command, CommandKind};
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        assert_eq!(classify_command("GETRANGE"), CommandKind::Read);
+        assert_eq!(classify_command("STRLEN"), CommandKind::Read);
+        assert_eq!(classify_command("SETRANGE"), CommandKind::Write);
+        assert_eq!(classify_command("APPEND"), CommandKind::Write);
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::BulkString(b"ell".to_vec())),
+            Ok(Value::Int(11)),
+            Ok(Value::Int(11)),
+            Ok(Value::Int(16)),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let substring: String = conn.getrange("greeting", 1, -8).await.unwrap();
+        assert_eq!(substring, "ell");
+        assert_eq!(conn.setrange("greeting", 6, "Redis").await.unwrap(), 11);
+        assert_eq!(conn.strlen("greeting").await.unwrap(), 11);
+        assert_eq!(conn.append("greeting", "!!!!!").await.unwrap(), 16);
+
+        for name in ["getrange", "setrange", "strlen", "append"] {
+            let otel_name = format!("redis {name}");
+            assert!(capture.by_otel_name(&otel_name).is_some(), "{otel_name}");
+        }
+        let getrange = capture.by_name("getrange").unwrap();
+        assert_eq!(getrange.field("redis.range.start"), Some("1"));
+        assert_eq!(getrange.field("redis.range.end"), Some("-8"));
+        let setrange = capture.by_name("setrange").unwrap();
+        assert_eq!(setrange.field("redis.range.start"), Some("6"));
+        assert_eq!(setrange.field("redis.range.end"), Some("10"));
+        assert_eq!(setrange.field("redis.string.length"), Some("11"));
+        let strlen = capture.by_name("strlen").unwrap();
+        assert_eq!(strlen.field("redis.string.length"), Some("11"));
+        let append = capture.by_name("append").unwrap();
+        assert_eq!(append.field("redis.string.length"), Some("16"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_command_spans_reference_connection_span() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use std::sync::Arc;
+        use tracing::Instrument;
+
+        for mode in [ConnectionSpanMode::Parent, ConnectionSpanMode::Link] {
+            let (_guard, capture) = test_support::capture_default();
+            let config = InstrumentationConfig::new().with_connection_span(mode);
+            let mut conn = InstrumentedAsyncConnection::with_config(
+                MockAsyncConnection::default(),
+                Arc::new(config),
+            );
+
+            let outer = tracing::info_span!("request");
+            async {
+                conn.req_command(&redis::cmd("PING")).await.unwrap();
+            }
+            .instrument(outer)
+            .await;
+
+            let connection = capture.by_name("redis_connection").unwrap();
+            assert_eq!(connection.parent, None);
+            assert_eq!(connection.field("db.redis.database_index"), Some("0"));
+            let request = capture.by_name("request").unwrap();
+            let command = capture.by_otel_name("redis ping").unwrap();
+            match mode {
+                ConnectionSpanMode::Parent => {
+                    assert_eq!(command.parent, Some(connection.id));
+                    assert!(command.follows_from.is_empty());
+                }
+                ConnectionSpanMode::Link => {
+                    assert_eq!(command.parent, Some(request.id));
+                    assert_eq!(command.follows_from, vec![connection.id]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_connections_only_link_connection_span() {
+        let config = InstrumentationConfig::new().with_connection_span(ConnectionSpanMode::Parent);
+        let ((), capture) = test_support::capture(|| {
+            let state = common::new_connection_state(2, &config, true);
+            let (_, mode) = state.connection_span().unwrap();
+            assert_eq!(mode, ConnectionSpanMode::Link);
+
+            let (span, _) =
+                common::create_connection_command_span(&redis::cmd("GET"), &config, &state);
+            drop(span);
+        });
+
+        let connection = capture.by_name("redis_connection").unwrap();
+        let command = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(command.parent, None);
+        assert_eq!(command.follows_from, vec![connection.id]);
+
+        let state = common::new_connection_state(0, &InstrumentationConfig::new(), false);
+        assert!(state.connection_span().is_none());
+    }
+
+    #[test]
+    fn test_writes_only_skips_reads() {
+        let config = InstrumentationConfig::new().with_writes_only(true);
+        let ((), capture) = test_support::capture(|| {
+            let (get, _) = common::create_command_span_with_config(&redis::cmd("GET"), &config);
+            assert!(get.is_disabled());
+            let (ping, _) = common::create_command_span_with_config(&redis::cmd("PING"), &config);
+            assert!(ping.is_disabled());
+
+            let mut set = redis::cmd("SET");
+            set.arg("key").arg("value");
+            let (span, attributes) = common::create_command_span_with_config(&set, &config);
+            assert!(!span.is_disabled());
+            common::apply_span_attributes(&span, &attributes);
+        });
+
+        assert!(capture.by_otel_name("redis get").is_none());
+        assert!(capture.by_otel_name("redis ping").is_none());
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("db.system"), Some("redis"));
+        assert_eq!(set.field("db.operation"), Some("SET"));
+
+        let config = config.with_writes_only_including_other(true);
+        let ((), capture) = test_support::capture(|| {
+            common::create_command_span_with_config(&redis::cmd("PING"), &config);
+            common::create_command_span_with_config(&redis::cmd("GET"), &config);
+        });
+        assert!(capture.by_otel_name("redis ping").is_some());
+        assert!(capture.by_otel_name("redis get").is_none());
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_error_hook_called_on_failure() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::{ErrorKind, RedisError, Value};
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        let config = InstrumentationConfig::new().with_error_hook(move |cmd, err| {
+            let operation = match cmd.args_iter().next() {
+                Some(redis::Arg::Simple(name)) => String::from_utf8_lossy(name).into_owned(),
+                _ => String::new(),
+            };
+            recorded.lock().unwrap().push((operation, err.kind()));
+        });
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Err(RedisError::from((
+                ErrorKind::TypeError,
+                "wrong kind of value",
+            ))),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, Arc::new(config));
+
+        conn.req_command(&redis::cmd("PING")).await.unwrap();
+        conn.req_command(&redis::cmd("INCR")).await.unwrap_err();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![("INCR".to_string(), ErrorKind::TypeError)]
+        );
+    }
+
+    #[test]
+    fn test_error_hook_panic_is_contained() {
+        use redis::{ErrorKind, RedisError};
+
+        let config = InstrumentationConfig::new().with_error_hook(|_, _| panic!("hook failed"));
+        let result: redis::RedisResult<()> =
+            Err(RedisError::from((ErrorKind::IoError, "connection reset")));
+
+        // The panic is caught and logged rather than propagated
+        common::run_error_hook(&redis::cmd("GET"), &result, &config);
+        common::run_error_hook(&redis::cmd("GET"), &Ok::<_, RedisError>(()), &config);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_hit_ratio_counts_lookups() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+        use std::sync::Arc;
+
+        let config = InstrumentationConfig::new()
+            .with_hit_ratio(true)
+            .with_hit_ratio_attribute(true);
+        let client = InstrumentedClient::with_config(
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            config,
+        );
+        assert_eq!(client.hit_ratio(), None);
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Nil),
+            Ok(Value::BulkString(b"v".to_vec())),
+            Ok(Value::BulkString(b"x".to_vec())),
+            Ok(Value::Int(0)),
+            Ok(Value::Nil),
+        ]);
+        let mut conn =
+            InstrumentedAsyncConnection::with_config(mock, Arc::new(client.config().clone()));
+
+        let missing: Option<String> = conn.get("a").await.unwrap();
+        assert_eq!(missing, None);
+        let found: Option<String> = conn.get("b").await.unwrap();
+        assert_eq!(found.as_deref(), Some("v"));
+        conn.req_command(redis::cmd("HGET").arg("h").arg("f"))
+            .await
+            .unwrap();
+        // EXISTS answering 0 is not a miss
+        assert!(!conn.exists("c").await.unwrap());
+        conn.req_command(redis::cmd("GETDEL").arg("d"))
+            .await
+            .unwrap();
+
+        let counters = client.config().hit_counters().unwrap();
+        assert_eq!((counters.hits(), counters.misses()), (2, 2));
+        assert_eq!(client.hit_ratio(), Some(0.5));
+
+        let ratios: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter_map(|span| span.field("redis.cache.hit_ratio").map(str::to_string))
+            .collect();
+        assert_eq!(ratios, ["0.0", "0.5", "0.6666666666666666", "0.5"]);
+    }
+
+    #[test]
+    fn test_parse_cluster_state() {
+        let info = "cluster_enabled:1\r\ncluster_state:ok\r\ncluster_slots_assigned:16384\r\n";
+        assert_eq!(common::parse_cluster_state(info), Some("ok"));
+        assert_eq!(
+            common::parse_cluster_state("cluster_state:fail\n"),
+            Some("fail")
+        );
+        assert_eq!(common::parse_cluster_state("cluster_enabled:0\r\n"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_cluster_topology_commands() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let info = "cluster_state:ok\r\ncluster_slots_assigned:16384\r\n";
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::BulkString(info.as_bytes().to_vec())),
+            Ok(Value::BulkString(
+                b"07c37dfe 127.0.0.1:30004@31004 master".to_vec(),
+            )),
+            Ok(Value::Array(vec![])),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        assert_eq!(conn.cluster_info().await.unwrap(), info);
+        assert!(conn.cluster_nodes().await.unwrap().contains("master"));
+        assert_eq!(conn.cluster_shards().await.unwrap(), Value::Array(vec![]));
+
+        for (method, operation) in [
+            ("cluster_info", "CLUSTER INFO"),
+            ("cluster_nodes", "CLUSTER NODES"),
+            ("cluster_shards", "CLUSTER SHARDS"),
+        ] {
+            let outer = capture.by_name(method).unwrap();
+            assert_eq!(outer.field("db.operation"), Some(operation));
+            assert_eq!(outer.field("redis.admin_command"), Some("true"));
+
+            let command = capture
+                .by_otel_name(&format!("redis {}", operation.to_lowercase()))
+                .unwrap();
+            assert_eq!(command.field("db.operation"), Some(operation));
+            assert_eq!(command.parent, Some(outer.id));
+        }
+        let info_span = capture.by_name("cluster_info").unwrap();
+        assert_eq!(info_span.field("redis.cluster.state"), Some("ok"));
+    }
+
+    #[test]
+    fn test_deferred_recorder_batches_fields() {
+        use crate::common::{DeferredRecorder, RecordFields};
+
+        let ((), capture) = test_support::capture(|| {
+            let (span, _) = create_command_span(&redis::cmd("SET"));
+            let recorder = DeferredRecorder::new(&span);
+            recorder.record_field("db.redis.database_index", 3);
+            recorder.record_field("redis.latency_bucket", "<1ms");
+            recorder.record_field("redis.latency_bucket", "1-10ms");
+            recorder.record_field("redis.conditional.applied", true);
+            recorder.record_field("not.declared", 1);
+            drop(recorder);
+        });
+
+        let span = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(span.records, 1);
+        assert_eq!(span.field("db.redis.database_index"), Some("3"));
+        assert_eq!(span.field("redis.latency_bucket"), Some("1-10ms"));
+        assert_eq!(span.field("redis.conditional.applied"), Some("true"));
+        assert_eq!(span.field("not.declared"), None);
+    }
+
+    #[test]
+    fn test_deferred_recorder_flushes_on_panic() {
+        use crate::common::{DeferredRecorder, RecordFields};
+
+        let ((), capture) = test_support::capture(|| {
+            let (span, _) = create_command_span(&redis::cmd("GET"));
+            let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let recorder = DeferredRecorder::new(&span);
+                recorder.record_field("redis.latency_bucket", "<1ms");
+                panic!("command failed");
+            }));
+            assert!(outcome.is_err());
+        });
+
+        let span = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(span.field("redis.latency_bucket"), Some("<1ms"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_deferred_recording_applies_attributes() {
+        let client = redis::Client::open(test_support::spawn_ok_server()).unwrap();
+        let config = InstrumentationConfig::new()
+            .with_deferred_recording(true)
+            .with_default_latency_buckets()
+            .with_include_db_index_always(true);
+        let instrumented = InstrumentedClient::with_config(client, config);
+        let mut conn = instrumented.get_connection().unwrap();
+
+        let (result, capture) =
+            test_support::capture(|| conn.req_command(redis::cmd("SET").arg("a").arg(1)));
+        assert_eq!(result.unwrap(), redis::Value::Okay);
+
+        let span = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(span.records, 1);
+        assert_eq!(span.field("db.redis.database_index"), Some("0"));
+        assert!(span.field("redis.latency_bucket").is_some());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_wrapper_over_custom_connection() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mut mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::BulkString(b"1".to_vec())),
+        ]);
+        mock.db = 4;
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let ((), capture) = test_support::capture(|| {
+            conn.set("a", 1).unwrap();
+            let value: i64 = conn.get("a").unwrap();
+            assert_eq!(value, 1);
+        });
+
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("db.operation"), Some("SET"));
+        assert_eq!(set.field("db.redis.database_index"), Some("4"));
+        assert!(capture.by_otel_name("redis get").is_some());
+        assert_eq!(conn.inner().commands.len(), 2);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_execution_context_records_thread() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use std::sync::Arc;
+
+        let config = Arc::new(InstrumentationConfig::new().with_execution_context(true));
+        let worker = std::thread::Builder::new()
+            .name("redis-worker".to_string())
+            .spawn(move || {
+                let mut conn =
+                    InstrumentedSyncConnection::with_config(MockSyncConnection::default(), config);
+                test_support::capture(|| conn.req_command(&redis::cmd("PING")).unwrap()).1
+            })
+            .unwrap();
+        let capture = worker.join().unwrap();
+
+        let span = capture.by_otel_name("redis ping").unwrap();
+        assert_eq!(span.field("thread.name"), Some("redis-worker"));
+        assert!(span.field("thread.id").unwrap().parse::<u64>().is_ok());
+        assert_eq!(span.field("tokio.task.id"), None);
+
+        let ((), capture) = test_support::capture(|| {
+            let mut conn = InstrumentedSyncConnection::new(MockSyncConnection::default());
+            conn.req_command(&redis::cmd("PING")).unwrap();
+        });
+        let span = capture.by_otel_name("redis ping").unwrap();
+        assert_eq!(span.field("thread.id"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_execution_context_records_task_id() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use std::sync::Arc;
+        use tracing::instrument::WithSubscriber;
+
+        let (_guard, capture) = test_support::capture_default();
+        let config = Arc::new(InstrumentationConfig::new().with_execution_context(true));
+        let mut conn =
+            InstrumentedAsyncConnection::with_config(MockAsyncConnection::default(), config);
+
+        // The test body itself is not a spawned task, so there is no task id
+        conn.req_command(&redis::cmd("PING")).await.unwrap();
+        let span = capture.by_otel_name("redis ping").unwrap();
+        assert!(span.field("thread.id").is_some());
+        assert_eq!(span.field("tokio.task.id"), None);
+
+        let task = tokio::spawn(
+            async move {
+                conn.req_command(&redis::cmd("ECHO")).await.unwrap();
+                tokio::task::id()
+            }
+            .with_current_subscriber(),
+        );
+        let task_id = task.await.unwrap();
+        let span = capture.by_otel_name("redis echo").unwrap();
+        assert_eq!(
+            span.field("tokio.task.id"),
+            Some(task_id.to_string().as_str())
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_key_redaction_patterns() {
+        use regex::Regex;
+        use tracing::field::Empty;
+
+        let config = InstrumentationConfig::new()
+            .with_key_redaction_patterns(vec![(Regex::new(r"\d+").unwrap(), "#")]);
+        assert_eq!(config.redact_key("user:12345"), "user:#");
+        assert_eq!(config.redact_key("order:7:item:42"), "order:#:item:#");
+        assert!(matches!(
+            config.redact_key("session:abc"),
+            std::borrow::Cow::Borrowed("session:abc")
+        ));
+        assert_eq!(
+            InstrumentationConfig::new().redact_key("user:12345"),
+            "user:12345"
+        );
+
+        let ((), capture) = test_support::capture(|| {
+            let span = tracing::info_span!(
+                "smove",
+                redis.smove.source = Empty,
+                redis.smove.destination = Empty
+            );
+            let cmd = redis::cmd("SMOVE")
+                .arg("user:12345")
+                .arg("archive")
+                .arg("m")
+                .clone();
+            common::record_smove_keys_with_config(&span, &cmd, &config);
+        });
+        let span = capture.by_name("smove").unwrap();
+        assert_eq!(span.field("redis.smove.source"), Some("user:#"));
+        assert_eq!(span.field("redis.smove.destination"), Some("archive"));
+    }
+
+    #[cfg(all(feature = "sync", feature = "aio"))]
+    #[tokio::test]
+    async fn test_getex_attributes_match_across_connection_types() {
+        use redis::Expiry;
+
+        let url = test_support::spawn_scripted_server(|name| match name {
+            "GETEX" | "GETDEL" => b"$5\r\nvalue\r\n".to_vec(),
+            _ => b"+OK\r\n".to_vec(),
+        });
+        let config = InstrumentationConfig::new().with_hit_ratio(true);
+        let client = InstrumentedClient::with_config(redis::Client::open(url).unwrap(), config);
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut sync_conn = client.get_connection().unwrap();
+        let value: String = sync_conn.getex("session", Expiry::PX(1500)).unwrap();
+        assert_eq!(value, "value");
+        let sync_spans = (
+            capture.by_name("getex").unwrap(),
+            capture.by_otel_name("redis getex").unwrap(),
+        );
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut multiplexed = client.get_multiplexed_async_connection().await.unwrap();
+        let mut clone = multiplexed.clone();
+        let value: String = multiplexed
+            .getex("session", Expiry::PX(1500))
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+        let value: String = clone.getdel("session").await.unwrap();
+        assert_eq!(value, "value");
+        let multiplexed_spans = (
+            capture.by_name("getex").unwrap(),
+            capture.by_otel_name("redis getex").unwrap(),
+        );
+
+        assert_eq!(sync_spans.0.fields, multiplexed_spans.0.fields);
+        assert_eq!(sync_spans.1.fields, multiplexed_spans.1.fields);
+        assert_eq!(sync_spans.0.field("redis.ttl.mode"), Some("PX"));
+        assert_eq!(sync_spans.0.field("redis.ttl.value"), Some("1500"));
+        assert!(capture.by_otel_name("redis getdel").is_some());
+
+        // Lookups from every connection and clone land in the same counters
+        let counters = client.config().hit_counters().unwrap();
+        assert_eq!((counters.hits(), counters.misses()), (3, 0));
+    }
+
+    #[test]
+    fn test_getex_expiry_parsing() {
+        use tracing::field::Empty;
+
+        let ((), capture) = test_support::capture(|| {
+            for (name, cmd) in [
+                ("ex", redis::cmd("GETEX").arg("k").arg("EX").arg(60).clone()),
+                (
+                    "persist",
+                    redis::cmd("GETEX").arg("k").arg("PERSIST").clone(),
+                ),
+                ("plain", redis::cmd("GETEX").arg("k").clone()),
+            ] {
+                let span = tracing::info_span!(
+                    "getex",
+                    case = name,
+                    redis.ttl.mode = Empty,
+                    redis.ttl.value = Empty
+                );
+                common::record_getex_expiry(&span, &cmd);
+            }
+        });
+        let spans = capture.spans();
+        assert_eq!(spans[0].field("redis.ttl.mode"), Some("EX"));
+        assert_eq!(spans[0].field("redis.ttl.value"), Some("60"));
+        assert_eq!(spans[1].field("redis.ttl.mode"), Some("PERSIST"));
+        assert_eq!(spans[1].field("redis.ttl.value"), None);
+        assert_eq!(spans[2].field("redis.ttl.mode"), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_readonly_sets_replica_routing() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Nil),
+            Ok(Value::Okay),
+            Ok(Value::Nil),
+            Ok(Value::Okay),
+            Ok(Value::Nil),
+        ]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let ((), capture) = test_support::capture(|| {
+            let _: Option<String> = conn.get("before").unwrap();
+            conn.req_command(&redis::cmd("READONLY")).unwrap();
+            let _: Option<String> = conn.get("during").unwrap();
+            conn.req_command(&redis::cmd("READWRITE")).unwrap();
+            let _: Option<String> = conn.get("after").unwrap();
+        });
+
+        let gets: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.field("otel.name") == Some("redis get"))
+            .collect();
+        assert_eq!(gets.len(), 3);
+        assert_eq!(gets[0].field("redis.routing"), Some("primary"));
+        assert_eq!(gets[1].field("redis.routing"), Some("replica"));
+        assert_eq!(gets[2].field("redis.routing"), Some("primary"));
+        assert!(!conn.state().replica_reads());
+    }
+
+    #[test]
+    fn test_record_progress_emits_event_on_current_span() {
+        let ((), capture) = test_support::capture(|| {
+            let span = tracing::info_span!("sort");
+            let _enter = span.enter();
+            common::record_progress(500, Some(2000));
+            common::record_progress(2000, None);
+        });
+
+        let span = capture.by_name("sort").unwrap();
+        assert_eq!(span.events.len(), 2);
+        assert_eq!(
+            span.events[0].get("message").map(String::as_str),
+            Some("redis.progress")
+        );
+        assert_eq!(
+            span.events[0]
+                .get("redis.progress.processed")
+                .map(String::as_str),
+            Some("500")
+        );
+        assert_eq!(
+            span.events[0]
+                .get("redis.progress.total")
+                .map(String::as_str),
+            Some("2000")
+        );
+        assert_eq!(span.events[1].get("redis.progress.total"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_scan_and_progress_lands_on_scan_span() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let reply = Value::Array(vec![
+            Value::BulkString(b"0".to_vec()),
+            Value::Array(vec![
+                Value::BulkString(b"a".to_vec()),
+                Value::BulkString(b"b".to_vec()),
+            ]),
+        ]);
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn =
+            InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies([Ok(reply)]));
+        let mut processed = 0;
+        conn.scan_and("*", |_key| {
+            processed += 1;
+            common::record_progress(processed, None);
+            async { Ok::<_, redis::RedisError>(()) }
+        })
+        .await
+        .unwrap();
+
+        let scan = capture.by_name("redis_scan").unwrap();
+        assert_eq!(scan.events.len(), 2);
+        assert_eq!(
+            scan.events[1]
+                .get("redis.progress.processed")
+                .map(String::as_str),
+            Some("2")
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_exists_records_found() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([Ok(Value::Int(0)), Ok(Value::Int(2))]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        assert!(!conn.exists("missing").await.unwrap());
+        assert!(conn.exists(&["a", "b", "c"]).await.unwrap());
+
+        let spans: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "exists")
+            .collect();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].field("redis.exists.found"), Some("false"));
+        assert_eq!(spans[0].field("redis.exists.found_count"), None);
+        assert_eq!(spans[0].field("otel.status_code"), Some("OK"));
+        assert_eq!(spans[1].field("redis.exists.found"), Some("true"));
+        assert_eq!(spans[1].field("redis.exists.found_count"), Some("2"));
+        assert_eq!(spans[1].field("otel.status_code"), Some("OK"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_exists_records_found() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mock = MockSyncConnection::with_replies([Ok(Value::Int(0)), Ok(Value::Int(1))]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let ((), capture) = test_support::capture(|| {
+            assert!(!conn.exists("missing").unwrap());
+            assert!(conn.exists("present").unwrap());
+        });
+
+        let spans: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "exists")
+            .collect();
+        assert_eq!(spans[0].field("redis.exists.found"), Some("false"));
+        assert_eq!(spans[0].field("otel.status_code"), Some("OK"));
+        assert_eq!(spans[1].field("redis.exists.found"), Some("true"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_error_statement_capture_redacts_credentials() {
+        let url = test_support::spawn_scripted_server(|name| match name {
+            "GET" => {
+                b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".to_vec()
+            }
+            "AUTH" => b"-WRONGPASS invalid username-password pair\r\n".to_vec(),
+            _ => b"+OK\r\n".to_vec(),
+        });
+        let config = InstrumentationConfig::new().with_error_statement_capture(true);
+        let client = InstrumentedClient::with_config(redis::Client::open(url).unwrap(), config);
+        let mut conn = client.get_connection().unwrap();
+
+        let ((), capture) = test_support::capture(|| {
+            conn.set("user:1", "value").unwrap();
+            assert!(conn.get::<_, String>("user:1").is_err());
+            let auth = redis::cmd("AUTH").arg("admin").arg("hunter2").clone();
+            let reply = conn.req_command(&auth).unwrap();
+            assert!(matches!(reply, redis::Value::ServerError(_)));
+        });
+
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("redis.error.statement"), None);
+
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("redis.error.statement"), Some("GET user:1"));
+        assert_eq!(
+            get.field("redis.error.reply"),
+            Some("WRONGTYPE Operation against a key holding the wrong kind of value")
+        );
+
+        let auth = capture.by_otel_name("redis auth").unwrap();
+        assert_eq!(auth.field("redis.error.statement"), Some("AUTH ? ?"));
+        assert!(auth.fields.values().all(|value| !value.contains("hunter2")));
+    }
+
+    #[test]
+    fn test_redacted_statement_masks_values() {
+        let config = InstrumentationConfig::new();
+        let set = redis::cmd("SET")
+            .arg("user:1")
+            .arg("secret")
+            .arg("EX")
+            .arg(60)
+            .clone();
+        assert_eq!(
+            common::redacted_statement(&set, &config),
+            "SET user:1 ? ? ?"
+        );
+        assert_eq!(
+            common::redacted_statement(&redis::cmd("PING"), &config),
+            "PING"
+        );
+        let hello = redis::cmd("HELLO")
+            .arg(3)
+            .arg("AUTH")
+            .arg("admin")
+            .arg("hunter2")
+            .clone();
+        assert_eq!(common::redacted_statement(&hello, &config), "HELLO ? ? ? ?");
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_instrumented_query_sync() {
+        use crate::prelude::*;
+
+        let url = test_support::spawn_scripted_server(|name| match name {
+            "GET" => b"$5\r\nvalue\r\n".to_vec(),
+            _ => b"+OK\r\n".to_vec(),
+        });
+        let mut conn = redis::Client::open(url).unwrap().get_connection().unwrap();
+
+        let ((), capture) = test_support::capture(|| {
+            redis::cmd("SET")
+                .arg("key")
+                .arg("value")
+                .instrumented_exec(&mut conn)
+                .unwrap();
+            let value: String = redis::cmd("GET")
+                .arg("key")
+                .instrumented_query(&mut conn)
+                .unwrap();
+            assert_eq!(value, "value");
+        });
+
+        assert!(capture.by_otel_name("redis set").is_some());
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.operation"), Some("GET"));
+        assert_eq!(get.field("redis.idempotent"), Some("true"));
+        assert!(get.field("redis.command.fingerprint").is_some());
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_instrumented_query_async() {
+        use crate::prelude::*;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::BulkString(b"value".to_vec())),
+        ]);
+
+        redis::cmd("SET")
+            .arg("key")
+            .arg("value")
+            .instrumented_exec_async(&mut conn)
+            .await
+            .unwrap();
+        let value: String = redis::cmd("GET")
+            .arg("key")
+            .instrumented_query_async(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(value, "value");
+
+        assert!(capture.by_otel_name("redis set").is_some());
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.operation"), Some("GET"));
+        assert_eq!(get.field("redis.idempotent"), Some("true"));
+        assert!(get.field("redis.command.fingerprint").is_some());
+    }
+
+    #[cfg(all(feature = "aio", feature = "metrics"))]
+    #[test]
+    fn test_batch_estimator_from_inflight_samples() {
+        use crate::aio::BatchEstimator;
+
+        // Two bursts of three commands, each starting on an idle connection
+        let estimator = BatchEstimator::new(6);
+        let estimates: Vec<_> = [0, 1, 2, 0, 1, 2]
+            .into_iter()
+            .map(|inflight| estimator.observe(inflight))
+            .collect();
+        assert_eq!(estimates, [None, None, None, None, None, Some(3.0)]);
+
+        // Strictly sequential commands are never batched, and the window starts afresh
+        let estimates: Vec<_> = (0..6).map(|_| estimator.observe(0)).collect();
+        assert_eq!(estimates[5], Some(1.0));
+    }
+
+    #[cfg(all(feature = "aio", feature = "metrics"))]
+    #[test]
+    fn test_batch_estimator_tracks_inflight_commands() {
+        use crate::aio::BatchEstimator;
+
+        let estimator = BatchEstimator::new(4);
+        let (first, _) = estimator.submit();
+        let (second, _) = estimator.submit();
+        assert_eq!(estimator.inflight(), 2);
+        drop((first, second));
+        assert_eq!(estimator.inflight(), 0);
+
+        // Two overlapping pairs: 4 commands in 2 batches
+        let (_third, _) = estimator.submit();
+        let (_fourth, estimate) = estimator.submit();
+        assert_eq!(estimate, Some(2.0));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_watch_conflict_recorded_as_error() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        // MULTI, the queued SET, then a nil EXEC because a watched key changed
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".to_string())),
+            Ok(Value::Nil),
+        ]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let ((), capture) = test_support::capture(|| {
+            let reply: Option<(i64,)> = redis::pipe()
+                .atomic()
+                .set("balance", 10)
+                .query(&mut conn)
+                .unwrap();
+            assert!(reply.is_none());
+        });
+
+        let span = capture.by_name("req_packed_commands").unwrap();
+        assert_eq!(span.field("otel.status_code"), Some("ERROR"));
+        assert_eq!(span.field("error.type"), Some("watch_conflict"));
+        assert_eq!(span.field("redis.transaction.conflict"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_watch_conflict_recorded_as_attribute() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let replies = || {
+            [
+                Ok(Value::Okay),
+                Ok(Value::SimpleString("QUEUED".to_string())),
+                Ok(Value::Nil),
+            ]
+        };
+        let mut transaction = redis::pipe();
+        transaction.atomic().set("balance", 10);
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn =
+            InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies(replies()));
+        assert!(conn
+            .execute_pipeline(&transaction)
+            .await
+            .unwrap()
+            .is_empty());
+        let span = capture.by_name("redis_pipeline").unwrap();
+        assert_eq!(span.field("error.type"), Some("watch_conflict"));
+
+        let (_guard, capture) = test_support::capture_default();
+        let config = InstrumentationConfig::new().with_watch_conflict_attribute(true);
+        let mut conn = InstrumentedAsyncConnection::with_config(
+            MockAsyncConnection::with_replies(replies()),
+            std::sync::Arc::new(config),
+        );
+        conn.execute_pipeline(&transaction).await.unwrap();
+        let span = capture.by_name("redis_pipeline").unwrap();
+        assert_eq!(span.field("redis.transaction.conflict"), Some("true"));
+        assert_eq!(span.field("otel.status_code"), Some("OK"));
+        assert_eq!(span.field("error.type"), None);
+    }
+
+    #[test]
+    fn test_find_slowlog_duration_matches_recent_entry() {
+        use redis::Value;
+
+        let entry = |id: i64, timestamp: i64, micros: i64, args: &[&str]| {
+            Value::Array(vec![
+                Value::Int(id),
+                Value::Int(timestamp),
+                Value::Int(micros),
+                Value::Array(
+                    args.iter()
+                        .map(|arg| Value::BulkString(arg.as_bytes().to_vec()))
+                        .collect(),
+                ),
+                Value::BulkString(b"127.0.0.1:50000".to_vec()),
+                Value::BulkString(Vec::new()),
+            ])
+        };
+        // Newest first: a slow SET, our KEYS, then an older run of the same KEYS
+        let slowlog = Value::Array(vec![
+            entry(3, 1_700_000_010, 900, &["SET", "a", "1"]),
+            entry(2, 1_700_000_009, 52_000, &["KEYS", "user:*"]),
+            entry(1, 1_700_000_000, 48_000, &["KEYS", "user:*"]),
+        ]);
+        let keys = redis::cmd("KEYS").arg("user:*").clone();
+
+        assert_eq!(
+            common::find_slowlog_duration(&slowlog, &keys, 1_700_000_009),
+            Some(52_000)
+        );
+        // Entries logged before the command started are too old to be it
+        assert_eq!(
+            common::find_slowlog_duration(&slowlog, &keys, 1_700_000_010),
+            None
+        );
+        // A different key doesn't match
+        let other = redis::cmd("KEYS").arg("session:*").clone();
+        assert_eq!(common::find_slowlog_duration(&slowlog, &other, 0), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_slowlog_correlation_records_server_time() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let slowlog = Value::Array(vec![Value::Array(vec![
+            Value::Int(7),
+            Value::Int(now),
+            Value::Int(15_000),
+            Value::Array(vec![
+                Value::BulkString(b"GET".to_vec()),
+                Value::BulkString(b"big".to_vec()),
+            ]),
+        ])]);
+        let config = InstrumentationConfig::new()
+            .with_slowlog_correlation(Duration::ZERO, Duration::from_secs(60));
+        let mock = MockAsyncConnection::with_replies([Ok(Value::Nil), Ok(slowlog), Ok(Value::Nil)]);
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, std::sync::Arc::new(config));
+        let _: Option<String> = conn.get("big").await.unwrap();
+        // Throttled: the second command doesn't issue another SLOWLOG GET
+        let _: Option<String> = conn.get("big").await.unwrap();
+
+        assert_eq!(conn.inner().commands.len(), 3);
+        let gets: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.field("otel.name") == Some("redis get"))
+            .collect();
+        assert_eq!(gets[0].field("redis.server.exec_us"), Some("15000"));
+        assert_eq!(gets[1].field("redis.server.exec_us"), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_slowlog_lookup_skipped_inside_multi() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+        use std::time::Duration;
+
+        let config = InstrumentationConfig::new()
+            .with_slowlog_correlation(Duration::ZERO, Duration::from_secs(60));
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::Array(vec![Value::Nil])),
+        ]);
+        let mut conn = InstrumentedSyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        conn.req_command(&redis::cmd("MULTI")).unwrap();
+        let queued = conn.req_command(redis::cmd("GET").arg("big")).unwrap();
+        assert_eq!(queued, Value::SimpleString("QUEUED".into()));
+        let exec = conn.req_command(&redis::cmd("EXEC")).unwrap();
+        assert_eq!(exec, Value::Array(vec![Value::Nil]));
+
+        // The first lookup is claimed by EXEC, once the transaction is closed
+        let sent: Vec<_> = conn
+            .inner()
+            .commands
+            .iter()
+            .map(|packed| crate::common::get_packed_command_name(packed).unwrap())
+            .collect();
+        assert_eq!(sent, ["MULTI", "GET", "EXEC", "SLOWLOG"]);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_response_attribute_extractor() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use opentelemetry::KeyValue;
+        use redis::{ErrorKind, RedisError, Value};
+
+        let config = InstrumentationConfig::new().with_response_attribute_extractor(
+            |operation, reply| match (operation, reply) {
+                ("INCR", Value::Int(value)) => vec![
+                    KeyValue::new("app.counter.value", *value),
+                    KeyValue::new("db.operation", "overridden"),
+                ],
+                _ => Vec::new(),
+            },
+        );
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Int(5)),
+            Err(RedisError::from((ErrorKind::TypeError, "not an integer"))),
+            Ok(Value::Okay),
+        ]);
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        conn.req_command(redis::cmd("INCR").arg("hits"))
+            .await
+            .unwrap();
+        assert!(conn
+            .req_command(redis::cmd("INCR").arg("name"))
+            .await
+            .is_err());
+        conn.req_command(redis::cmd("SET").arg("a").arg(1))
+            .await
+            .unwrap();
+
+        let spans: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .collect();
+        assert_eq!(spans.len(), 3);
+        // Declared keys land on the span, the rest on a response event
+        assert_eq!(spans[0].field("db.operation"), Some("overridden"));
+        assert_eq!(spans[0].events.len(), 1);
+        let event = &spans[0].events[0];
+        assert_eq!(
+            event.get("message").map(String::as_str),
+            Some("redis.response")
+        );
+        assert_eq!(
+            event.get("app.counter.value").map(String::as_str),
+            Some("5")
+        );
+        // Failed commands and operations the extractor ignores record nothing
+        assert!(spans[1].events.is_empty());
+        assert!(spans[2].events.is_empty());
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_script_cache_commands() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let sha = "e0e1f9fabfc9d4800c877a703b823ac0578ff8db";
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::BulkString(sha.as_bytes().to_vec())),
+            Ok(Value::Array(vec![Value::Int(1), Value::Int(0)])),
+            Ok(Value::Okay),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        assert_eq!(conn.script_load("return 1").await.unwrap(), sha);
+        assert_eq!(
+            conn.script_exists(&[sha, "0000"]).await.unwrap(),
+            [true, false]
+        );
+        conn.script_flush().await.unwrap();
+
+        for (method, operation) in [
+            ("script_load", "SCRIPT LOAD"),
+            ("script_exists", "SCRIPT EXISTS"),
+            ("script_flush", "SCRIPT FLUSH"),
+        ] {
+            let outer = capture.by_name(method).unwrap();
+            assert_eq!(outer.field("db.operation"), Some(operation));
+            let command = capture
+                .by_otel_name(&format!("redis {}", operation.to_lowercase()))
+                .unwrap();
+            assert_eq!(command.field("db.operation"), Some(operation));
+            assert_eq!(command.parent, Some(outer.id));
+        }
+
+        let load = capture.by_name("script_load").unwrap();
+        assert_eq!(load.field("db.redis.script.sha"), Some(sha));
+        let exists = capture.by_name("script_exists").unwrap();
+        assert_eq!(exists.field("redis.script.count"), Some("2"));
+        assert_eq!(exists.field("redis.script.exists_count"), Some("1"));
+        let flush = capture.by_name("script_flush").unwrap();
+        assert_eq!(flush.field("redis.admin_command"), Some("true"));
+        assert_eq!(load.field("redis.admin_command"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_span_preprocessor() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use opentelemetry::KeyValue;
+        use redis::Value;
+
+        let config =
+            InstrumentationConfig::new().with_span_preprocessor(|attributes, operation| {
+                if operation == "PING" {
+                    return SpanDecision::Skip;
+                }
+                attributes.push(KeyValue::new("app.tenant", "acme"));
+                attributes.push(KeyValue::new("redis.criticality", "high"));
+                SpanDecision::Create
+            });
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::SimpleString("PONG".into())),
+            Ok(Value::BulkString(b"1".to_vec())),
+        ]);
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        conn.req_command(&redis::cmd("PING")).await.unwrap();
+        conn.req_command(redis::cmd("GET").arg("a")).await.unwrap();
+
+        let spans: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .collect();
+        // The skipped command is still sent, without a span
+        assert_eq!(conn.inner().commands.len(), 2);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].field("otel.name"), Some("redis get"));
+        // Declared keys land on the span, the rest on an attributes event
+        assert_eq!(spans[0].field("redis.criticality"), Some("high"));
+        let event = &spans[0].events[0];
+        assert_eq!(
+            event.get("message").map(String::as_str),
+            Some("redis.attributes")
+        );
+        assert_eq!(event.get("app.tenant").map(String::as_str), Some("acme"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_reconnect_state() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::{RedisError, Value};
+        use std::io::{Error, ErrorKind};
+
+        let config = InstrumentationConfig::new().with_reconnect_state(true);
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Err(RedisError::from(Error::from(ErrorKind::BrokenPipe))),
+            Err(RedisError::from(Error::from(ErrorKind::ConnectionRefused))),
+            Ok(Value::Okay),
+            Ok(Value::Okay),
+        ]);
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        for _ in 0..5 {
+            let _ = conn.req_command(redis::cmd("SET").arg("a").arg(1)).await;
+        }
+
+        // Each span records the state the command was sent in
+        let states: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .map(|span| span.field("redis.connection.state").map(str::to_owned))
+            .collect();
+        assert_eq!(
+            states,
+            ["ready", "ready", "connecting", "backoff", "ready"]
+                .map(|state| Some(state.to_owned()))
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_concurrent_command_spans_do_not_nest() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use tracing::Instrument;
+
+        let (_guard, capture) = test_support::capture_default();
+        let connection = || {
+            InstrumentedAsyncConnection::new(MockAsyncConnection {
+                yield_before_reply: true,
+                ..MockAsyncConnection::default()
+            })
+        };
+        let (mut first, mut second) = (connection(), connection());
+        let (get_a, get_b) = (
+            redis::cmd("GET").arg("a").clone(),
+            redis::cmd("GET").arg("b").clone(),
+        );
+
+        let caller = tracing::info_span!("caller");
+        async {
+            let (a, b) = tokio::join!(first.req_command(&get_a), second.req_command(&get_b));
+            a.unwrap();
+            b.unwrap();
+        }
+        .instrument(caller)
+        .await;
+
+        let caller = capture.by_name("caller").unwrap();
+        let commands: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .collect();
+        assert_eq!(commands.len(), 2);
+        // A command suspended mid-flight must not leave its span entered for the other
+        for command in &commands {
+            assert_eq!(command.parent, Some(caller.id));
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_setnx_and_msetnx() {
+        use crate::common::{classify_command, CommandKind};
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mock = MockSyncConnection::with_replies([Ok(Value::Int(1)), Ok(Value::Int(0))]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let ((), capture) = test_support::capture(|| {
+            assert!(conn.setnx("lock", "owner").unwrap());
+            assert!(!conn.msetnx(&[("a", 1), ("b", 2)]).unwrap());
+        });
+
+        assert_eq!(classify_command("SETNX"), CommandKind::Write);
+        assert_eq!(classify_command("MSETNX"), CommandKind::Write);
+        let setnx = capture.by_name("setnx").unwrap();
+        assert_eq!(setnx.field("db.operation"), Some("SETNX"));
+        assert_eq!(setnx.field("redis.conditional.applied"), Some("true"));
+        let command = capture.by_otel_name("redis setnx").unwrap();
+        assert_eq!(command.field("db.operation"), Some("SETNX"));
+        // MSETNX returning 0 means none of the keys were set
+        let msetnx = capture.by_name("msetnx").unwrap();
+        assert_eq!(msetnx.field("db.operation"), Some("MSETNX"));
+        assert_eq!(msetnx.field("redis.keys.count"), Some("2"));
+        assert_eq!(msetnx.field("redis.conditional.applied"), Some("false"));
+        let command = capture.by_otel_name("redis msetnx").unwrap();
+        assert_eq!(command.field("redis.conditional.applied"), Some("false"));
+    }
+
+    #[test]
+    fn test_select_records_database_index() {
+        use opentelemetry::KeyValue;
+
+        let attributes = extract_command_attributes(redis::cmd("SELECT").arg(3));
+        assert!(attributes.contains(&KeyValue::new("db.redis.database_index", 3_i64)));
+        let attributes = extract_command_attributes(redis::cmd("GET").arg(3));
+        assert!(!attributes
+            .iter()
+            .any(|attribute| attribute.key.as_str() == "db.redis.database_index"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_database_index_from_connection_url() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let client = InstrumentedClient::new(redis::Client::open("redis://127.0.0.1/5").unwrap());
+        assert_eq!(client.database_index(), 5);
+
+        // A connection opened against database 5 reports it through `get_db`
+        let mut mock = MockSyncConnection::with_replies([Ok(Value::Okay), Ok(Value::Okay)]);
+        mock.db = client.database_index();
+        let mut conn = InstrumentedSyncConnection::new(mock);
+        let ((), capture) = test_support::capture(|| {
+            conn.set("a", 1).unwrap();
+            conn.req_command(redis::cmd("SELECT").arg(3)).unwrap();
+        });
+
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("db.redis.database_index"), Some("5"));
+        let select = capture.by_otel_name("redis select").unwrap();
+        assert_eq!(select.field("db.redis.database_index"), Some("3"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_window_aggregation() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use std::time::Duration;
+
+        let window = Duration::from_millis(20);
+        let config = InstrumentationConfig::new().with_window_aggregation(window);
+        let ((), capture) = test_support::capture(|| {
+            let mut conn = InstrumentedSyncConnection::with_config(
+                MockSyncConnection::default(),
+                std::sync::Arc::new(config),
+            );
+            for key in ["a", "b"] {
+                conn.req_command(redis::cmd("GET").arg(key)).unwrap();
+            }
+            conn.req_command(redis::cmd("SET").arg("a").arg(1)).unwrap();
+            std::thread::sleep(window * 2);
+            // The first command after the window flushes it and starts the next one
+            conn.req_command(redis::cmd("GET").arg("c")).unwrap();
+            // Dropping the connection flushes the last window
+        });
+
+        let spans = capture.spans();
+        assert!(!spans.iter().any(|span| span.name == "redis_command"));
+        let summaries: Vec<_> = spans
+            .iter()
+            .filter(|span| span.field("otel.name") == Some("redis summary"))
+            .collect();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].field("db.system"), Some("redis"));
+        assert_eq!(summaries[0].field("redis.summary.get.count"), Some("2"));
+        assert_eq!(summaries[0].field("redis.summary.set.count"), Some("1"));
+        assert!(summaries[0].field("redis.summary.get.p99_ms").is_some());
+        let window_ms: f64 = summaries[0]
+            .field("redis.summary.window_ms")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(window_ms >= 20.0);
+        assert_eq!(summaries[1].field("redis.summary.get.count"), Some("1"));
+        assert_eq!(summaries[1].field("redis.summary.set.count"), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_server_address_from_connection_info() {
+        use crate::common::ServerAddress;
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+
+        let client = InstrumentedClient::new(redis::Client::open("redis://example:6380/").unwrap());
+        assert_eq!(
+            client.server_address(),
+            &ServerAddress {
+                address: "example".to_string(),
+                port: Some(6380),
+            }
+        );
+
+        let mut conn = InstrumentedSyncConnection::new(MockSyncConnection::default())
+            .with_server_address(client.server_address().clone());
+        let ((), capture) = test_support::capture(|| {
+            conn.req_command(redis::cmd("GET").arg("a")).unwrap();
+        });
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("server.address"), Some("example"));
+        assert_eq!(get.field("server.port"), Some("6380"));
+
+        // Unix sockets record the socket path and no port
+        let info: redis::ConnectionInfo = "redis+unix:///tmp/redis.sock".parse().unwrap();
+        let server = ServerAddress::from_connection_info(&info);
+        let mut conn = InstrumentedSyncConnection::new(MockSyncConnection::default())
+            .with_server_address(server);
+        let ((), capture) = test_support::capture(|| {
+            conn.req_command(redis::cmd("GET").arg("a")).unwrap();
+        });
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("server.address"), Some("/tmp/redis.sock"));
+        assert_eq!(get.field("server.port"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_get_many_dedupes_keys() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let mock = MockAsyncConnection::with_replies([Ok(Value::Array(vec![
+            Value::BulkString(b"1".to_vec()),
+            Value::Nil,
+            Value::BulkString(b"3".to_vec()),
+        ]))]);
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let values: Vec<Option<i64>> = conn.get_many(&["a", "b", "a", "c", "b"]).await.unwrap();
+
+        // Results follow the input order, repeats included
+        assert_eq!(values, [Some(1), None, Some(1), Some(3), None]);
+        assert_eq!(conn.inner().commands.len(), 1);
+        assert_eq!(
+            conn.inner().commands[0],
+            redis::cmd("MGET")
+                .arg("a")
+                .arg("b")
+                .arg("c")
+                .get_packed_command()
+        );
+        let span = capture.by_name("get_many").unwrap();
+        assert_eq!(span.field("redis.keys.count"), Some("5"));
+        assert_eq!(span.field("redis.batch.deduped"), Some("2"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_tls_handshake_recorded_on_acquire_span() {
+        let info = redis::ConnectionInfo {
+            addr: redis::ConnectionAddr::TcpTls {
+                host: "127.0.0.1".to_string(),
+                port: 1,
+                insecure: true,
+                tls_params: None,
+            },
+            redis: Default::default(),
+        };
+        let client = InstrumentedClient::new(redis::Client::open(info).unwrap());
+        let plain = InstrumentedClient::new(redis::Client::open("redis://127.0.0.1:1/").unwrap());
+
+        let ((), capture) = test_support::capture(|| {
+            // The attempts fail, and a failing handshake is still timed
+            assert!(client.get_connection().is_err());
+            assert!(plain.get_connection().is_err());
+        });
+
+        let acquires: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "get_connection")
+            .collect();
+        assert_eq!(acquires.len(), 2);
+        assert_eq!(acquires[0].field("redis.tls"), Some("true"));
+        let handshake_ms: f64 = acquires[0]
+            .field("redis.tls.handshake_ms")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(handshake_ms >= 0.0);
+        assert_eq!(acquires[1].field("redis.tls"), None);
+        assert_eq!(acquires[1].field("redis.tls.handshake_ms"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_wrapper_as_connection_like() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::{AsyncCommands, Value};
+
+        let mut mock = MockAsyncConnection::with_replies([
+            Ok(Value::Int(1)),
+            Ok(Value::BulkString(b"job".to_vec())),
+            Ok(Value::Int(0)),
+        ]);
+        mock.db = 2;
+        let (_guard, capture) = test_support::capture_default();
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let len: i64 = AsyncCommands::lpush(&mut conn, "queue", "job")
+            .await
+            .unwrap();
+        assert_eq!(len, 1);
+        let (job, len): (String, i64) = redis::pipe()
+            .rpop("queue", None)
+            .llen("queue")
+            .query_async(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!((job.as_str(), len), ("job", 0));
+        assert_eq!(redis::aio::ConnectionLike::get_db(&conn), 2);
+
+        let lpush = capture.by_otel_name("redis lpush").unwrap();
+        assert_eq!(lpush.field("db.operation"), Some("LPUSH"));
+        let pipeline = capture.by_name("req_packed_commands").unwrap();
+        assert_eq!(pipeline.field("db.operation"), Some("pipeline"));
+        assert_eq!(pipeline.field("redis.pipeline.count"), Some("2"));
+        assert_eq!(pipeline.field("otel.status_code"), Some("OK"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_req_command_with_span_fn() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+
+        let mut conn = InstrumentedSyncConnection::new(MockSyncConnection::default());
+        let ((), capture) = test_support::capture(|| {
+            conn.req_command_with_span_fn(redis::cmd("SET").arg("order:42").arg(1), |span| {
+                span.record("db.redis.database_name", "orders");
+                tracing::info!(parent: span, app.order_id = 42, "enriched");
+            })
+            .unwrap();
+        });
+
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("db.operation"), Some("SET"));
+        assert_eq!(set.field("db.system"), Some("redis"));
+        assert_eq!(set.field("db.redis.database_name"), Some("orders"));
+        assert_eq!(set.events.len(), 1);
+        assert_eq!(
+            set.events[0].get("app.order_id").map(String::as_str),
+            Some("42")
+        );
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_latency_and_slowlog_commands() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let entry = |id| {
+            Value::Array(vec![
+                Value::Int(id),
+                Value::Int(1_700_000_000),
+                Value::Int(15_000),
+                Value::Array(vec![Value::BulkString(b"KEYS".to_vec())]),
+            ])
+        };
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Array(vec![])),
+            Ok(Value::Array(vec![])),
+            Ok(Value::Array(vec![entry(2), entry(1)])),
+            Ok(Value::Okay),
+        ]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let ((), capture) = test_support::capture(|| {
+            let _: Value = conn.latency_latest().unwrap();
+            let _: Value = conn.latency_history("command").unwrap();
+            let entries: Vec<Value> = conn.slowlog_get(Some(5)).unwrap();
+            assert_eq!(entries.len(), 2);
+            conn.slowlog_reset().unwrap();
+        });
+
+        let operations: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .map(|span| span.field("db.operation").unwrap().to_string())
+            .collect();
+        assert_eq!(
+            operations,
+            [
+                "LATENCY LATEST",
+                "LATENCY HISTORY",
+                "SLOWLOG GET",
+                "SLOWLOG RESET"
+            ]
+        );
+        let slowlog = capture.by_name("slowlog_get").unwrap();
+        assert_eq!(slowlog.field("redis.admin_command"), Some("true"));
+        assert_eq!(slowlog.field("redis.slowlog.entries"), Some("2"));
+        assert_eq!(
+            capture
+                .by_name("latency_latest")
+                .unwrap()
+                .field("redis.admin_command"),
+            Some("true")
+        );
+        assert_eq!(
+            conn.inner().commands[2],
+            redis::cmd("SLOWLOG").arg("GET").arg(5).get_packed_command()
+        );
+    }
+
+    #[cfg(all(feature = "sync", feature = "metrics"))]
+    #[test]
+    fn test_sync_operation_duration_metric() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::{measurements, MockSyncConnection};
+        use redis::{ErrorKind, RedisError, Value};
+
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Int(1_700_000_000)),
+            Err(RedisError::from((ErrorKind::IoError, "connection reset"))),
+        ]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+        conn.req_command(&redis::cmd("LASTSAVE")).unwrap();
+        assert!(conn.req_command(&redis::cmd("LOLWUT")).is_err());
+
+        // Measurements are process-wide, so only look at this test's operations
+        let durations: Vec<_> = measurements("db.client.operation.duration")
+            .into_iter()
+            .filter(|m| {
+                matches!(
+                    m.attribute("db.operation").as_deref(),
+                    Some("LASTSAVE" | "LOLWUT")
+                )
+            })
+            .collect();
+        assert_eq!(durations.len(), 2);
+        assert_eq!(
+            durations[0].attribute("db.system").as_deref(),
+            Some("redis")
+        );
+        assert_eq!(
+            durations[0].attribute("db.operation").as_deref(),
+            Some("LASTSAVE")
+        );
+        assert_eq!(
+            durations[0].attribute("redis.status").as_deref(),
+            Some("ok")
+        );
+        assert_eq!(
+            durations[1].attribute("redis.status").as_deref(),
+            Some("error")
+        );
+        assert!(durations.iter().all(|m| m.value >= 0.0 && m.value < 1.0));
+    }
+
+    #[cfg(all(feature = "aio", feature = "metrics"))]
+    #[tokio::test]
+    async fn test_async_operations_counter() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::{measurements, MockAsyncConnection};
+        use redis::{ErrorKind, RedisError, Value};
+
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Int(3)),
+            Ok(Value::Int(4)),
+            Err(RedisError::from((
+                ErrorKind::ResponseError,
+                "wrong kind of value",
+            ))),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+        for _ in 0..3 {
+            let _ = conn
+                .req_command(redis::cmd("PFCOUNT").arg("visitors"))
+                .await;
+        }
+
+        // Measurements are process-wide, so only look at this test's operation
+        let counts: Vec<_> = measurements("db.client.operations")
+            .into_iter()
+            .filter(|m| m.attribute("db.operation").as_deref() == Some("PFCOUNT"))
+            .collect();
+        assert_eq!(counts.len(), 3);
+        assert!(counts.iter().all(|m| m.value == 1.0));
+        let statuses: Vec<_> = counts
+            .iter()
+            .map(|m| m.attribute("redis.status").unwrap())
+            .collect();
+        assert_eq!(statuses, ["ok", "ok", "error"]);
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        use crate::common::is_idempotent;
+
+        assert!(is_idempotent(redis::cmd("GET").arg("key")));
+        assert!(is_idempotent(redis::cmd("EXISTS").arg("key")));
+        assert!(is_idempotent(redis::cmd("SADD").arg("set").arg("member")));
+        assert!(is_idempotent(
+            redis::cmd("set").arg("key").arg("value").arg("EX").arg(60)
+        ));
+        assert!(is_idempotent(
+            redis::cmd("ZADD").arg("zset").arg(1).arg("member")
+        ));
+
+        assert!(!is_idempotent(redis::cmd("INCR").arg("counter")));
+        assert!(!is_idempotent(redis::cmd("LPUSH").arg("list").arg("item")));
+        assert!(!is_idempotent(redis::cmd("SPOP").arg("set")));
+        assert!(!is_idempotent(
+            redis::cmd("SET").arg("key").arg("value").arg("get")
+        ));
+        assert!(!is_idempotent(
+            redis::cmd("ZADD")
+                .arg("zset")
+                .arg("INCR")
+                .arg(1)
+                .arg("member")
+        ));
+        assert!(!is_idempotent(redis::cmd("EVAL").arg("return 1").arg(0)));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_idempotent_attribute_on_command_span() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::{capture, MockSyncConnection};
+        use redis::Value;
+
+        let ((), capture) = capture(|| {
+            let mock = MockSyncConnection::with_replies([Ok(Value::Okay), Ok(Value::Int(1))]);
+            let mut conn = InstrumentedSyncConnection::new(mock);
+            conn.req_command(redis::cmd("GET").arg("key")).unwrap();
+            conn.req_command(redis::cmd("INCR").arg("counter")).unwrap();
+        });
+
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("redis.idempotent"), Some("true"));
+        let incr = capture.by_otel_name("redis incr").unwrap();
+        assert_eq!(incr.field("redis.idempotent"), Some("false"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_conversion_failure_records_types() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::{ErrorKind, Value};
+
+        let mock = MockSyncConnection::with_replies([Ok(Value::BulkString(b"abc".to_vec()))]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let ((), capture) = test_support::capture(|| {
+            let err = conn.get::<_, i64>("name").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::TypeError);
+        });
+
+        let get = capture.by_name("get").unwrap();
+        assert_eq!(get.events.len(), 1);
+        let event = &get.events[0];
+        assert_eq!(
+            event.get("message").map(String::as_str),
+            Some("redis.conversion")
+        );
+        assert_eq!(
+            event
+                .get("redis.conversion.target_type")
+                .map(String::as_str),
+            Some("i64")
+        );
+        assert_eq!(
+            event
+                .get("redis.conversion.actual_type")
+                .map(String::as_str),
+            Some("BulkString")
+        );
+        // The command span had closed by the time the reply was converted
+        let command = capture.by_otel_name("redis get").unwrap();
+        assert!(command.events.is_empty());
+    }
+
+    #[test]
+    fn test_command_span_kind_is_client() {
+        let ((span, _), capture) =
+            test_support::capture(|| common::create_command_span(&redis::cmd("PING")));
+
+        let metadata = span.metadata().expect("span is enabled");
+        assert!(metadata.fields().field("otel.kind").is_some());
+        let captured = capture.by_otel_name("redis ping").unwrap();
+        assert_eq!(captured.field("otel.kind"), Some("client"));
+    }
+
+    #[test]
+    fn test_span_name_strategies() {
+        use crate::config::SpanNameStrategy;
+
+        fn span_names(config: &InstrumentationConfig) -> Vec<String> {
+            let (_, capture) = test_support::capture(|| {
+                let get = redis::cmd("GET").arg("user:1").clone();
+                let ping = redis::cmd("PING");
+                let _ = common::create_command_span_with_config(&get, config);
+                let _ = common::create_command_span_with_config(&ping, config);
+            });
+            capture
+                .spans()
+                .into_iter()
+                .filter_map(|span| span.field("otel.name").map(str::to_owned))
+                .collect()
+        }
+
+        let default = InstrumentationConfig::new();
+        assert_eq!(
+            default.span_name_strategy(),
+            SpanNameStrategy::RedisPrefixed
+        );
+        assert_eq!(span_names(&default), ["redis get", "redis ping"]);
+
+        let operation_only =
+            InstrumentationConfig::new().with_span_name_strategy(SpanNameStrategy::OperationOnly);
+        assert_eq!(span_names(&operation_only), ["GET", "PING"]);
+
+        let with_collection = InstrumentationConfig::new()
+            .with_span_name_strategy(SpanNameStrategy::OperationAndCollection);
+        assert_eq!(span_names(&with_collection), ["GET user:1", "PING"]);
+
+        // The operation part follows the configured casing
+        let lower = InstrumentationConfig::new()
+            .with_operation_case(OperationCase::Lower)
+            .with_span_name_strategy(SpanNameStrategy::OperationAndCollection);
+        assert_eq!(span_names(&lower), ["get user:1", "ping"]);
+    }
+
+    #[test]
+    fn test_collection_span_name_skips_commands_without_leading_key() {
+        use crate::config::SpanNameStrategy;
+
+        let config = InstrumentationConfig::new()
+            .with_span_name_strategy(SpanNameStrategy::OperationAndCollection);
+        let (_, capture) = test_support::capture(|| {
+            let zunion = redis::cmd("ZUNION").arg(2).arg("a").arg("b").clone();
+            let hset = redis::cmd("HSET").arg("users").arg("1").arg("x").clone();
+            let _ = common::create_command_span_with_config(&zunion, &config);
+            let _ = common::create_command_span_with_config(&hset, &config);
+        });
+
+        assert!(capture.by_otel_name("ZUNION").is_some());
+        assert!(capture.by_otel_name("HSET users").is_some());
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_migrate_keys() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::{ErrorKind, RedisError, Value};
+
+        let batch = |next: &str, keys: &[&str]| {
+            Ok(Value::Array(vec![
+                Value::BulkString(next.as_bytes().to_vec()),
+                Value::Array(
+                    keys.iter()
+                        .map(|key| Value::BulkString(key.as_bytes().to_vec()))
+                        .collect(),
+                ),
+            ]))
+        };
+        let (_guard, capture) = test_support::capture_default();
+        let mut source = InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies([
+            batch("17", &["user:1", "user:2"]),
+            batch("0", &["user:3"]),
+        ]));
+        let mut dest = InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Err(RedisError::from((ErrorKind::ReadOnly, "read only replica"))),
+            Ok(Value::Okay),
+        ]));
+
+        let processed = source
+            .migrate_keys(
+                "user:*",
+                &mut dest,
+                Some(5),
+                async |key, dest: &mut InstrumentedAsyncConnection<MockAsyncConnection>| {
+                    dest.req_command(redis::cmd("SET").arg(key).arg(1)).await
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(processed, 3);
+        // The scan resumes from the given cursor
+        assert_eq!(
+            source.inner().commands[0],
+            redis::cmd("SCAN")
+                .arg(5)
+                .arg("MATCH")
+                .arg("user:*")
+                .get_packed_command()
+        );
+        assert_eq!(dest.inner().commands.len(), 3);
+
+        let migrate = capture.by_name("redis_migrate").unwrap();
+        assert_eq!(migrate.field("otel.name"), Some("redis migrate"));
+        assert_eq!(migrate.field("redis.migrate.start_cursor"), Some("5"));
+        assert_eq!(migrate.field("redis.migrate.keys_processed"), Some("3"));
+        assert_eq!(migrate.field("redis.migrate.keys_failed"), Some("1"));
+        assert_eq!(migrate.field("redis.command_count"), Some("2"));
+        assert_eq!(migrate.field("otel.status_code"), Some("OK"));
+
+        // Both the SCAN calls and the copies nest under the migration span
+        let commands: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .collect();
+        assert_eq!(commands.len(), 5);
+        assert!(commands.iter().all(|span| span.parent == Some(migrate.id)));
+
+        let cursors: Vec<_> = migrate
+            .events
+            .iter()
+            .map(|event| event.get("redis.migrate.cursor").unwrap().as_str())
+            .collect();
+        assert_eq!(cursors, ["17", "0"]);
+        let processed: Vec<_> = migrate
+            .events
+            .iter()
+            .map(|event| event.get("redis.progress.processed").unwrap().as_str())
+            .collect();
+        assert_eq!(processed, ["2", "3"]);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_collapse_subcommands() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let config = InstrumentationConfig::new().with_collapse_subcommands(true);
+        assert!(config.collapse_subcommands());
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Array(vec![])),
+            Ok(Value::Array(vec![])),
+            Ok(Value::Array(vec![])),
+        ]);
+        let mut conn = InstrumentedSyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        let ((), capture) = test_support::capture(|| {
+            let config_get = redis::cmd("CONFIG").arg("GET").arg("maxmemory").clone();
+            conn.req_command_raw(&config_get, "CONFIG GET", &[])
+                .unwrap();
+            conn.req_command(redis::cmd("FT.SEARCH").arg("idx").arg("*"))
+                .unwrap();
+            conn.req_command(redis::cmd("config").arg("set").arg("a").arg("b"))
+                .unwrap();
+        });
+
+        let operations: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .map(|span| span.field("db.operation").unwrap().to_owned())
+            .collect();
+        assert_eq!(operations, ["CONFIG", "FT", "CONFIG"]);
+        assert!(capture.by_otel_name("redis config").is_some());
+        assert!(capture.by_otel_name("redis ft").is_some());
+
+        // Without the option, the raw operation and module command are kept
+        let mock = MockSyncConnection::with_replies([Ok(Value::Array(vec![]))]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+        let ((), capture) = test_support::capture(|| {
+            conn.req_command(redis::cmd("FT.SEARCH").arg("idx").arg("*"))
+                .unwrap();
+        });
+        assert!(capture.by_otel_name("redis ft.search").is_some());
+    }
+
+    #[test]
+    fn test_statement_capture() {
+        use crate::config::StatementCapture;
+
+        fn statement(cmd: &redis::Cmd, config: &InstrumentationConfig) -> Option<String> {
+            common::extract_command_attributes_with_config(cmd, config)
+                .into_iter()
+                .find(|attribute| attribute.key.as_str() == "db.statement")
+                .map(|attribute| attribute.value.to_string())
+        }
+
+        let get = redis::cmd("GET").arg("user:123").clone();
+        assert_eq!(statement(&get, &InstrumentationConfig::new()), None);
+
+        let operation_only =
+            InstrumentationConfig::new().with_statement_capture(StatementCapture::OperationOnly);
+        assert_eq!(statement(&get, &operation_only).as_deref(), Some("GET"));
+
+        let full = InstrumentationConfig::new().with_statement_capture(StatementCapture::Full);
+        assert_eq!(
+            full.statement_max_length(),
+            config::DEFAULT_STATEMENT_MAX_LENGTH
+        );
+        assert_eq!(statement(&get, &full).as_deref(), Some("GET user:123"));
+        let set = redis::cmd("SET")
+            .arg("k")
+            .arg("v")
+            .arg("EX")
+            .arg(60)
+            .clone();
+        assert_eq!(statement(&set, &full).as_deref(), Some("SET k v EX 60"));
+
+        // Credentials are masked
+        let auth = redis::cmd("AUTH").arg("admin").arg("hunter2").clone();
+        assert_eq!(statement(&auth, &full).as_deref(), Some("AUTH ? ?"));
+        let hello = redis::cmd("HELLO")
+            .arg(3)
+            .arg("AUTH")
+            .arg("admin")
+            .arg("hunter2")
+            .clone();
+        assert_eq!(
+            statement(&hello, &full).as_deref(),
+            Some("HELLO 3 AUTH ? ?")
+        );
+        let hello = redis::cmd("HELLO").arg(3).clone();
+        assert_eq!(statement(&hello, &full).as_deref(), Some("HELLO 3"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_statement_truncation() {
+        use crate::config::StatementCapture;
+
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let config = InstrumentationConfig::new()
+            .with_statement_capture(StatementCapture::Full)
+            .with_statement_max_length(10);
+        let mock = MockSyncConnection::with_replies([Ok(Value::Okay), Ok(Value::Nil)]);
+        let mut conn =
+            InstrumentedSyncConnection::with_config(mock, std::sync::Arc::new(config.clone()));
+        let ((), capture) = test_support::capture(|| {
+            conn.req_command(redis::cmd("SET").arg("key").arg("a long value"))
+                .unwrap();
+            conn.req_command(redis::cmd("GET").arg("key")).unwrap();
+        });
+
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("db.statement"), Some("SET key a…"));
+        assert_eq!(set.field("db.statement").unwrap().chars().count(), 10);
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.statement"), Some("GET key"));
+
+        // Truncation counts characters, not bytes
+        let config = config.with_statement_max_length(5);
+        let cmd = redis::cmd("GET").arg("ключ").clone();
+        let attributes = common::extract_command_attributes_with_config(&cmd, &config);
+        let statement = attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == "db.statement")
+            .unwrap();
+        assert_eq!(statement.value.to_string(), "GET …");
+    }
+
+    #[test]
+    fn test_cursor_commands_keep_their_name() {
+        let commands = [
+            (
+                "SCAN",
+                redis::cmd("SCAN")
+                    .cursor_arg(0)
+                    .arg("MATCH")
+                    .arg("user:*")
+                    .clone(),
+            ),
+            (
+                "HSCAN",
+                redis::cmd("HSCAN").arg("myhash").cursor_arg(0).clone(),
+            ),
+            (
+                "SSCAN",
+                redis::cmd("SSCAN").arg("myset").cursor_arg(0).clone(),
+            ),
+            (
+                "ZSCAN",
+                redis::cmd("ZSCAN").arg("myzset").cursor_arg(0).clone(),
+            ),
+        ];
+
+        for (expected, cmd) in commands {
+            let attributes = extract_command_attributes(&cmd);
+            let operation = attributes
+                .iter()
+                .find(|attribute| {
+                    attribute.key.as_str()
+                        == opentelemetry_semantic_conventions::attribute::DB_OPERATION_NAME
+                })
+                .map(|attribute| attribute.value.to_string());
+            assert_eq!(operation.as_deref(), Some(expected));
+
+            let config = InstrumentationConfig::new().with_operation_case(OperationCase::Preserve);
+            let (_, capture) = test_support::capture(|| {
+                let _ = common::create_command_span_with_config(&cmd, &config);
+            });
+            let span = capture.by_name("redis_command").unwrap();
+            assert_eq!(span.field("db.operation"), Some(expected));
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_begin_command_returns_redis_span() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use opentelemetry::KeyValue;
+        use redis::{ConnectionLike, ErrorKind, RedisError, Value};
+
+        let config = InstrumentationConfig::new().with_default_latency_buckets();
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::BulkString(b"alice".to_vec())),
+            Err(RedisError::from((ErrorKind::ResponseError, "WRONGTYPE"))),
+        ]);
+        let mut conn = InstrumentedSyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        let ((), capture) = test_support::capture(|| {
+            let get = redis::cmd("GET").arg("user:1").clone();
+            let command = conn.begin_command(&get);
+            assert!(command
+                .attributes()
+                .iter()
+                .any(|attribute| attribute.key.as_str() == "redis.idempotent"));
+            let result = conn.inner_mut().req_command(&get);
+            command.record_result(&result);
+            command.set_attribute(KeyValue::new("db.redis.database_name", "users"));
+            command.set_attribute(KeyValue::new("app.cache.layer", "l2"));
+            command.finish();
+
+            let incr = redis::cmd("INCR").arg("user:1").clone();
+            let command = conn.begin_command(&incr);
+            let result = conn.inner_mut().req_command(&incr);
+            command.record_result(&result);
+        });
+
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.operation"), Some("GET"));
+        assert_eq!(get.field("redis.idempotent"), Some("true"));
+        assert_eq!(get.field("otel.status_code"), Some("OK"));
+        assert!(get.field("redis.latency_bucket").is_some());
+        assert_eq!(get.field("db.redis.database_name"), Some("users"));
+        assert_eq!(
+            get.events[0].get("app.cache.layer").map(String::as_str),
+            Some("l2")
+        );
+
+        let incr = capture.by_otel_name("redis incr").unwrap();
+        assert_eq!(incr.field("otel.status_code"), Some("ERROR"));
+        assert_eq!(incr.field("error.type"), Some("response_error"));
+        // The commands went through the inner connection only
+        assert_eq!(conn.inner().commands.len(), 2);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_expire_unit_attributes() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let in_an_hour = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64
+            + 3_600_000;
+        let replies = [
+            Ok(Value::Int(1)),
+            Ok(Value::Int(1)),
+            Ok(Value::Okay),
+            Ok(Value::Nil),
+        ];
+        let (_, capture) = capture_sync(InstrumentationConfig::new(), replies, |conn| {
+            conn.expire("session", 30).unwrap();
+            conn.req_command(redis::cmd("PEXPIREAT").arg("session").arg(in_an_hour))
+                .unwrap();
+            conn.req_command(redis::cmd("set").arg("k").arg("v").arg("px").arg(250))
+                .unwrap();
+            conn.req_command(redis::cmd("GETEX").arg("k").arg("PERSIST"))
+                .unwrap();
+        });
+
+        let expire = capture.by_otel_name("redis expire").unwrap();
+        assert_eq!(expire.field("redis.expire.unit"), Some("seconds"));
+        assert_eq!(expire.field("redis.expire.ttl_ms"), Some("30000"));
+        assert_eq!(expire.field("redis.expire.absolute"), Some("false"));
+
+        let pexpireat = capture.by_otel_name("redis pexpireat").unwrap();
+        assert_eq!(pexpireat.field("redis.expire.unit"), Some("milliseconds"));
+        assert_eq!(pexpireat.field("redis.expire.absolute"), Some("true"));
+        let ttl_ms: i64 = pexpireat
+            .field("redis.expire.ttl_ms")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(ttl_ms > 3_500_000 && ttl_ms <= 3_600_000);
+
+        let set = capture.by_otel_name("redis set").unwrap();
+        assert_eq!(set.field("redis.expire.unit"), Some("milliseconds"));
+        assert_eq!(set.field("redis.expire.ttl_ms"), Some("250"));
+
+        let getex = capture.by_otel_name("redis getex").unwrap();
+        assert_eq!(getex.field("redis.expire.unit"), None);
+    }
+
+    #[test]
+    fn test_command_expiry_parsing() {
+        use crate::common::command_expiry;
+
+        let expiry = command_expiry(redis::cmd("EXPIREAT").arg("k").arg(1)).unwrap();
+        assert_eq!((expiry.unit, expiry.absolute), ("seconds", true));
+        // Times in the past leave no TTL
+        assert_eq!(expiry.ttl_ms, 0);
+
+        let getex = redis::cmd("GETEX").arg("k").arg("EX").arg(5).clone();
+        assert_eq!(command_expiry(&getex).unwrap().ttl_ms, 5000);
+        // A value that happens to read EX isn't an option
+        let set = redis::cmd("SET").arg("k").arg("EX").arg("NX").clone();
+        assert_eq!(command_expiry(&set), None);
+        assert_eq!(
+            command_expiry(redis::cmd("EXPIRE").arg("k").arg("soon")),
+            None
+        );
+        assert_eq!(command_expiry(redis::cmd("TTL").arg("k")), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_key_count_attribute() {
+        use crate::common::key_count;
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let replies = [
+            Ok(Value::Int(3)),
+            Ok(Value::Okay),
+            Ok(Value::Int(2)),
+            Ok(Value::Nil),
+        ];
+        let (_, capture) = capture_sync(InstrumentationConfig::new(), replies, |conn| {
+            conn.del(&["k1", "k2", "k3"]).unwrap();
+            conn.req_command(redis::cmd("MSET").arg("k1").arg("v1").arg("k2").arg("v2"))
+                .unwrap();
+            conn.sadd("s", &["a", "b"]).unwrap();
+            conn.req_command(redis::cmd("GET").arg("k1")).unwrap();
+        });
+
+        let count = |name: &str| {
+            capture
+                .by_otel_name(name)
+                .unwrap()
+                .field("db.redis.key_count")
+                .map(str::to_owned)
+        };
+        assert_eq!(count("redis del").as_deref(), Some("3"));
+        assert_eq!(count("redis mset").as_deref(), Some("2"));
+        assert_eq!(count("redis sadd").as_deref(), Some("2"));
+        assert_eq!(count("redis get"), None);
+
+        // Field/value pairs and ZADD flags count as logical units
+        let hset = redis::cmd("HSET")
+            .arg("h")
+            .arg("f1")
+            .arg(1)
+            .arg("f2")
+            .arg(2)
+            .clone();
+        assert_eq!(key_count(&hset), Some(2));
+        let zadd = redis::cmd("ZADD")
+            .arg("z")
+            .arg("NX")
+            .arg("CH")
+            .arg(1)
+            .arg("a")
+            .clone();
+        assert_eq!(key_count(&zadd), Some(1));
+        assert_eq!(key_count(&redis::cmd("SADD")), None);
+    }
+
+    #[test]
+    fn test_db_system_attribute_can_be_disabled() {
+        use opentelemetry_semantic_conventions::attribute::{DB_OPERATION_NAME, DB_SYSTEM_NAME};
+
+        let config = InstrumentationConfig::new().with_db_system_attribute(false);
+        assert!(!config.db_system_attribute());
+        assert!(InstrumentationConfig::new().db_system_attribute());
+
+        let cmd = redis::cmd("GET").arg("key").clone();
+        let attributes = common::extract_command_attributes_with_config(&cmd, &config);
+        assert!(!attributes
+            .iter()
+            .any(|attribute| attribute.key.as_str() == DB_SYSTEM_NAME));
+        assert!(attributes
+            .iter()
+            .any(|attribute| attribute.key.as_str() == DB_OPERATION_NAME));
+
+        let targeted = config.clone().with_tracing_target("myapp::redis");
+        let (_, capture) = test_support::capture(|| {
+            let _ = common::create_command_span_with_config(&cmd, &config);
+            let _ = common::create_command_span_with_config(&cmd, &targeted);
+            let _ = common::create_command_span(&cmd);
+        });
+        let spans = capture.spans();
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].field("db.system"), None);
+        assert_eq!(spans[1].field("db.system"), None);
+        assert_eq!(spans[1].field("db.operation"), Some("GET"));
+        assert_eq!(spans[2].field("db.system"), Some("redis"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_db_system_attribute_disabled_on_raw_and_transaction_spans() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let config = InstrumentationConfig::new().with_db_system_attribute(false);
+        let replies = [
+            Ok(Value::Int(1)),
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::Array(vec![Value::Okay])),
+        ];
+        let (_, capture) = capture_sync(config, replies, |conn| {
+            conn.req_command_raw(redis::cmd("INCR").arg("hits"), "CACHE_HIT", &[])
+                .unwrap();
+            let _: Vec<Value> = conn
+                .transaction(|pipe| {
+                    pipe.set("key", "value");
+                })
+                .unwrap();
+        });
+
+        let raw = capture.by_otel_name("redis cache_hit").unwrap();
+        assert_eq!(raw.field("db.operation"), Some("CACHE_HIT"));
+        assert_eq!(raw.field("db.system"), None);
+        let multi = capture.by_otel_name("redis MULTI").unwrap();
+        assert_eq!(multi.field("db.system"), None);
+        assert!(capture
+            .spans()
+            .iter()
+            .all(|span| span.field("db.system").is_none()));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_req_command_raw_records_like_req_command() {
+        use crate::config::SpanNameStrategy;
+        use crate::test_support::capture_sync;
+        use redis::{ErrorKind, RedisError, Value};
+        use std::sync::{Arc, Mutex};
+
+        let hook_span = Arc::new(Mutex::new(None));
+        let recorded = hook_span.clone();
+        let config = InstrumentationConfig::new()
+            .with_span_name_strategy(SpanNameStrategy::OperationOnly)
+            .with_tracing_target("app::redis")
+            .with_error_hook(move |_, _| {
+                let current = tracing::Span::current();
+                *recorded.lock().unwrap() = current.metadata().map(|meta| meta.name());
+            });
+        let replies = [
+            Ok(Value::Okay),
+            Err(RedisError::from((
+                ErrorKind::TypeError,
+                "wrong kind of value",
+            ))),
+        ];
+        let (_, capture) = capture_sync(config, replies, |conn| {
+            conn.req_command(redis::cmd("SELECT").arg(3)).unwrap();
+            conn.req_command_raw(redis::cmd("INCR").arg("hits"), "CACHE_HIT", &[])
+                .unwrap_err();
+        });
+
+        let raw = capture.by_otel_name("CACHE_HIT").unwrap();
+        assert_eq!(raw.target, "app::redis");
+        assert_eq!(raw.field("db.operation"), Some("CACHE_HIT"));
+        // The database selected earlier on the connection is recorded
+        assert_eq!(raw.field("db.redis.database_index"), Some("3"));
+        assert_eq!(raw.field("otel.status_code"), Some("ERROR"));
+        // The error hook runs within the command span
+        assert_eq!(*hook_span.lock().unwrap(), Some("redis_command"));
+
+        // Writes-only instrumentation skips raw reads as well
+        let config = InstrumentationConfig::new().with_writes_only(true);
+        let (_, capture) = capture_sync(config, [Ok(Value::Nil)], |conn| {
+            conn.req_command_raw(redis::cmd("GET").arg("key"), "GET", &[])
+                .unwrap();
+        });
+        assert!(capture.by_name("redis_command").is_none());
+    }
+
+    #[test]
+    fn test_response_size_sums_string_payloads() {
+        use crate::common::response_size;
+        use redis::Value;
+
+        assert_eq!(
+            response_size(&Value::BulkString(b"hello".to_vec())),
+            Some(5)
+        );
+        assert_eq!(response_size(&Value::Array(vec![])), Some(0));
+        let mget = Value::Array(vec![
+            Value::BulkString(b"abc".to_vec()),
+            Value::Nil,
+            Value::BulkString(b"defgh".to_vec()),
+        ]);
+        assert_eq!(response_size(&mget), Some(8));
+        let hgetall = Value::Map(vec![
+            (
+                Value::BulkString(b"field".to_vec()),
+                Value::BulkString(b"value".to_vec()),
+            ),
+            (Value::SimpleString("n".into()), Value::Int(42)),
+        ]);
+        assert_eq!(response_size(&hgetall), Some(11));
+        let nested = Value::Array(vec![
+            Value::BulkString(b"0".to_vec()),
+            Value::Array(vec![Value::BulkString(b"key".to_vec()), Value::Int(1)]),
+        ]);
+        assert_eq!(response_size(&nested), Some(4));
+
+        assert_eq!(response_size(&Value::Int(7)), None);
+        assert_eq!(response_size(&Value::Nil), None);
+        assert_eq!(response_size(&Value::Okay), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_response_size_is_recorded_when_enabled() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let replies = || {
+            [
+                Ok(Value::BulkString(b"payload".to_vec())),
+                Ok(Value::Array(vec![
+                    Value::BulkString(b"ab".to_vec()),
+                    Value::BulkString(b"cde".to_vec()),
+                ])),
+                Ok(Value::Int(3)),
+            ]
+        };
+        let config = InstrumentationConfig::new().with_response_size(true);
+        assert!(config.response_size());
+        assert!(!InstrumentationConfig::new().response_size());
+
+        let (_, capture) = capture_sync(config, replies(), |conn| {
+            let _: String = conn.get("a").unwrap();
+            let _: Vec<String> = conn.lrange("l", 0, -1).unwrap();
+            let _: i64 = conn.incr("n").unwrap();
+        });
+        let size = |name| {
+            capture
+                .by_otel_name(name)
+                .unwrap()
+                .field("db.redis.response.size")
+                .map(str::to_owned)
+        };
+        assert_eq!(size("redis get").as_deref(), Some("7"));
+        assert_eq!(size("redis lrange").as_deref(), Some("5"));
+        assert_eq!(size("redis incr"), None);
+
+        let (_, plain) = capture_sync(InstrumentationConfig::new(), replies(), |conn| {
+            let _: String = conn.get("a").unwrap();
+        });
+        let get = plain.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("db.redis.response.size"), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_list_commands() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let items = Value::Array(vec![
+            Value::BulkString(b"a".to_vec()),
+            Value::BulkString(b"b".to_vec()),
+        ]);
+        let replies = [
+            Ok(Value::Int(2)),
+            Ok(Value::Int(3)),
+            Ok(Value::BulkString(b"a".to_vec())),
+            Ok(items.clone()),
+            Ok(Value::Int(1)),
+            Ok(items),
+        ];
+        let (conn, capture) = capture_sync(InstrumentationConfig::new(), replies, |conn| {
+            assert_eq!(conn.lpush("queue", &["a", "b"]).unwrap(), 2);
+            assert_eq!(conn.rpush("queue", "c").unwrap(), 3);
+            let head: Option<String> = conn.lpop("queue", None).unwrap();
+            assert_eq!(head.as_deref(), Some("a"));
+            let tail: Vec<String> = conn.rpop("queue", Some(2)).unwrap();
+            assert_eq!(tail, ["a", "b"]);
+            assert_eq!(conn.llen("queue").unwrap(), 1);
+            let range: Vec<String> = conn.lrange("queue", 0, -1).unwrap();
+            assert_eq!(range, ["a", "b"]);
+        });
+
+        for operation in ["LPUSH", "RPUSH", "LPOP", "RPOP", "LLEN", "LRANGE"] {
+            let command = capture.method_command(&operation.to_lowercase(), operation);
+            assert_eq!(command.field("db.operation"), Some(operation));
+        }
+        let commands = &conn.inner().commands;
+        assert_eq!(
+            commands[3],
+            redis::cmd("RPOP").arg("queue").arg(2).get_packed_command()
+        );
+        assert_eq!(
+            commands[5],
+            redis::cmd("LRANGE")
+                .arg("queue")
+                .arg(0)
+                .arg(-1)
+                .get_packed_command()
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_list_commands() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Int(1)),
+            Ok(Value::Int(2)),
+            Ok(Value::Nil),
+            Ok(Value::BulkString(b"b".to_vec())),
+            Ok(Value::Int(0)),
+            Ok(Value::Array(vec![])),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        assert_eq!(conn.lpush("queue", "a").await.unwrap(), 1);
+        assert_eq!(conn.rpush("queue", "b").await.unwrap(), 2);
+        let head: Option<String> = conn.lpop("queue", None).await.unwrap();
+        assert_eq!(head, None);
+        let tail: String = conn.rpop("queue", None).await.unwrap();
+        assert_eq!(tail, "b");
+        assert_eq!(conn.llen("queue").await.unwrap(), 0);
+        let range: Vec<String> = conn.lrange("queue", 0, 10).await.unwrap();
+        assert!(range.is_empty());
+
+        for operation in ["LPUSH", "RPUSH", "LPOP", "RPOP", "LLEN", "LRANGE"] {
+            let command = capture
+                .by_otel_name(&format!("redis {}", operation.to_lowercase()))
+                .unwrap();
+            assert_eq!(command.field("db.operation"), Some(operation));
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_shard_resolver_records_shard_of_first_key() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let config = InstrumentationConfig::new().with_shard_resolver(|key| match key {
+            "user:1" => 3,
+            _ => 0,
+        });
+        assert!(config.shard_resolver().is_some());
+        assert!(InstrumentationConfig::new().shard_resolver().is_none());
+
+        let replies = [
+            Ok(Value::BulkString(b"alice".to_vec())),
+            Ok(Value::SimpleString("PONG".into())),
+        ];
+        let (_, capture) = capture_sync(config, replies, |conn| {
+            let _: String = conn.get("user:1").unwrap();
+            conn.req_command(&redis::cmd("PING")).unwrap();
+        });
+
+        let get = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(get.field("redis.shard"), Some("3"));
+        let ping = capture.by_otel_name("redis ping").unwrap();
+        assert_eq!(ping.field("redis.shard"), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sorted_set_commands() {
+        use crate::common::ZAddFlag;
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let members = Value::Array(vec![Value::BulkString(b"alice".to_vec())]);
+        let replies = [
+            Ok(Value::Int(1)),
+            Ok(Value::BulkString(b"42".to_vec())),
+            Ok(members.clone()),
+            Ok(members),
+            Ok(Value::Int(1)),
+        ];
+        let (conn, capture) = capture_sync(InstrumentationConfig::new(), replies, |conn| {
+            assert_eq!(conn.zadd("board", "alice", 42, &[ZAddFlag::Nx]).unwrap(), 1);
+            let score: Option<f64> = conn.zscore("board", "alice").unwrap();
+            assert_eq!(score, Some(42.0));
+            let top: Vec<String> = conn.zrange("board", 0, -1).unwrap();
+            assert_eq!(top, ["alice"]);
+            let scored: Vec<String> = conn.zrangebyscore("board", "(10", "+inf").unwrap();
+            assert_eq!(scored, ["alice"]);
+            assert_eq!(conn.zrem("board", &["alice", "bob"]).unwrap(), 1);
+        });
+
+        for operation in ["ZADD", "ZSCORE", "ZRANGE", "ZRANGEBYSCORE", "ZREM"] {
+            capture.method_command(&operation.to_lowercase(), operation);
+        }
+        let zadd = capture.by_name("zadd").unwrap();
+        assert_eq!(zadd.field("redis.zset.flags"), Some("NX"));
+        let zrangebyscore = capture.by_name("zrangebyscore").unwrap();
+        assert_eq!(
+            zrangebyscore.field("redis.zset.min_exclusive"),
+            Some("true")
+        );
+
+        let packed = |cmd: &mut redis::Cmd| cmd.get_packed_command();
+        let commands = &conn.inner().commands;
+        assert_eq!(
+            commands[0],
+            packed(
+                redis::cmd("ZADD")
+                    .arg("board")
+                    .arg("NX")
+                    .arg(42)
+                    .arg("alice")
+            )
+        );
+        assert_eq!(
+            commands[1],
+            packed(redis::cmd("ZSCORE").arg("board").arg("alice"))
+        );
+        assert_eq!(
+            commands[2],
+            packed(redis::cmd("ZRANGE").arg("board").arg(0).arg(-1))
+        );
+        assert_eq!(
+            commands[3],
+            packed(
+                redis::cmd("ZRANGEBYSCORE")
+                    .arg("board")
+                    .arg("(10")
+                    .arg("+inf")
+            )
+        );
+        assert_eq!(
+            commands[4],
+            packed(redis::cmd("ZREM").arg("board").arg("alice").arg("bob"))
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_sorted_set_lookups() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Nil),
+            Ok(Value::Array(vec![])),
+            Ok(Value::Int(0)),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let score: Option<f64> = conn.zscore("board", "carol").await.unwrap();
+        assert_eq!(score, None);
+        let top: Vec<String> = conn.zrange("board", 0, 9).await.unwrap();
+        assert!(top.is_empty());
+        assert_eq!(conn.zrem("board", "carol").await.unwrap(), 0);
+
+        for operation in ["ZSCORE", "ZRANGE", "ZREM"] {
+            let command = capture
+                .by_otel_name(&format!("redis {}", operation.to_lowercase()))
+                .unwrap();
+            assert_eq!(command.field("db.operation"), Some(operation));
+        }
+        assert_eq!(
+            conn.inner().commands[1],
+            redis::cmd("ZRANGE")
+                .arg("board")
+                .arg(0)
+                .arg(9)
+                .get_packed_command()
+        );
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_unix_timestamps_are_recorded_when_enabled() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+        use std::time::SystemTime;
+
+        let config = InstrumentationConfig::new().with_unix_timestamps(true);
+        assert!(config.unix_timestamps());
+        assert!(!InstrumentationConfig::new().unix_timestamps());
+
+        let before = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let ping = |conn: &mut crate::sync::InstrumentedSyncConnection<_>| {
+            conn.req_command(&redis::cmd("PING")).unwrap();
+        };
+        let (_, capture) = capture_sync(config, [Ok(Value::Okay)], ping);
+        let (_, plain) = capture_sync(InstrumentationConfig::new(), [Ok(Value::Okay)], ping);
+
+        let span = capture.by_otel_name("redis ping").unwrap();
+        let nanos = |field| span.field(field).unwrap().parse::<i64>().unwrap();
+        let (start, end) = (
+            nanos("redis.start_unix_nanos"),
+            nanos("redis.end_unix_nanos"),
+        );
+        assert!(start >= before);
+        assert!(end >= start);
+        let span = plain.by_otel_name("redis ping").unwrap();
+        assert_eq!(span.field("redis.start_unix_nanos"), None);
+        assert_eq!(span.field("redis.end_unix_nanos"), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_numeric_commands() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let replies = [
+            Ok(Value::Int(1)),
+            Ok(Value::Int(0)),
+            Ok(Value::Int(-5)),
+            Ok(Value::BulkString(b"2.5".to_vec())),
+        ];
+        let (conn, capture) = capture_sync(InstrumentationConfig::new(), replies, |conn| {
+            assert_eq!(conn.incr("visits").unwrap(), 1);
+            assert_eq!(conn.decr("visits").unwrap(), 0);
+            assert_eq!(conn.incr_by("visits", -5).unwrap(), -5);
+            assert_eq!(conn.incr_by_float("ratio", 2.5).unwrap(), 2.5);
+        });
+
+        for (method, operation) in [
+            ("incr", "INCR"),
+            ("decr", "DECR"),
+            ("incr_by", "INCRBY"),
+            ("incr_by_float", "INCRBYFLOAT"),
+        ] {
+            capture.method_command(method, operation);
+        }
+        let commands = &conn.inner().commands;
+        assert_eq!(
+            commands[0],
+            redis::cmd("INCR").arg("visits").get_packed_command()
+        );
+        assert_eq!(
+            commands[2],
+            redis::cmd("INCRBY")
+                .arg("visits")
+                .arg(-5)
+                .get_packed_command()
+        );
+        assert_eq!(
+            commands[3],
+            redis::cmd("INCRBYFLOAT")
+                .arg("ratio")
+                .arg(2.5)
+                .get_packed_command()
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_numeric_commands() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Int(1)),
+            Ok(Value::Int(0)),
+            Ok(Value::Int(10)),
+            Ok(Value::BulkString(b"-0.5".to_vec())),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        assert_eq!(conn.incr("visits").await.unwrap(), 1);
+        assert_eq!(conn.decr("visits").await.unwrap(), 0);
+        assert_eq!(conn.incr_by("visits", 10).await.unwrap(), 10);
+        assert_eq!(conn.incr_by_float("ratio", -0.5).await.unwrap(), -0.5);
+
+        for operation in ["INCR", "DECR", "INCRBY", "INCRBYFLOAT"] {
+            let command = capture
+                .by_otel_name(&format!("redis {}", operation.to_lowercase()))
+                .unwrap();
+            assert_eq!(command.field("db.operation"), Some(operation));
+        }
+        assert_eq!(
+            conn.inner().commands[1],
+            redis::cmd("DECR").arg("visits").get_packed_command()
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_multiplexed_send_packed_command() {
+        use futures_util::FutureExt;
+
+        let url = test_support::spawn_scripted_server(|name| match name {
+            "GET" => b"$5\r\nhello\r\n".to_vec(),
+            // Never answered, like a fire-and-forget send
+            "BLPOP" => Vec::new(),
+            _ => b"+OK\r\n".to_vec(),
+        });
+        let client = InstrumentedClient::new(redis::Client::open(url).unwrap());
+        let mut conn = client.get_multiplexed_async_connection().await.unwrap();
+
+        let (_guard, capture) = test_support::capture_default();
+        let reply = conn
+            .send_packed_command(redis::cmd("get").arg("greeting"))
+            .await
+            .unwrap();
+        assert_eq!(reply, redis::Value::BulkString(b"hello".to_vec()));
+        let pending = conn
+            .send_packed_command(redis::cmd("BLPOP").arg("queue").arg(0))
+            .now_or_never();
+        assert!(pending.is_none());
+
+        let spans: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "send_packed_command")
+            .collect();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].field("db.operation"), Some("GET"));
+        assert_eq!(spans[0].field("otel.name"), Some("redis get"));
+        assert_eq!(spans[0].field("redis.reply.received"), Some("true"));
+        assert_eq!(spans[0].field("otel.status_code"), Some("OK"));
+        assert_eq!(spans[1].field("db.operation"), Some("BLPOP"));
+        assert_eq!(spans[1].field("redis.reply.received"), Some("false"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_mget_and_mset() {
+        use crate::test_support::capture_sync;
+        use redis::Value;
+
+        let replies = [
+            Ok(Value::Array(vec![
+                Value::BulkString(b"1".to_vec()),
+                Value::Nil,
+                Value::BulkString(b"3".to_vec()),
+            ])),
+            Ok(Value::Okay),
+        ];
+        let (conn, capture) = capture_sync(InstrumentationConfig::new(), replies, |conn| {
+            let values: Vec<Option<String>> = conn.mget(&["a", "b", "c"]).unwrap();
+            assert_eq!(values, [Some("1".into()), None, Some("3".into())]);
+            conn.mset(&[("a", "1"), ("b", "2")]).unwrap();
+            let empty: Vec<String> = conn.mget::<&str, _>(&[]).unwrap();
+            assert!(empty.is_empty());
+        });
+
+        let mget = capture.by_name("mget").unwrap();
+        assert_eq!(mget.field("db.operation"), Some("MGET"));
+        assert_eq!(mget.field("redis.keys.count"), Some("3"));
+        let mset = capture.by_name("mset").unwrap();
+        assert_eq!(mset.field("db.operation"), Some("MSET"));
+        assert_eq!(mset.field("redis.keys.count"), Some("2"));
+        // The command span carries the command attribute, like every multi-key command
+        let command = capture.by_otel_name("redis mset").unwrap();
+        assert_eq!(command.field("db.redis.key_count"), Some("2"));
+
+        let commands = &conn.inner().commands;
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[0],
+            redis::cmd("MGET")
+                .arg("a")
+                .arg("b")
+                .arg("c")
+                .get_packed_command()
+        );
+        assert_eq!(
+            commands[1],
+            redis::cmd("MSET")
+                .arg("a")
+                .arg("1")
+                .arg("b")
+                .arg("2")
+                .get_packed_command()
+        );
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_mget_and_mset() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::Array(vec![
+                Value::BulkString(b"1".to_vec()),
+                Value::BulkString(b"2".to_vec()),
+                Value::Nil,
+            ])),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        conn.mset(&[("a", "1"), ("b", "2")]).await.unwrap();
+        let values: Vec<Option<i64>> = conn.mget(&["a", "b", "c"]).await.unwrap();
+        assert_eq!(values, [Some(1), Some(2), None]);
+
+        for (method, count) in [("mset", "2"), ("mget", "3")] {
+            let span = capture.by_name(method).unwrap();
+            assert_eq!(
+                span.field("db.operation"),
+                Some(method.to_uppercase().as_str())
+            );
+            assert_eq!(span.field("redis.keys.count"), Some(count));
+        }
+    }
+
+    #[test]
+    fn test_numkeys_attribute() {
+        use crate::common::{key_count, numkeys};
+
+        let eval = redis::cmd("EVAL")
+            .arg("return redis.call('GET', KEYS[1])")
+            .arg(2)
+            .arg("a")
+            .arg("b")
+            .arg("arg")
+            .clone();
+        assert_eq!(numkeys(&eval), Some(2));
+        assert_eq!(key_count(&eval), Some(2));
+        let attributes = extract_command_attributes(&eval);
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.key.as_str() == key)
+                .map(|attribute| attribute.value.to_string())
+        };
+        assert_eq!(attribute("db.redis.numkeys").as_deref(), Some("2"));
+        assert_eq!(attribute("db.redis.key_count").as_deref(), Some("2"));
+
+        let store = redis::cmd("ZUNIONSTORE")
+            .arg("dest")
+            .arg(2)
+            .arg("z1")
+            .arg("z2")
+            .clone();
+        assert_eq!(numkeys(&store), Some(2));
+        assert_eq!(key_count(&store), Some(3));
+        assert_eq!(
+            numkeys(redis::cmd("LMPOP").arg(1).arg("list").arg("LEFT")),
+            Some(1)
+        );
+        assert_eq!(numkeys(redis::cmd("FCALL").arg("fn").arg(0)), Some(0));
+
+        // Malformed numkeys skip the attribute
+        let malformed = redis::cmd("EVAL")
+            .arg("return 1")
+            .arg("two")
+            .arg("a")
+            .clone();
+        assert_eq!(numkeys(&malformed), None);
+        assert_eq!(key_count(&malformed), None);
+        assert!(!extract_command_attributes(&malformed)
+            .iter()
+            .any(|attribute| attribute.key.as_str() == "db.redis.numkeys"));
+        assert_eq!(numkeys(redis::cmd("EVAL").arg("return 1").arg(-1)), None);
+        assert_eq!(
+            numkeys(redis::cmd("EVAL").arg("return 1").arg(3).arg("a")),
+            None
+        );
+        assert_eq!(numkeys(redis::cmd("GET").arg("2")), None);
+    }
+
+    #[test]
+    fn test_command_fingerprint_ignores_literal_values() {
+        use crate::common::command_fingerprint;
+
+        let fingerprint = |cmd: &Cmd| {
+            extract_command_attributes(cmd)
+                .into_iter()
+                .find(|attribute| attribute.key.as_str() == "redis.command.fingerprint")
+                .map(|attribute| attribute.value.to_string())
+        };
+        let get_a = fingerprint(redis::cmd("GET").arg("user:1"));
+        assert!(get_a.is_some());
+        assert_eq!(get_a, fingerprint(redis::cmd("GET").arg("session:abc")));
+        assert_ne!(get_a, fingerprint(redis::cmd("SET").arg("user:1").arg("v")));
+        assert_ne!(get_a, fingerprint(redis::cmd("MGET").arg("a").arg("b")));
+
+        // The kind of each argument is part of the shape, its value is not
+        assert_eq!(
+            command_fingerprint(redis::cmd("EXPIRE").arg("a").arg(10)),
+            command_fingerprint(redis::cmd("EXPIRE").arg("b").arg(60))
+        );
+        assert_ne!(
+            command_fingerprint(redis::cmd("SCAN").cursor_arg(0)),
+            command_fingerprint(redis::cmd("SCAN").arg("0"))
+        );
+        assert_eq!(command_fingerprint(&Cmd::new()), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_span_enricher_adds_attributes() {
+        use crate::test_support::capture_sync;
+        use opentelemetry::KeyValue;
+        use redis::Value;
+
+        struct Tenant;
+
+        impl SpanEnricher for Tenant {
+            fn enrich(&self, _cmd: &redis::Cmd, attributes: &mut Vec<KeyValue>) {
+                attributes.push(KeyValue::new("tenant.id", "acme"));
+                attributes.push(KeyValue::new("redis.criticality", "high"));
+            }
+        }
+
+        let config = InstrumentationConfig::new().with_span_enricher(Tenant);
+        assert!(config.span_enricher().is_some());
+        assert!(InstrumentationConfig::new().span_enricher().is_none());
+        let client = InstrumentedClient::with_config(
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            config,
+        );
+
+        let cmd = redis::cmd("GET").arg("a").clone();
+        let (_, attributes) = common::create_command_span_with_config(&cmd, client.config());
+        assert!(attributes.contains(&KeyValue::new("tenant.id", "acme")));
+
+        let replies = [Ok(Value::BulkString(b"1".to_vec()))];
+        let (_, capture) = capture_sync(client.config().clone(), replies, |conn| {
+            conn.req_command(&cmd).unwrap();
+        });
+
+        let span = capture.by_otel_name("redis get").unwrap();
+        // Declared keys land on the span, the rest on an attributes event
+        assert_eq!(span.field("redis.criticality"), Some("high"));
+        let event = &span.events[0];
+        assert_eq!(
+            event.get("message").map(String::as_str),
+            Some("redis.attributes")
+        );
+        assert_eq!(event.get("tenant.id").map(String::as_str), Some("acme"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[test]
+    fn test_pubsub_subscription_tracking() {
+        use crate::aio::Subscriptions;
+
+        let subscriptions = Subscriptions::new();
+        let shared = subscriptions.clone();
+        subscriptions.add(vec![b"news".to_vec(), b"sports".to_vec()], false);
+        subscriptions.add(vec![b"news".to_vec()], false);
+        subscriptions.add(vec![b"user:*".to_vec()], true);
+        assert_eq!(shared.channels(), [b"news".to_vec(), b"sports".to_vec()]);
+        assert_eq!(shared.patterns(), [b"user:*".to_vec()]);
+        assert_eq!(shared.len(), 3);
+
+        // Unsubscribing a channel leaves a pattern of the same name alone
+        subscriptions.remove(vec![b"news".to_vec(), b"user:*".to_vec()], false);
+        assert_eq!(shared.channels(), [b"sports".to_vec()]);
+        assert_eq!(shared.patterns(), [b"user:*".to_vec()]);
+
+        // No names drops every subscription of that kind
+        subscriptions.remove(Vec::new(), true);
+        assert!(shared.patterns().is_empty());
+        subscriptions.remove(Vec::new(), false);
+        assert!(shared.is_empty());
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_pubsub_ping_and_reconnect() {
+        fn respond(command: &str) -> Vec<u8> {
+            match command {
+                "SUBSCRIBE" => b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n".to_vec(),
+                "PSUBSCRIBE" => b"*3\r\n$10\r\npsubscribe\r\n$6\r\nuser:*\r\n:2\r\n".to_vec(),
+                "PING" => b"*2\r\n$4\r\npong\r\n$0\r\n\r\n".to_vec(),
+                _ => b"+OK\r\n".to_vec(),
+            }
+        }
+
+        let client = redis::Client::open(test_support::spawn_scripted_server(respond)).unwrap();
+        let instrumented = InstrumentedClient::new(client);
+        let (mut sink, _stream) = instrumented.get_async_pubsub_split().await.unwrap();
+
+        let (_guard, capture) = test_support::capture_default();
+        sink.subscribe("news").await.unwrap();
+        sink.psubscribe("user:*").await.unwrap();
+        let _: redis::Value = sink.ping().await.unwrap();
+        assert_eq!(sink.subscriptions().len(), 2);
+
+        let pubsub = instrumented.inner().get_async_pubsub().await.unwrap();
+        let stream = sink.reconnect(pubsub).await.unwrap();
+        assert_eq!(stream.active_channels().get(), 2);
+
+        let ping = capture.by_otel_name("redis ping").unwrap();
+        assert_eq!(ping.field("db.operation"), Some("PING"));
+        assert_eq!(ping.field("redis.pubsub.active_channels"), Some("2"));
+        assert_eq!(ping.field("otel.status_code"), Some("OK"));
+        let reconnect = capture.by_otel_name("redis reconnect").unwrap();
+        assert_eq!(
+            reconnect.field("redis.pubsub.resubscribed_channels"),
+            Some("2")
+        );
+        assert_eq!(reconnect.field("redis.pubsub.active_channels"), Some("2"));
+        assert_eq!(reconnect.field("otel.status_code"), Some("OK"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_pubsub_ping() {
+        fn respond(command: &str) -> Vec<u8> {
+            match command {
+                "SUBSCRIBE" => b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n".to_vec(),
+                "PING" => b"*2\r\n$4\r\npong\r\n$0\r\n\r\n".to_vec(),
+                _ => b"+OK\r\n".to_vec(),
+            }
+        }
+
+        let client = redis::Client::open(test_support::spawn_scripted_server(respond)).unwrap();
+        let mut conn = InstrumentedClient::new(client).get_connection().unwrap();
+
+        let ((), capture) = test_support::capture(|| {
+            let mut pubsub = conn.as_pubsub();
+            pubsub.subscribe("news").unwrap();
+            let _: redis::Value = pubsub.ping().unwrap();
+        });
+
+        let ping = capture.by_name("ping").unwrap();
+        assert_eq!(ping.field("db.operation"), Some("PING"));
+    }
+
+    #[test]
+    fn test_apply_span_attributes_records_arrays() {
+        use opentelemetry::{Array, KeyValue, StringValue, Value};
+
+        let config = InstrumentationConfig::new();
+        let ((), capture) = test_support::capture(|| {
+            let (span, _) = common::create_command_span_with_config(&redis::cmd("MGET"), &config);
+            common::apply_span_attributes(
+                &span,
+                &[
+                    KeyValue::new(
+                        "db.statement",
+                        Value::Array(Array::String(vec![
+                            StringValue::from("user:1"),
+                            StringValue::from("user:2"),
+                        ])),
+                    ),
+                    KeyValue::new("redis.criticality", Value::Array(Array::I64(vec![1, 2]))),
+                    KeyValue::new("db.redis.key_count", 2_i64),
+                ],
+            );
+        });
+
+        let span = capture.by_otel_name("redis mget").unwrap();
+        assert_eq!(span.field("db.statement"), Some("user:1,user:2"));
+        assert_eq!(span.field("redis.criticality"), Some("1,2"));
+        assert_eq!(span.field("db.redis.key_count"), Some("2"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_command_weights_record_attribute_and_running_total() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        let config = InstrumentationConfig::new()
+            .with_command_weights(HashMap::from([("KEYS", 50), ("get", 1)]))
+            .with_default_command_weight(5);
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Nil),
+            Ok(Value::Array(vec![])),
+            Ok(Value::Okay),
+            Ok(Value::Nil),
+        ]);
+        let mut conn = InstrumentedSyncConnection::with_config(mock, Arc::new(config));
+
+        let ((), capture) = test_support::capture(|| {
+            let _: redis::RedisResult<Value> = redis::cmd("GET").arg("a").query(&mut conn);
+            let _: redis::RedisResult<Value> = redis::cmd("KEYS").arg("*").query(&mut conn);
+            let _: redis::RedisResult<Value> = redis::cmd("SET").arg("a").arg(1).query(&mut conn);
+            let _: redis::RedisResult<Value> = redis::cmd("get").arg("b").query(&mut conn);
+        });
+
+        let weights: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .map(|span| span.field("redis.command.weight").map(str::to_string))
+            .collect();
+        assert_eq!(
+            weights,
+            ["1", "50", "5", "1"].map(|weight| Some(weight.to_string()))
+        );
+        assert_eq!(conn.state().command_weight_total(), 57);
+
+        let unweighted = InstrumentationConfig::new();
+        assert_eq!(unweighted.command_weight("GET"), None);
+    }
+
+    #[test]
+    fn test_reply_byte_counter_frames_replies() {
+        use crate::common::{record_resp_reply_bytes, ReplyByteCounter};
+        use std::collections::VecDeque;
+        use std::io::Read;
+
+        /// Hands out one scripted chunk per read
+        struct Chunks(VecDeque<&'static [u8]>);
+
+        impl Read for Chunks {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let Some(chunk) = self.0.pop_front() else {
+                    return Ok(0);
+                };
+                buf[..chunk.len()].copy_from_slice(chunk);
+                Ok(chunk.len())
+            }
+        }
+
+        // A bulk reply split across reads, then a pipeline answered by a single read
+        let mut reader = ReplyByteCounter::new(Chunks(VecDeque::from([
+            &b"$5\r\nhe"[..],
+            &b"llo\r\n"[..],
+            &b"+OK\r\n:42\r\n*2\r\n$1\r\na\r\n_\r\n"[..],
+        ])));
+        let mut parser = redis::Parser::new();
+        assert_eq!(
+            parser.parse_value(&mut reader).unwrap(),
+            redis::Value::BulkString(b"hello".to_vec())
+        );
+        assert_eq!(reader.take_reply_sizes(), [11]);
+        for _ in 0..3 {
+            parser.parse_value(&mut reader).unwrap();
+        }
+        assert_eq!(reader.take_reply_sizes(), [5, 5, 14]);
+        assert!(reader.is_counting());
+
+        // The counted size of a reply read for a command lands on its span
+        let config = InstrumentationConfig::new();
+        let reply = b"$12\r\nhello, world\r\n";
+        let mut reader = ReplyByteCounter::new(Chunks(VecDeque::from([&reply[..]])));
+        let ((), capture) = test_support::capture(|| {
+            let (span, _) = common::create_command_span_with_config(&redis::cmd("GET"), &config);
+            parser.parse_value(&mut reader).unwrap();
+            for size in reader.take_reply_sizes() {
+                record_resp_reply_bytes(&span, size);
+            }
+        });
+        let span = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(
+            span.field("redis.resp.reply_bytes"),
+            Some(reply.len().to_string().as_str())
+        );
+
+        // Bytes that aren't RESP stop the counting instead of piling up
+        let mut reader = ReplyByteCounter::new(Chunks(VecDeque::from([
+            &b"+OK\r\nnot resp\r\n"[..],
+            &b"+OK\r\n"[..],
+        ])));
+        let mut buf = [0; 64];
+        while reader.read(&mut buf).unwrap() > 0 {}
+        assert_eq!(reader.take_reply_sizes(), [5]);
+        assert!(!reader.is_counting());
+
+        // So does a reply outgrowing the limit
+        let mut reader =
+            ReplyByteCounter::new(Chunks(VecDeque::from([&b"$100\r\n"[..], &[b'x'; 32][..]])))
+                .with_max_reply_len(16);
+        while reader.read(&mut buf).unwrap() > 0 {}
+        assert!(reader.take_reply_sizes().is_empty());
+        assert!(!reader.is_counting());
+    }
+
+    #[cfg(feature = "connection-manager")]
+    #[tokio::test]
+    async fn test_connection_manager_wrapper() {
+        let url = test_support::spawn_scripted_server(|name| match name {
+            "GET" => b"$5\r\nvalue\r\n".to_vec(),
+            _ => b"+OK\r\n".to_vec(),
+        });
+        let client = InstrumentedClient::new(redis::Client::open(url).unwrap());
+        let (_guard, capture) = test_support::capture_default();
+        let manager = client.get_connection_manager().await.unwrap();
+        let mut clone = manager.clone();
+        let value: String = clone.get("key").await.unwrap();
+        assert_eq!(value, "value");
+
+        let span = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(span.field("db.operation"), Some("GET"));
+        assert_eq!(span.field("otel.status_code"), Some("OK"));
+        let acquire = capture.by_name("get_connection_manager").unwrap();
+        assert_eq!(acquire.field("redis.connection.reused"), Some("false"));
+    }
+
+    #[cfg(all(feature = "cluster", feature = "sync"))]
+    #[test]
+    fn test_cluster_connection_wrapper() {
+        use crate::cluster::InstrumentedClusterClient;
+        use redis::cluster::ClusterClient;
+
+        let client = ClusterClient::new(vec!["redis://127.0.0.1:1/"]).unwrap();
+        let instrumented = InstrumentedClusterClient::new(client);
+        assert!(instrumented.get_connection().is_err());
+        assert_eq!(instrumented.connections_created(), 0);
+    }
+
+    #[test]
+    fn test_command_result_records_redirect_node() {
+        let moved: redis::RedisResult<()> = Err(redis::RedisError::from((
+            redis::ErrorKind::Moved,
+            "An error was signalled by the server",
+            "3999 127.0.0.1:6381".to_string(),
+        )));
+
+        let config = InstrumentationConfig::new();
+        let ((), capture) = test_support::capture(|| {
+            let (span, _) = common::create_command_span_with_config(&redis::cmd("GET"), &config);
+            common::record_command_result(&span, &moved);
+        });
+
+        let span = capture.by_otel_name("redis get").unwrap();
+        assert_eq!(span.field("error.type"), Some("moved"));
+        assert_eq!(span.field("redis.redirect.node"), Some("127.0.0.1:6381"));
+        assert_eq!(span.field("redis.redirect.slot"), Some("3999"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_instrumented_redis_trait_objects() {
+        use crate::aio::{InstrumentedAsyncConnection, InstrumentedRedis};
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let url = test_support::spawn_scripted_server(|name| match name {
+            "GET" => b"$6\r\nserver\r\n".to_vec(),
+            _ => b"+OK\r\n".to_vec(),
+        });
+        let client = InstrumentedClient::new(redis::Client::open(url).unwrap());
+        let multiplexed = client.get_multiplexed_async_connection().await.unwrap();
+        let mock = MockAsyncConnection::with_replies([Ok(Value::BulkString(b"mock".to_vec()))]);
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut connections: Vec<Box<dyn InstrumentedRedis + Send>> = vec![
+            Box::new(InstrumentedAsyncConnection::new(mock)),
+            Box::new(multiplexed),
+        ];
+        let mut replies = Vec::new();
+        for conn in &mut connections {
+            let mut cmd = redis::cmd("GET");
+            cmd.arg("key");
+            replies.push(conn.req_command(&cmd).await.unwrap());
+        }
+
+        assert_eq!(
+            replies,
+            [
+                Value::BulkString(b"mock".to_vec()),
+                Value::BulkString(b"server".to_vec())
+            ]
+        );
+        let spans: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.field("otel.name") == Some("redis get"))
+            .collect();
+        assert_eq!(spans.len(), 2);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_pubsub_records_channel_names() {
+        fn respond(command: &str) -> Vec<u8> {
+            match command {
+                "SUBSCRIBE" => [
+                    &b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n"[..],
+                    b"*3\r\n$9\r\nsubscribe\r\n$6\r\nsports\r\n:2\r\n",
+                    b"*3\r\n$9\r\nsubscribe\r\n$7\r\nweather\r\n:3\r\n",
+                ]
+                .concat(),
+                "PSUBSCRIBE" => b"*3\r\n$10\r\npsubscribe\r\n$6\r\nuser:*\r\n:4\r\n".to_vec(),
+                _ => b"+OK\r\n".to_vec(),
+            }
+        }
+
+        let client = redis::Client::open(test_support::spawn_scripted_server(respond)).unwrap();
+        let instrumented = InstrumentedClient::new(client);
+        let (mut sink, _stream) = instrumented.get_async_pubsub_split().await.unwrap();
+
+        let (_guard, capture) = test_support::capture_default();
+        sink.subscribe(&["news", "sports", "weather"])
+            .await
+            .unwrap();
+        sink.psubscribe("user:*").await.unwrap();
+
+        let subscribe = capture.by_otel_name("redis subscribe").unwrap();
+        assert_eq!(subscribe.field("redis.pubsub.channel_count"), Some("3"));
+        assert_eq!(
+            subscribe.field("redis.pubsub.channels"),
+            Some("news,sports,weather")
+        );
+        assert_eq!(subscribe.field("redis.pubsub.patterns"), None);
+        let psubscribe = capture.by_otel_name("redis psubscribe").unwrap();
+        assert_eq!(psubscribe.field("redis.pubsub.patterns"), Some("user:*"));
+        assert_eq!(psubscribe.field("redis.pubsub.channels"), None);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_pubsub_subscribe_and_publish_spans() {
+        fn respond(command: &str) -> Vec<u8> {
+            match command {
+                "SUBSCRIBE" => [
+                    &b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n"[..],
+                    b"*3\r\n$9\r\nsubscribe\r\n$6\r\nsports\r\n:2\r\n",
+                ]
+                .concat(),
+                "PSUBSCRIBE" => b"*3\r\n$10\r\npsubscribe\r\n$6\r\nuser:*\r\n:3\r\n".to_vec(),
+                "PUBLISH" => b":2\r\n".to_vec(),
+                _ => b"+OK\r\n".to_vec(),
+            }
+        }
+
+        let client = InstrumentedClient::new(
+            redis::Client::open(test_support::spawn_scripted_server(respond)).unwrap(),
+        );
+        let mut subscriber = client.get_connection().unwrap();
+        let mut publisher = client.get_connection().unwrap();
+
+        let ((), capture) = test_support::capture(|| {
+            let mut pubsub = subscriber.as_pubsub();
+            pubsub.subscribe(&["news", "sports"]).unwrap();
+            pubsub.psubscribe("user:*").unwrap();
+            assert_eq!(publisher.publish("news", "hello").unwrap(), 2);
+        });
+
+        let subscribe = capture.by_name("subscribe").unwrap();
+        assert_eq!(subscribe.field("db.operation"), Some("SUBSCRIBE"));
+        assert_eq!(
+            subscribe.field("redis.pubsub.channels"),
+            Some("news,sports")
+        );
+        let psubscribe = capture.by_name("psubscribe").unwrap();
+        assert_eq!(psubscribe.field("db.operation"), Some("PSUBSCRIBE"));
+        assert_eq!(psubscribe.field("redis.pubsub.patterns"), Some("user:*"));
+        let publish = capture.by_name("publish").unwrap();
+        assert_eq!(publish.field("db.operation"), Some("PUBLISH"));
+        assert_eq!(publish.field("redis.pubsub.channel"), Some("news"));
+        assert_eq!(publish.field("redis.pubsub.receivers"), Some("2"));
+        assert!(capture.by_otel_name("redis publish").is_some());
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_publish_records_channel() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([Ok(redis::Value::Int(0))]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+        assert_eq!(conn.publish("alerts", "disk full").await.unwrap(), 0);
+
+        let publish = capture.by_name("publish").unwrap();
+        assert_eq!(publish.field("redis.pubsub.channel"), Some("alerts"));
+        assert_eq!(publish.field("redis.pubsub.receivers"), Some("0"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_transaction_nests_commands_under_multi_span() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::Array(vec![Value::Okay, Value::Int(1)])),
+        ]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let (counter, capture) = test_support::capture(|| {
+            let ((), counter): ((), i64) = conn
+                .transaction(|pipe| {
+                    pipe.set("key", "value").incr("counter", 1);
+                })
+                .unwrap();
+            counter
+        });
+        assert_eq!(counter, 1);
+
+        let transaction = capture.by_otel_name("redis MULTI").unwrap();
+        assert_eq!(transaction.name, "redis_transaction");
+        assert_eq!(
+            transaction.field("db.redis.transaction.command_count"),
+            Some("2")
+        );
+        assert_eq!(transaction.field("otel.status_code"), Some("OK"));
+        let children: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.name == "redis_command")
+            .collect();
+        assert_eq!(children.len(), 4);
+        assert!(children
+            .iter()
+            .all(|span| span.parent == Some(transaction.id)));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_transaction_sends_no_side_channel_commands() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+        use std::time::Duration;
+
+        let config = InstrumentationConfig::new()
+            .with_eviction_sampling(Duration::ZERO)
+            .with_slowlog_correlation(Duration::ZERO, Duration::ZERO);
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::Array(vec![Value::Okay, Value::Int(2)])),
+        ]);
+        let mut conn = InstrumentedSyncConnection::with_config(mock, std::sync::Arc::new(config));
+
+        let replies: Vec<Value> = conn
+            .transaction(|pipe| {
+                pipe.set("key", "value").incr("counter", 1);
+            })
+            .unwrap();
+        assert_eq!(replies, [Value::Okay, Value::Int(2)]);
+
+        // INFO and SLOWLOG may only follow EXEC, never be queued before it
+        let sent: Vec<_> = conn
+            .inner()
+            .commands
+            .iter()
+            .map(|packed| crate::common::get_packed_command_name(packed).unwrap())
+            .collect();
+        assert_eq!(sent[..4], ["MULTI", "SET", "INCRBY", "EXEC"]);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_watch_transaction_records_single_attempt() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::BulkString(b"5".to_vec())),
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::Array(vec![
+                Value::Okay,
+                Value::BulkString(b"15".to_vec()),
+            ])),
+            Ok(Value::Okay),
+        ]);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+
+        let (balance, capture) = test_support::capture(|| {
+            let (balance,): (i64,) = conn
+                .watch_transaction(&["balance", "limit"], |conn, pipe| {
+                    let balance: i64 = redis::cmd("GET").arg("balance").query(conn)?;
+                    pipe.set("balance", balance + 10)
+                        .ignore()
+                        .get("balance")
+                        .query(conn)
+                })
+                .unwrap();
+            balance
+        });
+        assert_eq!(balance, 15);
+
+        let transaction = capture.by_otel_name("redis transaction").unwrap();
+        assert_eq!(transaction.field("redis.transaction.attempts"), Some("1"));
+        assert_eq!(
+            transaction.field("redis.transaction.watched_keys"),
+            Some("balance,limit")
+        );
+        assert_eq!(transaction.field("otel.status_code"), Some("OK"));
+        for name in ["redis watch", "redis get", "redis unwatch"] {
+            let span = capture.by_otel_name(name).unwrap();
+            assert_eq!(span.parent, Some(transaction.id), "{name}");
+        }
+        let pipeline = capture.by_name("req_packed_commands").unwrap();
+        assert_eq!(pipeline.parent, Some(transaction.id));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_transaction_records_exec_abort() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::{ErrorKind, RedisError, Value};
+
+        let (_guard, capture) = test_support::capture_default();
+        let mock = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Err(RedisError::from((
+                ErrorKind::ResponseError,
+                "An error was signalled by the server",
+                "unknown command 'NOPE'".to_string(),
+            ))),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Err(RedisError::from((
+                ErrorKind::ExecAbortError,
+                "An error was signalled by the server",
+                "Transaction discarded because of previous errors.".to_string(),
+            ))),
+        ]);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+
+        let result: redis::RedisResult<Value> = conn
+            .transaction(|pipe| {
+                pipe.cmd("NOPE").incr("counter", 1);
+            })
+            .await;
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::ExecAbortError);
+
+        let transaction = capture.by_otel_name("redis MULTI").unwrap();
+        assert_eq!(
+            transaction.field("db.redis.transaction.command_count"),
+            Some("1")
+        );
+        assert_eq!(transaction.field("otel.status_code"), Some("ERROR"));
+        assert_eq!(transaction.field("error.type"), Some("exec_abort_error"));
+        let exec = capture.by_otel_name("redis exec").unwrap();
+        assert_eq!(exec.parent, Some(transaction.id));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_instrumented_pipeline_creates_child_span_per_command() {
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mut mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::BulkString(b"value".to_vec())),
+            Ok(Value::Int(5)),
+        ]);
+
+        let (reply, capture) = test_support::capture(|| {
+            let reply: (String, i64) = InstrumentedPipeline::new()
+                .cmd("SET")
+                .arg("key")
+                .arg("value")
+                .ignore()
+                .cmd("GET")
+                .arg("key")
+                .cmd("STRLEN")
+                .arg("key")
+                .query(&mut mock)
+                .unwrap();
+            reply
+        });
+        assert_eq!(reply, ("value".to_string(), 5));
+
+        let pipeline = capture.by_name("redis_pipeline").unwrap();
+        assert_eq!(pipeline.field("db.operation"), Some("pipeline"));
+        assert_eq!(pipeline.field("redis.pipeline.count"), Some("3"));
+        assert_eq!(pipeline.field("otel.status_code"), Some("OK"));
+        let children: Vec<_> = capture
+            .spans()
+            .into_iter()
+            .filter(|span| span.parent == Some(pipeline.id))
+            .collect();
+        let names: Vec<_> = children
+            .iter()
+            .map(|span| span.field("otel.name").unwrap())
+            .collect();
+        assert_eq!(names, ["redis set", "redis get", "redis strlen"]);
+        assert!(children
+            .iter()
+            .all(|span| span.field("otel.status_code") == Some("OK")));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_instrumented_pipeline_records_command_errors() {
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let wrong_type = redis::parse_redis_value(
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n",
+        )
+        .unwrap();
+        // MULTI, the three queued commands, then EXEC with the INCR failing
+        let mut mock = MockAsyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::SimpleString("QUEUED".into())),
+            Ok(Value::Array(vec![Value::Okay, wrong_type, Value::Int(1)])),
+        ]);
+
+        let result: redis::RedisResult<Value> = InstrumentedPipeline::new()
+            .atomic()
+            .cmd("SET")
+            .arg("key")
+            .arg("value")
+            .cmd("INCR")
+            .arg("key")
+            .cmd("EXISTS")
+            .arg("key")
+            .query_async(&mut mock)
+            .await;
+        assert!(result.is_err());
+
+        let pipeline = capture.by_name("redis_pipeline").unwrap();
+        assert_eq!(pipeline.field("redis.pipeline.count"), Some("3"));
+        assert_eq!(pipeline.field("redis.command_count"), Some("5"));
+        assert_eq!(pipeline.field("otel.status_code"), Some("ERROR"));
+        let incr = capture.by_otel_name("redis incr").unwrap();
+        assert_eq!(incr.parent, Some(pipeline.id));
+        assert_eq!(incr.field("otel.status_code"), Some("ERROR"));
+        let exists = capture.by_otel_name("redis exists").unwrap();
+        assert_eq!(exists.field("otel.status_code"), Some("OK"));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_pipeline_records_result_count() {
+        use crate::sync::InstrumentedSyncConnection;
+        use crate::test_support::MockSyncConnection;
+        use redis::Value;
+
+        let mut mock = MockSyncConnection::with_replies([
+            Ok(Value::Okay),
+            Ok(Value::Int(1)),
+            Ok(Value::Int(2)),
+        ]);
+        mock.max_pipeline_replies = Some(2);
+        let mut conn = InstrumentedSyncConnection::new(mock);
+        let pipeline = redis::pipe().set("a", 1).incr("a", 1).incr("a", 1).clone();
+
+        let (values, capture) = test_support::capture(|| {
+            conn.req_packed_commands(&pipeline.get_packed_pipeline(), 0, 3)
+                .unwrap()
+        });
+        assert_eq!(values.len(), 2);
+
+        let span = capture.by_name("req_packed_commands").unwrap();
+        assert_eq!(span.field("redis.pipeline.count"), Some("3"));
+        assert_eq!(span.field("redis.pipeline.result_count"), Some("2"));
+
+        // A failed pipeline doesn't know how many replies it read
+        conn.inner_mut().replies = [
+            Ok(Value::Okay),
+            Err(redis::RedisError::from(std::io::Error::from(
+                std::io::ErrorKind::ConnectionReset,
+            ))),
+        ]
+        .into();
+        let ((), capture) = test_support::capture(|| {
+            assert!(conn
+                .req_packed_commands(&pipeline.get_packed_pipeline(), 0, 3)
+                .is_err());
+        });
+        let span = capture.by_name("req_packed_commands").unwrap();
+        assert_eq!(span.field("otel.status_code"), Some("ERROR"));
+        assert_eq!(span.field("redis.pipeline.result_count"), None);
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_pipeline_records_result_count() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+
+        let (_guard, capture) = test_support::capture_default();
+        let mut mock = MockAsyncConnection::with_replies([Ok(Value::Okay), Ok(Value::Int(1))]);
+        mock.max_pipeline_replies = Some(1);
+        let mut conn = InstrumentedAsyncConnection::new(mock);
+        let pipeline = redis::pipe().set("a", 1).incr("a", 1).clone();
+
+        let values = conn.req_packed_commands(&pipeline, 0, 2).await.unwrap();
+        assert_eq!(values, [Value::Okay]);
+
+        let span = capture.by_name("req_packed_commands").unwrap();
+        assert_eq!(span.field("redis.pipeline.count"), Some("2"));
+        assert_eq!(span.field("redis.pipeline.result_count"), Some("1"));
+    }
+
+    #[cfg(feature = "aio")]
+    #[tokio::test]
+    async fn test_async_dispatch_timing_is_recorded_when_enabled() {
+        use crate::aio::InstrumentedAsyncConnection;
+        use crate::test_support::MockAsyncConnection;
+        use redis::Value;
+        use std::sync::Arc;
+
+        let (_guard, capture) = test_support::capture_default();
+        let config = InstrumentationConfig::new().with_dispatch_timing(true);
+        assert!(config.dispatch_timing());
+        assert!(!InstrumentationConfig::new().dispatch_timing());
+        // The reply arrives after a round trip to the executor, like a real one
+        let mock = MockAsyncConnection {
+            yield_before_reply: true,
+            ..MockAsyncConnection::with_replies([Ok(Value::Okay)])
+        };
+        let mut conn = InstrumentedAsyncConnection::with_config(mock, Arc::new(config));
+        let mut plain =
+            InstrumentedAsyncConnection::new(MockAsyncConnection::with_replies([Ok(Value::Okay)]));
+
+        conn.req_command(&redis::cmd("PING")).await.unwrap();
+        plain.req_command(&redis::cmd("PING")).await.unwrap();
+
+        let spans = capture.spans();
+        let millis = |field| spans[0].field(field).unwrap().parse::<f64>().unwrap();
+        assert!(millis("redis.queue_wait_ms") >= 0.0);
+        assert!(millis("redis.server_wait_ms") >= 0.0);
+        assert_eq!(spans[1].field("redis.queue_wait_ms"), None);
+        assert_eq!(spans[1].field("redis.server_wait_ms"), None);
+    }
 }
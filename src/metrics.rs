@@ -0,0 +1,89 @@
+//! OpenTelemetry metrics recorded by the instrumentation
+//!
+//! Every command sent through the connection wrappers is recorded in the
+//! `db.client.operation.duration` histogram and counted by the `db.client.operations`
+//! counter, both with `db.system`, `db.operation` and `redis.status` (`ok` or
+//! `error`) attributes. Without the `metrics` feature none
+//! of this is compiled in.
+
+use crate::scope::instrumentation_scope;
+use opentelemetry::global;
+#[cfg(feature = "aio")]
+use opentelemetry::metrics::Gauge;
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::KeyValue;
+use redis::{RedisResult, Value};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Bucket boundaries of `db.client.operation.duration`, in seconds, as recommended by
+/// the database client semantic conventions
+const DURATION_BOUNDARIES: [f64; 14] = [
+    0.001, 0.005, 0.01, 0.02, 0.03, 0.05, 0.1, 0.2, 0.3, 0.5, 1.0, 2.0, 5.0, 10.0,
+];
+
+/// Returns the meter from the global meter provider under [`instrumentation_scope`].
+///
+/// The meter is created on first use, so the global meter provider must be installed
+/// before the first command is sent.
+pub fn meter() -> &'static Meter {
+    static METER: OnceLock<Meter> = OnceLock::new();
+    METER.get_or_init(|| global::meter_with_scope(instrumentation_scope()))
+}
+
+/// The `db.client.operation.duration` histogram
+fn operation_duration() -> &'static Histogram<f64> {
+    static HISTOGRAM: OnceLock<Histogram<f64>> = OnceLock::new();
+    HISTOGRAM.get_or_init(|| {
+        meter()
+            .f64_histogram("db.client.operation.duration")
+            .with_description("Duration of Redis commands")
+            .with_unit("s")
+            .with_boundaries(DURATION_BOUNDARIES.to_vec())
+            .build()
+    })
+}
+
+/// The `db.client.operations` counter
+fn operations() -> &'static Counter<u64> {
+    static COUNTER: OnceLock<Counter<u64>> = OnceLock::new();
+    COUNTER.get_or_init(|| {
+        meter()
+            .u64_counter("db.client.operations")
+            .with_description("Number of Redis commands sent")
+            .with_unit("{operation}")
+            .build()
+    })
+}
+
+/// Records a command of `operation` that took `elapsed` and completed with `result`.
+///
+/// Server error replies count as errors, like failed commands. The connection
+/// wrappers call this for every command; custom wrappers can call it as well.
+pub fn record_operation(operation: &str, elapsed: Duration, result: &RedisResult<Value>) {
+    let status = match result {
+        Ok(Value::ServerError(_)) | Err(_) => "error",
+        Ok(_) => "ok",
+    };
+    let attributes = [
+        KeyValue::new("db.system", "redis"),
+        KeyValue::new("db.operation", operation.to_string()),
+        KeyValue::new("redis.status", status),
+    ];
+    operation_duration().record(elapsed.as_secs_f64(), &attributes);
+    operations().add(1, &attributes);
+}
+
+/// The `redis.batch.efficiency` gauge, see
+/// [`crate::InstrumentationConfig::with_batch_efficiency`]
+#[cfg(feature = "aio")]
+pub(crate) fn batch_efficiency() -> &'static Gauge<f64> {
+    static GAUGE: OnceLock<Gauge<f64>> = OnceLock::new();
+    GAUGE.get_or_init(|| {
+        meter()
+            .f64_gauge("redis.batch.efficiency")
+            .with_description("Estimated commands sent per flush on multiplexed connections")
+            .with_unit("{command}")
+            .build()
+    })
+}
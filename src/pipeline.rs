@@ -0,0 +1,300 @@
+//! A pipeline builder that traces each of its commands
+
+use crate::common::{
+    apply_span_attributes, create_command_span_with_config, record_command_result,
+    record_watch_conflict,
+};
+use crate::config::InstrumentationConfig;
+use redis::{Cmd, ErrorKind, FromRedisValue, RedisResult, ToRedisArgs, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::field::Empty;
+#[cfg(feature = "aio")]
+use tracing::Instrument;
+use tracing::Span;
+
+/// A pipeline whose commands each get a child span under the pipeline's span.
+///
+/// A [`redis::Pipeline`] queried through a connection wrapper gets a single
+/// `redis_pipeline` span. Commands added through this builder are kept, so querying
+/// it also opens a command span per command, named from its operation like the
+/// wrappers' command spans, and records the outcome of its own reply. The replies
+/// of a pipeline arrive together, so the child spans share its duration.
+///
+/// The aggregate `redis_pipeline` span records `db.operation = "pipeline"`,
+/// `redis.pipeline.count`, the number of commands added, and
+/// `redis.command_count`, which also counts a transaction's `MULTI` and `EXEC`.
+/// The default [`InstrumentationConfig`] applies unless [`Self::with_config`] sets
+/// another one.
+///
+/// # Example
+/// ```rust,no_run
+/// use otel_instrumentation_redis::InstrumentedPipeline;
+///
+/// # #[cfg(feature = "sync")]
+/// # fn example() -> redis::RedisResult<()> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut conn = client.get_connection()?;
+///
+/// let (value, length): (String, i64) = InstrumentedPipeline::new()
+///     .cmd("SET").arg("key").arg("value").ignore()
+///     .cmd("GET").arg("key")
+///     .cmd("STRLEN").arg("key")
+///     .query(&mut conn)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct InstrumentedPipeline {
+    pipeline: redis::Pipeline,
+    // `redis::Pipeline` doesn't expose which commands are ignored, nor whether it is
+    // atomic without the `aio` feature
+    ignored: HashSet<usize>,
+    atomic: bool,
+    config: Arc<InstrumentationConfig>,
+}
+
+impl InstrumentedPipeline {
+    /// Creates an empty pipeline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty pipeline whose spans are recorded according to `config`
+    pub fn with_config(config: Arc<InstrumentationConfig>) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Runs the pipeline as a `MULTI`/`EXEC` transaction, like [`redis::Pipeline::atomic`]
+    pub fn atomic(&mut self) -> &mut Self {
+        self.pipeline.atomic();
+        self.atomic = true;
+        self
+    }
+
+    /// Adds a command to the pipeline
+    pub fn add_command(&mut self, cmd: Cmd) -> &mut Self {
+        self.pipeline.add_command(cmd);
+        self
+    }
+
+    /// Starts a new command named `name`, whose arguments follow with [`Self::arg`]
+    pub fn cmd(&mut self, name: &str) -> &mut Self {
+        self.pipeline.cmd(name);
+        self
+    }
+
+    /// Adds an argument to the last command
+    ///
+    /// # Panics
+    ///
+    /// Panics if no command was started, like [`redis::Pipeline::arg`].
+    pub fn arg<T: ToRedisArgs>(&mut self, arg: T) -> &mut Self {
+        self.pipeline.arg(arg);
+        self
+    }
+
+    /// Leaves the reply of the last command out of the pipeline's result
+    ///
+    /// # Panics
+    ///
+    /// Panics if no command was started, like [`redis::Pipeline::ignore`].
+    pub fn ignore(&mut self) -> &mut Self {
+        self.pipeline.ignore();
+        self.ignored.insert(self.pipeline.len() - 1);
+        self
+    }
+
+    /// Returns the number of commands in the pipeline
+    pub fn len(&self) -> usize {
+        self.pipeline.len()
+    }
+
+    /// Returns `true` if the pipeline has no commands
+    pub fn is_empty(&self) -> bool {
+        self.pipeline.is_empty()
+    }
+
+    /// Returns the underlying [`redis::Pipeline`]
+    pub fn as_pipeline(&self) -> &redis::Pipeline {
+        &self.pipeline
+    }
+
+    /// Like [`redis::Pipeline::query`], inside a pipeline span with a child span per command
+    ///
+    /// # Errors
+    ///
+    /// Returns an error variant of [`RedisResult`] if:
+    /// - The connection doesn't support pipelining, or the connection to Redis fails.
+    /// - The server replies with an error to any command, even an ignored one.
+    /// - The replies can't be converted to `T`.
+    #[cfg(feature = "sync")]
+    pub fn query<T: FromRedisValue>(&self, con: &mut dyn redis::ConnectionLike) -> RedisResult<T> {
+        if self.is_empty() {
+            return redis::from_owned_redis_value(Value::Array(vec![]));
+        }
+        if !con.supports_pipelining() {
+            return Err((
+                ErrorKind::ResponseError,
+                "This connection does not support pipelining.",
+            )
+                .into());
+        }
+
+        let span = self.pipeline_span();
+        let children = self.command_spans(&span);
+        let (offset, count) = self.reply_range();
+        let replies = span.in_scope(|| {
+            con.req_packed_commands(&self.pipeline.get_packed_pipeline(), offset, count)
+        });
+        self.finish(&span, &children, replies)
+    }
+
+    /// Like [`redis::Pipeline::exec`], inside a pipeline span with a child span per command
+    ///
+    /// # Errors
+    ///
+    /// Returns an error variant of [`RedisResult`] in the same cases as [`Self::query`].
+    #[cfg(feature = "sync")]
+    pub fn exec(&self, con: &mut dyn redis::ConnectionLike) -> RedisResult<()> {
+        self.query(con)
+    }
+
+    /// Like [`redis::Pipeline::query_async`], inside a pipeline span with a child span
+    /// per command
+    ///
+    /// # Errors
+    ///
+    /// Returns an error variant of [`RedisResult`] in the same cases as [`Self::query`].
+    #[cfg(feature = "aio")]
+    pub async fn query_async<T: FromRedisValue>(
+        &self,
+        con: &mut impl redis::aio::ConnectionLike,
+    ) -> RedisResult<T> {
+        if self.is_empty() {
+            return redis::from_owned_redis_value(Value::Array(vec![]));
+        }
+
+        let span = self.pipeline_span();
+        let children = self.command_spans(&span);
+        let (offset, count) = self.reply_range();
+        let replies = con
+            .req_packed_commands(&self.pipeline, offset, count)
+            .instrument(span.clone())
+            .await;
+        self.finish(&span, &children, replies)
+    }
+
+    /// Like [`redis::Pipeline::exec_async`], inside a pipeline span with a child span
+    /// per command
+    ///
+    /// # Errors
+    ///
+    /// Returns an error variant of [`RedisResult`] in the same cases as [`Self::query`].
+    #[cfg(feature = "aio")]
+    pub async fn exec_async(&self, con: &mut impl redis::aio::ConnectionLike) -> RedisResult<()> {
+        self.query_async(con).await
+    }
+
+    fn pipeline_span(&self) -> Span {
+        // A transaction also sends MULTI and EXEC, as many commands as it reads replies
+        let (offset, count) = self.reply_range();
+        tracing::info_span!(
+            "redis_pipeline",
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = "pipeline",
+            redis.pipeline.count = self.len() as i64,
+            redis.command_count = (offset + count) as i64,
+            redis.transaction.conflict = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
+        )
+    }
+
+    /// Opens the span of each command under the pipeline's `span`
+    fn command_spans(&self, span: &Span) -> Vec<Span> {
+        span.in_scope(|| {
+            self.pipeline
+                .cmd_iter()
+                .map(|cmd| {
+                    let (child, attributes) = create_command_span_with_config(cmd, &self.config);
+                    apply_span_attributes(&child, &attributes);
+                    child
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the replies to skip and to read: a transaction only reads `EXEC`'s
+    fn reply_range(&self) -> (usize, usize) {
+        if self.atomic {
+            (self.len() + 1, 1)
+        } else {
+            (0, self.len())
+        }
+    }
+
+    /// Records the replies on the command spans and the pipeline's `span`, and
+    /// converts the ones not ignored to `T`
+    fn finish<T: FromRedisValue>(
+        &self,
+        span: &Span,
+        children: &[Span],
+        replies: RedisResult<Vec<Value>>,
+    ) -> RedisResult<T> {
+        let replies = replies.and_then(|mut replies| {
+            if !self.atomic {
+                return Ok(Some(replies));
+            }
+            match replies.pop() {
+                // A WATCH conflict aborted the transaction
+                Some(Value::Nil) => Ok(None),
+                Some(Value::Array(items)) => Ok(Some(items)),
+                _ => Err((
+                    ErrorKind::ResponseError,
+                    "Invalid response when parsing multi response",
+                )
+                    .into()),
+            }
+        });
+
+        let result = match replies {
+            Ok(Some(replies)) => {
+                for (child, reply) in children.iter().zip(&replies) {
+                    let outcome = match reply {
+                        Value::ServerError(err) => Err(err.clone().into()),
+                        _ => Ok(()),
+                    };
+                    record_command_result(child, &outcome);
+                }
+                Value::Array(replies)
+                    .extract_error()
+                    .map(|value| match value {
+                        Value::Array(replies) => Value::Array(
+                            replies
+                                .into_iter()
+                                .enumerate()
+                                .filter(|(index, _)| !self.ignored.contains(index))
+                                .map(|(_, reply)| reply)
+                                .collect(),
+                        ),
+                        value => value,
+                    })
+            }
+            Ok(None) => {
+                record_watch_conflict(span, &self.config);
+                return redis::from_owned_redis_value(Value::Nil);
+            }
+            Err(err) => Err(err),
+        };
+        record_command_result(span, &result);
+
+        redis::from_owned_redis_value(result?)
+    }
+}
@@ -0,0 +1,90 @@
+//! Instrumentation for existing `redis::cmd(...)` call sites
+
+use crate::common::{apply_span_attributes, create_command_span, record_command_result};
+use redis::{Cmd, FromRedisValue, RedisResult};
+#[cfg(feature = "aio")]
+use std::future::Future;
+#[cfg(feature = "aio")]
+use tracing::Instrument;
+
+/// Adds instrumented counterparts of `query` and `exec` to [`redis::Cmd`].
+///
+/// Each call gets a command span, the same one the connection wrappers create, around
+/// redis-rs's own query, so existing call sites can be instrumented without switching
+/// to a wrapper. The default [`crate::InstrumentationConfig`] applies.
+///
+/// # Example
+/// ```rust,no_run
+/// use otel_instrumentation_redis::prelude::*;
+///
+/// # #[cfg(feature = "sync")]
+/// # fn example() -> redis::RedisResult<()> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut conn = client.get_connection()?;
+///
+/// redis::cmd("SET").arg("key").arg("value").instrumented_exec(&mut conn)?;
+/// let value: String = redis::cmd("GET").arg("key").instrumented_query(&mut conn)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait InstrumentedQuery {
+    /// Like [`Cmd::query`], inside a command span
+    #[cfg(feature = "sync")]
+    fn instrumented_query<T: FromRedisValue>(
+        &self,
+        con: &mut dyn redis::ConnectionLike,
+    ) -> RedisResult<T>;
+
+    /// Like [`Cmd::exec`], inside a command span
+    #[cfg(feature = "sync")]
+    fn instrumented_exec(&self, con: &mut dyn redis::ConnectionLike) -> RedisResult<()> {
+        self.instrumented_query(con)
+    }
+
+    /// Like [`Cmd::query_async`], inside a command span
+    #[cfg(feature = "aio")]
+    fn instrumented_query_async<T: FromRedisValue>(
+        &self,
+        con: &mut impl redis::aio::ConnectionLike,
+    ) -> impl Future<Output = RedisResult<T>>;
+
+    /// Like [`Cmd::exec_async`], inside a command span
+    #[cfg(feature = "aio")]
+    fn instrumented_exec_async(
+        &self,
+        con: &mut impl redis::aio::ConnectionLike,
+    ) -> impl Future<Output = RedisResult<()>> {
+        self.instrumented_query_async(con)
+    }
+}
+
+impl InstrumentedQuery for Cmd {
+    #[cfg(feature = "sync")]
+    fn instrumented_query<T: FromRedisValue>(
+        &self,
+        con: &mut dyn redis::ConnectionLike,
+    ) -> RedisResult<T> {
+        let (span, attributes) = create_command_span(self);
+        apply_span_attributes(&span, &attributes);
+        let _enter = span.enter();
+
+        let result = self.query(con);
+        record_command_result(&span, &result);
+
+        result
+    }
+
+    #[cfg(feature = "aio")]
+    async fn instrumented_query_async<T: FromRedisValue>(
+        &self,
+        con: &mut impl redis::aio::ConnectionLike,
+    ) -> RedisResult<T> {
+        let (span, attributes) = create_command_span(self);
+        apply_span_attributes(&span, &attributes);
+
+        let result = self.query_async(con).instrument(span.clone()).await;
+        record_command_result(&span, &result);
+
+        result
+    }
+}
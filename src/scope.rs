@@ -0,0 +1,29 @@
+//! The OpenTelemetry instrumentation scope identifying spans from this crate
+
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::InstrumentationScope;
+use opentelemetry_semantic_conventions as semconv;
+
+/// Name of the instrumentation scope, the package name of this crate
+pub const SCOPE_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Version of the instrumentation scope, the package version of this crate
+pub const SCOPE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Returns the instrumentation scope spans from this crate belong to.
+///
+/// The scope is named `otel-instrumentation-redis`, carries the crate version and
+/// the semantic conventions schema URL the attributes follow. Pass it to
+/// `TracerProvider::tracer_with_scope` when creating OpenTelemetry spans directly
+/// rather than through `tracing`.
+pub fn instrumentation_scope() -> InstrumentationScope {
+    InstrumentationScope::builder(SCOPE_NAME)
+        .with_version(SCOPE_VERSION)
+        .with_schema_url(semconv::SCHEMA_URL)
+        .build()
+}
+
+/// Returns a tracer from the global tracer provider under [`instrumentation_scope`].
+pub fn tracer() -> BoxedTracer {
+    global::tracer_with_scope(instrumentation_scope())
+}
@@ -2,17 +2,51 @@
 //! enable enhanced tracing and monitoring capabilities for Redis operations.
 //! The `InstrumentedConnection` enables capturing command spans and attributes,
 
-use crate::common::{apply_span_attributes, create_command_span, record_command_result};
-use redis::{Cmd, Connection, ConnectionLike, RedisResult, Value};
+#[cfg(feature = "metrics")]
+use crate::common::record_command_metrics;
+use crate::common::{
+    apply_span_attributes, collapse_operation, command_start_time, convert_reply, count_operation,
+    create_connection_command_span, create_connection_raw_command_span, dedupe_keys,
+    eviction_sample_due, generate_span_name, get_packed_command_name, method_operation,
+    new_connection_state, parse_cluster_state, parse_evicted_keys, record_command_count,
+    record_command_result, record_command_weight, record_connection_state, record_database_index,
+    record_error_statement, record_eviction_pressure, record_execution_context,
+    record_exists_result, record_extracted_attributes, record_getex_expiry, record_hash_set_fields,
+    record_keyspace_lookup, record_latency_bucket, record_multi_key_counts,
+    record_pipeline_result_count, record_pipeline_result_events, record_publish_channel,
+    record_reply_attributes, record_response_size, record_score_bounds, record_server_exec_time,
+    record_set_sample_count, record_smove_keys_with_config, record_string_length,
+    record_string_range, record_transaction_result, record_unix_timestamps, record_watch_conflict,
+    record_watched_keys, record_window_aggregate, record_zadd_flags, run_error_hook,
+    slowlog_lookup_due, transaction_span, watch_transaction_span, ConnectionState,
+    DeferredRecorder, RecordFields, RedisSpan, ServerAddress, ZAddFlag, SLOWLOG_LOOKUP_ENTRIES,
+};
+use crate::config::InstrumentationConfig;
+use opentelemetry::KeyValue;
+use redis::{Cmd, Connection, ConnectionLike, Msg, RedisError, RedisResult, Value};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::field::Empty;
 use tracing::{instrument, Span};
 
+mod pubsub;
+
+pub use pubsub::InstrumentedPubSub;
+
+use pubsub::{receive_span, record_received_message};
+
 /// A struct that represents a connection with added instrumentation capabilities.
 ///
-/// The `InstrumentedConnection` wraps an inner `Connection` and can provide additional
-/// functionality such as logging, tracking metrics, or monitoring the usage of the connection.
+/// The `InstrumentedSyncConnection` wraps any synchronous `redis::ConnectionLike`,
+/// such as a `redis::Connection`, a cluster connection or a custom type, and can
+/// provide additional functionality such as logging, tracking metrics, or monitoring
+/// the usage of the connection. [`InstrumentedConnection`] names the common case of
+/// wrapping a `redis::Connection`.
 ///
 /// # Fields
-/// - `inner`: The underlying `Connection` object that this struct wraps and extends.
+/// - `inner`: The underlying connection that this struct wraps and extends.
+/// - `config`: The [`InstrumentationConfig`] controlling what is recorded on each span.
+/// - `state`: The [`ConnectionState`] observed from commands sent over this connection.
 ///
 /// # Examples
 /// ```ignore
@@ -25,16 +59,27 @@ use tracing::{instrument, Span};
 ///
 /// // Use `instrumented_connection` as needed
 /// ```
-pub struct InstrumentedConnection {
-    inner: Connection,
+pub struct InstrumentedSyncConnection<C> {
+    inner: C,
+    config: Arc<InstrumentationConfig>,
+    state: Arc<ConnectionState>,
 }
 
-impl InstrumentedConnection {
+/// An [`InstrumentedSyncConnection`] wrapping a `redis::Connection`
+pub type InstrumentedConnection = InstrumentedSyncConnection<Connection>;
+
+/// An [`InstrumentedSyncConnection`] wrapping a Redis Cluster connection, see
+/// [`crate::InstrumentedClusterClient::get_connection`]
+#[cfg(feature = "cluster")]
+pub type InstrumentedClusterConnection =
+    InstrumentedSyncConnection<redis::cluster::ClusterConnection>;
+
+impl<C: ConnectionLike> InstrumentedSyncConnection<C> {
     /// Creates a new instance of the struct with the provided database connection.
     ///
     /// # Arguments
     ///
-    /// * `connection` - A `Connection` object that represents the database connection.
+    /// * `connection` - The connection to instrument.
     ///
     /// # Returns
     ///
@@ -47,11 +92,47 @@ impl InstrumentedConnection {
     /// let connection = Connection::new();
     /// let instance = StructName::new(connection);
     /// ```
-    pub fn new(connection: Connection) -> Self {
-        Self { inner: connection }
+    pub fn new(connection: C) -> Self {
+        Self::with_config(connection, Arc::new(InstrumentationConfig::default()))
+    }
+
+    /// Creates a new instance that records spans according to a shared configuration.
+    ///
+    /// This is how [`crate::InstrumentedClient`] hands its configuration to the
+    /// connections it creates.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - The connection to instrument.
+    /// * `config` - The [`InstrumentationConfig`] to apply to every command.
+    pub fn with_config(connection: C, config: Arc<InstrumentationConfig>) -> Self {
+        Self {
+            state: Arc::new(new_connection_state(connection.get_db(), &config, false)),
+            inner: connection,
+            config,
+        }
+    }
+
+    /// Records `server` as `server.address` and `server.port` on every command span.
+    ///
+    /// [`crate::InstrumentedClient`] sets this from its connection info on the
+    /// connections it hands out.
+    pub fn with_server_address(self, server: ServerAddress) -> Self {
+        self.state.set_server_address(server);
+        self
+    }
+
+    /// Returns the instrumentation configuration used by this connection.
+    pub fn config(&self) -> &InstrumentationConfig {
+        &self.config
+    }
+
+    /// Returns the connection-scoped state observed from commands sent through this wrapper.
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
     }
 
-    /// Returns a reference to the inner `Connection` object.
+    /// Returns a reference to the inner connection.
     ///
     /// # Examples
     ///
@@ -61,12 +142,12 @@ impl InstrumentedConnection {
     /// ```
     ///
     /// # Returns
-    /// A reference to the `Connection` stored within the struct.
-    pub fn inner(&self) -> &Connection {
+    /// A reference to the connection stored within the struct.
+    pub fn inner(&self) -> &C {
         &self.inner
     }
 
-    /// Provides mutable access to the inner `Connection` object.
+    /// Provides mutable access to the inner connection.
     ///
     /// This method allows modification of the underlying `Connection` instance
     /// by returning a mutable reference to it.
@@ -81,13 +162,13 @@ impl InstrumentedConnection {
     ///
     /// # Returns
     ///
-    /// A mutable reference to the inner `Connection`.
+    /// A mutable reference to the inner connection.
     ///
     /// # Note
     ///
     /// Use this method with caution, as modifying the inner state could
-    /// potentially impact other parts of the code relying on the `Connection` state.
-    pub fn inner_mut(&mut self) -> &mut Connection {
+    /// potentially impact other parts of the code relying on the connection state.
+    pub fn inner_mut(&mut self) -> &mut C {
         &mut self.inner
     }
 
@@ -138,21 +219,181 @@ impl InstrumentedConnection {
     /// # Errors
     /// - Returns a `RedisError` if the command execution fails.
     pub fn req_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
-        let (span, attributes) = create_command_span(cmd);
+        self.req_command_with_span_fn(cmd, |_| {})
+    }
+
+    /// Sends a command with tracing, letting `enrich` add to its span first.
+    ///
+    /// `enrich` is called with the command span once it is created, before the
+    /// command is sent, e.g. to record a business-entity id derived from the
+    /// caller's context. The span only keeps values of the fields it declares; other
+    /// keys can be attached as events under it. Command attributes are recorded
+    /// after `enrich` runs and take precedence.
+    pub fn req_command_with_span_fn(
+        &mut self,
+        cmd: &Cmd,
+        enrich: impl FnOnce(&Span),
+    ) -> RedisResult<Value> {
+        let command = self.open_command(cmd, enrich);
+        self.run_command(cmd, &command, None)
+    }
+
+    /// Runs `cmd` within the span of `command`.
+    ///
+    /// `raw_operation` is the operation given to [`Self::req_command_raw`], which
+    /// names the command in metrics instead of the parsed command name.
+    fn run_command(
+        &mut self,
+        cmd: &Cmd,
+        command: &RedisSpan,
+        raw_operation: Option<&'static str>,
+    ) -> RedisResult<Value> {
+        let span = command.span();
         let _enter = span.enter();
 
-        // Apply additional attributes
-        apply_span_attributes(&span, &attributes);
+        let result = if self.config.deferred_recording() {
+            // Flushed when dropped, so the batch lands on the span even on a panic
+            let recorder = DeferredRecorder::new(span);
+            self.execute_command(cmd, command.attributes(), &recorder, raw_operation)
+        } else {
+            self.execute_command(cmd, command.attributes(), span, raw_operation)
+        };
+        record_extracted_attributes(span, cmd, &result, &self.config);
+
+        result
+    }
+
+    /// Creates the span of a command the caller executes, with the attributes
+    /// `req_command` records before sending it.
+    ///
+    /// Execute the command through [`Self::inner_mut`] or any other means, then call
+    /// [`RedisSpan::record_result`] and [`RedisSpan::finish`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use otel_instrumentation_redis::sync::InstrumentedConnection;
+    /// use redis::ConnectionLike;
+    ///
+    /// # fn main() -> redis::RedisResult<()> {
+    /// let client = redis::Client::open("redis://127.0.0.1/")?;
+    /// let mut conn = InstrumentedConnection::new(client.get_connection()?);
+    ///
+    /// let cmd = redis::cmd("GET").arg("key").clone();
+    /// let command = conn.begin_command(&cmd);
+    /// let result = conn.inner_mut().req_command(&cmd);
+    /// command.record_result(&result);
+    /// command.finish();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn begin_command(&self, cmd: &Cmd) -> RedisSpan {
+        let command = self.open_command(cmd, |_| {});
+        self.record_command_start(cmd, command.attributes(), &command);
+        command
+    }
+
+    /// Creates and counts the span of `cmd`, letting `enrich` add to it
+    fn open_command(&self, cmd: &Cmd, enrich: impl FnOnce(&Span)) -> RedisSpan {
+        let (span, attributes) = create_connection_command_span(cmd, &self.config, &self.state);
+        count_operation(cmd, &self.config);
+        enrich(&span);
+        RedisSpan::new(span, attributes, self.config.clone())
+    }
+
+    /// Records what is known about a command before it is sent
+    fn record_command_start(&self, cmd: &Cmd, attributes: &[KeyValue], span: &impl RecordFields) {
+        // Command attributes such as the target of SELECT override the connection's database
+        record_database_index(span, &self.config, &self.state);
+        apply_span_attributes(span, attributes);
+        record_connection_state(span, cmd, &self.state);
+        record_command_weight(span, cmd, &self.config, &self.state);
+        record_execution_context(span, &self.config);
+    }
+
+    /// Runs `cmd` and records its attributes on `span`, directly or through a batch
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn execute_command(
+        &mut self,
+        cmd: &Cmd,
+        attributes: &[KeyValue],
+        span: &impl RecordFields,
+        raw_operation: Option<&'static str>,
+    ) -> RedisResult<Value> {
+        self.record_command_start(cmd, attributes, span);
 
         // Execute the command
+        let started_at = command_start_time(&self.config);
+        let started = Instant::now();
         let result = self.inner.req_command(cmd);
+        let elapsed = started.elapsed();
+        record_unix_timestamps(span, started_at);
+        record_latency_bucket(span, elapsed, &self.config);
+        #[cfg(feature = "metrics")]
+        match raw_operation {
+            Some(operation) => crate::metrics::record_operation(operation, elapsed, &result),
+            None => record_command_metrics(cmd, elapsed, &result, &self.config),
+        }
+        record_window_aggregate(cmd, elapsed, &self.state);
+        if result.is_ok() {
+            self.state.observe_command(cmd);
+        }
+        self.state.observe_transaction(cmd, &result);
+        record_reply_attributes(span, cmd, &result);
+        record_keyspace_lookup(span, cmd, &result, &self.config);
+        record_response_size(span, &result, &self.config);
+        if eviction_sample_due(cmd, &self.config, &self.state) {
+            let info: RedisResult<String> = redis::cmd("INFO").arg("stats").query(&mut self.inner);
+            if let Some(evicted_keys) = info.ok().as_deref().and_then(parse_evicted_keys) {
+                self.state.eviction().record(evicted_keys);
+            }
+        }
+        record_eviction_pressure(span, cmd, &self.state);
+        if slowlog_lookup_due(cmd, elapsed, &self.config, &self.state) {
+            let slowlog: RedisResult<Value> = redis::cmd("SLOWLOG")
+                .arg("GET")
+                .arg(SLOWLOG_LOOKUP_ENTRIES)
+                .query(&mut self.inner);
+            if let Ok(slowlog) = &slowlog {
+                record_server_exec_time(span, cmd, slowlog, elapsed);
+            }
+        }
 
         // Record the result
-        record_command_result(&span, &result);
+        record_command_result(span, &result);
+        record_error_statement(span, cmd, &result, &self.config);
+        run_error_hook(cmd, &result, &self.config);
 
         result
     }
 
+    /// Execute a Redis command with tracing, using a precomputed operation name and attributes.
+    ///
+    /// The command is not parsed to build its span: the span is built by
+    /// [`crate::common::create_raw_command_span`] from `operation` and `attributes` as
+    /// given, which suits callers that already know the command's shape. `operation` is
+    /// collapsed when [`InstrumentationConfig::with_collapse_subcommands`] is set, and
+    /// names the command in metrics. Everything else `req_command` records is recorded
+    /// as well.
+    pub fn req_command_raw(
+        &mut self,
+        cmd: &Cmd,
+        operation: &'static str,
+        attributes: &[KeyValue],
+    ) -> RedisResult<Value> {
+        let operation = if self.config.collapse_subcommands() {
+            collapse_operation(operation)
+        } else {
+            operation
+        };
+        let span =
+            create_connection_raw_command_span(operation, attributes, &self.config, &self.state);
+        if let Some(counters) = self.config.local_counters() {
+            counters.increment(operation);
+        }
+        let command = RedisSpan::new(span, attributes.to_vec(), self.config.clone());
+        self.run_command(cmd, &command, Some(operation))
+    }
+
     /// Executes a packed Redis command and records the result.
     ///
     /// This function sends a packed binary command to the Redis server and captures its response.
@@ -160,7 +401,9 @@ impl InstrumentedConnection {
     ///
     /// ## Instrumentation
     /// - This function is instrumented with the `tracing` crate to provide additional context for the operation.
-    /// - `db.system` is set to `"redis"`, and `db.operation` is set to `"packed_command"`.
+    /// - `db.system` is set to `"redis"`, and `db.operation` is set to the command name parsed from
+    ///   the packed bytes (see [`get_packed_command_name`]), falling back to `"packed_command"` if the
+    ///   bytes cannot be parsed.
     /// - The tracing span allows for logging and tracing the execution of this operation, including its result.
     ///
     /// ## Parameters
@@ -197,15 +440,25 @@ impl InstrumentedConnection {
     #[instrument(
         skip(self, cmd),
         fields(
-            db.system = "redis",
-            db.operation = "packed_command"
+            db.system = self.config.db_system_attribute().then_some("redis"),
+            db.operation = Empty,
+            otel.name = Empty,
+            redis.latency_bucket = Empty
         )
     )]
     pub fn req_packed_command(&mut self, cmd: &[u8]) -> RedisResult<Value> {
         let span = Span::current();
 
+        // Recover the operation name from the packed bytes
+        let operation =
+            get_packed_command_name(cmd).unwrap_or_else(|| "packed_command".to_string());
+        span.record("db.operation", operation.as_str());
+        span.record("otel.name", generate_span_name(&operation).as_str());
+
         // Execute the command
+        let started = Instant::now();
         let result = self.inner.req_packed_command(cmd);
+        record_latency_bucket(&span, started.elapsed(), &self.config);
 
         // Record the result
         record_command_result(&span, &result);
@@ -239,6 +492,7 @@ impl InstrumentedConnection {
     ///   - `db.system`: `"redis"`
     ///   - `db.operation`: `"pipeline"`
     ///   - `redis.pipeline.count`: The count of commands executed in the pipeline.
+    ///   - `redis.pipeline.result_count`: The count of replies returned, on success.
     /// * The span associated with the telemetry will have the current command execution
     ///   results recorded using [`record_command_result`].
     ///
@@ -266,9 +520,19 @@ impl InstrumentedConnection {
     #[instrument(
         skip(self, cmd),
         fields(
-            db.system = "redis",
+            db.system = self.config.db_system_attribute().then_some("redis"),
             db.operation = "pipeline",
-            redis.pipeline.count = %count
+            redis.pipeline.count = %count,
+            redis.pipeline.result_count = Empty,
+            redis.command_count = Empty,
+            redis.latency_bucket = Empty,
+            redis.pipeline.events_truncated = Empty,
+            redis.transaction.conflict = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty,
+            error = Empty,
+            error.message = Empty,
+            error.type = Empty
         )
     )]
     pub fn req_packed_commands(
@@ -278,18 +542,162 @@ impl InstrumentedConnection {
         count: usize,
     ) -> RedisResult<Vec<Value>> {
         let span = Span::current();
+        // The buffer holds `offset` commands whose replies are skipped, such as a
+        // transaction's MULTI and queued commands, followed by `count` more
+        record_command_count(&span, offset + count);
 
         // Execute the commands
+        let started = Instant::now();
         let result = self.inner.req_packed_commands(cmd, offset, count);
+        record_latency_bucket(&span, started.elapsed(), &self.config);
+        if let Ok(values) = &result {
+            record_pipeline_result_count(&span, values.len());
+            record_pipeline_result_events(&span, values, &self.config);
+        }
 
         // Record the result
         record_command_result(&span, &result);
+        // Only transactions skip replies; a nil EXEC reply means a WATCH conflict
+        if offset > 0 && matches!(result.as_deref(), Ok([Value::Nil])) {
+            record_watch_conflict(&span, &self.config);
+        }
+
+        result
+    }
+
+    /// Runs the commands `queue` adds to a pipeline as a `MULTI`/`EXEC` transaction,
+    /// with tracing.
+    ///
+    /// The transaction gets a `redis MULTI` span, under which `MULTI`, each queued
+    /// command and `EXEC` get their own command span. It records the number of
+    /// commands the server queued as `db.redis.transaction.command_count`, and the
+    /// outcome of `EXEC`: an `EXECABORT` reply, sent when a command was rejected while
+    /// queuing, marks it as failed, and a nil reply is recorded as a `WATCH` conflict.
+    ///
+    /// Returns the `EXEC` reply converted to `RV`, with one entry per queued command:
+    /// commands marked with [`redis::Pipeline::ignore`] are not filtered out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error variant of [`RedisResult`] if:
+    /// - The connection to Redis fails.
+    /// - `EXEC` is aborted because a command was rejected while queuing.
+    /// - The reply can't be converted to `RV`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use otel_instrumentation_redis::sync::InstrumentedConnection;
+    ///
+    /// let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    /// let mut conn = InstrumentedConnection::new(client.get_connection().unwrap());
+    ///
+    /// let ((), count): ((), i64) = conn
+    ///     .transaction(|pipe| {
+    ///         pipe.set("key", "value").incr("counter", 1);
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn transaction<RV: redis::FromRedisValue>(
+        &mut self,
+        queue: impl FnOnce(&mut redis::Pipeline),
+    ) -> RedisResult<RV> {
+        let mut pipeline = redis::pipe();
+        queue(&mut pipeline);
+
+        let span = transaction_span(&self.config);
+        let _entered = span.enter();
+        let mut queued = 0;
+        let result = (|| {
+            self.req_command(&redis::cmd("MULTI"))?;
+            for cmd in pipeline.cmd_iter() {
+                match self.req_command(cmd) {
+                    Ok(_) => queued += 1,
+                    // The server rejected the command; EXEC aborts the transaction
+                    Err(err) if err.code().is_some() => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            self.req_command(&redis::cmd("EXEC"))
+        })();
+        record_transaction_result(&span, queued, &result, &self.config);
+
+        convert_reply(&result?)
+    }
 
+    /// Runs `func` in a `WATCH`/`MULTI`/`EXEC` retry loop on `keys`, with tracing, like
+    /// [`redis::transaction`] does on an uninstrumented connection.
+    ///
+    /// Each attempt watches `keys`, then calls `func` with this connection and an
+    /// atomic pipeline, so the commands it sends are traced too. `func` returns
+    /// `Ok(None)` to retry, which a pipeline query does when `EXEC` was aborted by a
+    /// change to a watched key, or `Ok(Some(_))` to finish, after which the keys are
+    /// unwatched in case `func` didn't run the pipeline.
+    ///
+    /// The loop gets a `redis transaction` span recording the number of attempts as
+    /// `redis.transaction.attempts` and the keys as `redis.transaction.watched_keys`,
+    /// after applying [`InstrumentationConfig::redact_key`].
+    ///
+    /// This is named apart from [`transaction`](Self::transaction), which runs a single
+    /// `MULTI`/`EXEC` without watching keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error variant of [`RedisResult`] if:
+    /// - The connection to Redis fails.
+    /// - `func` returns an error, which ends the loop without retrying.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use otel_instrumentation_redis::sync::InstrumentedConnection;
+    ///
+    /// let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    /// let mut conn = InstrumentedConnection::new(client.get_connection().unwrap());
+    ///
+    /// let (balance,): (i64,) = conn
+    ///     .watch_transaction(&["balance"], |conn, pipe| {
+    ///         let balance: i64 = conn.get("balance")?;
+    ///         pipe.set("balance", balance + 10).ignore()
+    ///             .get("balance")
+    ///             .query(conn)
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn watch_transaction<K, T, F>(&mut self, keys: &[K], mut func: F) -> RedisResult<T>
+    where
+        K: redis::ToRedisArgs,
+        F: FnMut(&mut Self, &mut redis::Pipeline) -> RedisResult<Option<T>>,
+    {
+        let span = watch_transaction_span(&self.config);
+        let _entered = span.enter();
+        let watched: Vec<Vec<u8>> = keys.iter().flat_map(|key| key.to_redis_args()).collect();
+        record_watched_keys(&span, &watched, &self.config);
+
+        let mut attempts = 0_i64;
+        let result = loop {
+            attempts += 1;
+            span.record("redis.transaction.attempts", attempts);
+            if let Err(err) = self.req_command(redis::cmd("WATCH").arg(keys)) {
+                break Err(err);
+            }
+            let mut pipeline = redis::pipe();
+            match func(self, pipeline.atomic()) {
+                Ok(None) => continue,
+                // Make sure no WATCH is left on the connection, even if `func` didn't
+                // run the pipeline
+                Ok(Some(response)) => {
+                    break self.req_command(&redis::cmd("UNWATCH")).map(|_| response)
+                }
+                Err(err) => break Err(err),
+            }
+        };
+        record_command_result(&span, &result);
         result
     }
 
     /// Convenience method: GET a key with instrumentation
-    #[instrument(skip(self, key), fields(db.operation = "GET"))]
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("GET", &self.config)))]
     pub fn get<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
         &mut self,
         key: K,
@@ -297,11 +705,11 @@ impl InstrumentedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("GET").arg(key);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: SET a key with instrumentation
-    #[instrument(skip(self, key, value), fields(db.operation = "SET"))]
+    #[instrument(skip(self, key, value), fields(db.operation = %method_operation("SET", &self.config)))]
     pub fn set<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -310,38 +718,513 @@ impl InstrumentedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("SET").arg(key).arg(value);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: DEL keys with instrumentation
-    #[instrument(skip(self, keys), fields(db.operation = "DEL"))]
+    #[instrument(skip(self, keys), fields(db.operation = %method_operation("DEL", &self.config)))]
     pub fn del<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("DEL").arg(keys);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
+    }
+
+    /// Convenience method: TOUCH keys to refresh their LRU/LFU access time, with instrumentation
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("TOUCH", &self.config),
+            redis.keys.count = Empty,
+            redis.keys.affected = Empty
+        )
+    )]
+    pub fn touch<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("TOUCH").arg(keys);
+        let result = self.req_command(&cmd);
+        record_multi_key_counts(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: UNLINK keys, reclaiming their memory in the background, with instrumentation
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("UNLINK", &self.config),
+            redis.keys.count = Empty,
+            redis.keys.affected = Empty
+        )
+    )]
+    pub fn unlink<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("UNLINK").arg(keys);
+        let result = self.req_command(&cmd);
+        record_multi_key_counts(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: GETRANGE a substring with instrumentation
+    ///
+    /// `start` and `end` are inclusive byte offsets; negative offsets count from the end.
+    #[instrument(
+        skip(self, key),
+        fields(
+            db.operation = %method_operation("GETRANGE", &self.config),
+            redis.range.start = Empty,
+            redis.range.end = Empty
+        )
+    )]
+    pub fn getrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        end: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GETRANGE").arg(key).arg(start).arg(end);
+        record_string_range(&Span::current(), &cmd);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SETRANGE to overwrite part of a string with instrumentation
+    ///
+    /// Returns the length of the string after the write.
+    #[instrument(
+        skip(self, key, value),
+        fields(
+            db.operation = %method_operation("SETRANGE", &self.config),
+            redis.range.start = Empty,
+            redis.range.end = Empty,
+            redis.string.length = Empty
+        )
+    )]
+    pub fn setrange<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        offset: usize,
+        value: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SETRANGE").arg(key).arg(offset).arg(value);
+        record_string_range(&Span::current(), &cmd);
+        let result = self.req_command(&cmd);
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: STRLEN with instrumentation
+    #[instrument(
+        skip(self, key),
+        fields(db.operation = %method_operation("STRLEN", &self.config), redis.string.length = Empty)
+    )]
+    pub fn strlen<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("STRLEN").arg(key);
+        let result = self.req_command(&cmd);
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: APPEND to a string with instrumentation
+    ///
+    /// Returns the length of the string after the append.
+    #[instrument(
+        skip(self, key, value),
+        fields(db.operation = %method_operation("APPEND", &self.config), redis.string.length = Empty)
+    )]
+    pub fn append<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("APPEND").arg(key).arg(value);
+        let result = self.req_command(&cmd);
+        record_string_length(&Span::current(), &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: INCR with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCR", &self.config)))]
+    pub fn incr<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCR").arg(key);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: DECR with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("DECR", &self.config)))]
+    pub fn decr<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("DECR").arg(key);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: INCRBY with instrumentation
+    ///
+    /// A negative `delta` decrements.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCRBY", &self.config)))]
+    pub fn incr_by<K: redis::ToRedisArgs>(&mut self, key: K, delta: i64) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCRBY").arg(key).arg(delta);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: INCRBYFLOAT with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("INCRBYFLOAT", &self.config)))]
+    pub fn incr_by_float<K: redis::ToRedisArgs>(&mut self, key: K, delta: f64) -> RedisResult<f64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("INCRBYFLOAT").arg(key).arg(delta);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: CLUSTER INFO with instrumentation
+    ///
+    /// Records the reported `cluster_state` as `redis.cluster.state`.
+    #[instrument(
+        skip(self),
+        fields(
+            db.operation = %method_operation("CLUSTER INFO", &self.config),
+            redis.admin_command = true,
+            redis.cluster.state = Empty
+        )
+    )]
+    pub fn cluster_info(&mut self) -> RedisResult<String> {
+        let result =
+            self.req_command_raw(redis::cmd("CLUSTER").arg("INFO"), "CLUSTER INFO", &[])?;
+        let info: String = convert_reply(&result)?;
+        if let Some(state) = parse_cluster_state(&info) {
+            Span::current().record("redis.cluster.state", state);
+        }
+        Ok(info)
+    }
+
+    /// Convenience method: CLUSTER NODES with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("CLUSTER NODES", &self.config), redis.admin_command = true)
+    )]
+    pub fn cluster_nodes(&mut self) -> RedisResult<String> {
+        let result =
+            self.req_command_raw(redis::cmd("CLUSTER").arg("NODES"), "CLUSTER NODES", &[])?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: CLUSTER SHARDS with instrumentation
+    ///
+    /// The reply is returned as is, one entry of slots and nodes per shard.
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("CLUSTER SHARDS", &self.config), redis.admin_command = true)
+    )]
+    pub fn cluster_shards(&mut self) -> RedisResult<Value> {
+        self.req_command_raw(redis::cmd("CLUSTER").arg("SHARDS"), "CLUSTER SHARDS", &[])
+    }
+
+    /// Convenience method: SCRIPT LOAD with instrumentation
+    ///
+    /// Records the returned SHA1 digest as `db.redis.script.sha`.
+    #[instrument(
+        skip(self, script),
+        fields(db.operation = %method_operation("SCRIPT LOAD", &self.config), db.redis.script.sha = Empty)
+    )]
+    pub fn script_load(&mut self, script: &str) -> RedisResult<String> {
+        let result = self.req_command_raw(
+            redis::cmd("SCRIPT").arg("LOAD").arg(script),
+            "SCRIPT LOAD",
+            &[],
+        )?;
+        let sha: String = convert_reply(&result)?;
+        Span::current().record("db.redis.script.sha", sha.as_str());
+        Ok(sha)
+    }
+
+    /// Convenience method: SCRIPT EXISTS with instrumentation
+    ///
+    /// Records how many of the queried scripts are cached as `redis.script.exists_count`.
+    #[instrument(
+        skip(self, shas),
+        fields(
+            db.operation = %method_operation("SCRIPT EXISTS", &self.config),
+            redis.script.count = shas.len(),
+            redis.script.exists_count = Empty
+        )
+    )]
+    pub fn script_exists(&mut self, shas: &[&str]) -> RedisResult<Vec<bool>> {
+        let result = self.req_command_raw(
+            redis::cmd("SCRIPT").arg("EXISTS").arg(shas),
+            "SCRIPT EXISTS",
+            &[],
+        )?;
+        let exists: Vec<bool> = convert_reply(&result)?;
+        let cached = exists.iter().filter(|exists| **exists).count();
+        Span::current().record("redis.script.exists_count", cached as i64);
+        Ok(exists)
+    }
+
+    /// Convenience method: SCRIPT FLUSH with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("SCRIPT FLUSH", &self.config), redis.admin_command = true)
+    )]
+    pub fn script_flush(&mut self) -> RedisResult<()> {
+        let result =
+            self.req_command_raw(redis::cmd("SCRIPT").arg("FLUSH"), "SCRIPT FLUSH", &[])?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LATENCY LATEST with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("LATENCY LATEST", &self.config), redis.admin_command = true)
+    )]
+    pub fn latency_latest<RV: redis::FromRedisValue>(&mut self) -> RedisResult<RV> {
+        let result =
+            self.req_command_raw(redis::cmd("LATENCY").arg("LATEST"), "LATENCY LATEST", &[])?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LATENCY HISTORY of one event with instrumentation
+    #[instrument(
+        skip(self, event),
+        fields(db.operation = %method_operation("LATENCY HISTORY", &self.config), redis.admin_command = true)
+    )]
+    pub fn latency_history<RV: redis::FromRedisValue>(&mut self, event: &str) -> RedisResult<RV> {
+        let result = self.req_command_raw(
+            redis::cmd("LATENCY").arg("HISTORY").arg(event),
+            "LATENCY HISTORY",
+            &[],
+        )?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SLOWLOG GET with instrumentation
+    ///
+    /// Fetches up to `count` entries, or the server's default of 10 when `None`, and
+    /// records the number of entries returned as `redis.slowlog.entries`.
+    #[instrument(
+        skip(self),
+        fields(
+            db.operation = %method_operation("SLOWLOG GET", &self.config),
+            redis.admin_command = true,
+            redis.slowlog.entries = Empty
+        )
+    )]
+    pub fn slowlog_get<RV: redis::FromRedisValue>(
+        &mut self,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::cmd("SLOWLOG");
+        cmd.arg("GET").arg(count);
+        let result = self.req_command_raw(&cmd, "SLOWLOG GET", &[])?;
+        if let Value::Array(entries) = &result {
+            Span::current().record("redis.slowlog.entries", entries.len() as i64);
+        }
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SLOWLOG RESET with instrumentation
+    #[instrument(
+        skip(self),
+        fields(db.operation = %method_operation("SLOWLOG RESET", &self.config), redis.admin_command = true)
+    )]
+    pub fn slowlog_reset(&mut self) -> RedisResult<()> {
+        let result =
+            self.req_command_raw(redis::cmd("SLOWLOG").arg("RESET"), "SLOWLOG RESET", &[])?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SETNX a key, setting it only if it doesn't exist, with instrumentation
+    ///
+    /// Returns `true` and records `redis.conditional.applied = true` if the key was set.
+    #[instrument(
+        skip(self, key, value),
+        fields(db.operation = %method_operation("SETNX", &self.config), redis.conditional.applied = Empty)
+    )]
+    pub fn setnx<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SETNX").arg(key).arg(value);
+        let result = self.req_command(&cmd);
+        record_reply_attributes(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: MSETNX several keys, setting them only if none exists, with instrumentation
+    ///
+    /// MSETNX is all-or-nothing: `false`, recorded as `redis.conditional.applied = false`,
+    /// means none of the keys were set. `redis.keys.count` is the number of pairs.
+    #[instrument(
+        skip(self, items),
+        fields(
+            db.operation = %method_operation("MSETNX", &self.config),
+            redis.keys.count = items.len() as i64,
+            redis.conditional.applied = Empty
+        )
+    )]
+    pub fn msetnx<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        items: &[(K, V)],
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MSETNX").arg(items);
+        let result = self.req_command(&cmd);
+        record_reply_attributes(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: GET several keys with one MGET, with instrumentation
+    ///
+    /// Keys repeated in `keys` are fetched once: the MGET carries each distinct key
+    /// and its value is fanned back out, so the result has one entry per input key,
+    /// in input order. `redis.keys.count` is the number of input keys and
+    /// `redis.batch.deduped` the number of repeats left out of the MGET.
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("MGET", &self.config),
+            redis.keys.count = keys.len() as i64,
+            redis.batch.deduped = Empty
+        )
+    )]
+    pub fn get_many<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        keys: &[K],
+    ) -> RedisResult<Vec<RV>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (unique, positions) = dedupe_keys(keys);
+        Span::current().record("redis.batch.deduped", (keys.len() - unique.len()) as i64);
+
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MGET").arg(&unique);
+        let values: Vec<Value> = convert_reply(&self.req_command(&cmd)?)?;
+        positions
+            .into_iter()
+            .map(|position| match values.get(position) {
+                Some(value) => convert_reply(value),
+                None => Err(RedisError::from((
+                    redis::ErrorKind::TypeError,
+                    "MGET returned fewer values than keys",
+                ))),
+            })
+            .collect()
+    }
+
+    /// Convenience method: MGET several keys with instrumentation
+    ///
+    /// Returns one value per key, in order, with nil for missing keys. Unlike
+    /// [`Self::get_many`], repeated keys are sent as given.
+    #[instrument(
+        skip(self, keys),
+        fields(db.operation = %method_operation("MGET", &self.config), redis.keys.count = keys.len() as i64)
+    )]
+    pub fn mget<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        keys: &[K],
+    ) -> RedisResult<Vec<RV>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MGET").arg(keys);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: MSET several key-value pairs with instrumentation
+    ///
+    /// `redis.keys.count` is the number of pairs.
+    #[instrument(
+        skip(self, items),
+        fields(db.operation = %method_operation("MSET", &self.config), redis.keys.count = items.len() as i64)
+    )]
+    pub fn mset<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        items: &[(K, V)],
+    ) -> RedisResult<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("MSET").arg(items);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: GETDEL a key, returning its value, with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("GETDEL", &self.config)))]
+    pub fn getdel<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GETDEL").arg(key);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: GETEX a key, returning its value and updating its TTL, with instrumentation
+    #[instrument(
+        skip(self, key, expiry),
+        fields(
+            db.operation = %method_operation("GETEX", &self.config),
+            redis.ttl.mode = Empty,
+            redis.ttl.value = Empty
+        )
+    )]
+    pub fn getex<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        expiry: redis::Expiry,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("GETEX").arg(key).arg(expiry);
+        record_getex_expiry(&Span::current(), &cmd);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
     }
 
     /// Convenience method: EXISTS check with instrumentation
-    #[instrument(skip(self, keys), fields(db.operation = "EXISTS"))]
+    #[instrument(
+        skip(self, keys),
+        fields(
+            db.operation = %method_operation("EXISTS", &self.config),
+            redis.exists.found = Empty,
+            redis.exists.found_count = Empty,
+            otel.status_code = Empty,
+            otel.status_description = Empty
+        )
+    )]
     pub fn exists<K: redis::ToRedisArgs>(&mut self, keys: K) -> RedisResult<bool> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("EXISTS").arg(keys);
-        let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        let result = self.req_command(&cmd);
+        record_exists_result(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
     }
 
     /// Convenience method: EXPIRE key with instrumentation
-    #[instrument(skip(self, key), fields(db.operation = "EXPIRE"))]
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("EXPIRE", &self.config)))]
     pub fn expire<K: redis::ToRedisArgs>(&mut self, key: K, seconds: usize) -> RedisResult<bool> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("EXPIRE").arg(key).arg(seconds);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: HGET hash field with instrumentation
-    #[instrument(skip(self, key, field), fields(db.operation = "HGET"))]
+    #[instrument(skip(self, key, field), fields(db.operation = %method_operation("HGET", &self.config)))]
     pub fn hget<K: redis::ToRedisArgs, F: redis::ToRedisArgs, RV: redis::FromRedisValue>(
         &mut self,
         key: K,
@@ -350,11 +1233,18 @@ impl InstrumentedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("HGET").arg(key).arg(field);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: HSET hash field with instrumentation
-    #[instrument(skip(self, key, field, value), fields(db.operation = "HSET"))]
+    #[instrument(
+        skip(self, key, field, value),
+        fields(
+            db.operation = %method_operation("HSET", &self.config),
+            redis.hash.fields_set = Empty,
+            redis.hash.new_fields = Empty
+        )
+    )]
     pub fn hset<K: redis::ToRedisArgs, F: redis::ToRedisArgs, V: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -363,12 +1253,131 @@ impl InstrumentedConnection {
     ) -> RedisResult<bool> {
         let mut cmd = redis::Cmd::new();
         cmd.arg("HSET").arg(key).arg(field).arg(value);
+        let result = self.req_command(&cmd);
+        record_hash_set_fields(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: HSET multiple hash fields with instrumentation
+    #[instrument(
+        skip(self, key, items),
+        fields(
+            db.operation = %method_operation("HSET", &self.config),
+            redis.hash.fields_set = Empty,
+            redis.hash.new_fields = Empty
+        )
+    )]
+    pub fn hset_multiple<K: redis::ToRedisArgs, F: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        items: &[(F, V)],
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("HSET").arg(key).arg(items);
+        let result = self.req_command(&cmd);
+        record_hash_set_fields(&Span::current(), &cmd, &result);
+        convert_reply(&result?)
+    }
+
+    /// Convenience method: ZADD a member with instrumentation
+    ///
+    /// `flags` are sent before the score and recorded as `redis.zset.flags`.
+    #[instrument(
+        skip(self, key, member, score, flags),
+        fields(db.operation = %method_operation("ZADD", &self.config), redis.zset.flags = Empty)
+    )]
+    pub fn zadd<K: redis::ToRedisArgs, M: redis::ToRedisArgs, S: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        member: M,
+        score: S,
+        flags: &[ZAddFlag],
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZADD").arg(key).arg(flags).arg(score).arg(member);
+        record_zadd_flags(&Span::current(), flags);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZRANGEBYSCORE with instrumentation
+    ///
+    /// Records the parsed score range, including `(` exclusive and `-inf`/`+inf` bounds.
+    #[instrument(
+        skip(self, key, min, max),
+        fields(
+            db.operation = %method_operation("ZRANGEBYSCORE", &self.config),
+            redis.zset.min = Empty,
+            redis.zset.max = Empty,
+            redis.zset.min_exclusive = Empty,
+            redis.zset.max_exclusive = Empty
+        )
+    )]
+    pub fn zrangebyscore<
+        K: redis::ToRedisArgs,
+        M: redis::ToRedisArgs,
+        MM: redis::ToRedisArgs,
+        RV: redis::FromRedisValue,
+    >(
+        &mut self,
+        key: K,
+        min: M,
+        max: MM,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZRANGEBYSCORE").arg(key).arg(min).arg(max);
+        record_score_bounds(&Span::current(), &cmd);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZREM members from a sorted set with instrumentation
+    #[instrument(skip(self, key, members), fields(db.operation = %method_operation("ZREM", &self.config)))]
+    pub fn zrem<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        members: M,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZREM").arg(key).arg(members);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZSCORE with instrumentation
+    ///
+    /// A missing member or key replies nil, so `RV` is typically `Option<f64>`.
+    #[instrument(skip(self, key, member), fields(db.operation = %method_operation("ZSCORE", &self.config)))]
+    pub fn zscore<K: redis::ToRedisArgs, M: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        member: M,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZSCORE").arg(key).arg(member);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: ZRANGE by rank with instrumentation
+    ///
+    /// `start` and `stop` are inclusive and may be negative to count from the highest
+    /// score.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("ZRANGE", &self.config)))]
+    pub fn zrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        stop: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("ZRANGE").arg(key).arg(start).arg(stop);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
     }
 
     /// Convenience method: SADD to set with instrumentation
-    #[instrument(skip(self, key, members), fields(db.operation = "SADD"))]
+    #[instrument(skip(self, key, members), fields(db.operation = %method_operation("SADD", &self.config)))]
     pub fn sadd<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -377,11 +1386,11 @@ impl InstrumentedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("SADD").arg(key).arg(members);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
     }
 
     /// Convenience method: SISMEMBER check with instrumentation
-    #[instrument(skip(self, key, member), fields(db.operation = "SISMEMBER"))]
+    #[instrument(skip(self, key, member), fields(db.operation = %method_operation("SISMEMBER", &self.config)))]
     pub fn sismember<K: redis::ToRedisArgs, M: redis::ToRedisArgs>(
         &mut self,
         key: K,
@@ -390,7 +1399,257 @@ impl InstrumentedConnection {
         let mut cmd = redis::Cmd::new();
         cmd.arg("SISMEMBER").arg(key).arg(member);
         let result = self.req_command(&cmd)?;
-        redis::FromRedisValue::from_redis_value(&result)
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SMOVE a member between sets with instrumentation
+    #[instrument(
+        skip(self, source, destination, member),
+        fields(
+            db.operation = %method_operation("SMOVE", &self.config),
+            redis.smove.source = Empty,
+            redis.smove.destination = Empty
+        )
+    )]
+    pub fn smove<S: redis::ToRedisArgs, D: redis::ToRedisArgs, M: redis::ToRedisArgs>(
+        &mut self,
+        source: S,
+        destination: D,
+        member: M,
+    ) -> RedisResult<bool> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SMOVE").arg(source).arg(destination).arg(member);
+        record_smove_keys_with_config(&Span::current(), &cmd, &self.config);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SPOP random members with instrumentation
+    ///
+    /// Without `count` a single member (or nil) is returned, with it a list.
+    #[instrument(
+        skip(self, key),
+        fields(db.operation = %method_operation("SPOP", &self.config), redis.spop.count = Empty)
+    )]
+    pub fn spop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SPOP").arg(key).arg(count);
+        record_set_sample_count(&Span::current(), &cmd);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: SRANDMEMBER with instrumentation
+    ///
+    /// A negative `count` may return duplicates and records `redis.set.allow_duplicates`.
+    #[instrument(
+        skip(self, key),
+        fields(
+            db.operation = %method_operation("SRANDMEMBER", &self.config),
+            redis.srandmember.count = Empty,
+            redis.set.allow_duplicates = Empty
+        )
+    )]
+    pub fn srandmember<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<isize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("SRANDMEMBER").arg(key).arg(count);
+        record_set_sample_count(&Span::current(), &cmd);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LPUSH values onto a list with instrumentation
+    #[instrument(skip(self, key, values), fields(db.operation = %method_operation("LPUSH", &self.config)))]
+    pub fn lpush<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        values: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LPUSH").arg(key).arg(values);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: RPUSH values onto a list with instrumentation
+    #[instrument(skip(self, key, values), fields(db.operation = %method_operation("RPUSH", &self.config)))]
+    pub fn rpush<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        key: K,
+        values: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("RPUSH").arg(key).arg(values);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LPOP with instrumentation
+    ///
+    /// Without `count` a single element (or nil) is returned, with it a list.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LPOP", &self.config)))]
+    pub fn lpop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LPOP").arg(key).arg(count);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: RPOP with instrumentation
+    ///
+    /// Without `count` a single element (or nil) is returned, with it a list.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("RPOP", &self.config)))]
+    pub fn rpop<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        count: Option<usize>,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("RPOP").arg(key).arg(count);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LLEN with instrumentation
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LLEN", &self.config)))]
+    pub fn llen<K: redis::ToRedisArgs>(&mut self, key: K) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LLEN").arg(key);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: LRANGE with instrumentation
+    ///
+    /// `start` and `stop` are inclusive and may be negative to count from the tail.
+    #[instrument(skip(self, key), fields(db.operation = %method_operation("LRANGE", &self.config)))]
+    pub fn lrange<K: redis::ToRedisArgs, RV: redis::FromRedisValue>(
+        &mut self,
+        key: K,
+        start: isize,
+        stop: isize,
+    ) -> RedisResult<RV> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("LRANGE").arg(key).arg(start).arg(stop);
+        let result = self.req_command(&cmd)?;
+        convert_reply(&result)
+    }
+
+    /// Convenience method: PUBLISH with instrumentation, returning the number of clients
+    /// that received the message
+    ///
+    /// The channel is recorded as `redis.pubsub.channel`, after the configured key
+    /// redactions, and the number of receivers as `redis.pubsub.receivers`.
+    #[instrument(
+        skip(self, channel, message),
+        fields(
+            db.operation = %method_operation("PUBLISH", &self.config),
+            redis.pubsub.channel = Empty,
+            redis.pubsub.receivers = Empty
+        )
+    )]
+    pub fn publish<K: redis::ToRedisArgs, V: redis::ToRedisArgs>(
+        &mut self,
+        channel: K,
+        message: V,
+    ) -> RedisResult<i64> {
+        let mut cmd = redis::Cmd::new();
+        cmd.arg("PUBLISH").arg(channel).arg(message);
+        let span = Span::current();
+        record_publish_channel(&span, &cmd, &self.config);
+        let result = self.req_command(&cmd)?;
+        let receivers: i64 = convert_reply(&result)?;
+        span.record("redis.pubsub.receivers", receivers);
+        Ok(receivers)
+    }
+}
+
+impl InstrumentedSyncConnection<Connection> {
+    /// Enters pub/sub mode, returning an instrumented wrapper around `redis::PubSub`.
+    ///
+    /// Any active subscriptions are cleared when the wrapper is dropped.
+    pub fn as_pubsub(&mut self) -> InstrumentedPubSub<'_> {
+        InstrumentedPubSub::with_config(self.inner.as_pubsub(), Arc::clone(&self.config))
+    }
+
+    /// Receives the next response or server-pushed value with tracing.
+    ///
+    /// Used to read pushed data, such as pub/sub messages under RESP3, that no `Cmd`
+    /// produced. The `redis receive` span covers the whole time spent blocked, and
+    /// records the channel and payload size when the value is a pub/sub message.
+    pub fn recv_response(&mut self) -> RedisResult<Value> {
+        let span = receive_span(&self.config);
+        let _enter = span.enter();
+
+        let result = self.inner.recv_response();
+        if let Some(msg) = result.as_ref().ok().and_then(Msg::from_value) {
+            record_received_message(&span, &msg);
+        }
+        record_command_result(&span, &result);
+
+        result
+    }
+}
+
+/// Allows an `InstrumentedSyncConnection` to be used anywhere redis-rs expects a connection,
+/// including the `redis::Commands` trait and `redis::Cmd::query`.
+///
+/// Commands issued this way are instrumented exactly like calls to the inherent methods:
+/// `req_command` creates a full command span, and packed commands recover their
+/// operation name from the RESP bytes.
+///
+/// # Example
+/// ```rust,ignore
+/// use redis::Commands;
+///
+/// let mut conn = instrumented_client.get_connection()?;
+/// // Produces a `redis get` span with `db.operation = GET`
+/// let value: Option<String> = conn.get("key")?;
+/// ```
+impl<C: ConnectionLike> ConnectionLike for InstrumentedSyncConnection<C> {
+    fn req_packed_command(&mut self, cmd: &[u8]) -> RedisResult<Value> {
+        Self::req_packed_command(self, cmd)
+    }
+
+    fn req_packed_commands(
+        &mut self,
+        cmd: &[u8],
+        offset: usize,
+        count: usize,
+    ) -> RedisResult<Vec<Value>> {
+        Self::req_packed_commands(self, cmd, offset, count)
+    }
+
+    fn req_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        Self::req_command(self, cmd)
+    }
+
+    fn get_db(&self) -> i64 {
+        self.inner.get_db()
+    }
+
+    fn supports_pipelining(&self) -> bool {
+        self.inner.supports_pipelining()
+    }
+
+    fn check_connection(&mut self) -> bool {
+        self.inner.check_connection()
+    }
+
+    fn is_open(&self) -> bool {
+        self.inner.is_open()
     }
 }
 
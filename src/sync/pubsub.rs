@@ -0,0 +1,141 @@
+//! Instrumented wrapper for a synchronous pub/sub connection
+
+use crate::common::{method_operation, record_command_result, record_subscription_names};
+use crate::config::InstrumentationConfig;
+use redis::{FromRedisValue, Msg, PubSub, RedisResult, ToRedisArgs};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::field::Empty;
+use tracing::{instrument, Span};
+
+/// An instrumented wrapper around `redis::PubSub`
+///
+/// Subscription changes get a span per call, recording the channel names as
+/// `redis.pubsub.channels` or the patterns as `redis.pubsub.patterns`, see
+/// [`record_subscription_names`]. Every received message gets a `redis receive`
+/// span recording its channel and payload size.
+pub struct InstrumentedPubSub<'a> {
+    inner: PubSub<'a>,
+    config: Arc<InstrumentationConfig>,
+}
+
+impl<'a> InstrumentedPubSub<'a> {
+    /// Create a new instrumented pub/sub connection
+    pub fn new(pubsub: PubSub<'a>) -> Self {
+        Self::with_config(pubsub, Arc::new(InstrumentationConfig::default()))
+    }
+
+    /// Create a new instrumented pub/sub connection with a shared configuration
+    pub fn with_config(pubsub: PubSub<'a>, config: Arc<InstrumentationConfig>) -> Self {
+        Self {
+            inner: pubsub,
+            config,
+        }
+    }
+
+    /// Get the underlying pub/sub connection
+    pub fn inner(&mut self) -> &mut PubSub<'a> {
+        &mut self.inner
+    }
+
+    /// Subscribe to channel(s) with tracing
+    #[instrument(
+        skip(self, channel),
+        fields(db.operation = %method_operation("SUBSCRIBE", &self.config), redis.pubsub.channels = Empty)
+    )]
+    pub fn subscribe<T: ToRedisArgs>(&mut self, channel: T) -> RedisResult<()> {
+        self.record_names(&channel, false);
+        self.inner.subscribe(channel)
+    }
+
+    /// Subscribe to channel pattern(s) with tracing
+    #[instrument(
+        skip(self, pattern),
+        fields(db.operation = %method_operation("PSUBSCRIBE", &self.config), redis.pubsub.patterns = Empty)
+    )]
+    pub fn psubscribe<T: ToRedisArgs>(&mut self, pattern: T) -> RedisResult<()> {
+        self.record_names(&pattern, true);
+        self.inner.psubscribe(pattern)
+    }
+
+    /// Unsubscribe from channel(s) with tracing
+    #[instrument(
+        skip(self, channel),
+        fields(db.operation = %method_operation("UNSUBSCRIBE", &self.config), redis.pubsub.channels = Empty)
+    )]
+    pub fn unsubscribe<T: ToRedisArgs>(&mut self, channel: T) -> RedisResult<()> {
+        self.record_names(&channel, false);
+        self.inner.unsubscribe(channel)
+    }
+
+    /// Unsubscribe from channel pattern(s) with tracing
+    #[instrument(
+        skip(self, pattern),
+        fields(db.operation = %method_operation("PUNSUBSCRIBE", &self.config), redis.pubsub.patterns = Empty)
+    )]
+    pub fn punsubscribe<T: ToRedisArgs>(&mut self, pattern: T) -> RedisResult<()> {
+        self.record_names(&pattern, true);
+        self.inner.punsubscribe(pattern)
+    }
+
+    /// Ping the server with tracing, e.g. to keep an idle subscriber connection alive
+    #[instrument(skip(self), fields(db.operation = %method_operation("PING", &self.config)))]
+    pub fn ping<T: FromRedisValue>(&mut self) -> RedisResult<T> {
+        self.inner.ping()
+    }
+
+    /// Receive the next message with tracing
+    ///
+    /// Blocks until a message arrives or the read timeout expires. The `redis receive`
+    /// span is opened before blocking, so its duration includes the time spent waiting.
+    pub fn get_message(&mut self) -> RedisResult<Msg> {
+        let span = receive_span(&self.config);
+        let _enter = span.enter();
+
+        let result = self.inner.get_message();
+        if let Ok(msg) = &result {
+            record_received_message(&span, msg);
+        }
+        record_command_result(&span, &result);
+
+        result
+    }
+
+    /// Set the read timeout used by [`Self::get_message`]; `None` blocks indefinitely
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> RedisResult<()> {
+        self.inner.set_read_timeout(timeout)
+    }
+
+    fn record_names<T: ToRedisArgs>(&self, names: &T, pattern: bool) {
+        let names = names.to_redis_args();
+        record_subscription_names(&Span::current(), &names, pattern, &self.config);
+    }
+}
+
+/// The span covering one receive of server-pushed data
+pub(crate) fn receive_span(config: &InstrumentationConfig) -> Span {
+    tracing::info_span!(
+        "redis_receive",
+        otel.name = "redis receive",
+        db.system = config.db_system_attribute().then_some("redis"),
+        db.operation = "RECEIVE",
+        redis.pubsub.channel = Empty,
+        redis.pubsub.pattern = Empty,
+        redis.pubsub.payload_size = Empty,
+        otel.status_code = Empty,
+        otel.status_description = Empty,
+        error = Empty,
+        error.message = Empty,
+        error.type = Empty
+    )
+}
+
+/// Records the channel and payload size of a received pub/sub message
+pub(crate) fn record_received_message(span: &Span, msg: &Msg) {
+    span.record("redis.pubsub.channel", msg.get_channel_name());
+    span.record("redis.pubsub.pattern", msg.from_pattern());
+    span.record(
+        "redis.pubsub.payload_size",
+        msg.get_payload_bytes().len() as i64,
+    );
+}
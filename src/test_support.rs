@@ -0,0 +1,464 @@
+//! Test helpers for asserting on the spans produced by the instrumentation
+
+// Not every helper is used under every feature combination
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+/// A span observed by [`SpanCapture`], with every field value rendered as a string
+#[derive(Debug, Clone)]
+pub struct CapturedSpan {
+    pub id: u64,
+    pub name: &'static str,
+    pub target: String,
+    pub parent: Option<u64>,
+    pub follows_from: Vec<u64>,
+    /// How many times values were recorded on the span after its creation
+    pub records: usize,
+    pub fields: HashMap<String, String>,
+    pub events: Vec<HashMap<String, String>>,
+}
+
+impl CapturedSpan {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+}
+
+/// A layer recording every span and event so tests can inspect them afterwards
+#[derive(Clone, Default)]
+pub struct SpanCapture {
+    spans: Arc<Mutex<Vec<CapturedSpan>>>,
+}
+
+impl SpanCapture {
+    pub fn spans(&self) -> Vec<CapturedSpan> {
+        self.spans.lock().unwrap().clone()
+    }
+
+    /// Returns the first span whose `otel.name` field matches `otel_name`
+    pub fn by_otel_name(&self, otel_name: &str) -> Option<CapturedSpan> {
+        self.spans()
+            .into_iter()
+            .find(|span| span.field("otel.name") == Some(otel_name))
+    }
+
+    /// Returns the first span created with the given tracing name
+    pub fn by_name(&self, name: &str) -> Option<CapturedSpan> {
+        self.spans().into_iter().find(|span| span.name == name)
+    }
+
+    /// Returns the command span of `operation`, asserting it is nested under the
+    /// convenience method span named `method`, which records the same operation
+    pub fn method_command(&self, method: &str, operation: &str) -> CapturedSpan {
+        let method = self
+            .by_name(method)
+            .unwrap_or_else(|| panic!("no `{method}` span"));
+        assert_eq!(method.field("db.operation"), Some(operation));
+        let command = self
+            .by_otel_name(&format!("redis {}", operation.to_lowercase()))
+            .unwrap_or_else(|| panic!("no `{operation}` command span"));
+        assert_eq!(command.parent, Some(method.id));
+        command
+    }
+
+    fn with_span(&self, id: &Id, f: impl FnOnce(&mut CapturedSpan)) {
+        let mut spans = self.spans.lock().unwrap();
+        if let Some(span) = spans.iter_mut().rev().find(|s| s.id == id.into_u64()) {
+            f(span);
+        }
+    }
+}
+
+impl<S> Layer<S> for SpanCapture
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let parent = ctx
+            .span(id)
+            .and_then(|span| span.parent())
+            .map(|parent| parent.id().into_u64());
+        self.spans.lock().unwrap().push(CapturedSpan {
+            id: id.into_u64(),
+            name: attrs.metadata().name(),
+            target: attrs.metadata().target().to_string(),
+            parent,
+            follows_from: Vec::new(),
+            records: 0,
+            fields,
+            events: Vec::new(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        self.with_span(id, |span| {
+            span.records += 1;
+            values.record(&mut FieldVisitor(&mut span.fields))
+        });
+    }
+
+    fn on_follows_from(&self, id: &Id, follows: &Id, _ctx: Context<'_, S>) {
+        self.with_span(id, |span| span.follows_from.push(follows.into_u64()));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.event_span(event) {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.with_span(&span.id(), |captured| captured.events.push(fields));
+        }
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+/// Runs `f` with a capturing subscriber installed and returns what it observed
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, SpanCapture) {
+    let capture = SpanCapture::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    let result = tracing::subscriber::with_default(subscriber, f);
+    (result, capture)
+}
+
+/// Runs `f` on a sync wrapper over a mock replaying `replies`, with a capturing
+/// subscriber installed, and returns the wrapper, whose mock holds the commands sent
+#[cfg(feature = "sync")]
+pub fn capture_sync(
+    config: crate::InstrumentationConfig,
+    replies: impl IntoIterator<Item = redis::RedisResult<redis::Value>>,
+    f: impl FnOnce(&mut crate::sync::InstrumentedSyncConnection<MockSyncConnection>),
+) -> (
+    crate::sync::InstrumentedSyncConnection<MockSyncConnection>,
+    SpanCapture,
+) {
+    let mut conn = crate::sync::InstrumentedSyncConnection::with_config(
+        MockSyncConnection::with_replies(replies),
+        Arc::new(config),
+    );
+    let ((), capture) = capture(|| f(&mut conn));
+    (conn, capture)
+}
+
+/// Installs a capturing subscriber for the current thread until the guard is dropped
+pub fn capture_default() -> (tracing::subscriber::DefaultGuard, SpanCapture) {
+    let capture = SpanCapture::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    (tracing::subscriber::set_default(subscriber), capture)
+}
+
+/// An in-memory async connection replaying canned replies and recording the commands it receives
+#[cfg(feature = "aio")]
+#[derive(Default)]
+pub struct MockAsyncConnection {
+    pub replies: std::collections::VecDeque<redis::RedisResult<redis::Value>>,
+    pub commands: Vec<Vec<u8>>,
+    pub db: i64,
+    /// Yields to the executor once before each reply, like a real round trip
+    pub yield_before_reply: bool,
+    /// Returns at most this many pipeline replies, like a connection cut short
+    pub max_pipeline_replies: Option<usize>,
+}
+
+#[cfg(feature = "aio")]
+impl MockAsyncConnection {
+    pub fn with_replies(
+        replies: impl IntoIterator<Item = redis::RedisResult<redis::Value>>,
+    ) -> Self {
+        Self {
+            replies: replies.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    fn next_reply(&mut self) -> redis::RedisResult<redis::Value> {
+        self.replies.pop_front().unwrap_or(Ok(redis::Value::Nil))
+    }
+}
+
+#[cfg(feature = "aio")]
+impl redis::aio::ConnectionLike for MockAsyncConnection {
+    fn req_packed_command<'a>(
+        &'a mut self,
+        cmd: &'a redis::Cmd,
+    ) -> redis::RedisFuture<'a, redis::Value> {
+        self.commands.push(cmd.get_packed_command());
+        let reply = self.next_reply();
+        let yield_before_reply = self.yield_before_reply;
+        Box::pin(async move {
+            if yield_before_reply {
+                tokio::task::yield_now().await;
+            }
+            reply
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        pipeline: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> redis::RedisFuture<'a, Vec<redis::Value>> {
+        self.commands.push(pipeline.get_packed_pipeline());
+        let replies: redis::RedisResult<Vec<redis::Value>> =
+            (0..offset + count).map(|_| self.next_reply()).collect();
+        let max = self.max_pipeline_replies.unwrap_or(count);
+        Box::pin(async move {
+            replies.map(|values| values.into_iter().skip(offset).take(max).collect())
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.db
+    }
+}
+
+/// An in-memory sync connection replaying canned replies and recording the commands it receives
+#[derive(Default)]
+pub struct MockSyncConnection {
+    pub replies: std::collections::VecDeque<redis::RedisResult<redis::Value>>,
+    pub commands: Vec<Vec<u8>>,
+    pub db: i64,
+    /// Returns at most this many pipeline replies, like a connection cut short
+    pub max_pipeline_replies: Option<usize>,
+}
+
+impl MockSyncConnection {
+    pub fn with_replies(
+        replies: impl IntoIterator<Item = redis::RedisResult<redis::Value>>,
+    ) -> Self {
+        Self {
+            replies: replies.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    fn next_reply(&mut self) -> redis::RedisResult<redis::Value> {
+        self.replies.pop_front().unwrap_or(Ok(redis::Value::Nil))
+    }
+}
+
+impl redis::ConnectionLike for MockSyncConnection {
+    fn req_packed_command(&mut self, cmd: &[u8]) -> redis::RedisResult<redis::Value> {
+        self.commands.push(cmd.to_vec());
+        self.next_reply()
+    }
+
+    fn req_packed_commands(
+        &mut self,
+        cmd: &[u8],
+        offset: usize,
+        count: usize,
+    ) -> redis::RedisResult<Vec<redis::Value>> {
+        self.commands.push(cmd.to_vec());
+        let replies: redis::RedisResult<Vec<redis::Value>> =
+            (0..offset + count).map(|_| self.next_reply()).collect();
+        let max = self.max_pipeline_replies.unwrap_or(count);
+        replies.map(|values| values.into_iter().skip(offset).take(max).collect())
+    }
+
+    fn get_db(&self) -> i64 {
+        self.db
+    }
+
+    fn check_connection(&mut self) -> bool {
+        true
+    }
+
+    fn is_open(&self) -> bool {
+        true
+    }
+}
+
+/// Starts a minimal server on localhost answering `+OK` to every command it receives.
+///
+/// It is enough for redis-rs to complete its connection handshake, so client-side
+/// acquisition can be tested without a real Redis. Returns the server's URL.
+pub fn spawn_ok_server() -> String {
+    spawn_scripted_server(|_| b"+OK\r\n".to_vec())
+}
+
+/// Starts a minimal server on localhost answering each command with `respond(name)`.
+///
+/// `name` is the uppercased command name. Returns the server's URL.
+pub fn spawn_scripted_server(respond: fn(&str) -> Vec<u8>) -> String {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("redis://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { return };
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 4096];
+                while let Ok(n) = stream.read(&mut buf) {
+                    if n == 0 {
+                        return;
+                    }
+                    // Every command is sent as a RESP array whose header starts a line
+                    // with `*`, followed by the command name as a bulk string
+                    let lines: Vec<&[u8]> = buf[..n].split(|&b| b == b'\n').collect();
+                    let mut reply = Vec::new();
+                    for (i, line) in lines.iter().enumerate() {
+                        if line.first() == Some(&b'*') {
+                            let name = lines.get(i + 2).copied().unwrap_or_default();
+                            let name = String::from_utf8_lossy(name).trim_end().to_uppercase();
+                            reply.extend(respond(&name));
+                        }
+                    }
+                    if stream.write_all(&reply).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    url
+}
+
+/// A measurement taken by an instrument of the capturing meter provider
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub instrument: String,
+    pub value: f64,
+    pub attributes: Vec<opentelemetry::KeyValue>,
+}
+
+#[cfg(feature = "metrics")]
+impl Measurement {
+    pub fn attribute(&self, key: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == key)
+            .map(|attribute| attribute.value.to_string())
+    }
+}
+
+#[cfg(feature = "metrics")]
+static MEASUREMENTS: Mutex<Vec<Measurement>> = Mutex::new(Vec::new());
+
+/// Returns every measurement recorded so far by the instrument named `instrument`.
+///
+/// Measurements are process-wide, so tests should filter them by attributes only
+/// they produce.
+#[cfg(feature = "metrics")]
+pub fn measurements(instrument: &str) -> Vec<Measurement> {
+    MEASUREMENTS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|measurement| measurement.instrument == instrument)
+        .cloned()
+        .collect()
+}
+
+/// Installs a global meter provider recording into [`measurements`].
+///
+/// Runs when the test binary loads, before [`crate::metrics::meter`] can create the
+/// crate's meter, so every test sees the instruments regardless of which one runs
+/// first.
+#[cfg(feature = "metrics")]
+#[ctor::ctor]
+fn install_metric_capture() {
+    opentelemetry::global::set_meter_provider(CaptureProvider);
+}
+
+#[cfg(feature = "metrics")]
+struct CaptureProvider;
+
+#[cfg(feature = "metrics")]
+impl opentelemetry::metrics::MeterProvider for CaptureProvider {
+    fn meter_with_scope(
+        &self,
+        _scope: opentelemetry::InstrumentationScope,
+    ) -> opentelemetry::metrics::Meter {
+        opentelemetry::metrics::Meter::new(Arc::new(CaptureInstruments))
+    }
+}
+
+#[cfg(feature = "metrics")]
+struct CaptureInstruments;
+
+#[cfg(feature = "metrics")]
+impl opentelemetry::metrics::InstrumentProvider for CaptureInstruments {
+    fn u64_counter(
+        &self,
+        builder: opentelemetry::metrics::InstrumentBuilder<
+            '_,
+            opentelemetry::metrics::Counter<u64>,
+        >,
+    ) -> opentelemetry::metrics::Counter<u64> {
+        opentelemetry::metrics::Counter::new(Arc::new(CaptureInstrument(builder.name.to_string())))
+    }
+
+    fn f64_histogram(
+        &self,
+        builder: opentelemetry::metrics::HistogramBuilder<
+            '_,
+            opentelemetry::metrics::Histogram<f64>,
+        >,
+    ) -> opentelemetry::metrics::Histogram<f64> {
+        opentelemetry::metrics::Histogram::new(Arc::new(CaptureInstrument(
+            builder.name.to_string(),
+        )))
+    }
+
+    fn f64_gauge(
+        &self,
+        builder: opentelemetry::metrics::InstrumentBuilder<'_, opentelemetry::metrics::Gauge<f64>>,
+    ) -> opentelemetry::metrics::Gauge<f64> {
+        opentelemetry::metrics::Gauge::new(Arc::new(CaptureInstrument(builder.name.to_string())))
+    }
+}
+
+/// Records the measurements of one instrument under its name
+#[cfg(feature = "metrics")]
+struct CaptureInstrument(String);
+
+#[cfg(feature = "metrics")]
+impl CaptureInstrument {
+    fn push(&self, value: f64, attributes: &[opentelemetry::KeyValue]) {
+        MEASUREMENTS.lock().unwrap().push(Measurement {
+            instrument: self.0.clone(),
+            value,
+            attributes: attributes.to_vec(),
+        });
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl opentelemetry::metrics::SyncInstrument<f64> for CaptureInstrument {
+    fn measure(&self, measurement: f64, attributes: &[opentelemetry::KeyValue]) {
+        self.push(measurement, attributes);
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl opentelemetry::metrics::SyncInstrument<u64> for CaptureInstrument {
+    fn measure(&self, measurement: u64, attributes: &[opentelemetry::KeyValue]) {
+        self.push(measurement as f64, attributes);
+    }
+}